@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use get_size::{GetSize, buffers_heap_size, buffer_map_heap_size};
+
+
+
+fn vec_u8(c: &mut Criterion) {
+    let value: Vec<u8> = vec![0; 1024 * 1024];
+
+    c.bench_function("Vec<u8>", |b| b.iter(|| black_box(&value).get_heap_size()));
+}
+
+fn vec_u64(c: &mut Criterion) {
+    let value: Vec<u64> = vec![0; 1024 * 1024];
+
+    c.bench_function("Vec<u64>", |b| b.iter(|| black_box(&value).get_heap_size()));
+}
+
+fn vec_string(c: &mut Criterion) {
+    let value: Vec<String> = (0..1024 * 64).map(|i| i.to_string()).collect();
+
+    c.bench_function("Vec<String>", |b| b.iter(|| black_box(&value).get_heap_size()));
+}
+
+fn hash_map_u64(c: &mut Criterion) {
+    let value: HashMap<u64, u64> = (0..1024 * 64).map(|i| (i, i)).collect();
+
+    c.bench_function("HashMap<u64, u64>", |b| b.iter(|| black_box(&value).get_heap_size()));
+}
+
+fn hash_map_string(c: &mut Criterion) {
+    let value: HashMap<u64, String> = (0..1024 * 64).map(|i| (i, i.to_string())).collect();
+
+    c.bench_function("HashMap<u64, String>", |b| b.iter(|| black_box(&value).get_heap_size()));
+}
+
+fn vec_vec_u8(c: &mut Criterion) {
+    let value: Vec<Vec<u8>> = (0..1024 * 64).map(|i| vec![0; i % 256]).collect();
+
+    c.bench_function("Vec<Vec<u8>>", |b| b.iter(|| black_box(&value).get_heap_size()));
+    c.bench_function("Vec<Vec<u8>> buffers_heap_size", |b| b.iter(|| buffers_heap_size(black_box(&value))));
+}
+
+fn hash_map_string_vec_u8(c: &mut Criterion) {
+    let value: HashMap<String, Vec<u8>> = (0..1024 * 64).map(|i| (i.to_string(), vec![0; i % 256])).collect();
+
+    c.bench_function("HashMap<String, Vec<u8>>", |b| b.iter(|| black_box(&value).get_heap_size()));
+    c.bench_function("HashMap<String, Vec<u8>> buffer_map_heap_size", |b| b.iter(|| buffer_map_heap_size(black_box(&value))));
+}
+
+criterion_group!(
+    benches,
+    vec_u8,
+    vec_u64,
+    vec_string,
+    hash_map_u64,
+    hash_map_string,
+    vec_vec_u8,
+    hash_map_string_vec_u8,
+);
+criterion_main!(benches);
@@ -2,8 +2,10 @@
 
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, format_ident};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
 use attribute_derive::Attribute;
 
 
@@ -11,293 +13,2578 @@ use attribute_derive::Attribute;
 #[derive(Attribute, Default, Debug)]
 #[attribute(ident = get_size)]
 struct StructFieldAttribute {
-    #[attribute(conflicts = [size_fn, ignore])]
+    #[attribute(conflicts = [size_fn, element_size_fn, leaked, ignore, iterative, skip_recursion])]
     size: Option<usize>,
-    #[attribute(conflicts = [size, ignore])]
+    #[attribute(conflicts = [size, element_size_fn, leaked, ignore, iterative, skip_recursion])]
     size_fn: Option<syn::Ident>,
-    #[attribute(conflicts = [size, size_fn])]
+    #[attribute(conflicts = [size, size_fn, leaked, ignore, iterative, skip_recursion])]
+    element_size_fn: Option<syn::Ident>,
+    #[attribute(conflicts = [size, size_fn, element_size_fn, ignore, iterative, skip_recursion])]
+    leaked: bool,
+    #[attribute(conflicts = [size, size_fn, element_size_fn, leaked, iterative, budget, skip_recursion])]
     ignore: bool,
+    #[attribute(conflicts = [size, size_fn, element_size_fn, leaked, ignore, skip_recursion])]
+    iterative: bool,
+    #[attribute(conflicts = [size, size_fn, element_size_fn, leaked, ignore, iterative])]
+    skip_recursion: bool,
+    rename: Option<String>,
+    #[attribute(conflicts = [ignore])]
+    budget: Option<usize>,
 }
 
+impl StructFieldAttribute {
+    // Whether the value gets measured by its own `GetSize` implementation.
+    fn is_measured(&self) -> bool {
+        self.size.is_none()
+            && self.size_fn.is_none()
+            && self.element_size_fn.is_none()
+            && !self.leaked
+            && !self.ignore
+    }
+
+    // Whether the field never owns any heap memory, so no code needs to be generated for it.
+    fn is_heap_free(&self, ty: &syn::Type) -> bool {
+        self.ignore || (self.is_measured() && !self.iterative && self.budget.is_none() && is_heap_free_type(ty))
+    }
+}
+
+// Whether the type is statically known to never own any heap memory, which is the case for
+// primitives, as well as arrays and tuples of them. Other types may still be heap free, but
+// only their `GetSize` implementation knows.
+fn is_heap_free_type(ty: &syn::Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize",
+        "i8", "i16", "i32", "i64", "i128", "isize",
+        "f32", "f64", "bool", "char",
+    ];
+
+    match ty {
+        syn::Type::Path(type_path) => {
+            type_path.qself.is_none()
+                && type_path.path.get_ident().is_some_and(|ident| PRIMITIVES.iter().any(|primitive| ident == primitive))
+        }
+        syn::Type::Array(array) => is_heap_free_type(&array.elem),
+        syn::Type::Tuple(tuple) => tuple.elems.iter().all(is_heap_free_type),
+        syn::Type::Paren(paren) => is_heap_free_type(&paren.elem),
+        syn::Type::Group(group) => is_heap_free_type(&group.elem),
+        _ => false,
+    }
+}
+
+// The attributes of a field or variant, together with the predicate of a `cfg(...)` attribute,
+// which restricts the field to only be accounted for if the predicate holds.
+//
+// The predicate gets split off before parsing the remaining attributes, as `attribute_derive`
+// does not support function like arguments.
+struct FieldAttribute {
+    attr: StructFieldAttribute,
+    cfg: Option<TokenStream2>,
+}
+
+impl FieldAttribute {
+    fn from_attributes(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut cfg = None;
+        let mut remaining = Vec::with_capacity(attrs.len());
+
+        for attr in attrs {
+            let list = match &attr.meta {
+                syn::Meta::List(list) if list.path.is_ident("get_size") => list,
+                _ => {
+                    remaining.push(attr.clone());
+                    continue;
+                }
+            };
+
+            let metas = list.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)?;
+            let mut kept = Punctuated::<syn::Meta, syn::Token![,]>::new();
+
+            for meta in metas {
+                match meta {
+                    syn::Meta::List(list) if list.path.is_ident("cfg") => {
+                        if cfg.is_some() {
+                            return Err(syn::Error::new_spanned(list, "the cfg attribute can only be given once"));
+                        }
+
+                        cfg = Some(list.tokens);
+                    }
+                    meta => kept.push(meta),
+                }
+            }
+
+            if !kept.is_empty() {
+                let mut attr = attr.clone();
+                attr.meta = syn::parse_quote!(get_size(#kept));
+
+                remaining.push(attr);
+            }
+        }
+
+        Ok(Self {
+            attr: StructFieldAttribute::from_attributes(&remaining)?,
+            cfg,
+        })
+    }
+
+    // Wraps the code accounting for the field, so that it only runs if the cfg predicate holds.
+    fn gate(&self, cmd: TokenStream2) -> TokenStream2 {
+        match &self.cfg {
+            Some(cfg) => quote! {
+                if ::core::cfg!(#cfg) {
+                    #cmd
+                }
+            },
+            None => cmd,
+        }
+    }
+
+    // Like `gate`, but for code generated by `field_cmd`, which passes the tracker along.
+    fn gate_tracked(&self, cmd: TokenStream2) -> TokenStream2 {
+        match &self.cfg {
+            Some(cfg) => quote! {
+                let (total_add, tracker) = if ::core::cfg!(#cfg) {
+                    let mut total = 0;
+
+                    #cmd
+
+                    (total, tracker)
+                } else {
+                    (0, tracker)
+                };
+                total += total_add;
+            },
+            None => cmd,
+        }
+    }
+}
+
+impl std::ops::Deref for FieldAttribute {
+    type Target = StructFieldAttribute;
+
+    fn deref(&self) -> &StructFieldAttribute {
+        &self.attr
+    }
+}
+
+
+
+#[derive(Debug)]
+struct ContainerAttributes {
+    ignored: Vec<syn::PathSegment>,
+    crate_path: syn::Path,
+    bound_all_params: bool,
+    fallible: bool,
+    padding: bool,
+    introspect: bool,
+    layout: Option<syn::Path>,
+    tracker: Option<syn::Expr>,
+    delegate_to: Option<syn::Member>,
+    delegate_extras: Vec<syn::Ident>,
+    extra_size: Option<syn::Expr>,
+    extra_size_fn: Option<syn::Expr>,
+    extra_kind: Option<syn::Ident>,
+}
+
+impl Default for ContainerAttributes {
+    fn default() -> Self {
+        Self {
+            ignored: Vec::new(),
+            crate_path: syn::parse_quote!(::get_size),
+            bound_all_params: false,
+            fallible: false,
+            padding: false,
+            introspect: false,
+            layout: None,
+            tracker: None,
+            delegate_to: None,
+            delegate_extras: Vec::new(),
+            extra_size: None,
+            extra_size_fn: None,
+            extra_kind: None,
+        }
+    }
+}
+
+
+
+impl ContainerAttributes {
+    // Generate the expression which determines the fixed overhead of an instance, which can be
+    // accessed as a reference by `receiver`, if any was declared by the `extra` or `extra_fn` attribute.
+    fn extra_size(&self, receiver: &TokenStream2) -> Option<TokenStream2> {
+        if let Some(extra_size) = &self.extra_size {
+            Some(quote! { (#extra_size) })
+        } else {
+            self.extra_size_fn.as_ref().map(|extra_size_fn| quote! { #extra_size_fn(#receiver) })
+        }
+    }
+
+    // Like `extra_size`, but only if the overhead resides on the heap. Overhead of other kinds,
+    // like GPU memory, is only listed by reports and not added to the heap size.
+    fn heap_extra_size(&self, receiver: &TokenStream2) -> Option<TokenStream2> {
+        match &self.extra_kind {
+            Some(extra_kind) if extra_kind!="Heap" => None,
+            _ => self.extra_size(receiver),
+        }
+    }
+}
+
+
+
+fn extract_container_attributes(list: &[syn::Attribute]) -> syn::Result<ContainerAttributes> {
+    let mut attributes = ContainerAttributes::default();
+
+    for attr in list.iter() {
+        extract_container_attribute(attr, &mut attributes)?;
+    }
+
+    if let (Some(_), Some(extra_size_fn)) = (&attributes.extra_size, &attributes.extra_size_fn) {
+        return Err(syn::Error::new_spanned(extra_size_fn, "the extra and extra_fn attributes can not be combined"));
+    }
+
+    if let Some(extra_kind) = &attributes.extra_kind {
+        if attributes.extra_size.is_none() && attributes.extra_size_fn.is_none() {
+            return Err(syn::Error::new_spanned(extra_kind, "the extra_kind attribute requires the extra or extra_fn attribute"));
+        }
+    }
+
+    Ok(attributes)
+}
+
+// Parses the value of an attribute as an expression, which may also be given as a string literal,
+// like `tracker = "MyTracker::new()"`.
+fn parse_expr_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<syn::Expr> {
+    match meta.value()?.parse()? {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => lit.parse(),
+        expr => Ok(expr),
+    }
+}
+
+fn extract_container_attribute(
+    attr: &syn::Attribute,
+    attributes: &mut ContainerAttributes,
+) -> syn::Result<()> {
+    // Skip all attributes which do not belong to us.
+    if !attr.meta.path().is_ident("get_size") {
+        return Ok(());
+    }
+
+    // Make sure it is a list.
+    let list = attr.meta.require_list()?;
+
+    // Parse the nested meta.
+    // #[get_size(ignore(A, B))]
+    // #[get_size(crate = "path::to::get_size")]
+    // #[get_size(bound_all_params)]
+    // #[get_size(fallible)]
+    // #[get_size(padding)]
+    // #[get_size(introspect)]
+    // #[get_size(layout)]
+    // #[get_size(tracker = "MyTracker::new()")]
+    // #[get_size(delegate_to = "inner")]
+    // #[get_size(delegate_extras(hits, misses))]
+    // #[get_size(extra = 4096)]
+    // #[get_size(extra_fn = "buffer_size")]
+    // #[get_size(extra_kind = "gpu")]
+    list.parse_nested_meta(|meta| {
+        if meta.path.is_ident("ignore") {
+            meta.parse_nested_meta(|meta| {
+                for segment in meta.path.segments {
+                    attributes.ignored.push(segment);
+                }
+
+                Ok(())
+            })?;
+        } else if meta.path.is_ident("crate") {
+            let path: syn::LitStr = meta.value()?.parse()?;
+
+            attributes.crate_path = path.parse()?;
+        } else if meta.path.is_ident("bound_all_params") {
+            attributes.bound_all_params = true;
+        } else if meta.path.is_ident("fallible") {
+            attributes.fallible = true;
+        } else if meta.path.is_ident("padding") {
+            attributes.padding = true;
+        } else if meta.path.is_ident("introspect") {
+            attributes.introspect = true;
+        } else if meta.path.is_ident("layout") {
+            attributes.layout = Some(meta.path.clone());
+        } else if meta.path.is_ident("tracker") {
+            let tracker: syn::LitStr = meta.value()?.parse()?;
+
+            attributes.tracker = Some(tracker.parse()?);
+        } else if meta.path.is_ident("delegate_to") {
+            let member: syn::LitStr = meta.value()?.parse()?;
+
+            attributes.delegate_to = Some(member.parse()?);
+        } else if meta.path.is_ident("extra") {
+            attributes.extra_size = Some(parse_expr_value(&meta)?);
+        } else if meta.path.is_ident("extra_fn") {
+            attributes.extra_size_fn = Some(parse_expr_value(&meta)?);
+        } else if meta.path.is_ident("extra_kind") {
+            let kind: syn::LitStr = meta.value()?.parse()?;
+
+            let variant = match kind.value().as_str() {
+                "heap" => "Heap",
+                "gpu" => "Gpu",
+                "mmap" => "Mmap",
+                "kernel" => "Kernel",
+                _ => return Err(syn::Error::new_spanned(kind, "unknown resource kind, expected `heap`, `gpu`, `mmap` or `kernel`")),
+            };
+
+            attributes.extra_kind = Some(syn::Ident::new(variant, kind.span()));
+        } else if meta.path.is_ident("delegate_extras") {
+            meta.parse_nested_meta(|meta| {
+                attributes.delegate_extras.push(meta.path.require_ident()?.clone());
+
+                Ok(())
+            })?;
+        } else {
+            return Err(meta.error("unknown get_size attribute, expected `ignore`, `crate`, `bound_all_params`, `fallible`, `padding`, `introspect`, `layout`, `tracker`, `delegate_to`, `delegate_extras`, `extra`, `extra_fn` or `extra_kind`"));
+        }
+
+        Ok(())
+    })
+}
+
+// Collects the type parameters mentioned inside a type, and whether the type refers to the
+// type we derive for itself.
+//
+// Type parameters which only appear inside `PhantomData`, behind raw pointers or behind references
+// to sized types are not collected, as the implementations for these never look into them.
+struct TypeVisitor<'a> {
+    params: &'a [syn::Ident],
+    unsized_params: &'a [syn::Ident],
+    name: &'a syn::Ident,
+    mentioned: Vec<syn::Ident>,
+    // The type parameters mentioned outside of the generic arguments of the type itself.
+    direct: Vec<syn::Ident>,
+    recursive: bool,
+    // How many paths referring to the type itself are currently being looked into.
+    self_depth: usize,
+}
+
+impl<'a> TypeVisitor<'a> {
+    fn new(params: &'a [syn::Ident], unsized_params: &'a [syn::Ident], name: &'a syn::Ident) -> Self {
+        Self {
+            params,
+            unsized_params,
+            name,
+            mentioned: Vec::new(),
+            direct: Vec::new(),
+            recursive: false,
+            self_depth: 0,
+        }
+    }
+
+    // Whether the type is known to be sized, so a reference to it is measured as a plain pointer.
+    fn is_sized(&self, ty: &syn::Type) -> bool {
+        match ty {
+            syn::Type::Slice(_) | syn::Type::TraitObject(_) => false,
+            syn::Type::Path(type_path) => match type_path.path.get_ident() {
+                Some(ident) => ident!="str" && !self.unsized_params.contains(ident),
+                None => true,
+            },
+            syn::Type::Paren(paren) => self.is_sized(&paren.elem),
+            syn::Type::Group(group) => self.is_sized(&group.elem),
+            _ => true,
+        }
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for TypeVisitor<'a> {
+    fn visit_type(&mut self, ty: &'ast syn::Type) {
+        match ty {
+            syn::Type::Ptr(_) => {}
+            syn::Type::Reference(reference) if self.is_sized(&reference.elem) => {}
+            _ => visit::visit_type(self, ty),
+        }
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if path.leading_colon.is_none() {
+            if let Some(first) = path.segments.first() {
+                if self.params.contains(&first.ident) {
+                    if !self.mentioned.contains(&first.ident) {
+                        self.mentioned.push(first.ident.clone());
+                    }
+
+                    if self.self_depth==0 && !self.direct.contains(&first.ident) {
+                        self.direct.push(first.ident.clone());
+                    }
+                }
+            }
+        }
+
+        if path.segments.last().is_some_and(|segment| segment.ident=="PhantomData") {
+            return;
+        }
+
+        let is_self = path.segments.iter().any(|segment| segment.ident==*self.name || segment.ident=="Self");
+
+        if is_self {
+            self.recursive = true;
+            self.self_depth += 1;
+        }
+
+        visit::visit_path(self, path);
+
+        if is_self {
+            self.self_depth -= 1;
+        }
+    }
+}
+
+// Collects the type parameters which are declared as `?Sized`, either inline or by the where clause.
+fn unsized_params(generics: &syn::Generics) -> Vec<syn::Ident> {
+    let is_maybe_sized = |bound: &syn::TypeParamBound| matches!(
+        bound,
+        syn::TypeParamBound::Trait(trait_bound) if matches!(trait_bound.modifier, syn::TraitBoundModifier::Maybe(_)),
+    );
+
+    let mut params: Vec<syn::Ident> = generics
+        .type_params()
+        .filter(|type_param| type_param.bounds.iter().any(is_maybe_sized))
+        .map(|type_param| type_param.ident.clone())
+        .collect();
+
+    if let Some(where_clause) = &generics.where_clause {
+        for predicate in where_clause.predicates.iter() {
+            if let syn::WherePredicate::Type(predicate) = predicate {
+                if let syn::Type::Path(type_path) = &predicate.bounded_ty {
+                    if let Some(ident) = type_path.path.get_ident() {
+                        if predicate.bounds.iter().any(is_maybe_sized) && !params.contains(ident) {
+                            params.push(ident.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    params
+}
+
+
+// Parses the attributes of a struct field.
+//
+// If the struct delegates to a single field, all fields besides it and the listed delegation extras
+// get ignored.
+fn struct_field_attribute(
+    field: &syn::Field,
+    index: usize,
+    attributes: &ContainerAttributes,
+) -> syn::Result<FieldAttribute> {
+    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+    let delegate_to = match &attributes.delegate_to {
+        Some(delegate_to) => delegate_to,
+        None => return Ok(attr),
+    };
+
+    let included = match field.ident.as_ref() {
+        Some(ident) => {
+            *delegate_to==syn::Member::Named(ident.clone()) || attributes.delegate_extras.contains(ident)
+        }
+        None => *delegate_to==syn::Member::Unnamed(syn::Index::from(index)),
+    };
+
+    if included {
+        Ok(attr)
+    } else {
+        Ok(FieldAttribute {
+            attr: StructFieldAttribute {
+                ignore: true,
+                ..StructFieldAttribute::default()
+            },
+            cfg: None,
+        })
+    }
+}
+
+// Makes sure the fields named by the `delegate_to` and `delegate_extras` attributes actually exist.
+fn check_delegation(data: &syn::Data, attributes: &ContainerAttributes) -> syn::Result<()> {
+    let delegate_to = match &attributes.delegate_to {
+        Some(delegate_to) => delegate_to,
+        None => {
+            if let Some(extra) = attributes.delegate_extras.first() {
+                return Err(syn::Error::new_spanned(extra, "the delegate_extras attribute requires the delegate_to attribute"));
+            }
+
+            return Ok(());
+        }
+    };
+
+    let data_struct = match data {
+        syn::Data::Struct(data_struct) => data_struct,
+        _ => return Err(syn::Error::new_spanned(delegate_to, "the delegate_to attribute is only supported on structs")),
+    };
+
+    let members: Vec<syn::Member> = data_struct.fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match field.ident.as_ref() {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(index)),
+        })
+        .collect();
+
+    if !members.contains(delegate_to) {
+        return Err(syn::Error::new_spanned(delegate_to, "the field to delegate to does not exist"));
+    }
+
+    for extra in attributes.delegate_extras.iter() {
+        if !members.contains(&syn::Member::Named(extra.clone())) {
+            return Err(syn::Error::new_spanned(extra, "the delegation extra field does not exist"));
+        }
+    }
+
+    Ok(())
+}
+
+// Collects the types of all fields which are not ignored or handled by helpers.
+fn extract_field_types<'a>(
+    data: &'a syn::Data,
+    attributes: &ContainerAttributes,
+) -> syn::Result<Vec<&'a syn::Type>> {
+    let mut types = Vec::new();
+
+    match data {
+        syn::Data::Struct(data_struct) => {
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                let attr = struct_field_attribute(field, index, attributes)?;
+
+                if attr.is_measured() {
+                    types.push(&field.ty);
+                }
+            }
+        }
+        syn::Data::Enum(data_enum) => {
+            for variant in data_enum.variants.iter() {
+                let attr = FieldAttribute::from_attributes(&variant.attrs)?;
+
+                if !attr.is_measured() {
+                    continue;
+                }
+
+                for field in variant.fields.iter() {
+                    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+                    if attr.is_measured() {
+                        types.push(&field.ty);
+                    }
+                }
+            }
+        }
+        syn::Data::Union(_) => (),
+    }
+
+    Ok(types)
+}
+
+// Generate the expression which determines if the type can allocate heap memory, which is the
+// case if any of its fields can. Fields handled by helpers are assumed to allocate heap memory.
+fn can_allocate_heap(data: &syn::Data, attributes: &ContainerAttributes) -> syn::Result<TokenStream2> {
+    let crate_path = &attributes.crate_path;
+
+    let mut terms = Vec::new();
+
+    // The fixed overhead gets added to the heap size of every instance, if it resides on the heap.
+    if attributes.heap_extra_size(&quote! { self }).is_some() {
+        terms.push(quote! { true });
+    }
+
+    let field_term = |attr: &FieldAttribute, ty: &syn::Type| {
+        let term = if attr.is_measured() {
+            quote! { <#ty as #crate_path::GetSize>::CAN_ALLOCATE_HEAP }
+        } else if !attr.ignore {
+            quote! { true }
+        } else {
+            return None;
+        };
+
+        match &attr.cfg {
+            Some(cfg) => Some(quote! { (::core::cfg!(#cfg) && #term) }),
+            None => Some(term),
+        }
+    };
+
+    match data {
+        syn::Data::Struct(data_struct) => {
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                let attr = struct_field_attribute(field, index, attributes)?;
+
+                terms.extend(field_term(&attr, &field.ty));
+            }
+        }
+        syn::Data::Enum(data_enum) => {
+            for variant in data_enum.variants.iter() {
+                let attr = FieldAttribute::from_attributes(&variant.attrs)?;
+
+                if attr.ignore {
+                    continue;
+                } else if !attr.is_measured() {
+                    terms.push(quote! { true });
+
+                    continue;
+                }
+
+                for field in variant.fields.iter() {
+                    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+                    terms.extend(field_term(&attr, &field.ty));
+                }
+            }
+        }
+        syn::Data::Union(_) => (),
+    }
+
+    Ok(quote! {
+        false #(|| #terms)*
+    })
+}
+
+// Add the bounds required for the fields to implement `GetSize`, or whichever trait is given by `trait_path`.
+//
+// For every field type which mentions a type parameter a bound like `Vec<T>: GetSize` or
+// `T::Buffer: GetSize` is added. Type parameters which only appear inside `PhantomData`, behind
+// raw pointers or behind references to sized types do not count as mentioned. Field types which
+// refer to the type itself would cause an infinite recursion when resolving the bounds, so the
+// type parameters mentioned by them get bound directly instead, but only if some field mentions
+// them besides the generic arguments of the type itself. Type parameters we shall ignore are
+// never bound.
+//
+// If `bound_all_params` is set, a bound `T: GetSize` gets added to every type parameter T instead.
+fn add_trait_bounds(
+    mut generics: syn::Generics,
+    name: &syn::Ident,
+    field_types: &[&syn::Type],
+    attributes: &ContainerAttributes,
+    trait_path: &syn::Path,
+) -> syn::Generics {
+    let params: Vec<syn::Ident> = generics
+        .type_params()
+        .map(|type_param| type_param.ident.clone())
+        .filter(|param| !attributes.ignored.iter().any(|ignored| ignored.ident==*param))
+        .collect();
+
+    if attributes.bound_all_params {
+        for param in &mut generics.params {
+            if let syn::GenericParam::Type(type_param) = param {
+                if params.contains(&type_param.ident) {
+                    type_param.bounds.push(syn::parse_quote!(#trait_path));
+                }
+            }
+        }
+
+        return generics;
+    }
+
+    let ignored: Vec<syn::Ident> = attributes.ignored
+        .iter()
+        .map(|ignored| ignored.ident.clone())
+        .collect();
+
+    let unsized_params = unsized_params(&generics);
+
+    let mut visitors = Vec::with_capacity(field_types.len());
+
+    for ty in field_types.iter() {
+        // Check if the type mentions any ignored type parameter.
+        let mut visitor = TypeVisitor::new(&ignored, &unsized_params, name);
+        visitor.visit_type(ty);
+
+        if !visitor.mentioned.is_empty() {
+            continue;
+        }
+
+        let mut visitor = TypeVisitor::new(&params, &unsized_params, name);
+        visitor.visit_type(ty);
+
+        visitors.push((ty, visitor));
+    }
+
+    // The type parameters whose values actually get measured by some field.
+    let mut measured: Vec<&syn::Ident> = Vec::new();
+
+    for (_, visitor) in visitors.iter() {
+        for param in visitor.direct.iter() {
+            if !measured.contains(&param) {
+                measured.push(param);
+            }
+        }
+    }
+
+    let mut predicates: Vec<syn::WherePredicate> = Vec::new();
+
+    for (ty, visitor) in visitors.iter() {
+        let new_predicates: Vec<syn::WherePredicate> = if visitor.recursive {
+            visitor.mentioned
+                .iter()
+                .filter(|param| measured.contains(param))
+                .map(|param| syn::parse_quote!(#param: #trait_path))
+                .collect()
+        } else if !visitor.mentioned.is_empty() {
+            vec![syn::parse_quote!(#ty: #trait_path)]
+        } else {
+            Vec::new()
+        };
+
+        for predicate in new_predicates {
+            if !predicates.contains(&predicate) {
+                predicates.push(predicate);
+            }
+        }
+    }
+
+    generics.make_where_clause().predicates.extend(predicates);
+
+    generics
+}
+
+
+
+// Structs with more measured fields than this get each of them measured by a function of its own.
+const OUT_OF_LINE_FIELDS: usize = 32;
+
+// Generate the code which accounts for a single field, which can be accessed as a reference by `accessor`.
+//
+// Returns `None` if the field shall be ignored.
+fn field_cmd(
+    attr: &StructFieldAttribute,
+    accessor: &TokenStream2,
+    crate_path: &syn::Path,
+) -> Option<TokenStream2> {
+    if let Some(size) = attr.size {
+        Some(quote! {
+            total += #size;
+        })
+    } else if let Some(size_fn) = &attr.size_fn {
+        Some(quote! {
+            total += #size_fn(#accessor);
+        })
+    } else if let Some(element_size_fn) = &attr.element_size_fn {
+        Some(quote! {
+            total += #crate_path::DynElements::get_heap_size_with(#accessor, |element| #element_size_fn(element));
+        })
+    } else if attr.leaked {
+        Some(quote! {
+            total += #crate_path::GetSizeLeaked::get_heap_size_leaked(#accessor);
+        })
+    } else if attr.ignore {
+        None
+    } else if attr.skip_recursion {
+        Some(quote! {
+            let (total_add, tracker) = #crate_path::runtime::shallow_heap_size_with_tracker(#accessor, tracker);
+            total += total_add;
+        })
+    } else {
+        Some(quote! {
+            let (total_add, tracker) = #crate_path::GetSize::get_heap_size_with_tracker(#accessor, tracker);
+            total += total_add;
+        })
+    }
+}
+
+
+
+// Generate the code which adds a single field, which can be accessed as a reference by `accessor`,
+// to a `SizeReport` under the given `label`. Fields measured without their `GetSize` implementation
+// are listed as a whole. The budget of the field, if any, gets noted as well.
+fn size_report_cmd(
+    attr: &StructFieldAttribute,
+    accessor: &TokenStream2,
+    label: &str,
+    crate_path: &syn::Path,
+) -> Option<TokenStream2> {
+    let size = if let Some(size) = attr.size {
+        Some(quote! { #size })
+    } else if let Some(size_fn) = &attr.size_fn {
+        Some(quote! { #size_fn(#accessor) })
+    } else if let Some(element_size_fn) = &attr.element_size_fn {
+        Some(quote! { #crate_path::DynElements::get_heap_size_with(#accessor, |element| #element_size_fn(element)) })
+    } else if attr.leaked {
+        Some(quote! { #crate_path::GetSizeLeaked::get_heap_size_leaked(#accessor) })
+    } else if attr.ignore {
+        return None;
+    } else if attr.skip_recursion {
+        Some(quote! { #crate_path::runtime::shallow_heap_size(#accessor) })
+    } else if attr.iterative {
+        // Listed as a whole, which gets measured by the worklist of the nested objects.
+        Some(quote! { report.heap_size_of(#accessor) })
+    } else {
+        None
+    };
+
+    let cmd = match size {
+        Some(size) => quote! {
+            report.add(&#crate_path::SizeReport::join(path, #label), #size);
+        },
+        None => quote! {
+            #crate_path::GetSize::report_size(
+                #accessor,
+                &#crate_path::SizeReport::join(path, #label),
+                report,
+            );
+        },
+    };
+
+    let budget_cmd = attr.budget.map(|budget| quote! {
+        report.set_budget(&#crate_path::SizeReport::join(path, #label), #budget);
+    });
+
+    Some(quote! {
+        #cmd
+        #budget_cmd
+    })
+}
+
+// Generate the code which adds the heap size of a single field, which can be accessed as a reference
+// by `accessor`, to an `accumulator`.
+//
+// Returns `None` if the field shall be ignored.
+fn accumulate_cmd(
+    attr: &StructFieldAttribute,
+    accessor: &TokenStream2,
+    crate_path: &syn::Path,
+) -> Option<TokenStream2> {
+    let size = if let Some(size) = attr.size {
+        quote! { #size }
+    } else if let Some(size_fn) = &attr.size_fn {
+        quote! { #size_fn(#accessor) }
+    } else if let Some(element_size_fn) = &attr.element_size_fn {
+        quote! { #crate_path::DynElements::get_heap_size_with(#accessor, |element| #element_size_fn(element)) }
+    } else if attr.leaked {
+        quote! { #crate_path::GetSizeLeaked::get_heap_size_leaked(#accessor) }
+    } else if attr.ignore {
+        return None;
+    } else if attr.skip_recursion {
+        quote! { #crate_path::runtime::shallow_heap_size(#accessor) }
+    } else {
+        return Some(quote! {
+            #crate_path::GetSize::accumulate_heap_size(#accessor, accumulator);
+        });
+    };
+
+    Some(quote! {
+        #crate_path::SizeAccumulator::add(accumulator, #size);
+    })
+}
+
+// Generate the code which adds the heap size of a single field, which can be accessed as a reference
+// by `accessor`, to the `total`, returning `None` from the surrounding function once the `total`
+// exceeds the `limit`. Fields measured by their `GetSize` implementation only get the bytes
+// remaining within the `limit`, so they can abort early as well.
+//
+// Returns `None` if the field shall be ignored.
+fn within_cmd(
+    attr: &StructFieldAttribute,
+    accessor: &TokenStream2,
+    crate_path: &syn::Path,
+) -> Option<TokenStream2> {
+    let size = if let Some(size) = attr.size {
+        quote! { #size }
+    } else if let Some(size_fn) = &attr.size_fn {
+        quote! { #size_fn(#accessor) }
+    } else if let Some(element_size_fn) = &attr.element_size_fn {
+        quote! { #crate_path::DynElements::get_heap_size_with(#accessor, |element| #element_size_fn(element)) }
+    } else if attr.leaked {
+        quote! { #crate_path::GetSizeLeaked::get_heap_size_leaked(#accessor) }
+    } else if attr.ignore {
+        return None;
+    } else if attr.skip_recursion {
+        quote! { #crate_path::runtime::shallow_heap_size(#accessor) }
+    } else {
+        return Some(quote! {
+            total += #crate_path::GetSize::get_heap_size_within(#accessor, limit - total)?;
+        });
+    };
+
+    Some(within_add(&size))
+}
+
+// Generate the code which adds the given `size` to the `total`, returning `None` from the
+// surrounding function once the `total` exceeds the `limit`.
+fn within_add(size: &TokenStream2) -> TokenStream2 {
+    quote! {
+        total = total.checked_add(#size).filter(|total| *total <= limit)?;
+    }
+}
+
+// Generate the code which records the allocations of a single field, which can be accessed as a
+// reference by `accessor` and has the type `ty`, with an `AllocationFinder` under the given `label`.
+// Fields measured without their `GetSize` implementation are recorded as a single allocation.
+fn allocation_cmd(
+    attr: &StructFieldAttribute,
+    accessor: &TokenStream2,
+    ty: &syn::Type,
+    label: &str,
+    crate_path: &syn::Path,
+) -> Option<TokenStream2> {
+    let size = if let Some(size) = attr.size {
+        quote! { #size }
+    } else if let Some(size_fn) = &attr.size_fn {
+        quote! { #size_fn(#accessor) }
+    } else if let Some(element_size_fn) = &attr.element_size_fn {
+        quote! { #crate_path::DynElements::get_heap_size_with(#accessor, |element| #element_size_fn(element)) }
+    } else if attr.leaked {
+        quote! { #crate_path::GetSizeLeaked::get_heap_size_leaked(#accessor) }
+    } else if attr.ignore {
+        return None;
+    } else if attr.skip_recursion {
+        quote! { #crate_path::runtime::shallow_heap_size(#accessor) }
+    } else {
+        return Some(quote! {
+            #crate_path::GetSize::record_allocations(
+                #accessor,
+                &#crate_path::SizeReport::join(path, #label),
+                finder,
+            );
+        });
+    };
+
+    Some(quote! {
+        finder.record(
+            &#crate_path::SizeReport::join(path, #label),
+            ::core::any::type_name::<#ty>(),
+            #size,
+        );
+    })
+}
+
+// The code generated for the analysis methods of `GetSize` for a single field.
+struct AnalysisCmds {
+    wasted: TokenStream2,
+    report: TokenStream2,
+    payloads: TokenStream2,
+    borrowed: TokenStream2,
+}
+
+// Generate the code which determines the spare capacity, records the payloads and determines
+// the borrowed bytes of a single field, which can be accessed as a reference by `accessor` and gets reported under the given `label`.
+//
+// Returns `None` unless the field is measured by its `GetSize` implementation, descending into it.
+fn analysis_cmds(
+    attr: &StructFieldAttribute,
+    accessor: &TokenStream2,
+    label: &str,
+    crate_path: &syn::Path,
+) -> Option<AnalysisCmds> {
+    if !attr.is_measured() || attr.skip_recursion {
+        return None;
+    }
+
+    Some(AnalysisCmds {
+        wasted: quote! {
+            total += #crate_path::GetSize::wasted_capacity(#accessor);
+        },
+        report: quote! {
+            #crate_path::GetSize::report_wasted_capacity(
+                #accessor,
+                &#crate_path::CapacityReport::join(path, #label),
+                report,
+            );
+        },
+        payloads: quote! {
+            #crate_path::GetSize::record_payloads(#accessor, estimator);
+        },
+        borrowed: quote! {
+            total += #crate_path::GetSize::borrowed_size(#accessor);
+        },
+    })
+}
+
+
+
+// Generate the implementation of `EnumLayout`, which compares the size of the enum with the
+// size of tuples consisting of the field types of each variant.
+fn derive_enum_layout(
+    ast: &syn::DeriveInput,
+    data_enum: &syn::DataEnum,
+    crate_path: &syn::Path,
+) -> TokenStream2 {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut payload_sizes = Vec::with_capacity(data_enum.variants.len());
+    let mut arms = Vec::with_capacity(data_enum.variants.len());
+    let mut names = Vec::with_capacity(data_enum.variants.len());
+
+    for variant in data_enum.variants.iter() {
+        let ident = &variant.ident;
+        let types = variant.fields.iter().map(|field| &field.ty);
+
+        let payload_size = quote! {
+            ::core::mem::size_of::<(#(#types,)*)>()
+        };
+
+        arms.push(quote! {
+            Self::#ident{..} => #payload_size,
+        });
+        payload_sizes.push(payload_size);
+        names.push(ident.to_string());
+    }
+
+    quote! {
+        impl #impl_generics #crate_path::EnumLayout for #name #ty_generics #where_clause {
+            fn variant_stack_overhead() -> ::core::primitive::usize {
+                let mut largest = 0;
+
+                #(
+                    let payload_size = #payload_sizes;
+
+                    if payload_size>largest {
+                        largest = payload_size;
+                    }
+                )*
+
+                ::core::mem::size_of::<Self>().saturating_sub(largest)
+            }
+
+            fn variant_payload_size(&self) -> ::core::primitive::usize {
+                match *self {
+                    #(#arms)*
+                }
+            }
+
+            fn variant_stack_sizes() -> ::std::vec::Vec<(&'static ::core::primitive::str, ::core::primitive::usize)> {
+                ::core::convert::From::from([
+                    #((#names, #payload_sizes),)*
+                ])
+            }
+        }
+    }
+}
+
+
+
+// Generate the implementation of `TryGetSize`, which uses the `TryGetSize` implementations of
+// all measured fields and descends one level into the `SizeContext` for the type itself.
+fn derive_try_get_size(
+    ast: &syn::DeriveInput,
+    field_types: &[&syn::Type],
+    attributes: &ContainerAttributes,
+) -> syn::Result<TokenStream2> {
+    let name = &ast.ident;
+    let crate_path = &attributes.crate_path;
+
+    let trait_path = syn::parse_quote!(#crate_path::TryGetSize);
+    let generics = add_trait_bounds(ast.generics.clone(), name, field_types, attributes, &trait_path);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Generate the code which accounts for a single field.
+    let try_field_cmd = |attr: &FieldAttribute, accessor: &TokenStream2| {
+        let cmd = if attr.skip_recursion {
+            quote! {
+                total += #crate_path::runtime::shallow_heap_size(#accessor);
+            }
+        } else if attr.is_measured() {
+            quote! {
+                total += #crate_path::TryGetSize::try_get_heap_size(#accessor, ctx)?;
+            }
+        } else {
+            field_cmd(attr, accessor, crate_path)?
+        };
+
+        Some(attr.gate(cmd))
+    };
+
+    let body = match &ast.data {
+        syn::Data::Struct(data_struct) => {
+            let mut cmds = Vec::with_capacity(data_struct.fields.len());
+
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                let member = match field.ident.as_ref() {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(index)),
+                };
+
+                let attr = struct_field_attribute(field, index, attributes)?;
+
+                // The nesting depth limited by the context can not be determined for objects
+                // measured one after another.
+                if attr.iterative {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "the iterative attribute is not supported together with the fallible attribute",
+                    ));
+                }
+
+                cmds.extend(try_field_cmd(&attr, &quote! { &self.#member }));
+            }
+
+            quote! {
+                let mut total = 0;
+
+                #(#cmds)*
+
+                ::core::result::Result::Ok(total)
+            }
+        }
+        syn::Data::Enum(data_enum) => {
+            let mut arms = Vec::with_capacity(data_enum.variants.len());
+
+            for variant in data_enum.variants.iter() {
+                let ident = &variant.ident;
+
+                let attr = FieldAttribute::from_attributes(&variant.attrs)?;
+
+                if let Some(size) = attr.size {
+                    arms.push(quote! { Self::#ident{..} => ::core::result::Result::Ok(#size), });
+
+                    continue;
+                } else if let Some(size_fn) = &attr.size_fn {
+                    arms.push(quote! { Self::#ident{..} => ::core::result::Result::Ok(#size_fn(self)), });
+
+                    continue;
+                } else if !attr.is_measured() {
+                    arms.push(quote! { Self::#ident{..} => ::core::result::Result::Ok(0), });
+
+                    continue;
+                }
+
+                let mut field_patterns = Vec::with_capacity(variant.fields.len());
+
+                let mut field_cmds = Vec::with_capacity(variant.fields.len());
+
+                for (index, field) in variant.fields.iter().enumerate() {
+                    let field_ident = match field.ident.as_ref() {
+                        Some(ident) => ident.clone(),
+                        None => format_ident!("v{}", index),
+                    };
+
+                    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+                    let used = attr.size.is_none() && !attr.ignore;
+
+                    if used {
+                        field_patterns.push(quote! { #field_ident });
+                    } else if field.ident.is_some() {
+                        field_patterns.push(quote! { #field_ident: _ });
+                    } else {
+                        field_patterns.push(quote! { _ });
+                    }
+
+                    field_cmds.extend(try_field_cmd(&attr, &quote! { #field_ident }));
+                }
+
+                let pattern = match &variant.fields {
+                    syn::Fields::Named(_) => quote! { Self::#ident{#(#field_patterns,)*} },
+                    syn::Fields::Unnamed(_) => quote! { Self::#ident(#(#field_patterns,)*) },
+                    syn::Fields::Unit => quote! { Self::#ident },
+                };
+
+                arms.push(quote! {
+                    #pattern => {
+                        let mut total = 0;
+
+                        #(#field_cmds)*
+
+                        ::core::result::Result::Ok(total)
+                    }
+                });
+            }
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        // Unions get rejected when deriving `GetSize`.
+        syn::Data::Union(_) => return Ok(TokenStream2::new()),
+    };
+
+    // The fixed overhead does not depend on the context.
+    let body = match attributes.heap_extra_size(&quote! { self }) {
+        Some(extra_size) => quote! {
+            let result: ::core::result::Result<::core::primitive::usize, #crate_path::SizeError> = { #body };
+
+            ::core::result::Result::map(result, |total| total + #extra_size)
+        },
+        None => body,
+    };
+
+    Ok(quote! {
+        impl #impl_generics #crate_path::TryGetSize for #name #ty_generics #where_clause {
+            #[allow(unused_mut, unused_variables)]
+            fn try_get_heap_size(
+                &self,
+                ctx: &mut #crate_path::SizeContext,
+            ) -> ::core::result::Result<::core::primitive::usize, #crate_path::SizeError> {
+                ctx.descend(|ctx| {
+                    #body
+                })
+            }
+        }
+    })
+}
+
+
+
+// Generate the implementation of `GetPadding`, which adds up the padding between the fields
+// of the type itself and the padding inside all measured fields.
+fn derive_get_padding(
+    ast: &syn::DeriveInput,
+    field_types: &[&syn::Type],
+    attributes: &ContainerAttributes,
+) -> syn::Result<TokenStream2> {
+    let name = &ast.ident;
+    let crate_path = &attributes.crate_path;
+
+    let trait_path = syn::parse_quote!(#crate_path::GetPadding);
+    let generics = add_trait_bounds(ast.generics.clone(), name, field_types, attributes, &trait_path);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let padding_field_cmd = |attr: &FieldAttribute, accessor: &TokenStream2| {
+        attr.is_measured().then(|| attr.gate(quote! {
+            total += #crate_path::GetPadding::get_padding_size(#accessor);
+        }))
+    };
+
+    let body = match &ast.data {
+        syn::Data::Struct(data_struct) => {
+            let types = data_struct.fields.iter().map(|field| &field.ty);
+            let mut cmds = Vec::with_capacity(data_struct.fields.len());
+
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                let member = match field.ident.as_ref() {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(index)),
+                };
+
+                let attr = struct_field_attribute(field, index, attributes)?;
+
+                cmds.extend(padding_field_cmd(&attr, &quote! { &self.#member }));
+            }
+
+            quote! {
+                let mut total = ::core::mem::size_of::<Self>()
+                    #(- ::core::mem::size_of::<#types>())*;
+
+                #(#cmds)*
+
+                total
+            }
+        }
+        syn::Data::Enum(data_enum) => {
+            let mut arms = Vec::with_capacity(data_enum.variants.len());
+
+            for variant in data_enum.variants.iter() {
+                let ident = &variant.ident;
+                let types: Vec<_> = variant.fields.iter().map(|field| &field.ty).collect();
+
+                // The padding between the fields of the variant, excluding the discriminant.
+                let own_padding = quote! {
+                    ::core::mem::size_of::<(#(#types,)*)>()
+                        #(- ::core::mem::size_of::<#types>())*
+                };
+
+                let attr = FieldAttribute::from_attributes(&variant.attrs)?;
+
+                if !attr.is_measured() {
+                    arms.push(quote! { Self::#ident{..} => #own_padding, });
+
+                    continue;
+                }
+
+                let mut field_patterns = Vec::with_capacity(variant.fields.len());
+                let mut field_cmds = Vec::with_capacity(variant.fields.len());
+
+                for (index, field) in variant.fields.iter().enumerate() {
+                    let field_ident = match field.ident.as_ref() {
+                        Some(ident) => ident.clone(),
+                        None => format_ident!("v{}", index),
+                    };
+
+                    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+                    if let Some(cmd) = padding_field_cmd(&attr, &quote! { #field_ident }) {
+                        field_patterns.push(quote! { #field_ident });
+                        field_cmds.push(cmd);
+                    } else if field.ident.is_some() {
+                        field_patterns.push(quote! { #field_ident: _ });
+                    } else {
+                        field_patterns.push(quote! { _ });
+                    }
+                }
+
+                let pattern = match &variant.fields {
+                    syn::Fields::Named(_) => quote! { Self::#ident{#(#field_patterns,)*} },
+                    syn::Fields::Unnamed(_) => quote! { Self::#ident(#(#field_patterns,)*) },
+                    syn::Fields::Unit => quote! { Self::#ident },
+                };
+
+                arms.push(quote! {
+                    #pattern => {
+                        let mut total = #own_padding;
+
+                        #(#field_cmds)*
+
+                        total
+                    }
+                });
+            }
+
+            if arms.is_empty() {
+                // Empty enums can not be matched by reference.
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        // Unions get rejected when deriving `GetSize`.
+        syn::Data::Union(_) => return Ok(TokenStream2::new()),
+    };
+
+    Ok(quote! {
+        impl #impl_generics #crate_path::GetPadding for #name #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn get_padding_size(&self) -> ::core::primitive::usize {
+                #body
+            }
+        }
+    })
+}
+
+
+
+// Formats a type the way it is usually written, e.g. `Vec<u8>` instead of `Vec < u8 >`,
+// by only keeping the spaces between two words or after a separator.
+fn type_label(ty: &syn::Type) -> String {
+    let tokens = quote!(#ty).to_string();
+    let chars: Vec<char> = tokens.chars().collect();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut label = String::with_capacity(tokens.len());
+
+    for (index, &c) in chars.iter().enumerate() {
+        if c == ' ' {
+            let before = index.checked_sub(1).map(|i| chars[i]);
+            let after = chars.get(index + 1).copied();
+
+            let keep = match (before, after) {
+                (Some(';' | ','), _) => true,
+                (Some(before), Some(after)) => is_word(before) && (is_word(after) || after == '\''),
+                _ => false,
+            };
+
+            if !keep {
+                continue;
+            }
+        }
+
+        label.push(c);
+    }
+
+    label
+}
+
+// Lists the kind and name of the type together with the names and types of all its variants
+// and fields, as hashed by `FieldSizes::size_shape_hash`.
+fn shape_parts(ast: &syn::DeriveInput) -> Vec<String> {
+    fn push_fields(parts: &mut Vec<String>, fields: &syn::Fields) {
+        let kind = match fields {
+            syn::Fields::Named(_) => "{}",
+            syn::Fields::Unnamed(_) => "()",
+            syn::Fields::Unit => ";",
+        };
+        parts.push(String::from(kind));
+
+        for (index, field) in fields.iter().enumerate() {
+            match field.ident.as_ref() {
+                Some(ident) => parts.push(ident.to_string()),
+                None => parts.push(index.to_string()),
+            }
+            parts.push(type_label(&field.ty));
+        }
+    }
+
+    let mut parts = Vec::new();
+
+    match &ast.data {
+        syn::Data::Struct(data_struct) => {
+            parts.push(String::from("struct"));
+            parts.push(ast.ident.to_string());
+
+            push_fields(&mut parts, &data_struct.fields);
+        }
+        syn::Data::Enum(data_enum) => {
+            parts.push(String::from("enum"));
+            parts.push(ast.ident.to_string());
+
+            for variant in data_enum.variants.iter() {
+                parts.push(String::from("|"));
+                parts.push(variant.ident.to_string());
+
+                push_fields(&mut parts, &variant.fields);
+            }
+        }
+        syn::Data::Union(_) => {}
+    }
+
+    parts
+}
+
+// Generate the implementation of `FieldSizes`, which lists the labels and types of all measured
+// fields, and measures the heap size of every field of a value on its own.
+fn derive_field_sizes(
+    ast: &syn::DeriveInput,
+    attributes: &ContainerAttributes,
+    tracker: &TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let name = &ast.ident;
+    let crate_path = &attributes.crate_path;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut labels = Vec::new();
+    let mut types = Vec::new();
+
+    // Measures a single field on its own and adds it under its label, handing the tracker back.
+    let field_size_cmd = |attr: &FieldAttribute, accessor: &TokenStream2, label: &str| {
+        field_cmd(attr, accessor, crate_path).map(|cmd| {
+            let cmd = attr.gate_tracked(cmd);
+
+            quote! {
+                let tracker = {
+                    let mut total = 0;
+
+                    #cmd
+
+                    sizes.push((#label, total));
+
+                    tracker
+                };
+            }
+        })
+    };
+
+    let body = match &ast.data {
+        syn::Data::Struct(data_struct) => {
+            let mut cmds = Vec::with_capacity(data_struct.fields.len());
+
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                let member = match field.ident.as_ref() {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(index)),
+                };
+
+                let attr = struct_field_attribute(field, index, attributes)?;
+
+                let label = match (&attr.rename, &member) {
+                    (Some(rename), _) => rename.clone(),
+                    (None, syn::Member::Named(ident)) => ident.to_string(),
+                    (None, syn::Member::Unnamed(index)) => index.index.to_string(),
+                };
+
+                if let Some(cmd) = field_size_cmd(&attr, &quote! { &self.#member }, &label) {
+                    let ty = &field.ty;
+
+                    cmds.push(cmd);
+                    labels.push(label);
+                    types.push(type_label(ty));
+                }
+            }
+
+            quote! {
+                #(#cmds)*
+            }
+        }
+        syn::Data::Enum(data_enum) => {
+            let mut arms = Vec::with_capacity(data_enum.variants.len());
+
+            for variant in data_enum.variants.iter() {
+                let ident = &variant.ident;
+                let attr = FieldAttribute::from_attributes(&variant.attrs)?;
+
+                let variant_label = match &attr.rename {
+                    Some(rename) => rename.clone(),
+                    None => ident.to_string(),
+                };
+
+                // Variants measured as a whole are listed as a single field.
+                let variant_size = if let Some(size) = attr.size {
+                    Some(quote! { #size })
+                } else {
+                    attr.size_fn.as_ref().map(|size_fn| quote! { #size_fn(self) })
+                };
+
+                if let Some(size) = variant_size {
+                    arms.push(quote! {
+                        Self::#ident{..} => {
+                            sizes.push((#variant_label, #size));
+
+                            tracker
+                        }
+                    });
+                    labels.push(variant_label);
+                    types.push(String::from("Self"));
+
+                    continue;
+                } else if attr.ignore {
+                    arms.push(quote! { Self::#ident{..} => tracker, });
+
+                    continue;
+                }
+
+                let mut field_patterns = Vec::with_capacity(variant.fields.len());
+                let mut field_cmds = Vec::with_capacity(variant.fields.len());
+
+                for (index, field) in variant.fields.iter().enumerate() {
+                    let field_ident = match field.ident.as_ref() {
+                        Some(ident) => ident.clone(),
+                        None => format_ident!("v{}", index),
+                    };
+
+                    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+                    let label = match (&attr.rename, field.ident.as_ref()) {
+                        (Some(rename), _) => format!("{}.{}", variant_label, rename),
+                        (None, Some(field_name)) => format!("{}.{}", variant_label, field_name),
+                        (None, None) => format!("{}.{}", variant_label, index),
+                    };
+
+                    if let Some(cmd) = field_size_cmd(&attr, &quote! { #field_ident }, &label) {
+                        let ty = &field.ty;
+
+                        field_patterns.push(quote! { #field_ident });
+                        field_cmds.push(cmd);
+                        labels.push(label);
+                        types.push(type_label(ty));
+                    } else if field.ident.is_some() {
+                        field_patterns.push(quote! { #field_ident: _ });
+                    } else {
+                        field_patterns.push(quote! { _ });
+                    }
+                }
+
+                let pattern = match &variant.fields {
+                    syn::Fields::Named(_) => quote! { Self::#ident{#(#field_patterns,)*} },
+                    syn::Fields::Unnamed(_) => quote! { Self::#ident(#(#field_patterns,)*) },
+                    syn::Fields::Unit => quote! { Self::#ident },
+                };
+
+                arms.push(quote! {
+                    #pattern => {
+                        #(#field_cmds)*
+
+                        tracker
+                    }
+                });
+            }
+
+            if arms.is_empty() {
+                // Empty enums can not be matched by reference.
+                quote! { let tracker = match *self {}; }
+            } else {
+                quote! {
+                    let tracker = match self {
+                        #(#arms)*
+                    };
+                }
+            }
+        }
+        // Unions get rejected when deriving `GetSize`.
+        syn::Data::Union(_) => return Ok(TokenStream2::new()),
+    };
+
+    let count = labels.len();
+
+    // The labels are part of the shape as well, since reports get keyed by them.
+    let mut shape = shape_parts(ast);
+    shape.push(String::from("="));
+    shape.extend(labels.iter().cloned());
+
+    Ok(quote! {
+        impl #impl_generics #crate_path::FieldSizes for #name #ty_generics #where_clause {
+            const GET_SIZE_FIELDS: &'static [&'static ::core::primitive::str] = &[#(#labels),*];
+
+            const GET_SIZE_FIELD_TYPES: &'static [&'static ::core::primitive::str] = &[#(#types),*];
+
+            fn size_shape_hash() -> ::core::primitive::u64 {
+                const HASH: ::core::primitive::u64 = #crate_path::shape_hash(&[#(#shape),*]);
+
+                HASH
+            }
+
+            fn field_heap_sizes(&self) -> ::std::vec::Vec<(&'static ::core::primitive::str, ::core::primitive::usize)> {
+                let tracker = #tracker;
+
+                #crate_path::FieldSizes::field_heap_sizes_with_tracker(self, tracker).0
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn field_heap_sizes_with_tracker<TRACKER: #crate_path::GetSizeTracker>(
+                &self,
+                tracker: TRACKER,
+            ) -> (::std::vec::Vec<(&'static ::core::primitive::str, ::core::primitive::usize)>, TRACKER) {
+                let mut sizes = ::std::vec::Vec::with_capacity(#count);
+
+                #body
+
+                (sizes, tracker)
+            }
+        }
+    })
+}
+
+
+
+#[proc_macro_derive(GetSize, attributes(get_size))]
+pub fn derive_get_size(input: TokenStream) -> TokenStream {
+    // Construct a representation of Rust code as a syntax tree
+    // that we can manipulate
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match derive(ast) {
+        Ok(gen) => gen,
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn derive(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
+     // The name of the sruct.
+    let name = &ast.ident;
+
+    // Extract all container attributes, like the generics we shall ignore.
+    let attributes = extract_container_attributes(&ast.attrs)?;
+
+    // The path under which the get_size crate can be found.
+    let crate_path = &attributes.crate_path;
+
+    // Make sure the fields we shall delegate to exist.
+    check_delegation(&ast.data, &attributes)?;
+
+    if let Some(layout) = &attributes.layout {
+        if !matches!(ast.data, syn::Data::Enum(_)) {
+            return Err(syn::Error::new_spanned(layout, "the layout attribute is only supported on enums"));
+        }
+    }
+
+    // Determine if the type can allocate heap memory.
+    let can_allocate_heap = can_allocate_heap(&ast.data, &attributes)?;
+
+    // Add the bounds required for all fields to implement `GetSize`.
+    let field_types = extract_field_types(&ast.data, &attributes)?;
+    let trait_path = syn::parse_quote!(#crate_path::GetSize);
+    let generics = add_trait_bounds(ast.generics.clone(), name, &field_types, &attributes, &trait_path);
+
+    // Extract the generics of the struct/enum.
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Also implement `TryGetSize` if requested.
+    let try_impl = if attributes.fallible {
+        derive_try_get_size(&ast, &field_types, &attributes)?
+    } else {
+        TokenStream2::new()
+    };
+
+    // The tracker used by `get_heap_size`.
+    let tracker = match &attributes.tracker {
+        Some(tracker) => quote! { #tracker },
+        None => quote! { <#crate_path::StandardTracker as ::core::default::Default>::default() },
+    };
+
+    // Also implement `GetPadding` if requested.
+    let padding_impl = if attributes.padding {
+        derive_get_padding(&ast, &field_types, &attributes)?
+    } else {
+        TokenStream2::new()
+    };
+
+    // Also implement `FieldSizes` if requested.
+    let introspect_impl = if attributes.introspect {
+        derive_field_sizes(&ast, &attributes, &tracker)?
+    } else {
+        TokenStream2::new()
+    };
+
+    // The fixed overhead of every instance declared by the `extra` or `extra_fn` attribute, which
+    // gets listed by reports as an external resource of the kind given by the `extra_kind` attribute.
+    // Only overhead residing on the heap gets added to the heap size.
+    let extra_size = attributes.extra_size(&quote! { self });
+    let heap_extra_size = attributes.heap_extra_size(&quote! { self });
+    let extra_kind = match &attributes.extra_kind {
+        Some(extra_kind) => extra_kind.clone(),
+        None => format_ident!("Heap"),
+    };
+    let extra_report = extra_size.as_ref().map(|extra_size| quote! {
+        report.add_external(
+            &#crate_path::SizeReport::join(path, "<external>"),
+            #crate_path::ResourceKind::#extra_kind,
+            #extra_size,
+        );
+    });
+    let extra_accumulate = heap_extra_size.as_ref().map(|extra_size| quote! {
+        #crate_path::SizeAccumulator::add(accumulator, #extra_size);
+    });
+
+    // Traverse the parsed data to generate the individual parts of the function.
+    match &ast.data {
+        syn::Data::Enum(data_enum) => {
+            // Enums expose information about their stack layout if requested.
+            let layout_impl = match attributes.layout {
+                Some(_) => derive_enum_layout(&ast, data_enum, crate_path),
+                None => TokenStream2::new(),
+            };
+
+            if data_enum.variants.is_empty() {
+                // Empty enums are easy to implement.
+                let gen = quote! {
+                    impl #impl_generics #crate_path::GetSize for #name #ty_generics #where_clause {
+                        const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+                    }
+
+                    #try_impl
+
+                    #padding_impl
+
+                    #introspect_impl
+
+                    #layout_impl
+                };
+                return Ok(gen.into());
+            }
+
+            let mut cmds = Vec::with_capacity(data_enum.variants.len());
+            let mut wasted_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut report_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut payload_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut borrowed_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut size_report_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut allocation_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut accumulate_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut within_arms = Vec::with_capacity(data_enum.variants.len());
+
+            // Variants which never own heap memory do not get an arm of their own, but share
+            // a single wildcard arm.
+            let mut heap_free_variants = 0;
+
+            for variant in data_enum.variants.iter() {
+                let ident = &variant.ident;
+
+                // Variants support the same attributes as fields, which apply to the variant as a whole.
+                let attr = FieldAttribute::from_attributes(&variant.attrs)?;
+
+                // Reports list the fields of the variant under its label.
+                let variant_label = match &attr.rename {
+                    Some(rename) => rename.clone(),
+                    None => ident.to_string(),
+                };
+
+                if attr.element_size_fn.is_some() || attr.leaked || attr.iterative || attr.skip_recursion || attr.cfg.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "the element_size_fn, leaked, iterative, skip_recursion and cfg attributes are only supported on fields",
+                    ));
+                }
+
+                if let Some(size) = attr.size {
+                    cmds.push(quote! {
+                        Self::#ident{..} => (#size, tracker),
+                    });
+                    size_report_arms.push(quote! { Self::#ident{..} => report.add(path, #size), });
+                    allocation_arms.push(quote! {
+                        Self::#ident{..} => finder.record(path, ::core::any::type_name::<Self>(), #size),
+                    });
+                    accumulate_arms.push(quote! {
+                        Self::#ident{..} => #crate_path::SizeAccumulator::add(accumulator, #size),
+                    });
+                    let within = within_add(&quote! { #size });
+                    within_arms.push(quote! { Self::#ident{..} => { #within } });
+                    wasted_arms.push(quote! { Self::#ident{..} => 0, });
+                    report_arms.push(quote! { Self::#ident{..} => {}, });
+                    payload_arms.push(quote! { Self::#ident{..} => {}, });
+                    borrowed_arms.push(quote! { Self::#ident{..} => 0, });
+
+                    continue;
+                } else if let Some(size_fn) = &attr.size_fn {
+                    cmds.push(quote! {
+                        Self::#ident{..} => (#size_fn(self), tracker),
+                    });
+                    size_report_arms.push(quote! { Self::#ident{..} => report.add(path, #size_fn(self)), });
+                    allocation_arms.push(quote! {
+                        Self::#ident{..} => finder.record(path, ::core::any::type_name::<Self>(), #size_fn(self)),
+                    });
+                    accumulate_arms.push(quote! {
+                        Self::#ident{..} => #crate_path::SizeAccumulator::add(accumulator, #size_fn(self)),
+                    });
+                    let within = within_add(&quote! { #size_fn(self) });
+                    within_arms.push(quote! { Self::#ident{..} => { #within } });
+                    wasted_arms.push(quote! { Self::#ident{..} => 0, });
+                    report_arms.push(quote! { Self::#ident{..} => {}, });
+                    payload_arms.push(quote! { Self::#ident{..} => {}, });
+                    borrowed_arms.push(quote! { Self::#ident{..} => 0, });
+
+                    continue;
+                } else if attr.ignore {
+                    heap_free_variants += 1;
 
+                    continue;
+                }
 
-fn extract_ignored_generics_list(list: &Vec<syn::Attribute>) -> Vec<syn::PathSegment> {
-    let mut collection = Vec::new();
+                let mut field_patterns = Vec::with_capacity(variant.fields.len());
+
+                let mut field_cmds = Vec::with_capacity(variant.fields.len());
+
+                // The analysis methods only look into fields measured by `GetSize`,
+                // so these get bound separately.
+                let mut capacity_patterns = Vec::with_capacity(variant.fields.len());
+                let mut wasted_cmds = Vec::with_capacity(variant.fields.len());
+                let mut report_cmds = Vec::with_capacity(variant.fields.len());
+                let mut payload_cmds = Vec::with_capacity(variant.fields.len());
+                let mut borrowed_cmds = Vec::with_capacity(variant.fields.len());
+                let mut size_report_cmds = Vec::with_capacity(variant.fields.len());
+                let mut allocation_cmds = Vec::with_capacity(variant.fields.len());
+                let mut accumulate_cmds = Vec::with_capacity(variant.fields.len());
+                let mut within_cmds = Vec::with_capacity(variant.fields.len());
+
+                // Whether none of the fields owns heap memory, which is also true for unit variants.
+                let mut heap_free = true;
+
+                for (index, field) in variant.fields.iter().enumerate() {
+                    // Bind the field to an identifier, which is either its name or derived from its index.
+                    let field_ident = match field.ident.as_ref() {
+                        Some(ident) => ident.clone(),
+                        None => format_ident!("v{}", index),
+                    };
+
+                    // Parse all relevant attributes.
+                    let attr = FieldAttribute::from_attributes(&field.attrs)?;
+
+                    if attr.iterative {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            "the iterative attribute is only supported on fields of structs",
+                        ));
+                    }
 
-    for attr in list.iter() {
-        let mut list = extract_ignored_generics(attr);
+                    // Fields which never own heap memory do not contribute anything.
+                    if attr.is_heap_free(&field.ty) {
+                        if field.ident.is_some() {
+                            field_patterns.push(quote! { #field_ident: _ });
+                            capacity_patterns.push(quote! { #field_ident: _ });
+                        } else {
+                            field_patterns.push(quote! { _ });
+                            capacity_patterns.push(quote! { _ });
+                        }
 
-        collection.append(&mut list);
-    }
+                        continue;
+                    }
 
-    collection
-}
+                    heap_free = false;
 
-fn extract_ignored_generics(attr: &syn::Attribute) -> Vec<syn::PathSegment> {
-    let mut collection = Vec::new();
+                    // Fields which are not used do not get bound, so no unused variables get created.
+                    let used = attr.size.is_none();
 
-    // Skip all attributes which do not belong to us.
-    if !attr.meta.path().is_ident("get_size") {
-        return collection;
-    }
+                    if used {
+                        field_patterns.push(quote! { #field_ident });
+                    } else if field.ident.is_some() {
+                        field_patterns.push(quote! { #field_ident: _ });
+                    } else {
+                        field_patterns.push(quote! { _ });
+                    }
 
-    // Make sure it is a list.
-    let list = attr.meta.require_list().unwrap();
+                    if let Some(cmd) = field_cmd(&attr, &quote! { #field_ident }, crate_path) {
+                        field_cmds.push(attr.gate_tracked(cmd));
+                    }
 
-    // Parse the nested meta.
-    // #[get_size(ignore(A, B))]
-    list.parse_nested_meta(|meta| {
-        // We only parse the ignore attributes.
-        if !meta.path.is_ident("ignore") {
-            return Ok(()); // Just skip.
-        }
+                    let label = match (&attr.rename, field.ident.as_ref()) {
+                        (Some(rename), _) => format!("{}.{}", variant_label, rename),
+                        (None, Some(field_name)) => format!("{}.{}", variant_label, field_name),
+                        (None, None) => format!("{}.{}", variant_label, index),
+                    };
 
-        meta.parse_nested_meta(|meta| {
-            for segment in meta.path.segments {
-                collection.push(segment);
-            }
+                    if let Some(cmd) = size_report_cmd(&attr, &quote! { #field_ident }, &label, crate_path) {
+                        size_report_cmds.push(attr.gate(cmd));
+                    }
 
-            Ok(())
-        })?;
+                    if let Some(cmd) = allocation_cmd(&attr, &quote! { #field_ident }, &field.ty, &label, crate_path) {
+                        allocation_cmds.push(attr.gate(cmd));
+                    }
 
-        Ok(())
-    }).unwrap();
+                    if let Some(cmd) = accumulate_cmd(&attr, &quote! { #field_ident }, crate_path) {
+                        accumulate_cmds.push(attr.gate(cmd));
+                    }
 
-    collection
-}
+                    if let Some(cmd) = within_cmd(&attr, &quote! { #field_ident }, crate_path) {
+                        within_cmds.push(attr.gate(cmd));
+                    }
 
-// Add a bound `T: GetSize` to every type parameter T, unless we ignore it.
-fn add_trait_bounds(
-    mut generics: syn::Generics,
-    ignored: &Vec<syn::PathSegment>,
-) -> syn::Generics {
-    for param in &mut generics.params {
-        if let syn::GenericParam::Type(type_param) = param {
-            let mut found = false;
-            for ignored in ignored.iter() {
-                if ignored.ident==type_param.ident {
-                    found = true;
-                    break;
+                    if let Some(analysis) = analysis_cmds(&attr, &quote! { #field_ident }, &label, crate_path) {
+                        capacity_patterns.push(quote! { #field_ident });
+                        wasted_cmds.push(attr.gate(analysis.wasted));
+                        report_cmds.push(attr.gate(analysis.report));
+                        payload_cmds.push(attr.gate(analysis.payloads));
+                        borrowed_cmds.push(attr.gate(analysis.borrowed));
+                    } else if field.ident.is_some() {
+                        capacity_patterns.push(quote! { #field_ident: _ });
+                    } else {
+                        capacity_patterns.push(quote! { _ });
+                    }
                 }
-            }
 
-            if found {
-                continue;
-            }
+                if heap_free {
+                    heap_free_variants += 1;
 
-            type_param.bounds.push(syn::parse_quote!(GetSize));
-        }
-    }
-    generics
-}
+                    continue;
+                }
 
+                let (pattern, capacity_pattern) = match &variant.fields {
+                    syn::Fields::Named(_) => (
+                        quote! { Self::#ident{#(#field_patterns,)*} },
+                        quote! { Self::#ident{#(#capacity_patterns,)*} },
+                    ),
+                    syn::Fields::Unnamed(_) => (
+                        quote! { Self::#ident(#(#field_patterns,)*) },
+                        quote! { Self::#ident(#(#capacity_patterns,)*) },
+                    ),
+                    // Unit variants are always heap free.
+                    syn::Fields::Unit => continue,
+                };
 
+                wasted_arms.push(quote! {
+                    #capacity_pattern => {
+                        let mut total = 0;
 
-#[proc_macro_derive(GetSize, attributes(get_size))]
-pub fn derive_get_size(input: TokenStream) -> TokenStream {
-    // Construct a representation of Rust code as a syntax tree
-    // that we can manipulate
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+                        #(#wasted_cmds)*
 
-     // The name of the sruct.
-    let name = &ast.ident;
+                        total
+                    }
+                });
+                report_arms.push(quote! {
+                    #capacity_pattern => {
+                        #(#report_cmds)*
+                    }
+                });
+                payload_arms.push(quote! {
+                    #capacity_pattern => {
+                        #(#payload_cmds)*
+                    }
+                });
+                borrowed_arms.push(quote! {
+                    #capacity_pattern => {
+                        let mut total = 0;
 
-    // Extract all generics we shall ignore.
-    let ignored = extract_ignored_generics_list(&ast.attrs);
+                        #(#borrowed_cmds)*
 
-    // Add a bound `T: GetSize` to every type parameter T.
-    let generics = add_trait_bounds(ast.generics, &ignored);
+                        total
+                    }
+                });
+                size_report_arms.push(quote! {
+                    #pattern => {
+                        #(#size_report_cmds)*
+                    }
+                });
+                allocation_arms.push(quote! {
+                    #pattern => {
+                        #(#allocation_cmds)*
+                    }
+                });
+                accumulate_arms.push(quote! {
+                    #pattern => {
+                        #(#accumulate_cmds)*
+                    }
+                });
+                within_arms.push(quote! {
+                    #pattern => {
+                        #(#within_cmds)*
+                    }
+                });
 
-    // Extract the generics of the struct/enum.
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+                cmds.push(quote! {
+                    #pattern => {
+                        let mut total = 0;
 
-    // Traverse the parsed data to generate the individual parts of the function.
-    match ast.data {
-        syn::Data::Enum(data_enum) => {
-            if data_enum.variants.is_empty() {
-                // Empty enums are easy to implement.
-                let gen = quote! {
-                    impl GetSize for #name {}
-                };
-                return gen.into()
-            }
+                        #(#field_cmds)*;
 
-            let mut cmds = Vec::with_capacity(data_enum.variants.len());
+                        (total, tracker)
+                    }
+                });
+            }
 
-            for variant in data_enum.variants.iter() {
-                let ident = &variant.ident;
+            if heap_free_variants>0 {
+                cmds.push(quote! { _ => (0, tracker), });
+                wasted_arms.push(quote! { _ => 0, });
+                report_arms.push(quote! { _ => {}, });
+                payload_arms.push(quote! { _ => {}, });
+                borrowed_arms.push(quote! { _ => 0, });
+                size_report_arms.push(quote! { _ => {}, });
+                allocation_arms.push(quote! { _ => {}, });
+                accumulate_arms.push(quote! { _ => {}, });
+                within_arms.push(quote! { _ => {}, });
+            }
 
-                match &variant.fields {
-                    syn::Fields::Unnamed(unnamed_fields) => {
-                        let num_fields = unnamed_fields.unnamed.len();
+            // A custom tracker might measure differently, so the bounded measurement falls back to it.
+            let extra_within = heap_extra_size.as_ref().map(within_add);
+            let heap_size_within = match &attributes.tracker {
+                Some(_) => TokenStream2::new(),
+                None => quote! {
+                    #[allow(unused_variables)]
+                    fn get_heap_size_within(
+                        &self,
+                        limit: ::core::primitive::usize,
+                    ) -> ::core::option::Option<::core::primitive::usize> {
+                        let mut total: ::core::primitive::usize = 0;
 
-                        let mut field_idents = Vec::with_capacity(num_fields);
-                        for i in 0..num_fields {
-                            let field_ident = String::from("v")+&i.to_string();
-                            let field_ident = syn::parse_str::<syn::Ident>(&field_ident).unwrap();
+                        #extra_within
 
-                            field_idents.push(field_ident);
+                        match self {
+                            #(#within_arms)*
                         }
 
-                        let mut field_cmds = Vec::with_capacity(num_fields);
+                        ::core::option::Option::Some(total)
+                    }
+                },
+            };
 
-                        for (i, _field) in unnamed_fields.unnamed.iter().enumerate() {
-                            let field_ident = String::from("v")+&i.to_string();
-                            let field_ident = syn::parse_str::<syn::Ident>(&field_ident).unwrap();
+            // The fixed overhead gets added on top of the size of the variant.
+            let heap_size_body = match &heap_extra_size {
+                Some(extra_size) => quote! {
+                    let (total, tracker) = match self {
+                        #(#cmds)*
+                    };
+
+                    (total + #extra_size, tracker)
+                },
+                None => quote! {
+                    match self {
+                        #(#cmds)*
+                    }
+                },
+            };
 
-                            field_cmds.push(quote! {
-                                let (total_add, tracker) = GetSize::get_heap_size_with_tracker(#field_ident, tracker);
-                                total += total_add;
-                            })
-                        }
+            // Build the trait implementation
+            let gen = quote! {
+                impl #impl_generics #crate_path::GetSize for #name #ty_generics #where_clause {
+                    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = #can_allocate_heap;
 
-                        cmds.push(quote! {
-                            Self::#ident(#(#field_idents,)*) => {
-                                let mut total = 0;
+                    fn get_heap_size(&self) -> ::core::primitive::usize {
+                        let tracker = #tracker;
 
-                                #(#field_cmds)*;
+                        let (total, _) = #crate_path::GetSize::get_heap_size_with_tracker(self, tracker);
 
-                                (total, tracker)
-                            }
-                        });
+                        total
                     }
-                    syn::Fields::Named(named_fields) => {
-                        let num_fields = named_fields.named.len();
-
-                        let mut field_idents = Vec::with_capacity(num_fields);
 
-                        let mut field_cmds = Vec::with_capacity(num_fields);
+                    fn get_heap_size_with_tracker<TRACKER: #crate_path::GetSizeTracker>(
+                        &self,
+                        mut tracker: TRACKER,
+                    ) -> (::core::primitive::usize, TRACKER) {
+                        if #crate_path::runtime::is_cancelled(&mut tracker) {
+                            return (0, tracker);
+                        }
 
-                        for field in named_fields.named.iter() {
-                            let field_ident = field.ident.as_ref().unwrap();
+                        #heap_size_body
+                    }
 
-                            field_idents.push(field_ident);
+                    #heap_size_within
 
-                            field_cmds.push(quote! {
-                                let (total_add, tracker) = GetSize::get_heap_size_with_tracker(#field_ident, tracker);
-                                total += total_add;
-                            })
+                    #[allow(unused_mut, unused_variables)]
+                    fn wasted_capacity(&self) -> ::core::primitive::usize {
+                        match self {
+                            #(#wasted_arms)*
                         }
+                    }
 
-                        cmds.push(quote! {
-                            Self::#ident{#(#field_idents,)*} => {
-                                let mut total = 0;
-
-                                #(#field_cmds)*;
+                    #[allow(unused_variables)]
+                    fn report_wasted_capacity(
+                        &self,
+                        path: &::core::primitive::str,
+                        report: &mut #crate_path::CapacityReport,
+                    ) {
+                        match self {
+                            #(#report_arms)*
+                        }
+                    }
 
-                                (total, tracker)
-                            }
-                        });
+                    #[allow(unused_variables)]
+                    fn record_payloads(&self, estimator: &mut #crate_path::DedupEstimator) {
+                        match self {
+                            #(#payload_arms)*
+                        }
                     }
-                    syn::Fields::Unit => {
-                        cmds.push(quote! {
-                            Self::#ident => (0, tracker),
-                        });
+
+                    #[allow(unused_mut, unused_variables)]
+                    fn borrowed_size(&self) -> ::core::primitive::usize {
+                        match self {
+                            #(#borrowed_arms)*
+                        }
                     }
-                }
-            }
 
-            // Build the trait implementation
-            let gen = quote! {
-                impl #impl_generics GetSize for #name #ty_generics #where_clause {
-                    fn get_heap_size(&self) -> usize {
-                        let tracker = get_size::StandardTracker::default();
+                    #[allow(unused_variables)]
+                    fn report_size(
+                        &self,
+                        path: &::core::primitive::str,
+                        report: &mut #crate_path::SizeReport,
+                    ) {
+                        match self {
+                            #(#size_report_arms)*
+                        }
 
-                        let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+                        #extra_report
+                    }
 
-                        total
+                    #[allow(unused_variables)]
+                    fn record_allocations(
+                        &self,
+                        path: &::core::primitive::str,
+                        finder: &mut #crate_path::AllocationFinder,
+                    ) {
+                        match self {
+                            #(#allocation_arms)*
+                        }
                     }
 
-                    fn get_heap_size_with_tracker<TRACKER: get_size::GetSizeTracker>(
+                    #[allow(unused_variables)]
+                    fn accumulate_heap_size<ACCUMULATOR: #crate_path::SizeAccumulator>(
                         &self,
-                        tracker: TRACKER,
-                    ) -> (usize, TRACKER) {
+                        accumulator: &mut ACCUMULATOR,
+                    ) {
                         match self {
-                            #(#cmds)*
+                            #(#accumulate_arms)*
                         }
+
+                        #extra_accumulate
                     }
                 }
+
+                #try_impl
+
+                #padding_impl
+
+                #introspect_impl
+
+                #layout_impl
             };
-            return gen.into();
+
+            Ok(gen.into())
         }
-        syn::Data::Union(_data_union) => panic!("Deriving GetSize for unions is currently not supported."),
+        syn::Data::Union(data_union) => Err(syn::Error::new(
+            data_union.union_token.span,
+            "deriving GetSize for unions is currently not supported",
+        )),
         syn::Data::Struct(data_struct) => {
-            if data_struct.fields.is_empty() {
+            if data_struct.fields.is_empty() && extra_size.is_none() {
                 // Empty structs are easy to implement.
                 let gen = quote! {
-                    impl GetSize for #name {}
+                    impl #impl_generics #crate_path::GetSize for #name #ty_generics #where_clause {
+                        const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+                    }
+
+                    #try_impl
+
+                    #padding_impl
+
+                    #introspect_impl
                 };
-                return gen.into();
+                return Ok(gen.into());
             }
 
             let mut cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut field_fns = Vec::with_capacity(data_struct.fields.len());
+            let mut iterative_cmds = Vec::new();
+            let mut iterative_wasted_cmds = Vec::new();
+            let mut iterative_visit_cmds = Vec::new();
+            let mut iterative_paths = Vec::new();
+            let mut iterative_allocation_cmds = Vec::new();
+            let mut wasted_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut report_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut payload_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut borrowed_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut size_report_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut allocation_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut accumulate_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut within_cmds = Vec::with_capacity(data_struct.fields.len());
+            let mut heap_free_reads = Vec::new();
+
+            // With iterative fields, the fields of all nested objects get measured one after another
+            // by a loop, which refers to the currently measured object as `node`.
+            let mut iterative = false;
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                iterative |= struct_field_attribute(field, index, &attributes)?.iterative;
+            }
+            let receiver = match iterative {
+                true => quote! { node },
+                false => quote! { self },
+            };
 
-            let mut unidentified_fields_count = 0; // For newtypes
-
-            for field in data_struct.fields.iter() {
+            for (index, field) in data_struct.fields.iter().enumerate() {
+                // The field is either accessed by its name or, in case of a tuple struct, by its index.
+                let member = match field.ident.as_ref() {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(index)),
+                };
 
                 // Parse all relevant attributes.
-                let attr = StructFieldAttribute::from_attributes(&field.attrs).unwrap();
+                let attr = struct_field_attribute(field, index, &attributes)?;
 
-                // NOTE There will be no attributes if this is a tuple struct.
-                if let Some(size) = attr.size {
-                    cmds.push(quote! {
-                        total += #size;
-                    });
+                // Fields which never own heap memory do not contribute anything, they only get
+                // read, so they do not count as dead code.
+                if attr.is_heap_free(&field.ty) {
+                    heap_free_reads.push(quote! { let _ = &self.#member; });
 
                     continue;
-                } else if let Some(size_fn) = attr.size_fn {
-                    let ident = field.ident.as_ref().unwrap();
+                }
 
-                    cmds.push(quote! {
-                        total += #size_fn(&self.#ident);
-                    });
+                let label = match (&attr.rename, &member) {
+                    (Some(rename), _) => rename.clone(),
+                    (None, syn::Member::Named(ident)) => ident.to_string(),
+                    (None, syn::Member::Unnamed(index)) => index.index.to_string(),
+                };
+
+                if attr.iterative {
+                    iterative_cmds.push(attr.gate(quote! {
+                        total += #crate_path::IterativeField::<Self>::visit_nodes(&node.#member, &mut pending);
+                    }));
+                    iterative_wasted_cmds.push(attr.gate(quote! {
+                        total += #crate_path::IterativeField::<Self>::spare_capacity(&node.#member);
+                        #crate_path::IterativeField::<Self>::visit_nodes(&node.#member, &mut pending);
+                    }));
+                    iterative_visit_cmds.push(attr.gate(quote! {
+                        #crate_path::IterativeField::<Self>::visit_nodes(&node.#member, &mut pending);
+                    }));
+
+                    // All nested objects are recorded under the path of the field of `self` holding them.
+                    let field_path = format_ident!("path_{}", index);
+                    iterative_paths.push(attr.gate(quote! {
+                        let #field_path = #crate_path::SizeReport::join(path, #label);
+                    }));
+                    iterative_allocation_cmds.push(attr.gate(quote! {
+                        #crate_path::IterativeField::<Self>::record_nodes(&node.#member, &#field_path, finder, &mut nested);
+                        pending.extend(nested.drain(..).map(|nested| (nested, #field_path.as_str())));
+                    }));
+                } else if let Some(cmd) = field_cmd(&attr, &quote! { &#receiver.#member }, crate_path) {
+                    cmds.push(attr.gate_tracked(cmd));
+
+                    if let Some(cmd) = field_cmd(&attr, &quote! { &value.#member }, crate_path) {
+                        let cmd = attr.gate_tracked(cmd);
+
+                        field_fns.push(quote! {
+                            |value: &Self, tracker: TRACKER| {
+                                let mut total = 0;
+
+                                #cmd
+
+                                (total, tracker)
+                            }
+                        });
+                    }
+                }
+
+                if let Some(cmd) = size_report_cmd(&attr, &quote! { &self.#member }, &label, crate_path) {
+                    size_report_cmds.push(attr.gate(cmd));
+                }
+
+                if let Some(cmd) = accumulate_cmd(&attr, &quote! { &self.#member }, crate_path) {
+                    accumulate_cmds.push(attr.gate(cmd));
+                }
+
+                if let Some(cmd) = within_cmd(&attr, &quote! { &self.#member }, crate_path) {
+                    within_cmds.push(attr.gate(cmd));
+                }
+
+                if attr.iterative {
+                    // Listed as a whole, which gets measured by the worklist of the nested objects.
+                    report_cmds.push(attr.gate(quote! {
+                        report.add(
+                            &#crate_path::CapacityReport::join(path, #label),
+                            #crate_path::GetSize::wasted_capacity(&self.#member),
+                        );
+                    }));
 
-                    continue;
-                } else if attr.ignore {
                     continue;
                 }
 
-                if let Some(ident) = field.ident.as_ref() {
-                    cmds.push(quote! {
-                        let (total_add, tracker) = GetSize::get_heap_size_with_tracker(&self.#ident, tracker);
-                        total += total_add;
-                    });
-                } else {
-                    let current_index = syn::Index::from(unidentified_fields_count);
-                    cmds.push(quote! {
-                        let (total_add, tracker) = GetSize::get_heap_size_with_tracker(&self.#current_index, tracker);
-                        total += total_add;
-                    });
+                if let Some(cmd) = allocation_cmd(&attr, &quote! { &#receiver.#member }, &field.ty, &label, crate_path) {
+                    allocation_cmds.push(attr.gate(cmd));
+                }
+
+                if let Some(analysis) = analysis_cmds(&attr, &quote! { &#receiver.#member }, &label, crate_path) {
+                    wasted_cmds.push(attr.gate(analysis.wasted));
+                    payload_cmds.push(attr.gate(analysis.payloads));
+                    borrowed_cmds.push(attr.gate(analysis.borrowed));
+                }
 
-                    unidentified_fields_count += 1;
+                if let Some(analysis) = analysis_cmds(&attr, &quote! { &self.#member }, &label, crate_path) {
+                    report_cmds.push(attr.gate(analysis.report));
                 }
             }
 
+            // Types with many fields measure each of them by a function of its own, so no huge
+            // function needs to be compiled.
+            let out_of_line = !iterative && field_fns.len()>OUT_OF_LINE_FIELDS;
+            let field_count = field_fns.len();
+
+            // The fixed overhead gets added for every instance, including the nested ones of iterative fields.
+            let extra_node_cmd = attributes.heap_extra_size(&receiver).map(|extra_size| quote! {
+                total += #extra_size;
+            });
+            let initial_total = heap_extra_size.clone().unwrap_or_else(|| quote! { 0 });
+            let out_of_line_sum = match &heap_extra_size {
+                Some(extra_size) => quote! {
+                    let (total, tracker) = #crate_path::runtime::sum_fields(self, &fields, tracker);
+
+                    (total + #extra_size, tracker)
+                },
+                None => quote! {
+                    #crate_path::runtime::sum_fields(self, &fields, tracker)
+                },
+            };
+
+            let heap_size_with_tracker = match iterative {
+                // Nested objects found inside iterative fields are put onto a worklist, instead of
+                // measuring them recursively, so long chains of them can not overflow the stack.
+                true => quote! {
+                    fn get_heap_size_with_tracker<TRACKER: #crate_path::GetSizeTracker>(
+                        &self,
+                        tracker: TRACKER,
+                    ) -> (::core::primitive::usize, TRACKER) {
+                        #(#heap_free_reads)*
+
+                        let mut total = 0;
+                        let mut pending: ::std::vec::Vec<&Self> = ::std::vec::Vec::new();
+                        let mut node = self;
+                        let mut state = tracker;
+
+                        loop {
+                            if #crate_path::runtime::is_cancelled(&mut state) {
+                                break;
+                            }
+
+                            let tracker = state;
+
+                            #extra_node_cmd
+
+                            #(#cmds)*
+
+                            #(#iterative_cmds)*
+
+                            state = tracker;
+
+                            match pending.pop() {
+                                ::core::option::Option::Some(next) => node = next,
+                                ::core::option::Option::None => break,
+                            }
+                        }
+
+                        (total, state)
+                    }
+                },
+                false if out_of_line => quote! {
+                    fn get_heap_size_with_tracker<TRACKER: #crate_path::GetSizeTracker>(
+                        &self,
+                        tracker: TRACKER,
+                    ) -> (::core::primitive::usize, TRACKER) {
+                        #(#heap_free_reads)*
+
+                        let fields: [#crate_path::runtime::FieldFn<Self, TRACKER>; #field_count] = [
+                            #(#field_fns,)*
+                        ];
+
+                        #out_of_line_sum
+                    }
+                },
+                false => quote! {
+                    fn get_heap_size_with_tracker<TRACKER: #crate_path::GetSizeTracker>(
+                        &self,
+                        mut tracker: TRACKER,
+                    ) -> (::core::primitive::usize, TRACKER) {
+                        #(#heap_free_reads)*
+
+                        if #crate_path::runtime::is_cancelled(&mut tracker) {
+                            return (0, tracker);
+                        }
+
+                        let mut total = #initial_total;
+
+                        #(#cmds)*;
+
+                        (total, tracker)
+                    }
+                },
+            };
+
+            // The analysis methods visit the fields of all nested objects one after another as well.
+            let worklist = |cmds: TokenStream2| match iterative {
+                true => quote! {
+                    let mut pending: ::std::vec::Vec<&Self> = ::std::vec::Vec::new();
+                    let mut node = self;
+
+                    loop {
+                        #cmds
+
+                        match pending.pop() {
+                            ::core::option::Option::Some(next) => node = next,
+                            ::core::option::Option::None => break,
+                        }
+                    }
+                },
+                false => cmds,
+            };
+            let wasted_body = worklist(quote! {
+                #(#wasted_cmds)*
+
+                #(#iterative_wasted_cmds)*
+            });
+            let payload_body = worklist(quote! {
+                #(#payload_cmds)*
+
+                #(#iterative_visit_cmds)*
+            });
+            let borrowed_body = worklist(quote! {
+                #(#borrowed_cmds)*
+
+                #(#iterative_visit_cmds)*
+            });
+            let allocation_body = match iterative {
+                true => quote! {
+                    #(#iterative_paths)*
+
+                    let mut pending: ::std::vec::Vec<(&Self, &::core::primitive::str)> = ::std::vec::Vec::new();
+                    let mut nested: ::std::vec::Vec<&Self> = ::std::vec::Vec::new();
+                    let mut node = self;
+                    let mut path = path;
+
+                    loop {
+                        #(#allocation_cmds)*
+
+                        #(#iterative_allocation_cmds)*
+
+                        match pending.pop() {
+                            ::core::option::Option::Some((next, next_path)) => {
+                                node = next;
+                                path = next_path;
+                            },
+                            ::core::option::Option::None => break,
+                        }
+                    }
+                },
+                false => quote! {
+                    #(#allocation_cmds)*
+                },
+            };
+
+            // Iterative fields are only measured as a whole, by the worklist of `get_heap_size`.
+            let accumulate_heap_size = match iterative {
+                true => TokenStream2::new(),
+                false => quote! {
+                    #[allow(unused_variables)]
+                    fn accumulate_heap_size<ACCUMULATOR: #crate_path::SizeAccumulator>(
+                        &self,
+                        accumulator: &mut ACCUMULATOR,
+                    ) {
+                        #(#accumulate_cmds)*
+
+                        #extra_accumulate
+                    }
+                },
+            };
+
+            // Iterative fields are only measured as a whole, and a custom tracker might measure
+            // differently, so the bounded measurement falls back to `get_heap_size` for both.
+            let extra_within = heap_extra_size.as_ref().map(within_add);
+            let heap_size_within = match iterative || attributes.tracker.is_some() {
+                true => TokenStream2::new(),
+                false => quote! {
+                    #[allow(unused_mut)]
+                    fn get_heap_size_within(
+                        &self,
+                        limit: ::core::primitive::usize,
+                    ) -> ::core::option::Option<::core::primitive::usize> {
+                        let mut total: ::core::primitive::usize = 0;
+
+                        #extra_within
+
+                        #(#within_cmds)*
+
+                        ::core::option::Option::Some(total)
+                    }
+                },
+            };
+
             // Build the trait implementation
             let gen = quote! {
-                impl #impl_generics GetSize for #name #ty_generics #where_clause {
-                    fn get_heap_size(&self) -> usize {
-                        let tracker = get_size::StandardTracker::default();
+                impl #impl_generics #crate_path::GetSize for #name #ty_generics #where_clause {
+                    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = #can_allocate_heap;
+
+                    fn get_heap_size(&self) -> ::core::primitive::usize {
+                        let tracker = #tracker;
+
+                        let (total, _) = #crate_path::GetSize::get_heap_size_with_tracker(self, tracker);
+
+                        total
+                    }
 
-                        let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+                    #heap_size_with_tracker
+
+                    #heap_size_within
+
+                    #[allow(unused_mut, unused_variables)]
+                    fn wasted_capacity(&self) -> ::core::primitive::usize {
+                        let mut total = 0;
+
+                        #wasted_body
 
                         total
                     }
 
-                    fn get_heap_size_with_tracker<TRACKER: get_size::GetSizeTracker>(
+                    #[allow(unused_variables)]
+                    fn report_wasted_capacity(
                         &self,
-                        tracker: TRACKER,
-                    ) -> (usize, TRACKER) {
+                        path: &::core::primitive::str,
+                        report: &mut #crate_path::CapacityReport,
+                    ) {
+                        #(#report_cmds)*
+                    }
+
+                    #[allow(unused_variables)]
+                    fn record_payloads(&self, estimator: &mut #crate_path::DedupEstimator) {
+                        #payload_body
+                    }
+
+                    #[allow(unused_mut)]
+                    fn borrowed_size(&self) -> ::core::primitive::usize {
                         let mut total = 0;
 
-                        #(#cmds)*;
+                        #borrowed_body
 
-                        (total, tracker)
+                        total
+                    }
+
+                    #[allow(unused_variables)]
+                    fn report_size(
+                        &self,
+                        path: &::core::primitive::str,
+                        report: &mut #crate_path::SizeReport,
+                    ) {
+                        #(#size_report_cmds)*
+
+                        #extra_report
                     }
+
+                    #[allow(unused_variables)]
+                    fn record_allocations(
+                        &self,
+                        path: &::core::primitive::str,
+                        finder: &mut #crate_path::AllocationFinder,
+                    ) {
+                        #allocation_body
+                    }
+
+                    #accumulate_heap_size
                 }
+
+                #try_impl
+
+                #padding_impl
+
+                #introspect_impl
             };
-            return gen.into();
+
+            Ok(gen.into())
         },
     }
 }
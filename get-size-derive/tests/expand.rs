@@ -0,0 +1,8 @@
+// Compares the code generated by the derive macro against the committed expansions.
+//
+// Requires `cargo expand`. After an intended change of the generated code, the expansions
+// can be regenerated by running `MACROTEST=overwrite cargo test --test expand`.
+#[test]
+fn expand() {
+    macrotest::expand("tests/expand/*.rs");
+}
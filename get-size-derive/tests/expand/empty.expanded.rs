@@ -0,0 +1,13 @@
+use get_size::GetSize;
+
+pub struct Unit;
+impl ::get_size::GetSize for Unit {
+    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+}
+
+pub enum Never {}
+impl ::get_size::GetSize for Never {
+    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+}
+
+fn main() {}
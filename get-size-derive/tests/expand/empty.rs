@@ -0,0 +1,9 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+pub struct Unit;
+
+#[derive(GetSize)]
+pub enum Never {}
+
+fn main() {}
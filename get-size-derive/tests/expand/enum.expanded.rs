@@ -0,0 +1,193 @@
+use get_size::GetSize;
+
+#[get_size(layout)]
+pub enum Message {
+    Quit,
+    Move {
+        x: i32,
+        y: i32,
+    },
+    Write(String),
+
+    #[get_size(ignore)]
+    Raw(Vec<u8>),
+}
+impl ::get_size::GetSize for Message {
+    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false
+        || <i32 as ::get_size::GetSize>::CAN_ALLOCATE_HEAP
+        || <i32 as ::get_size::GetSize>::CAN_ALLOCATE_HEAP
+        || <String as ::get_size::GetSize>::CAN_ALLOCATE_HEAP;
+    fn get_heap_size(&self) -> ::core::primitive::usize {
+        let tracker = <::get_size::StandardTracker as ::core::default::Default>::default();
+        let (total, _) = ::get_size::GetSize::get_heap_size_with_tracker(self, tracker);
+        total
+    }
+    fn get_heap_size_with_tracker<TRACKER: ::get_size::GetSizeTracker>(
+        &self,
+        mut tracker: TRACKER,
+    ) -> (::core::primitive::usize, TRACKER) {
+        if ::get_size::runtime::is_cancelled(&mut tracker) {
+            return (0, tracker);
+        }
+        match self {
+            Self::Write(v0) => {
+                let mut total = 0;
+                let (total_add, tracker) =
+                    ::get_size::GetSize::get_heap_size_with_tracker(v0, tracker);
+                total += total_add;
+                (total, tracker)
+            }
+            _ => (0, tracker),
+        }
+    }
+    #[allow(unused_variables)]
+    fn get_heap_size_within(
+        &self,
+        limit: ::core::primitive::usize,
+    ) -> ::core::option::Option<::core::primitive::usize> {
+        let mut total: ::core::primitive::usize = 0;
+        match self {
+            Self::Write(v0) => {
+                total += ::get_size::GetSize::get_heap_size_within(v0, limit - total)?;
+            }
+            _ => {}
+        }
+        ::core::option::Option::Some(total)
+    }
+    #[allow(unused_mut, unused_variables)]
+    fn wasted_capacity(&self) -> ::core::primitive::usize {
+        match self {
+            Self::Write(v0) => {
+                let mut total = 0;
+                total += ::get_size::GetSize::wasted_capacity(v0);
+                total
+            }
+            _ => 0,
+        }
+    }
+    #[allow(unused_variables)]
+    fn report_wasted_capacity(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::CapacityReport,
+    ) {
+        match self {
+            Self::Write(v0) => {
+                ::get_size::GetSize::report_wasted_capacity(
+                    v0,
+                    &::get_size::CapacityReport::join(path, "Write.0"),
+                    report,
+                );
+            }
+            _ => {}
+        }
+    }
+    #[allow(unused_variables)]
+    fn record_payloads(&self, estimator: &mut ::get_size::DedupEstimator) {
+        match self {
+            Self::Write(v0) => {
+                ::get_size::GetSize::record_payloads(v0, estimator);
+            }
+            _ => {}
+        }
+    }
+    #[allow(unused_mut, unused_variables)]
+    fn borrowed_size(&self) -> ::core::primitive::usize {
+        match self {
+            Self::Write(v0) => {
+                let mut total = 0;
+                total += ::get_size::GetSize::borrowed_size(v0);
+                total
+            }
+            _ => 0,
+        }
+    }
+    #[allow(unused_variables)]
+    fn report_size(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::SizeReport,
+    ) {
+        match self {
+            Self::Write(v0) => {
+                ::get_size::GetSize::report_size(
+                    v0,
+                    &::get_size::SizeReport::join(path, "Write.0"),
+                    report,
+                );
+            }
+            _ => {}
+        }
+    }
+    #[allow(unused_variables)]
+    fn record_allocations(
+        &self,
+        path: &::core::primitive::str,
+        finder: &mut ::get_size::AllocationFinder,
+    ) {
+        match self {
+            Self::Write(v0) => {
+                ::get_size::GetSize::record_allocations(
+                    v0,
+                    &::get_size::SizeReport::join(path, "Write.0"),
+                    finder,
+                );
+            }
+            _ => {}
+        }
+    }
+    #[allow(unused_variables)]
+    fn accumulate_heap_size<ACCUMULATOR: ::get_size::SizeAccumulator>(
+        &self,
+        accumulator: &mut ACCUMULATOR,
+    ) {
+        match self {
+            Self::Write(v0) => {
+                ::get_size::GetSize::accumulate_heap_size(v0, accumulator);
+            }
+            _ => {}
+        }
+    }
+}
+impl ::get_size::EnumLayout for Message {
+    fn variant_stack_overhead() -> ::core::primitive::usize {
+        let mut largest = 0;
+        let payload_size = ::core::mem::size_of::<()>();
+        if payload_size > largest {
+            largest = payload_size;
+        }
+        let payload_size = ::core::mem::size_of::<(i32, i32)>();
+        if payload_size > largest {
+            largest = payload_size;
+        }
+        let payload_size = ::core::mem::size_of::<(String,)>();
+        if payload_size > largest {
+            largest = payload_size;
+        }
+        let payload_size = ::core::mem::size_of::<(Vec<u8>,)>();
+        if payload_size > largest {
+            largest = payload_size;
+        }
+        ::core::mem::size_of::<Self>().saturating_sub(largest)
+    }
+    fn variant_payload_size(&self) -> ::core::primitive::usize {
+        match *self {
+            Self::Quit { .. } => ::core::mem::size_of::<()>(),
+            Self::Move { .. } => ::core::mem::size_of::<(i32, i32)>(),
+            Self::Write { .. } => ::core::mem::size_of::<(String,)>(),
+            Self::Raw { .. } => ::core::mem::size_of::<(Vec<u8>,)>(),
+        }
+    }
+    fn variant_stack_sizes() -> ::std::vec::Vec<
+        (&'static ::core::primitive::str, ::core::primitive::usize),
+    > {
+        ::core::convert::From::from([
+            ("Quit", ::core::mem::size_of::<()>()),
+            ("Move", ::core::mem::size_of::<(i32, i32)>()),
+            ("Write", ::core::mem::size_of::<(String,)>()),
+            ("Raw", ::core::mem::size_of::<(Vec<u8>,)>()),
+        ])
+    }
+}
+
+fn main() {}
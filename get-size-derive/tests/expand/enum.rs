@@ -0,0 +1,13 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+#[get_size(layout)]
+pub enum Message {
+    Quit,
+    Move { x: i32, y: i32 },
+    Write(String),
+    #[get_size(ignore)]
+    Raw(Vec<u8>),
+}
+
+fn main() {}
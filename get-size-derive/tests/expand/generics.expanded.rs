@@ -0,0 +1,128 @@
+use std::marker::PhantomData;
+
+use get_size::GetSize;
+
+#[get_size(ignore(M))]
+pub struct Cache<K, V, M> {
+    entries: Vec<(K, V)>,
+    marker: PhantomData<M>,
+}
+impl<K, V, M> ::get_size::GetSize for Cache<K, V, M>
+where
+    Vec<(K, V)>: ::get_size::GetSize,
+{
+    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false
+        || <Vec<(K, V)> as ::get_size::GetSize>::CAN_ALLOCATE_HEAP
+        || <PhantomData<M> as ::get_size::GetSize>::CAN_ALLOCATE_HEAP;
+    fn get_heap_size(&self) -> ::core::primitive::usize {
+        let tracker = <::get_size::StandardTracker as ::core::default::Default>::default();
+        let (total, _) = ::get_size::GetSize::get_heap_size_with_tracker(self, tracker);
+        total
+    }
+    fn get_heap_size_with_tracker<TRACKER: ::get_size::GetSizeTracker>(
+        &self,
+        mut tracker: TRACKER,
+    ) -> (::core::primitive::usize, TRACKER) {
+        if ::get_size::runtime::is_cancelled(&mut tracker) {
+            return (0, tracker);
+        }
+        let mut total = 0;
+        let (total_add, tracker) =
+            ::get_size::GetSize::get_heap_size_with_tracker(&self.entries, tracker);
+        total += total_add;
+        let (total_add, tracker) =
+            ::get_size::GetSize::get_heap_size_with_tracker(&self.marker, tracker);
+        total += total_add;
+        (total, tracker)
+    }
+    #[allow(unused_mut)]
+    fn get_heap_size_within(
+        &self,
+        limit: ::core::primitive::usize,
+    ) -> ::core::option::Option<::core::primitive::usize> {
+        let mut total: ::core::primitive::usize = 0;
+        total += ::get_size::GetSize::get_heap_size_within(&self.entries, limit - total)?;
+        total += ::get_size::GetSize::get_heap_size_within(&self.marker, limit - total)?;
+        ::core::option::Option::Some(total)
+    }
+    #[allow(unused_mut, unused_variables)]
+    fn wasted_capacity(&self) -> ::core::primitive::usize {
+        let mut total = 0;
+        total += ::get_size::GetSize::wasted_capacity(&self.entries);
+        total += ::get_size::GetSize::wasted_capacity(&self.marker);
+        total
+    }
+    #[allow(unused_variables)]
+    fn report_wasted_capacity(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::CapacityReport,
+    ) {
+        ::get_size::GetSize::report_wasted_capacity(
+            &self.entries,
+            &::get_size::CapacityReport::join(path, "entries"),
+            report,
+        );
+        ::get_size::GetSize::report_wasted_capacity(
+            &self.marker,
+            &::get_size::CapacityReport::join(path, "marker"),
+            report,
+        );
+    }
+    #[allow(unused_variables)]
+    fn record_payloads(&self, estimator: &mut ::get_size::DedupEstimator) {
+        ::get_size::GetSize::record_payloads(&self.entries, estimator);
+        ::get_size::GetSize::record_payloads(&self.marker, estimator);
+    }
+    #[allow(unused_mut)]
+    fn borrowed_size(&self) -> ::core::primitive::usize {
+        let mut total = 0;
+        total += ::get_size::GetSize::borrowed_size(&self.entries);
+        total += ::get_size::GetSize::borrowed_size(&self.marker);
+        total
+    }
+    #[allow(unused_variables)]
+    fn report_size(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::SizeReport,
+    ) {
+        ::get_size::GetSize::report_size(
+            &self.entries,
+            &::get_size::SizeReport::join(path, "entries"),
+            report,
+        );
+        ::get_size::GetSize::report_size(
+            &self.marker,
+            &::get_size::SizeReport::join(path, "marker"),
+            report,
+        );
+    }
+    #[allow(unused_variables)]
+    fn record_allocations(
+        &self,
+        path: &::core::primitive::str,
+        finder: &mut ::get_size::AllocationFinder,
+    ) {
+        ::get_size::GetSize::record_allocations(
+            &self.entries,
+            &::get_size::SizeReport::join(path, "entries"),
+            finder,
+        );
+        ::get_size::GetSize::record_allocations(
+            &self.marker,
+            &::get_size::SizeReport::join(path, "marker"),
+            finder,
+        );
+    }
+    #[allow(unused_variables)]
+    fn accumulate_heap_size<ACCUMULATOR: ::get_size::SizeAccumulator>(
+        &self,
+        accumulator: &mut ACCUMULATOR,
+    ) {
+        ::get_size::GetSize::accumulate_heap_size(&self.entries, accumulator);
+        ::get_size::GetSize::accumulate_heap_size(&self.marker, accumulator);
+    }
+}
+
+fn main() {}
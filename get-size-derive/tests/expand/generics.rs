@@ -0,0 +1,12 @@
+use std::marker::PhantomData;
+
+use get_size::GetSize;
+
+#[derive(GetSize)]
+#[get_size(ignore(M))]
+pub struct Cache<K, V, M> {
+    entries: Vec<(K, V)>,
+    marker: PhantomData<M>,
+}
+
+fn main() {}
@@ -0,0 +1,115 @@
+use get_size::GetSize;
+
+pub struct Person {
+    name: String,
+    age: u8,
+    #[get_size(size = 32)]
+    avatar: Vec<u8>,
+    #[get_size(ignore)]
+    session: u64,
+}
+impl ::get_size::GetSize for Person {
+    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false
+        || <String as ::get_size::GetSize>::CAN_ALLOCATE_HEAP
+        || <u8 as ::get_size::GetSize>::CAN_ALLOCATE_HEAP
+        || true;
+    fn get_heap_size(&self) -> ::core::primitive::usize {
+        let tracker = <::get_size::StandardTracker as ::core::default::Default>::default();
+        let (total, _) = ::get_size::GetSize::get_heap_size_with_tracker(self, tracker);
+        total
+    }
+    fn get_heap_size_with_tracker<TRACKER: ::get_size::GetSizeTracker>(
+        &self,
+        mut tracker: TRACKER,
+    ) -> (::core::primitive::usize, TRACKER) {
+        let _ = &self.age;
+        let _ = &self.session;
+        if ::get_size::runtime::is_cancelled(&mut tracker) {
+            return (0, tracker);
+        }
+        let mut total = 0;
+        let (total_add, tracker) =
+            ::get_size::GetSize::get_heap_size_with_tracker(&self.name, tracker);
+        total += total_add;
+        total += 32usize;
+        (total, tracker)
+    }
+    #[allow(unused_mut)]
+    fn get_heap_size_within(
+        &self,
+        limit: ::core::primitive::usize,
+    ) -> ::core::option::Option<::core::primitive::usize> {
+        let mut total: ::core::primitive::usize = 0;
+        total += ::get_size::GetSize::get_heap_size_within(&self.name, limit - total)?;
+        total = total.checked_add(32usize).filter(|total| *total <= limit)?;
+        ::core::option::Option::Some(total)
+    }
+    #[allow(unused_mut, unused_variables)]
+    fn wasted_capacity(&self) -> ::core::primitive::usize {
+        let mut total = 0;
+        total += ::get_size::GetSize::wasted_capacity(&self.name);
+        total
+    }
+    #[allow(unused_variables)]
+    fn report_wasted_capacity(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::CapacityReport,
+    ) {
+        ::get_size::GetSize::report_wasted_capacity(
+            &self.name,
+            &::get_size::CapacityReport::join(path, "name"),
+            report,
+        );
+    }
+    #[allow(unused_variables)]
+    fn record_payloads(&self, estimator: &mut ::get_size::DedupEstimator) {
+        ::get_size::GetSize::record_payloads(&self.name, estimator);
+    }
+    #[allow(unused_mut)]
+    fn borrowed_size(&self) -> ::core::primitive::usize {
+        let mut total = 0;
+        total += ::get_size::GetSize::borrowed_size(&self.name);
+        total
+    }
+    #[allow(unused_variables)]
+    fn report_size(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::SizeReport,
+    ) {
+        ::get_size::GetSize::report_size(
+            &self.name,
+            &::get_size::SizeReport::join(path, "name"),
+            report,
+        );
+        report.add(&::get_size::SizeReport::join(path, "avatar"), 32usize);
+    }
+    #[allow(unused_variables)]
+    fn record_allocations(
+        &self,
+        path: &::core::primitive::str,
+        finder: &mut ::get_size::AllocationFinder,
+    ) {
+        ::get_size::GetSize::record_allocations(
+            &self.name,
+            &::get_size::SizeReport::join(path, "name"),
+            finder,
+        );
+        finder.record(
+            &::get_size::SizeReport::join(path, "avatar"),
+            ::core::any::type_name::<Vec<u8>>(),
+            32usize,
+        );
+    }
+    #[allow(unused_variables)]
+    fn accumulate_heap_size<ACCUMULATOR: ::get_size::SizeAccumulator>(
+        &self,
+        accumulator: &mut ACCUMULATOR,
+    ) {
+        ::get_size::GetSize::accumulate_heap_size(&self.name, accumulator);
+        ::get_size::SizeAccumulator::add(accumulator, 32usize);
+    }
+}
+
+fn main() {}
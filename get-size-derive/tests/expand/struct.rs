@@ -0,0 +1,13 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+pub struct Person {
+    name: String,
+    age: u8,
+    #[get_size(size = 32)]
+    avatar: Vec<u8>,
+    #[get_size(ignore)]
+    session: u64,
+}
+
+fn main() {}
@@ -0,0 +1,97 @@
+use get_size::GetSize;
+
+pub struct Wrapper(String, u32);
+impl ::get_size::GetSize for Wrapper {
+    const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false
+        || <String as ::get_size::GetSize>::CAN_ALLOCATE_HEAP
+        || <u32 as ::get_size::GetSize>::CAN_ALLOCATE_HEAP;
+    fn get_heap_size(&self) -> ::core::primitive::usize {
+        let tracker = <::get_size::StandardTracker as ::core::default::Default>::default();
+        let (total, _) = ::get_size::GetSize::get_heap_size_with_tracker(self, tracker);
+        total
+    }
+    fn get_heap_size_with_tracker<TRACKER: ::get_size::GetSizeTracker>(
+        &self,
+        mut tracker: TRACKER,
+    ) -> (::core::primitive::usize, TRACKER) {
+        let _ = &self.1;
+        if ::get_size::runtime::is_cancelled(&mut tracker) {
+            return (0, tracker);
+        }
+        let mut total = 0;
+        let (total_add, tracker) =
+            ::get_size::GetSize::get_heap_size_with_tracker(&self.0, tracker);
+        total += total_add;
+        (total, tracker)
+    }
+    #[allow(unused_mut)]
+    fn get_heap_size_within(
+        &self,
+        limit: ::core::primitive::usize,
+    ) -> ::core::option::Option<::core::primitive::usize> {
+        let mut total: ::core::primitive::usize = 0;
+        total += ::get_size::GetSize::get_heap_size_within(&self.0, limit - total)?;
+        ::core::option::Option::Some(total)
+    }
+    #[allow(unused_mut, unused_variables)]
+    fn wasted_capacity(&self) -> ::core::primitive::usize {
+        let mut total = 0;
+        total += ::get_size::GetSize::wasted_capacity(&self.0);
+        total
+    }
+    #[allow(unused_variables)]
+    fn report_wasted_capacity(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::CapacityReport,
+    ) {
+        ::get_size::GetSize::report_wasted_capacity(
+            &self.0,
+            &::get_size::CapacityReport::join(path, "0"),
+            report,
+        );
+    }
+    #[allow(unused_variables)]
+    fn record_payloads(&self, estimator: &mut ::get_size::DedupEstimator) {
+        ::get_size::GetSize::record_payloads(&self.0, estimator);
+    }
+    #[allow(unused_mut)]
+    fn borrowed_size(&self) -> ::core::primitive::usize {
+        let mut total = 0;
+        total += ::get_size::GetSize::borrowed_size(&self.0);
+        total
+    }
+    #[allow(unused_variables)]
+    fn report_size(
+        &self,
+        path: &::core::primitive::str,
+        report: &mut ::get_size::SizeReport,
+    ) {
+        ::get_size::GetSize::report_size(
+            &self.0,
+            &::get_size::SizeReport::join(path, "0"),
+            report,
+        );
+    }
+    #[allow(unused_variables)]
+    fn record_allocations(
+        &self,
+        path: &::core::primitive::str,
+        finder: &mut ::get_size::AllocationFinder,
+    ) {
+        ::get_size::GetSize::record_allocations(
+            &self.0,
+            &::get_size::SizeReport::join(path, "0"),
+            finder,
+        );
+    }
+    #[allow(unused_variables)]
+    fn accumulate_heap_size<ACCUMULATOR: ::get_size::SizeAccumulator>(
+        &self,
+        accumulator: &mut ACCUMULATOR,
+    ) {
+        ::get_size::GetSize::accumulate_heap_size(&self.0, accumulator);
+    }
+}
+
+fn main() {}
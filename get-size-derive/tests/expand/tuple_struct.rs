@@ -0,0 +1,6 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+pub struct Wrapper(String, u32);
+
+fn main() {}
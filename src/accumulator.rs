@@ -0,0 +1,80 @@
+use std::num::Saturating;
+
+
+
+/// Sums up the sizes determined by [`accumulate_heap_size`](crate::GetSize::accumulate_heap_size).
+///
+/// Sizes are added one object at a time, so the total is never summed up as a `usize`. This
+/// allows to measure objects whose accounted size exceeds `usize::MAX` on 32-bit targets like
+/// `wasm32`, e.g. because shared data is accounted for multiple times, by accumulating into a
+/// [`u64`], a [`Saturating`] or a checked [`Option`], which becomes `None` on overflow.
+///
+/// # Example
+///
+/// ```rust
+/// use std::num::Saturating;
+/// use get_size::GetSize;
+///
+/// let value = vec![String::from("Hello"), String::from("World")];
+///
+/// assert_eq!(value.get_heap_size_as::<u64>(), value.get_heap_size() as u64);
+/// assert_eq!(value.get_heap_size_as::<Saturating<usize>>(), Saturating(value.get_heap_size()));
+/// assert_eq!(value.get_heap_size_as::<Option<usize>>(), Some(value.get_heap_size()));
+/// ```
+pub trait SizeAccumulator {
+    /// The accumulator before anything was added.
+    const ZERO: Self;
+
+    /// Adds `bytes` to the accumulated size.
+    fn add(&mut self, bytes: usize);
+}
+
+/// Panics on overflow in debug builds, just like summing up sizes by [`get_heap_size`](crate::GetSize::get_heap_size).
+impl SizeAccumulator for usize {
+    const ZERO: Self = 0;
+
+    fn add(&mut self, bytes: usize) {
+        *self += bytes;
+    }
+}
+
+impl SizeAccumulator for u64 {
+    const ZERO: Self = 0;
+
+    fn add(&mut self, bytes: usize) {
+        *self += bytes as u64;
+    }
+}
+
+impl SizeAccumulator for u128 {
+    const ZERO: Self = 0;
+
+    fn add(&mut self, bytes: usize) {
+        *self += bytes as u128;
+    }
+}
+
+impl SizeAccumulator for Saturating<usize> {
+    const ZERO: Self = Saturating(0);
+
+    fn add(&mut self, bytes: usize) {
+        *self += bytes;
+    }
+}
+
+impl SizeAccumulator for Saturating<u64> {
+    const ZERO: Self = Saturating(0);
+
+    fn add(&mut self, bytes: usize) {
+        *self += bytes as u64;
+    }
+}
+
+/// Becomes `None` as soon as the accumulated size overflows a `usize`, and stays `None` afterwards.
+impl SizeAccumulator for Option<usize> {
+    const ZERO: Self = Some(0);
+
+    fn add(&mut self, bytes: usize) {
+        *self = self.and_then(|total| total.checked_add(bytes));
+    }
+}
@@ -0,0 +1,9 @@
+use ::ahash::{AHasher, RandomState};
+
+use crate::GetSize;
+
+
+
+// The random keys are stored inline, so a `RandomState` never owns any heap memory.
+impl GetSize for RandomState { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for AHasher { const CAN_ALLOCATE_HEAP: bool = false; }
@@ -0,0 +1,27 @@
+use ::axum::extract::State;
+use ::axum::{Extension, Json};
+
+use crate::{GetSize, GetSizeTracker};
+
+
+
+// The extractors are thin wrappers, which hold their value inline.
+macro_rules! impl_size_wrapper {
+    ($name:ident) => {
+        impl<T> GetSize for $name<T> where T: GetSize {
+            const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+            fn get_heap_size(&self) -> usize {
+                GetSize::get_heap_size(&self.0)
+            }
+
+            fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, tracker: TR) -> (usize, TR) {
+                GetSize::get_heap_size_with_tracker(&self.0, tracker)
+            }
+        }
+    }
+}
+
+impl_size_wrapper!(State);
+impl_size_wrapper!(Extension);
+impl_size_wrapper!(Json);
@@ -0,0 +1,31 @@
+use ::bitvec::boxed::BitBox;
+use ::bitvec::order::BitOrder;
+use ::bitvec::store::BitStore;
+use ::bitvec::vec::BitVec;
+
+use crate::GetSize;
+
+
+
+// The number of bits stored by a single element of the backing buffer.
+fn bits_of<T>() -> usize {
+    std::mem::size_of::<T>() * 8
+}
+
+/// The backing buffer is accounted for by its capacity in elements of `T`, so a bit-vector
+/// holding `n` bits occupies about `n / 8` bytes, compared to `n` bytes for a `Vec<bool>`.
+impl<T, O> GetSize for BitVec<T, O> where T: BitStore, O: BitOrder {
+    fn get_heap_size(&self) -> usize {
+        self.capacity().div_ceil(bits_of::<T>()) * std::mem::size_of::<T>()
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        GetSize::get_heap_size(self) - std::mem::size_of_val(self.as_raw_slice())
+    }
+}
+
+impl<T, O> GetSize for BitBox<T, O> where T: BitStore, O: BitOrder {
+    fn get_heap_size(&self) -> usize {
+        std::mem::size_of_val(self.as_raw_slice())
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt;
+
+use crate::{GetSize, SizeFormatter, UnitFormatter};
+
+
+
+/// Compares the heap memory owned by objects with the memory they only borrow, like the data
+/// of a [`Cow::Borrowed`](std::borrow::Cow::Borrowed), as determined by
+/// [`borrowed_size`](GetSize::borrowed_size).
+///
+/// This helps to decide whether data like configuration blobs should rather be owned or
+/// `'static`. A report can aggregate any number of objects.
+///
+/// # Example
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use get_size::BorrowReport;
+///
+/// static DEFAULT_CONFIG: &[u8] = b"port = 8080";
+///
+/// let configs: Vec<Cow<'static, [u8]>> = vec![
+///     Cow::Borrowed(DEFAULT_CONFIG),
+///     Cow::Owned(b"port = 9090".to_vec()),
+/// ];
+///
+/// let report = BorrowReport::of(&configs);
+///
+/// assert_eq!(report.borrowed(), 11);
+/// assert_eq!(report.owned(), 2 * std::mem::size_of::<Cow<[u8]>>() + 11);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BorrowReport {
+    owned: usize,
+    borrowed: usize,
+}
+
+impl BorrowReport {
+    /// Creates a new empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a report for the given `value`.
+    pub fn of<T: GetSize>(value: &T) -> Self {
+        let mut report = Self::new();
+
+        report.record(value);
+
+        report
+    }
+
+    /// Adds the heap memory owned by `value` and the memory borrowed by it to this report.
+    pub fn record<T: GetSize>(&mut self, value: &T) {
+        self.owned += GetSize::get_heap_size(value);
+        self.borrowed += GetSize::borrowed_size(value);
+    }
+
+    /// Adds all bytes counted by `other` to this report.
+    pub fn merge(&mut self, other: &Self) {
+        self.owned += other.owned;
+        self.borrowed += other.borrowed;
+    }
+
+    /// Returns the number of bytes owned inside the heap.
+    pub fn owned(&self) -> usize {
+        self.owned
+    }
+
+    /// Returns the number of bytes borrowed.
+    pub fn borrowed(&self) -> usize {
+        self.borrowed
+    }
+
+    /// Returns the total number of bytes, owned or borrowed.
+    pub fn total(&self) -> usize {
+        self.owned + self.borrowed
+    }
+
+    /// Returns the share of borrowed bytes, between 0.0 and 1.0. An empty report returns 0.0.
+    pub fn borrowed_ratio(&self) -> f64 {
+        if self.total()==0 {
+            return 0.0;
+        }
+
+        self.borrowed as f64 / self.total() as f64
+    }
+
+    /// Renders this report just like its [`Display`](fmt::Display) implementation, but formats
+    /// all sizes with the given `formatter`.
+    pub fn render<F: SizeFormatter + ?Sized>(&self, formatter: &F) -> String {
+        format!(
+            "{} owned, {} borrowed",
+            formatter.format_size(self.owned),
+            formatter.format_size(self.borrowed),
+        )
+    }
+}
+
+impl fmt::Display for BorrowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&UnitFormatter::new()))
+    }
+}
+
+impl GetSize for BorrowReport {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
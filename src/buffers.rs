@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::GetSize;
+
+
+
+/// A flat buffer of bytes, whose heap size is simply its capacity.
+///
+/// Collections of such buffers dominate blob caches. Their heap size can be determined by the
+/// helpers [`buffers_heap_size`] and [`buffer_map_heap_size`] in a single pass over the buffers,
+/// without going through the generic [`GetSize`] machinery for every element.
+pub trait ByteBuffer: GetSize {
+    /// Returns the number of bytes allocated for the buffer.
+    fn buffer_capacity(&self) -> usize;
+}
+
+impl ByteBuffer for Vec<u8> {
+    fn buffer_capacity(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl ByteBuffer for String {
+    fn buffer_capacity(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl ByteBuffer for Box<[u8]> {
+    fn buffer_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+impl ByteBuffer for Box<str> {
+    fn buffer_capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Determines the heap size of a vector of byte buffers, like `Vec<Vec<u8>>` or `Vec<String>`.
+///
+/// The result is the same as that of [`get_heap_size`](GetSize::get_heap_size).
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, buffers_heap_size};
+///
+/// let blobs: Vec<Vec<u8>> = vec![vec![0; 128], vec![0; 64]];
+///
+/// assert_eq!(buffers_heap_size(&blobs), blobs.get_heap_size());
+/// ```
+pub fn buffers_heap_size<B: ByteBuffer>(buffers: &Vec<B>) -> usize {
+    let elements: usize = buffers.iter().map(ByteBuffer::buffer_capacity).sum();
+
+    buffers.capacity() * std::mem::size_of::<B>() + elements
+}
+
+/// Determines the heap size of a map with byte buffers as keys and values, like
+/// `HashMap<String, Vec<u8>>`.
+///
+/// The result is the same as that of [`get_heap_size`](GetSize::get_heap_size).
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use get_size::{GetSize, buffer_map_heap_size};
+///
+/// let mut blobs: HashMap<String, Vec<u8>> = HashMap::new();
+/// blobs.insert("logo".into(), vec![0; 1024]);
+///
+/// assert_eq!(buffer_map_heap_size(&blobs), blobs.get_heap_size());
+/// ```
+pub fn buffer_map_heap_size<K, V, S>(map: &HashMap<K, V, S>) -> usize
+where
+    K: ByteBuffer,
+    V: ByteBuffer,
+{
+    let elements: usize = map.iter()
+        .map(|(k, v)| k.buffer_capacity() + v.buffer_capacity())
+        .sum();
+
+    map.capacity() * (std::mem::size_of::<K>() + std::mem::size_of::<V>()) + elements
+}
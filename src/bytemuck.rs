@@ -0,0 +1,44 @@
+/// Implements [`GetSize`](crate::GetSize) for the given types implementing [`bytemuck::Pod`].
+///
+/// Plain old data can not own any heap memory, so the types are treated as only occupying
+/// the stack. The `Pod` bound gets checked at compile time.
+///
+/// A blanket implementation for all `Pod` types is not possible, as it would conflict with
+/// the implementations for the primitive types.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSize;
+///
+/// #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+/// #[repr(C)]
+/// struct Vertex {
+///     position: [f32; 3],
+///     color: u32,
+/// }
+///
+/// get_size::impl_pod!(Vertex);
+///
+/// let vertices = vec![Vertex { position: [0.0; 3], color: 0 }; 10];
+///
+/// assert_eq!(vertices.get_heap_size(), 10 * std::mem::size_of::<Vertex>());
+/// ```
+#[macro_export]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytemuck")))]
+macro_rules! impl_pod {
+    ($($name:ty),* $(,)?) => {
+        $(
+            // Fails to compile if the type does not implement the trait.
+            const _: fn() = {
+                fn assert_pod<T: $crate::__private::bytemuck::Pod>() {}
+
+                assert_pod::<$name>
+            };
+
+            impl $crate::GetSize for $name {
+                const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+            }
+        )*
+    }
+}
@@ -0,0 +1,23 @@
+use ::bytes::{Bytes, BytesMut};
+
+use crate::GetSize;
+
+
+
+/// Only the bytes viewed by this handle are accounted for. Handles sharing the same buffer
+/// are thus accounted for separately, while static buffers are accounted for as well.
+impl GetSize for Bytes {
+    fn get_heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl GetSize for BytesMut {
+    fn get_heap_size(&self) -> usize {
+        self.capacity()
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        self.capacity() - self.len()
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use crate::{GetSize, SizeFormatter, TraversalOrder, UnitFormatter};
+
+
+
+/// Lists the fields of an object which hold spare capacity, together with the number of bytes
+/// which could be reclaimed by shrinking them, as determined by
+/// [`report_wasted_capacity`](GetSize::report_wasted_capacity).
+///
+/// Fields are identified by their path relative to the measured object, e.g. `names` or
+/// `entries.Variant.buffer`. Fields without any spare capacity are not listed.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{CapacityReport, GetSize};
+///
+/// let mut names = Vec::<String>::with_capacity(8);
+/// names.push(String::with_capacity(16));
+///
+/// assert_eq!(names.wasted_capacity(), 7 * std::mem::size_of::<String>() + 16);
+///
+/// let report = CapacityReport::of(&names);
+///
+/// assert_eq!(report.total(), names.wasted_capacity());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapacityReport {
+    entries: Vec<(String, usize)>,
+    order: TraversalOrder,
+}
+
+impl CapacityReport {
+    /// Creates a new empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a report listing the spare capacity found inside `value`.
+    pub fn of<T: GetSize>(value: &T) -> Self {
+        let mut report = Self::new();
+
+        GetSize::report_wasted_capacity(value, "", &mut report);
+
+        report
+    }
+
+    /// Joins the path of an object with the name of one of its fields.
+    pub fn join(path: &str, field: &str) -> String {
+        if path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", path, field)
+        }
+    }
+
+    /// Adds `bytes` of spare capacity found at `path`. Nothing gets added if `bytes` is 0.
+    pub fn add(&mut self, path: &str, bytes: usize) {
+        if bytes>0 {
+            self.entries.push((path.to_string(), bytes));
+        }
+    }
+
+    /// Returns all entries in the order they were added.
+    pub fn entries(&self) -> &[(String, usize)] {
+        &self.entries
+    }
+
+    /// Returns the total number of bytes which could be reclaimed.
+    pub fn total(&self) -> usize {
+        self.entries.iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Returns the [`TraversalOrder`] applied by [`top`](Self::top).
+    pub fn order(&self) -> TraversalOrder {
+        self.order
+    }
+
+    /// Changes the [`TraversalOrder`] applied by [`top`](Self::top).
+    pub fn set_order(&mut self, order: TraversalOrder) {
+        self.order = order;
+    }
+
+    /// Returns up to `n` entries with the most reclaimable bytes, largest first. Entries of equal
+    /// size are ordered according to the [order](Self::order) of this report.
+    pub fn top(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut entries: Vec<_> = self.entries.iter()
+            .map(|(path, bytes)| (path.as_str(), *bytes))
+            .collect();
+
+        self.order.sort_largest_first(&mut entries);
+        entries.truncate(n);
+
+        entries
+    }
+
+    /// Renders this report just like its [`Display`](fmt::Display) implementation, but formats
+    /// all sizes with the given `formatter`.
+    pub fn render<F: SizeFormatter + ?Sized>(&self, formatter: &F) -> String {
+        let mut rendered = String::new();
+
+        for (path, bytes) in self.top(self.entries.len()) {
+            rendered.push_str(&format!("{}: {} reclaimable\n", path, formatter.format_size(bytes)));
+        }
+
+        rendered
+    }
+}
+
+/// Lists all entries, largest first.
+impl fmt::Display for CapacityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&UnitFormatter::new()))
+    }
+}
+
+impl GetSize for CapacityReport {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.entries)
+    }
+}
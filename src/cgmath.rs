@@ -0,0 +1,47 @@
+use ::cgmath::{
+    Basis2,
+    Basis3,
+    Deg,
+    Euler,
+    Matrix2,
+    Matrix3,
+    Matrix4,
+    Ortho,
+    Perspective,
+    PerspectiveFov,
+    Point1,
+    Point2,
+    Point3,
+    Quaternion,
+    Rad,
+    Vector1,
+    Vector2,
+    Vector3,
+    Vector4,
+};
+
+use crate::GetSize;
+
+
+
+// All types store their scalars inline. Scalars being `Copy` means they cannot own any heap
+// memory either.
+impl<S: Copy> GetSize for Vector1<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Vector2<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Vector3<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Vector4<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Point1<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Point2<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Point3<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Matrix2<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Matrix3<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Matrix4<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Quaternion<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Basis2<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Basis3<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Rad<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Deg<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<A: Copy> GetSize for Euler<A> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Ortho<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for Perspective<S> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<S: Copy> GetSize for PerspectiveFov<S> { const CAN_ALLOCATE_HEAP: bool = false; }
@@ -0,0 +1,585 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::sync::Weak as ArcWeak;
+
+use crate::{GetSize, GetSizeTracker, Progress, SizeError, SizeMemo, StandardTracker, WeakPolicy};
+
+
+
+/// Determines how the overhead of the memory allocator is accounted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AllocatorModel {
+    /// Only the requested bytes are accounted for.
+    #[default]
+    Exact,
+    /// Every heap allocation is assumed to occupy the given number of additional bytes,
+    /// e.g. for the allocators bookkeeping. Allocations are counted as reported by
+    /// [`allocation_found`](TraversalContext::allocation_found), i.e. one per non-empty
+    /// collection buffer, string, box and shared object visited the first time. Types which
+    /// do not look into their heap memory count as a single allocation.
+    PerAllocationOverhead(usize),
+}
+
+impl AllocatorModel {
+    /// Returns the overhead caused by the given number of allocations.
+    pub fn overhead(&self, allocations: usize) -> usize {
+        match self {
+            Self::Exact => 0,
+            Self::PerAllocationOverhead(overhead) => allocations * overhead,
+        }
+    }
+}
+
+
+
+/// Determines how the heap size of a [`String`] is accounted for.
+///
+/// Whether a string accounts for the memory allocated for it or only for the bytes it
+/// actually holds is a matter of what the result is used for. Memory budgets usually need the
+/// former, while comparing the amount of data held by different structures needs the latter.
+///
+/// Measurements using a tracker apply the policy of its [`TraversalContext`], like the policies
+/// of a [`SizingContext`], which includes derived types, as they hand the tracker on to their
+/// fields. A [`SizeReport`](crate::SizeReport) applies the policies it was created with, see
+/// [`SizeReport::of_with_policies`](crate::SizeReport::of_with_policies). Measurements without
+/// either, like [`get_heap_size`](crate::GetSize::get_heap_size), apply the default
+/// [`Capacity`](Self::Capacity) policy.
+///
+/// Besides [`String`], the policy applies to the owned strings of a [`Cow<str>`](std::borrow::Cow),
+/// to [`OsString`](std::ffi::OsString) and to [`PathBuf`](std::path::PathBuf). Strings without
+/// spare capacity, like [`str`], `Box<str>` and `&str`, account for their length under both
+/// policies.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, SizingContext, StringPolicy};
+///
+/// let mut value = String::with_capacity(16);
+/// value.push_str("Hello");
+///
+/// assert_eq!(value.get_heap_size(), 16);
+///
+/// let mut context = SizingContext::new();
+/// let mut policies = context.policies();
+/// policies.set_strings(StringPolicy::Len);
+/// context.set_policies(policies);
+///
+/// assert_eq!(context.measure(&value), Ok(std::mem::size_of::<String>() + 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StringPolicy {
+    /// The allocated capacity of a string is accounted for.
+    #[default]
+    Capacity,
+    /// Only the bytes held by a string are accounted for, ignoring its spare capacity.
+    Len,
+}
+
+impl StringPolicy {
+    /// Returns the heap size of `value` according to this policy.
+    pub fn heap_size_of(&self, value: &String) -> usize {
+        self.select(value.capacity(), value.len())
+    }
+
+    // Picks the heap size of a string buffer with the given `capacity` and `len`.
+    pub(crate) fn select(&self, capacity: usize, len: usize) -> usize {
+        match self {
+            Self::Capacity => capacity,
+            Self::Len => len,
+        }
+    }
+}
+
+
+
+/// Determines whether reports and [`find_largest`](crate::find_largest) list their results in a
+/// reproducible order, so they diff cleanly between runs, e.g. in CI.
+///
+/// The order is chosen per report, by [`SizeReport::set_order`](crate::SizeReport::set_order),
+/// [`CapacityReport::set_order`](crate::CapacityReport::set_order) and
+/// [`find_largest_with_order`](crate::find_largest_with_order).
+///
+/// By default, entries of equal size are listed in the order they were found, and the elements
+/// of hash tables are numbered in their iteration order, which differs between runs due to the
+/// random seed of their hasher. With the [`Deterministic`](Self::Deterministic) order, entries of
+/// equal size are sorted by their path, and the elements of hash tables are numbered by sorting
+/// them by their allocations, largest first. Elements with identical allocations may still swap
+/// their numbers, which does not change the result.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use get_size::{find_largest_with_order, TraversalOrder};
+///
+/// let names: HashSet<String> = ["a", "bbb", "cc"].into_iter().map(String::from).collect();
+/// let largest = find_largest_with_order(&names, 4, TraversalOrder::Deterministic);
+///
+/// assert_eq!(largest[1].path, "[0]");
+/// assert_eq!(largest[1].size, 3);
+/// assert_eq!(largest[3].path, "[2]");
+/// assert_eq!(largest[3].size, 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TraversalOrder {
+    /// Results are listed in the order they were found, which is the fastest.
+    #[default]
+    Native,
+    /// Results are listed in an order which only depends on the measured objects.
+    Deterministic,
+}
+
+impl TraversalOrder {
+    // Sorts the entries of a report by their size, largest first, breaking ties according to
+    // this order.
+    pub(crate) fn sort_largest_first(self, entries: &mut [(&str, usize)]) {
+        match self {
+            Self::Native => entries.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes)),
+            Self::Deterministic => entries.sort_by(|(a_path, a_bytes), (b_path, b_bytes)| {
+                b_bytes.cmp(a_bytes).then_with(|| a_path.cmp(b_path))
+            }),
+        }
+    }
+}
+
+
+
+/// The policies applied while traversing an object, as held by the [`TraversalContext`] of the
+/// tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct SizingPolicies {
+    /// How weak references get accounted for.
+    pub weak: WeakPolicy,
+    /// Whether the data referenced by borrowed slices and strings, like `&str` and `&[T]`,
+    /// gets accounted for. Each referenced region is only accounted for once.
+    pub count_borrowed: bool,
+    /// How the overhead of the memory allocator gets accounted for.
+    pub allocator: AllocatorModel,
+    /// How the heap size of strings gets accounted for.
+    pub strings: StringPolicy,
+    /// Whether collections only account for their own buffer, without measuring the heap part
+    /// of their elements, as done by the [`ShallowTracker`](crate::ShallowTracker).
+    pub shallow: bool,
+}
+
+impl SizingPolicies {
+    /// Creates the default policies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Changes how weak references get accounted for.
+    pub fn set_weak(&mut self, weak: WeakPolicy) {
+        self.weak = weak;
+    }
+
+    /// Changes whether the data referenced by borrowed slices and strings gets accounted for.
+    pub fn set_count_borrowed(&mut self, count_borrowed: bool) {
+        self.count_borrowed = count_borrowed;
+    }
+
+    /// Changes how the overhead of the memory allocator gets accounted for.
+    pub fn set_allocator(&mut self, allocator: AllocatorModel) {
+        self.allocator = allocator;
+    }
+
+    /// Changes how the heap size of strings gets accounted for.
+    pub fn set_strings(&mut self, strings: StringPolicy) {
+        self.strings = strings;
+    }
+
+    /// Changes whether collections only account for their own buffer.
+    pub fn set_shallow(&mut self, shallow: bool) {
+        self.shallow = shallow;
+    }
+}
+
+
+
+/// The state of a measurement which is shared by all objects visited by it, as handed out by
+/// [`GetSizeTracker::context`].
+///
+/// It holds the [`SizingPolicies`] to apply, the [`SizeMemo`] to look up sizes in, and keeps
+/// track of the allocations found, the progress made and whether the measurement got cancelled.
+/// Trackers wrapping another tracker, like the [`ShallowTracker`](crate::ShallowTracker) or the
+/// [`SizingContext`], adjust the context of the wrapped tracker instead of holding their own.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, GetSizeTracker, StandardTracker};
+///
+/// let mut tracker = StandardTracker::new();
+///
+/// let (_, mut tracker) = vec![String::from("Hello")].get_heap_size_with_tracker(&mut tracker);
+///
+/// // The buffer of the vector and the one of the string.
+/// assert_eq!(tracker.context(|context| context.allocations()), 2);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraversalContext<'a> {
+    policies: SizingPolicies,
+    generation: u64,
+    memo: Option<&'a SizeMemo>,
+    allocations: usize,
+    progress: Progress,
+    cancelled: bool,
+    observed: bool,
+}
+
+impl<'a> TraversalContext<'a> {
+    /// Creates a new context applying the default policies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the policies which should be applied while traversing an object.
+    pub fn policies(&self) -> SizingPolicies {
+        self.policies
+    }
+
+    /// Changes the policies which should be applied while traversing an object.
+    pub fn set_policies(&mut self, policies: SizingPolicies) {
+        self.policies = policies;
+    }
+
+    /// Returns the current generation of the tracker.
+    ///
+    /// The generation must change whenever the tracker forgets about previously tracked
+    /// objects, e.g. because it got cleared. Since a forgotten object might have been
+    /// deallocated, its address might now be used by a different object. Comparing the
+    /// generation allows to detect this. Trackers which never forget a tracked object stay
+    /// at generation 0.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Starts a new generation, see [`generation`](Self::generation).
+    pub fn next_generation(&mut self) {
+        self.generation += 1;
+    }
+
+    /// Returns the memo the sizes of shared objects are looked up in, if any.
+    pub fn memo(&self) -> Option<&'a SizeMemo> {
+        self.memo
+    }
+
+    /// Returns the memoized size of the shared object located at `addr`, as measured under the
+    /// current policies, allowing an [`Arc`](std::sync::Arc) to skip measuring it again.
+    pub fn memoized_size(&self, addr: *const u8) -> Option<usize> {
+        self.memo?.lookup(addr, self.policies)
+    }
+
+    /// Memoizes the total `size` of the shared object located at `addr`, as measured under the
+    /// current policies. The `anchor` returns a weak reference to the object, which keeps its
+    /// allocation and thus its address reserved. Does nothing without a [memo](Self::memo).
+    ///
+    /// The size must only be memoized if no shared object nested inside of the object had
+    /// already been seen before, as told by the [`hit_count`](GetSizeTracker::hit_count), since
+    /// it would otherwise depend on what else got measured by the tracker.
+    pub fn memoize_size<F: FnOnce() -> ArcWeak<dyn Any>>(&self, addr: *const u8, size: usize, anchor: F) {
+        if let Some(memo) = self.memo {
+            memo.store(addr, self.policies, size, anchor);
+        }
+    }
+
+    /// Notes a heap allocation found while measuring, e.g. the buffer of a [`Vec`] or the
+    /// shared object behind an [`Arc`](std::sync::Arc) visited the first time. Types which do
+    /// not look into their heap memory report it as a single allocation.
+    ///
+    /// This allows to model the bookkeeping overhead of the memory allocator, as done by the
+    /// [`SizingContext`].
+    pub fn allocation_found(&mut self) {
+        self.allocations += 1;
+    }
+
+    /// Returns the number of heap allocations found so far.
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// Returns `true` if the measurement is observed, e.g. by the [`ProgressTracker`](crate::ProgressTracker).
+    ///
+    /// Only observed measurements inform the context about every element of a collection and
+    /// stop visiting further elements once cancelled. All other measurements skip these calls,
+    /// so the progress stays untouched.
+    pub fn is_observed(&self) -> bool {
+        self.observed
+    }
+
+    /// Changes whether the measurement is observed, see [`is_observed`](Self::is_observed).
+    pub fn set_observed(&mut self, observed: bool) {
+        self.observed = observed;
+    }
+
+    /// Gets called by collections right before measuring one of their elements.
+    ///
+    /// Returns a marker, which gets handed back to [`element_measured`](Self::element_measured)
+    /// once the element has been measured.
+    pub fn element_started(&mut self) -> usize {
+        self.progress.bytes
+    }
+
+    /// Gets called by collections right after measuring one of their elements, with the
+    /// `marker` returned by [`element_started`](Self::element_started) and the total `size`
+    /// of the element.
+    ///
+    /// Since elements may hold collections themselves, calls for nested elements happen in
+    /// between, which have already been accounted for. The progress stays as it was at the time
+    /// the measurement got cancelled.
+    pub fn element_measured(&mut self, marker: usize, size: usize) {
+        if self.cancelled {
+            return;
+        }
+
+        let nested = self.progress.bytes - marker;
+
+        self.progress.bytes += size.saturating_sub(nested);
+        self.progress.elements += 1;
+    }
+
+    /// Returns the progress made so far, see [`Progress`].
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Cancels the measurement.
+    ///
+    /// Derived types stop visiting further fields once cancelled, as do collections if the
+    /// measurement is [observed](Self::is_observed), so the result of a cancelled measurement
+    /// is incomplete.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    /// Returns `true` if the measurement got cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    // Continues a measurement from the given progress and cancellation.
+    pub(crate) fn resume(&mut self, progress: Progress, cancelled: bool) {
+        self.progress = progress;
+        self.cancelled = cancelled;
+    }
+
+    // Returns a copy of this context looking up sizes in the given `memo` instead.
+    pub(crate) fn with_memo<'b>(&self, memo: Option<&'b SizeMemo>) -> TraversalContext<'b> {
+        TraversalContext {
+            policies: self.policies,
+            generation: self.generation,
+            memo,
+            allocations: self.allocations,
+            progress: self.progress,
+            cancelled: self.cancelled,
+            observed: self.observed,
+        }
+    }
+
+    // Takes over the state of a copy returned by `with_memo`, keeping the own memo.
+    pub(crate) fn update(&mut self, copy: &TraversalContext<'_>) {
+        self.policies = copy.policies;
+        self.generation = copy.generation;
+        self.allocations = copy.allocations;
+        self.progress = copy.progress;
+        self.cancelled = copy.cancelled;
+        self.observed = copy.observed;
+    }
+}
+
+
+
+/// A named context for measuring objects, bundling a tracker with a budget and the
+/// [`SizingPolicies`] to apply.
+///
+/// The context itself implements [`GetSizeTracker`], so it can be passed to every
+/// [`get_size_with_tracker`](GetSize::get_size_with_tracker) call, including those of derived
+/// implementations. All objects measured with the same context share the same tracker, so
+/// shared ownership objects are only accounted for once across all of them.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{SizingContext, SizingPolicies, SizeError};
+///
+/// let mut ctx = SizingContext::named("cache");
+/// ctx.set_budget(Some(100));
+///
+/// assert_eq!(ctx.name(), Some("cache"));
+/// assert_eq!(ctx.measure(&String::from("Hello")), Ok(std::mem::size_of::<String>() + 5));
+/// assert_eq!(ctx.measure(&vec![0u8; 100]), Err(SizeError::BudgetExceeded));
+///
+/// // Borrowed data can be accounted for too.
+/// let mut policies = SizingPolicies::new();
+/// policies.set_count_borrowed(true);
+///
+/// let mut ctx = SizingContext::new();
+/// ctx.set_policies(policies);
+///
+/// assert_eq!(ctx.measure(&"Hello"), Ok(std::mem::size_of::<&str>() + 5));
+/// ```
+#[derive(Debug, Default)]
+pub struct SizingContext<T = StandardTracker> {
+    name: Option<Cow<'static, str>>,
+    tracker: T,
+    policies: SizingPolicies,
+    budget: Option<usize>,
+    used: usize,
+    allocations: usize,
+}
+
+impl SizingContext<StandardTracker> {
+    /// Creates a new unnamed context using a [`StandardTracker`] and the default policies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new context with the given name, using a [`StandardTracker`] and the default policies.
+    pub fn named(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::default()
+        }
+    }
+}
+
+impl<T: GetSizeTracker> SizingContext<T> {
+    /// Creates a new unnamed context using the given tracker and the default policies.
+    pub fn with_tracker(tracker: T) -> Self {
+        Self {
+            name: None,
+            tracker,
+            policies: SizingPolicies::default(),
+            budget: None,
+            used: 0,
+            allocations: 0,
+        }
+    }
+
+    /// Returns the name of this context, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Changes the name of this context.
+    pub fn set_name(&mut self, name: Option<Cow<'static, str>>) {
+        self.name = name;
+    }
+
+    /// Returns the policies applied by this context.
+    pub fn policies(&self) -> SizingPolicies {
+        self.policies
+    }
+
+    /// Changes the policies applied by this context.
+    pub fn set_policies(&mut self, policies: SizingPolicies) {
+        self.policies = policies;
+    }
+
+    /// Returns the total number of bytes all measurements done by [`measure`](Self::measure)
+    /// may add up to, if any.
+    pub fn budget(&self) -> Option<usize> {
+        self.budget
+    }
+
+    /// Changes the budget of this context.
+    pub fn set_budget(&mut self, budget: Option<usize>) {
+        self.budget = budget;
+    }
+
+    /// Returns the total number of bytes measured by [`measure`](Self::measure) so far.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Returns the number of bytes left within the budget, if any.
+    pub fn remaining(&self) -> Option<usize> {
+        self.budget.map(|budget| budget.saturating_sub(self.used))
+    }
+
+    /// Returns a reference to the tracker used by this context.
+    pub fn tracker(&self) -> &T {
+        &self.tracker
+    }
+
+    /// Returns a mutable reference to the tracker used by this context.
+    pub fn tracker_mut(&mut self) -> &mut T {
+        &mut self.tracker
+    }
+
+    /// Consumes the context, returning the tracker used by it.
+    pub fn into_tracker(self) -> T {
+        self.tracker
+    }
+
+    /// Determines the total size of `value` within this context.
+    ///
+    /// Objects already seen by this context are not accounted for again. The overhead of
+    /// the heap allocations found while measuring gets added according to the [`AllocatorModel`].
+    ///
+    /// Returns [`SizeError::BudgetExceeded`] if the result does not fit into the remaining
+    /// budget, in which case it is not added to the [used](Self::used) bytes. The shared
+    /// ownership objects visited by the failed measurement stay tracked nonetheless, so later
+    /// measurements would not account for them. Discard the context after this error, or
+    /// replace its tracker using [`tracker_mut`](Self::tracker_mut).
+    pub fn measure<V: GetSize>(&mut self, value: &V) -> Result<usize, SizeError> {
+        self.allocations = 0;
+
+        let (total, ctx) = GetSize::get_size_with_tracker(value, &mut *self);
+
+        let total = total + ctx.policies.allocator.overhead(ctx.allocations);
+
+        if let Some(remaining) = ctx.remaining() {
+            if total>remaining {
+                return Err(SizeError::BudgetExceeded);
+            }
+        }
+
+        ctx.used += total;
+
+        Ok(total)
+    }
+}
+
+impl<T: GetSizeTracker> GetSizeTracker for SizingContext<T> {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        GetSizeTracker::track(&mut self.tracker, addr, strong_ref)
+    }
+
+    fn visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(&self.tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(&self.tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let policies = self.policies;
+        let allocations = &mut self.allocations;
+
+        GetSizeTracker::context(&mut self.tracker, |context| {
+            let outer = (context.policies, context.allocations);
+
+            context.policies = policies;
+            context.allocations = *allocations;
+
+            let result = f(context);
+
+            // The allocations found count towards the wrapped tracker as well.
+            let found = context.allocations - *allocations;
+            *allocations = context.allocations;
+
+            context.policies = outer.0;
+            context.allocations = outer.1 + found;
+
+            result
+        })
+    }
+}
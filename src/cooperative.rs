@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{GetSize, SizeError};
+
+
+
+/// The state carried along by [`GetSizeAsync`], which determines how often the measurement
+/// yields back to the runtime and whether it got cancelled.
+#[derive(Debug, Clone)]
+pub struct YieldContext {
+    interval: usize,
+    visited: usize,
+    cancel_token: Option<Arc<AtomicBool>>,
+}
+
+impl Default for YieldContext {
+    fn default() -> Self {
+        Self {
+            interval: 1024,
+            visited: 0,
+            cancel_token: None,
+        }
+    }
+}
+
+impl YieldContext {
+    /// Creates a new context, which yields every 1024 elements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new context, which yields every `interval` elements.
+    ///
+    /// An `interval` of 0 is treated like 1.
+    pub fn with_interval(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the number of elements measured in between two yield points.
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// Sets a token which cancels the measurement once it gets set to `true`.
+    ///
+    /// The token gets checked at every yield point.
+    pub fn set_cancel_token(&mut self, token: Arc<AtomicBool>) {
+        self.cancel_token = Some(token);
+    }
+
+    /// Returns the number of elements visited so far.
+    pub fn visited(&self) -> usize {
+        self.visited
+    }
+
+    /// Records that another element has been measured, yielding back to the runtime every
+    /// [`interval`](Self::interval) elements.
+    ///
+    /// Returns [`SizeError::Cancelled`] if the measurement got cancelled in the meantime.
+    pub async fn element_visited(&mut self) -> Result<(), SizeError> {
+        self.visited += 1;
+
+        if !self.visited.is_multiple_of(self.interval) {
+            return Ok(());
+        }
+
+        tokio::task::yield_now().await;
+
+        match &self.cancel_token {
+            Some(token) if token.load(Ordering::Relaxed) => Err(SizeError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The cancellation token is shared, so it is not accounted for.
+impl GetSize for YieldContext {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+
+
+/// Determines the heap size of very large collections on an async runtime, without stalling it.
+///
+/// The elements get measured one after another by [`GetSize`], while yielding back to the
+/// runtime every given number of elements, as determined by the [`YieldContext`]. Yield points
+/// only exist in between the elements of the collection itself, each element gets measured at
+/// once. The result is the same as that of [`get_heap_size`](GetSize::get_heap_size).
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, GetSizeAsync, YieldContext};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let state: Vec<String> = vec![String::from("Hello"); 10_000];
+///
+/// let mut ctx = YieldContext::with_interval(100);
+/// let size = state.get_heap_size_async(&mut ctx).await;
+///
+/// assert_eq!(size, Ok(state.get_heap_size()));
+/// assert_eq!(ctx.visited(), 10_000);
+/// # });
+/// ```
+pub trait GetSizeAsync: GetSize + Sync {
+    /// Determines how many bytes this collection occupies inside the heap, yielding back to
+    /// the runtime in between.
+    ///
+    /// Returns [`SizeError::Cancelled`] if the measurement got cancelled by the cancellation
+    /// token of the `ctx`.
+    fn get_heap_size_async(
+        &self,
+        ctx: &mut YieldContext,
+    ) -> impl Future<Output = Result<usize, SizeError>> + Send;
+
+    /// Determines the total size of this collection, yielding back to the runtime in between.
+    ///
+    /// The default implementation simply adds the result of [`get_stack_size`](GetSize::get_stack_size)
+    /// to that of [`get_heap_size_async`](Self::get_heap_size_async).
+    fn get_size_async(
+        &self,
+        ctx: &mut YieldContext,
+    ) -> impl Future<Output = Result<usize, SizeError>> + Send {
+        async move {
+            let heap_size = self.get_heap_size_async(ctx).await?;
+
+            Ok(Self::get_stack_size() + heap_size)
+        }
+    }
+}
+
+macro_rules! impl_size_async_set {
+    ($name:ident, $slots:expr) => {
+        impl<T> GetSizeAsync for $name<T> where T: GetSize + Sync {
+            fn get_heap_size_async(
+                &self,
+                ctx: &mut YieldContext,
+            ) -> impl Future<Output = Result<usize, SizeError>> + Send {
+                async move {
+                    if !T::CAN_ALLOCATE_HEAP {
+                        // No need to look at the individual values.
+                        return Ok(GetSize::get_heap_size(self));
+                    }
+
+                    let slots: fn(&Self) -> usize = $slots;
+                    let mut total = slots(self) * T::get_stack_size();
+
+                    for v in self.iter() {
+                        total += GetSize::get_heap_size(v);
+
+                        ctx.element_visited().await?;
+                    }
+
+                    Ok(total)
+                }
+            }
+        }
+    }
+}
+
+macro_rules! impl_size_async_map {
+    ($name:ident, $slots:expr) => {
+        impl<K, V> GetSizeAsync for $name<K, V> where K: GetSize + Sync, V: GetSize + Sync {
+            fn get_heap_size_async(
+                &self,
+                ctx: &mut YieldContext,
+            ) -> impl Future<Output = Result<usize, SizeError>> + Send {
+                async move {
+                    if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                        // No need to look at the individual keys and values.
+                        return Ok(GetSize::get_heap_size(self));
+                    }
+
+                    let slots: fn(&Self) -> usize = $slots;
+                    let mut total = slots(self) * (K::get_stack_size() + V::get_stack_size());
+
+                    for (k, v) in self.iter() {
+                        total += GetSize::get_heap_size(k);
+                        total += GetSize::get_heap_size(v);
+
+                        ctx.element_visited().await?;
+                    }
+
+                    Ok(total)
+                }
+            }
+        }
+    }
+}
+
+impl_size_async_set!(Vec, |v| v.capacity());
+impl_size_async_set!(VecDeque, |v| v.capacity());
+impl_size_async_set!(BinaryHeap, |v| v.capacity());
+impl_size_async_set!(HashSet, |v| v.capacity());
+impl_size_async_set!(BTreeSet, |v| v.len());
+impl_size_async_set!(LinkedList, |v| v.len());
+impl_size_async_map!(HashMap, |v| v.capacity());
+impl_size_async_map!(BTreeMap, |v| v.len());
+
+impl<T> GetSizeAsync for Option<T> where T: GetSizeAsync {
+    async fn get_heap_size_async(&self, ctx: &mut YieldContext) -> Result<usize, SizeError> {
+        match self {
+            // The options stack size already accounts for the values stack size.
+            Some(t) => t.get_heap_size_async(ctx).await,
+            None => Ok(0),
+        }
+    }
+}
+
+impl<T, E> GetSizeAsync for Result<T, E> where T: GetSizeAsync, E: GetSizeAsync {
+    async fn get_heap_size_async(&self, ctx: &mut YieldContext) -> Result<usize, SizeError> {
+        match self {
+            // The results stack size already accounts for the values stack size.
+            Ok(t) => t.get_heap_size_async(ctx).await,
+            Err(e) => e.get_heap_size_async(ctx).await,
+        }
+    }
+}
+
+impl<T> GetSizeAsync for Box<T> where T: GetSizeAsync {
+    fn get_heap_size_async(
+        &self,
+        ctx: &mut YieldContext,
+    ) -> impl Future<Output = Result<usize, SizeError>> + Send {
+        // The box holds the stack part of its value inside the heap.
+        (**self).get_size_async(ctx)
+    }
+}
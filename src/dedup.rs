@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::GetSize;
+
+
+
+/// Estimates how many bytes could be saved by deduplicating equal immutable payloads,
+/// e.g. by interning equal [`String`]s, as determined by [`record_payloads`](GetSize::record_payloads).
+///
+/// The contents of each recorded payload get hashed, and every payload equal to one recorded
+/// before is accounted for as a duplicate which could be replaced by a shared copy.
+/// As only the hashes are kept, the result is an estimate: payloads with colliding hashes
+/// are treated as being equal, which is very unlikely but not impossible.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::DedupEstimator;
+///
+/// let tags = vec![String::from("admin"), String::from("admin"), String::from("user")];
+///
+/// let estimator = DedupEstimator::of(&tags);
+///
+/// assert_eq!(estimator.payloads(), 3);
+/// assert_eq!(estimator.unique_payloads(), 2);
+/// assert_eq!(estimator.duplicates(), 1);
+/// assert_eq!(estimator.savings(), 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DedupEstimator {
+    seen: HashSet<(u64, usize)>,
+    payloads: usize,
+    payload_bytes: usize,
+    duplicates: usize,
+    savings: usize,
+}
+
+impl DedupEstimator {
+    /// Creates a new estimator without any recorded payloads.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an estimator and records all payloads found inside `value`.
+    pub fn of<T: GetSize>(value: &T) -> Self {
+        let mut estimator = Self::new();
+
+        GetSize::record_payloads(value, &mut estimator);
+
+        estimator
+    }
+
+    /// Records a payload with the given `contents`, which occupies `heap_size` bytes inside the heap.
+    ///
+    /// The `heap_size` is what would be saved if this payload got replaced by a shared copy,
+    /// so it should include spare capacity. Empty payloads are ignored.
+    pub fn record(&mut self, contents: &[u8], heap_size: usize) {
+        if contents.is_empty() {
+            return;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+
+        self.payloads += 1;
+        self.payload_bytes += heap_size;
+
+        if !self.seen.insert((hasher.finish(), contents.len())) {
+            self.duplicates += 1;
+            self.savings += heap_size;
+        }
+    }
+
+    /// Returns the number of recorded payloads.
+    pub fn payloads(&self) -> usize {
+        self.payloads
+    }
+
+    /// Returns the number of distinct payloads.
+    pub fn unique_payloads(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Returns the number of payloads equal to a payload recorded before.
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Returns the total number of heap bytes occupied by all recorded payloads.
+    pub fn payload_bytes(&self) -> usize {
+        self.payload_bytes
+    }
+
+    /// Returns the number of heap bytes which could be saved by deduplicating equal payloads.
+    pub fn savings(&self) -> usize {
+        self.savings
+    }
+}
+
+impl GetSize for DedupEstimator {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.seen)
+    }
+}
@@ -0,0 +1,135 @@
+use std::ops::{Deref, DerefMut};
+
+use ::deepsize::{Context, DeepSizeOf};
+
+use crate::GetSize;
+
+
+
+/// Measures a type implementing [`DeepSizeOf`] through [`GetSize`].
+///
+/// This allows using types of crates which only support `deepsize` inside types deriving
+/// [`GetSize`], without implementing both traits. The heap size is whatever `deepsize`
+/// determines for the children of the wrapped value, so its handling of shared ownership
+/// applies instead of the one of the tracker.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, ViaDeepSize};
+///
+/// #[derive(deepsize::DeepSizeOf)]
+/// struct Legacy {
+///     name: String,
+/// }
+///
+/// #[derive(GetSize)]
+/// struct Modern {
+///     legacy: ViaDeepSize<Legacy>,
+///     tags: Vec<u8>,
+/// }
+///
+/// let value = Modern {
+///     legacy: ViaDeepSize(Legacy { name: String::from("Hello") }),
+///     tags: vec![1, 2, 3],
+/// };
+///
+/// assert_eq!(value.get_heap_size(), 5 + 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ViaDeepSize<T>(pub T);
+
+impl<T> ViaDeepSize<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ViaDeepSize<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ViaDeepSize<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> GetSize for ViaDeepSize<T> where T: DeepSizeOf {
+    fn get_heap_size(&self) -> usize {
+        // `deep_size_of` includes the stack size of the value itself.
+        DeepSizeOf::deep_size_of(&self.0) - std::mem::size_of::<T>()
+    }
+}
+
+
+
+/// Measures a type implementing [`GetSize`] through [`DeepSizeOf`].
+///
+/// This is the counterpart of [`ViaDeepSize`], which allows using types only implementing
+/// [`GetSize`] inside types deriving [`DeepSizeOf`].
+///
+/// # Example
+///
+/// ```rust
+/// use deepsize::DeepSizeOf;
+/// use get_size::{GetSize, ViaGetSize};
+///
+/// #[derive(GetSize)]
+/// struct Modern {
+///     name: String,
+/// }
+///
+/// #[derive(DeepSizeOf)]
+/// struct Legacy {
+///     modern: ViaGetSize<Modern>,
+/// }
+///
+/// let value = Legacy {
+///     modern: ViaGetSize(Modern { name: String::from("Hello") }),
+/// };
+///
+/// assert_eq!(value.deep_size_of(), std::mem::size_of::<Legacy>() + 5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ViaGetSize<T>(pub T);
+
+impl<T> ViaGetSize<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ViaGetSize<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ViaGetSize<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> DeepSizeOf for ViaGetSize<T> where T: GetSize {
+    fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+        GetSize::get_heap_size(&self.0)
+    }
+}
+
+impl<T> GetSize for ViaGetSize<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.0)
+    }
+}
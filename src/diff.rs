@@ -0,0 +1,237 @@
+use std::fmt;
+
+use crate::{SizeFormatter, SizeReport, UnitFormatter};
+
+
+
+/// How the size found at a path changed between two [`SizeReport`]s, as listed by a [`SizeDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SizeChange {
+    /// The path of the entry, or `total` for the total size of the measured objects.
+    pub path: String,
+    /// The number of bytes listed by the earlier report, which is zero if the entry is new.
+    pub before: usize,
+    /// The number of bytes listed by the later report, which is zero if the entry is gone.
+    pub after: usize,
+}
+
+impl SizeChange {
+    /// Returns by how many bytes the size grew, which is negative if it shrank.
+    pub fn delta(&self) -> i128 {
+        self.after as i128 - self.before as i128
+    }
+
+    /// Returns by how many percent the size grew, which is negative if it shrank, or `None` if
+    /// the entry is new.
+    pub fn delta_percent(&self) -> Option<f64> {
+        match self.before {
+            0 => None,
+            before => Some(self.delta() as f64 / before as f64 * 100.0),
+        }
+    }
+}
+
+/// Compares two [`SizeReport`]s of the same object, e.g. taken before and after a change or at
+/// two points of an incident, and renders the differences as a compact plain-text table.
+///
+/// Every path found in either of the reports becomes a [`SizeChange`]. Rendering lists the
+/// changed entries with their size before and after, as well as the absolute and relative
+/// change, largest change first, followed by the total. Changes below the thresholds set by
+/// [`set_min_delta`](Self::set_min_delta) and [`set_min_delta_percent`](Self::set_min_delta_percent)
+/// are hidden as noise, only counting them.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{SizeDiff, SizeReport};
+///
+/// let mut before = SizeReport::new();
+/// before.add("entries", 1000);
+/// before.add("index", 400);
+///
+/// let mut after = SizeReport::new();
+/// after.add("entries", 1500);
+/// after.add("index", 401);
+/// after.add("cache", 200);
+///
+/// let mut diff = SizeDiff::between(&before, &after);
+/// diff.set_min_delta(10);
+///
+/// assert_eq!(diff.render(&|bytes: usize| format!("{} B", bytes)), "\
+/// field    before   after   delta  delta%
+/// entries  1000 B  1500 B  +500 B  +50.0%
+/// cache       0 B   200 B  +200 B     new
+/// total    1400 B  2101 B  +701 B  +50.1%
+/// (1 smaller change hidden)
+/// ");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeDiff {
+    changes: Vec<SizeChange>,
+    total: SizeChange,
+    min_delta: usize,
+    min_delta_percent: f64,
+}
+
+impl SizeDiff {
+    /// Compares the `before` report with the `after` report.
+    ///
+    /// The changes are listed in the order the paths appear in `before`, followed by the paths
+    /// only found in `after`.
+    pub fn between(before: &SizeReport, after: &SizeReport) -> Self {
+        let mut changes: Vec<SizeChange> = Vec::with_capacity(before.entries().len());
+
+        for (path, bytes) in before.entries() {
+            match changes.iter_mut().find(|change| change.path==*path) {
+                Some(change) => change.before += bytes,
+                None => changes.push(SizeChange { path: path.clone(), before: *bytes, after: 0 }),
+            }
+        }
+
+        for (path, bytes) in after.entries() {
+            match changes.iter_mut().find(|change| change.path==*path) {
+                Some(change) => change.after += bytes,
+                None => changes.push(SizeChange { path: path.clone(), before: 0, after: *bytes }),
+            }
+        }
+
+        Self {
+            changes,
+            total: SizeChange {
+                path: String::from("total"),
+                before: before.total(),
+                after: after.total(),
+            },
+            min_delta: 0,
+            min_delta_percent: 0.0,
+        }
+    }
+
+    /// Returns the changes of all paths, including unchanged ones.
+    pub fn changes(&self) -> &[SizeChange] {
+        &self.changes
+    }
+
+    /// Returns the change of the total size of the measured object.
+    pub fn total(&self) -> &SizeChange {
+        &self.total
+    }
+
+    /// Hides all changes of fewer than `bytes` bytes when rendering. Defaults to zero.
+    pub fn set_min_delta(&mut self, bytes: usize) {
+        self.min_delta = bytes;
+    }
+
+    /// Hides all changes of less than `percent` percent when rendering, besides new entries.
+    /// Defaults to zero.
+    pub fn set_min_delta_percent(&mut self, percent: f64) {
+        self.min_delta_percent = percent;
+    }
+
+    /// Returns the changes which pass the thresholds, largest change first. Unchanged entries
+    /// are never included.
+    pub fn significant(&self) -> Vec<&SizeChange> {
+        let mut changes: Vec<&SizeChange> = self.changes.iter()
+            .filter(|change| self.is_significant(change))
+            .collect();
+
+        changes.sort_by_key(|change| std::cmp::Reverse(change.delta().unsigned_abs()));
+
+        changes
+    }
+
+    fn is_significant(&self, change: &SizeChange) -> bool {
+        let delta = change.delta().unsigned_abs();
+
+        if delta==0 || delta<self.min_delta as u128 {
+            return false;
+        }
+
+        match change.delta_percent() {
+            Some(percent) => percent.abs()>=self.min_delta_percent,
+            None => true,
+        }
+    }
+
+    /// Renders the differences just like the [`Display`](fmt::Display) implementation, but
+    /// formats all sizes with the given `formatter`.
+    pub fn render<F: SizeFormatter + ?Sized>(&self, formatter: &F) -> String {
+        let significant = self.significant();
+
+        let mut rows: Vec<[String; 5]> = Vec::with_capacity(significant.len() + 2);
+        rows.push([
+            String::from("field"),
+            String::from("before"),
+            String::from("after"),
+            String::from("delta"),
+            String::from("delta%"),
+        ]);
+
+        for change in significant.iter().copied().chain(std::iter::once(&self.total)) {
+            rows.push(row(change, formatter));
+        }
+
+        // The first column is aligned to the left, all others to the right.
+        let mut widths = [0; 5];
+
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut rendered = String::new();
+
+        for row in rows.iter() {
+            rendered.push_str(&format!(
+                "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}  {:>w4$}\n",
+                row[0], row[1], row[2], row[3], row[4],
+                w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4],
+            ));
+        }
+
+        let hidden = self.changes.iter()
+            .filter(|change| change.delta()!=0)
+            .count() - significant.len();
+
+        match hidden {
+            0 => {}
+            1 => rendered.push_str("(1 smaller change hidden)\n"),
+            hidden => rendered.push_str(&format!("({} smaller changes hidden)\n", hidden)),
+        }
+
+        rendered
+    }
+}
+
+// Formats the cells of a single row of the table.
+fn row<F: SizeFormatter + ?Sized>(change: &SizeChange, formatter: &F) -> [String; 5] {
+    let delta = change.delta();
+
+    let delta_cell = match delta {
+        0 => formatter.format_size(0),
+        delta if delta>0 => format!("+{}", formatter.format_size(delta.unsigned_abs() as usize)),
+        delta => format!("-{}", formatter.format_size(delta.unsigned_abs() as usize)),
+    };
+
+    let percent_cell = match change.delta_percent() {
+        Some(percent) => format!("{:+.1}%", percent),
+        None if change.after>0 => String::from("new"),
+        None => String::from("-"),
+    };
+
+    [
+        change.path.clone(),
+        formatter.format_size(change.before),
+        formatter.format_size(change.after),
+        delta_cell,
+        percent_cell,
+    ]
+}
+
+/// Renders the table with all sizes formatted in binary units.
+impl fmt::Display for SizeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&UnitFormatter::new()))
+    }
+}
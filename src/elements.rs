@@ -0,0 +1,240 @@
+use crate::{Allocation, AllocationFinder, GetSize, GetSizeTracker, TraversalContext, TraversalOrder};
+
+
+
+// Measures the heap part of the elements of a collection while using a tracker. Shallow
+// measurements skip all elements.
+//
+// Only observed measurements inform the tracker about every element and skip the remaining
+// elements once it got cancelled, all others measure the elements without consulting the tracker.
+//
+// The stack part of the elements is held by the buffer of the collection, which is accounted for
+// by the collection itself.
+pub fn elements_heap_size_with_tracker<'a, T, I, TR>(elements: I, mut tracker: TR) -> (usize, TR)
+where
+    T: GetSize + 'a,
+    I: Iterator<Item = &'a T>,
+    TR: GetSizeTracker,
+{
+    let (shallow, observed) = tracker.context(|context| (context.policies().shallow, context.is_observed()));
+
+    if shallow {
+        return (0, tracker);
+    }
+
+    let mut total = 0;
+
+    if !observed {
+        for v in elements {
+            let (size, returned) = GetSize::get_heap_size_with_tracker(v, tracker);
+            tracker = returned;
+
+            total += size;
+        }
+
+        return (total, tracker);
+    }
+
+    let mut marker = tracker.context(next_marker);
+
+    for v in elements {
+        let Some(started) = marker else {
+            break;
+        };
+
+        let (size, returned) = GetSize::get_heap_size_with_tracker(v, tracker);
+        tracker = returned;
+
+        marker = tracker.context(|context| {
+            context.element_measured(started, size);
+
+            next_marker(context)
+        });
+        total += size;
+    }
+
+    (total, tracker)
+}
+
+// Like `elements_heap_size_with_tracker`, but for the entries of a map.
+pub fn entries_heap_size_with_tracker<'a, K, V, I, TR>(entries: I, mut tracker: TR) -> (usize, TR)
+where
+    K: GetSize + 'a,
+    V: GetSize + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+    TR: GetSizeTracker,
+{
+    let (shallow, observed) = tracker.context(|context| (context.policies().shallow, context.is_observed()));
+
+    if shallow {
+        return (0, tracker);
+    }
+
+    let mut total = 0;
+
+    if !observed {
+        for (k, v) in entries {
+            let (key_size, returned) = GetSize::get_heap_size_with_tracker(k, tracker);
+            let (value_size, returned) = GetSize::get_heap_size_with_tracker(v, returned);
+            tracker = returned;
+
+            total += key_size + value_size;
+        }
+
+        return (total, tracker);
+    }
+
+    let mut marker = tracker.context(next_marker);
+
+    for (k, v) in entries {
+        let Some(started) = marker else {
+            break;
+        };
+
+        let (key_size, returned) = GetSize::get_heap_size_with_tracker(k, tracker);
+        let (value_size, returned) = GetSize::get_heap_size_with_tracker(v, returned);
+        tracker = returned;
+
+        marker = tracker.context(|context| {
+            context.element_measured(started, key_size + value_size);
+
+            next_marker(context)
+        });
+        total += key_size + value_size;
+    }
+
+    (total, tracker)
+}
+
+// Starts measuring the next element, unless the measurement got cancelled.
+fn next_marker(context: &mut TraversalContext<'_>) -> Option<usize> {
+    if context.is_cancelled() {
+        return None;
+    }
+
+    Some(context.element_started())
+}
+
+// Records the allocations of all elements of a collection, identified by their index.
+pub fn record_element_allocations<'a, T, I>(iter: I, path: &str, finder: &mut AllocationFinder)
+where
+    T: GetSize + 'a,
+    I: Iterator<Item = &'a T>,
+{
+    if !T::CAN_ALLOCATE_HEAP {
+        return;
+    }
+
+    for (index, element) in iter.enumerate() {
+        GetSize::record_allocations(element, &format!("{path}[{index}]"), finder);
+    }
+}
+
+// Records the allocations of all keys and values of a map, identified by the index of their entry.
+pub fn record_entry_allocations<'a, K, V, I>(iter: I, path: &str, finder: &mut AllocationFinder)
+where
+    K: GetSize + 'a,
+    V: GetSize + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+        return;
+    }
+
+    for (index, (k, v)) in iter.enumerate() {
+        let path = format!("{path}[{index}]");
+
+        GetSize::record_allocations(k, &path, finder);
+        GetSize::record_allocations(v, &path, finder);
+    }
+}
+
+// Like `record_element_allocations`, but for hash tables, whose iteration order differs between
+// runs. With the deterministic `TraversalOrder`, the elements get numbered by sorting them by their
+// allocations instead.
+pub fn record_table_element_allocations<'a, T, I>(iter: I, path: &str, finder: &mut AllocationFinder)
+where
+    T: GetSize + 'a,
+    I: Iterator<Item = &'a T>,
+{
+    match finder.order() {
+        TraversalOrder::Native => record_element_allocations(iter, path, finder),
+        TraversalOrder::Deterministic => {
+            if !T::CAN_ALLOCATE_HEAP {
+                return;
+            }
+
+            let (limit, order) = (finder.limit(), finder.order());
+
+            record_sorted(iter.map(|element| {
+                let mut element_finder = AllocationFinder::with_order(limit, order);
+                GetSize::record_allocations(element, "", &mut element_finder);
+
+                element_finder.into_allocations()
+            }), path, finder);
+        }
+    }
+}
+
+// Like `record_table_element_allocations`, but for the entries of a hash map.
+pub fn record_table_entry_allocations<'a, K, V, I>(iter: I, path: &str, finder: &mut AllocationFinder)
+where
+    K: GetSize + 'a,
+    V: GetSize + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    match finder.order() {
+        TraversalOrder::Native => record_entry_allocations(iter, path, finder),
+        TraversalOrder::Deterministic => {
+            if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                return;
+            }
+
+            let (limit, order) = (finder.limit(), finder.order());
+
+            record_sorted(iter.map(|(k, v)| {
+                let mut entry_finder = AllocationFinder::with_order(limit, order);
+                GetSize::record_allocations(k, "", &mut entry_finder);
+                GetSize::record_allocations(v, "", &mut entry_finder);
+
+                entry_finder.into_allocations()
+            }), path, finder);
+        }
+    }
+}
+
+// Numbers the elements by sorting them by their allocations, which were recorded relative to the
+// element, and records these under the path of the element.
+fn record_sorted<I>(elements: I, path: &str, finder: &mut AllocationFinder)
+where
+    I: Iterator<Item = Vec<Allocation>>,
+{
+    let mut elements: Vec<Vec<Allocation>> = elements.collect();
+
+    elements.sort_by(|a, b| {
+        let a_size: usize = a.iter().map(|allocation| allocation.size).sum();
+        let b_size: usize = b.iter().map(|allocation| allocation.size).sum();
+
+        b_size.cmp(&a_size).then_with(|| {
+            let a_key = a.iter().map(|allocation| (&allocation.path, allocation.type_name, allocation.size));
+            let b_key = b.iter().map(|allocation| (&allocation.path, allocation.type_name, allocation.size));
+
+            a_key.cmp(b_key)
+        })
+    });
+
+    for (index, allocations) in elements.into_iter().enumerate() {
+        let element_path = format!("{path}[{index}]");
+
+        for allocation in allocations {
+            // Paths of fields get joined by a dot, while those of elements follow directly.
+            let full_path = match allocation.path.as_str() {
+                "" => element_path.clone(),
+                relative if relative.starts_with('[') => format!("{element_path}{relative}"),
+                relative => format!("{element_path}.{relative}"),
+            };
+
+            finder.record(&full_path, allocation.type_name, allocation.size);
+        }
+    }
+}
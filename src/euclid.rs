@@ -0,0 +1,51 @@
+use ::euclid::{
+    Angle,
+    Box2D,
+    Box3D,
+    HomogeneousVector,
+    Length,
+    Point2D,
+    Point3D,
+    Rect,
+    RigidTransform3D,
+    Rotation2D,
+    Rotation3D,
+    Scale,
+    SideOffsets2D,
+    Size2D,
+    Size3D,
+    Transform2D,
+    Transform3D,
+    Translation2D,
+    Translation3D,
+    Vector2D,
+    Vector3D,
+};
+
+use crate::GetSize;
+
+
+
+// The geometric types store their scalars inline, while the units are only phantom markers.
+// Scalars being `Copy` means they cannot own any heap memory either.
+impl<T: Copy> GetSize for Angle<T> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Length<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Point2D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Point3D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Vector2D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Vector3D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for HomogeneousVector<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Size2D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Size3D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Rect<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Box2D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for Box3D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, U> GetSize for SideOffsets2D<T, U> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Scale<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Transform2D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Transform3D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Translation2D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Translation3D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Rotation2D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for Rotation3D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
+impl<T: Copy, Src, Dst> GetSize for RigidTransform3D<T, Src, Dst> { const CAN_ALLOCATE_HEAP: bool = false; }
@@ -0,0 +1,57 @@
+use crate::{GetSizeUnsized, SizeFormatter, UnitFormatter};
+
+
+
+/// Short, tracker-free convenience methods for determining the size of an object.
+///
+/// This trait is implemented for every type implementing [`GetSize`](crate::GetSize) (as well
+/// as for unsized types like `str` and `[T]`), so simply importing it is enough to use it.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSizeExt;
+///
+/// let value = vec![1u8; 2048];
+///
+/// assert_eq!(value.heap_size(), 2048);
+/// assert_eq!(value.size(), std::mem::size_of::<Vec<u8>>() + 2048);
+/// assert_eq!(value.size_formatted(), "2.0 KiB");
+/// ```
+pub trait GetSizeExt: GetSizeUnsized {
+    /// Determines the total size of the object in bytes, including its stack and heap part.
+    fn size(&self) -> usize {
+        self.get_size_of_val()
+    }
+
+    /// Determines how many bytes the object occupies inside the heap.
+    fn heap_size(&self) -> usize {
+        self.get_heap_size_of_val()
+    }
+
+    /// Determines the total size of the object and formats it as a human readable string,
+    /// e.g. `"1.5 KiB"`.
+    fn size_formatted(&self) -> String {
+        format_size(self.size())
+    }
+}
+
+impl<T> GetSizeExt for T where T: GetSizeUnsized + ?Sized {}
+
+/// Formats the given number of bytes as a human readable string using binary units.
+///
+/// Values below 1 KiB are printed as whole bytes, larger ones with a single decimal place.
+/// Use a [`UnitFormatter`] for decimal units, a different precision or separators.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::format_size;
+///
+/// assert_eq!(format_size(512), "512 B");
+/// assert_eq!(format_size(1536), "1.5 KiB");
+/// assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+/// ```
+pub fn format_size(bytes: usize) -> String {
+    UnitFormatter::new().format_size(bytes)
+}
@@ -0,0 +1,165 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::GetSize;
+
+
+
+/// A type erased value, as stored by extension maps. Implemented for `dyn Any` as well as for
+/// its `Send` and `Sync` variants.
+pub trait AnyValue: Any {
+    /// Returns the value as a plain `&dyn Any`.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl AnyValue for dyn Any {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl AnyValue for dyn Any + Send {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl AnyValue for dyn Any + Send + Sync {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// The functions measuring values of a single registered type.
+#[derive(Clone, Copy)]
+pub(crate) struct ExtensionSizer {
+    // Returns the heap size of the value, which is of the registered type.
+    pub(crate) heap_size: fn(&dyn Any) -> usize,
+    // Returns the boxed size of the value of the registered type stored in the extensions, if any.
+    #[cfg(feature = "http")]
+    pub(crate) http_extension: fn(&::http::Extensions) -> Option<usize>,
+}
+
+/// Measures extension maps holding type erased values, like `HashMap<TypeId, Box<dyn Any>>` or
+/// the `Extensions` of a HTTP request, which can not be looked into by [`GetSize`].
+///
+/// Values of types [registered](Self::register) beforehand are measured by their [`GetSize`]
+/// implementation, while all other values are assumed to occupy a fixed number of bytes
+/// inside the heap, the [fallback](Self::fallback), which is 0 by default. With the `http`
+/// feature enabled, it can also measure `http::Extensions` by
+/// `heap_size_of_extensions`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::any::{Any, TypeId};
+/// use std::collections::HashMap;
+/// use get_size::ExtensionsSizer;
+///
+/// let mut extensions: HashMap<TypeId, Box<dyn Any>> = HashMap::new();
+/// extensions.insert(TypeId::of::<String>(), Box::new(String::from("Hello")));
+/// extensions.insert(TypeId::of::<u64>(), Box::new(42u64));
+///
+/// let mut sizer = ExtensionsSizer::with_fallback(100);
+/// sizer.register::<String>();
+///
+/// let entry_size = std::mem::size_of::<(TypeId, Box<dyn Any>)>();
+/// let expected = extensions.capacity() * entry_size
+///     + std::mem::size_of::<String>() + 5
+///     + std::mem::size_of::<u64>() + 100;
+///
+/// assert_eq!(sizer.heap_size_of_map(&extensions), expected);
+/// ```
+#[derive(Clone, Default)]
+pub struct ExtensionsSizer {
+    pub(crate) sizers: HashMap<TypeId, ExtensionSizer>,
+    fallback: usize,
+}
+
+impl ExtensionsSizer {
+    /// Creates a new sizer without any registered types and a fallback of 0 bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new sizer without any registered types, assuming values of unknown types
+    /// to occupy `fallback` bytes inside the heap.
+    pub fn with_fallback(fallback: usize) -> Self {
+        Self {
+            fallback,
+            ..Self::default()
+        }
+    }
+
+    /// Registers the type `T`, so its values get measured by their [`GetSize`] implementation.
+    ///
+    /// The type has to be `Send` and `Sync`, so it can be looked up inside extension maps
+    /// requiring them.
+    pub fn register<T>(&mut self) where T: GetSize + Send + Sync + 'static {
+        self.sizers.insert(TypeId::of::<T>(), ExtensionSizer {
+            heap_size: |value| match value.downcast_ref::<T>() {
+                Some(value) => GetSize::get_heap_size(value),
+                None => 0,
+            },
+            #[cfg(feature = "http")]
+            http_extension: crate::http::extension_size::<T>,
+        });
+    }
+
+    /// Whether the type `T` was registered.
+    pub fn is_registered<T: Any>(&self) -> bool {
+        self.sizers.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns the number of bytes values of unknown types are assumed to occupy inside the heap.
+    pub fn fallback(&self) -> usize {
+        self.fallback
+    }
+
+    /// Changes the number of bytes values of unknown types are assumed to occupy inside the heap.
+    pub fn set_fallback(&mut self, fallback: usize) {
+        self.fallback = fallback;
+    }
+
+    /// Determines how many bytes the given type erased `value` occupies inside the heap, or
+    /// returns the fallback if its type is unknown.
+    pub fn heap_size_of_val(&self, value: &dyn Any) -> usize {
+        match self.sizers.get(&value.type_id()) {
+            Some(sizer) => (sizer.heap_size)(value),
+            None => self.fallback,
+        }
+    }
+
+    /// Determines how many bytes the given extension map occupies inside the heap, including
+    /// its buffer as well as the boxed values.
+    pub fn heap_size_of_map<V, S>(&self, map: &HashMap<TypeId, Box<V>, S>) -> usize
+    where
+        V: AnyValue + ?Sized,
+    {
+        let mut total = map.capacity() * std::mem::size_of::<(TypeId, Box<V>)>();
+
+        for value in map.values() {
+            let value = value.as_any();
+
+            total += std::mem::size_of_val(value);
+            total += self.heap_size_of_val(value);
+        }
+
+        total
+    }
+}
+
+impl std::fmt::Debug for ExtensionsSizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtensionsSizer")
+            .field("registered", &self.sizers.len())
+            .field("fallback", &self.fallback)
+            .finish()
+    }
+}
+
+impl GetSize for ExtensionsSizer {
+    fn get_heap_size(&self) -> usize {
+        self.sizers.capacity() * std::mem::size_of::<(TypeId, ExtensionSizer)>()
+    }
+}
@@ -0,0 +1,298 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Mutex, RwLock, TryLockError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::GetSize;
+
+
+
+/// The reasons why [`TryGetSize`] failed to determine the size of an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SizeError {
+    /// The object is nested deeper than allowed by the [`SizeContext`].
+    RecursionLimitExceeded,
+    /// A lock or `RefCell` could not be acquired without blocking, e.g. because it is currently
+    /// held by another thread or borrowed mutably.
+    LockUnavailable,
+    /// A lock was poisoned, because another thread panicked while holding it.
+    Poisoned,
+    /// The measured size exceeds the budget of a [`SizingContext`](crate::SizingContext).
+    BudgetExceeded,
+    /// The measurement was cancelled, e.g. by a [`ProgressHook`](crate::ProgressHook).
+    Cancelled,
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RecursionLimitExceeded => f.write_str("the recursion limit was exceeded"),
+            Self::LockUnavailable => f.write_str("a lock could not be acquired"),
+            Self::Poisoned => f.write_str("a lock was poisoned"),
+            Self::BudgetExceeded => f.write_str("the budget was exceeded"),
+            Self::Cancelled => f.write_str("the measurement was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+impl GetSize for SizeError {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl<T> From<TryLockError<T>> for SizeError {
+    fn from(err: TryLockError<T>) -> Self {
+        match err {
+            TryLockError::Poisoned(_) => Self::Poisoned,
+            TryLockError::WouldBlock => Self::LockUnavailable,
+        }
+    }
+}
+
+
+
+/// The state carried along while determining the size of an object with [`TryGetSize`].
+///
+/// It keeps track of the current nesting depth, so deeply nested or maliciously constructed
+/// data results in an error instead of a stack overflow.
+#[derive(Debug, Clone)]
+pub struct SizeContext {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl GetSize for SizeContext {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl Default for SizeContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SizeContext {
+    /// The default maximum nesting depth.
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+    /// Creates a new context using the [default maximum depth](Self::DEFAULT_MAX_DEPTH).
+    pub fn new() -> Self {
+        Self::with_max_depth(Self::DEFAULT_MAX_DEPTH)
+    }
+
+    /// Creates a new context which allows objects to be nested up to `max_depth` levels deep.
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            depth: 0,
+            max_depth,
+        }
+    }
+
+    /// The current nesting depth.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The maximum nesting depth.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Executes `f` one nesting level deeper.
+    ///
+    /// Returns [`SizeError::RecursionLimitExceeded`] without executing `f` if the
+    /// maximum depth has already been reached.
+    pub fn descend<F>(&mut self, f: F) -> Result<usize, SizeError>
+    where
+        F: FnOnce(&mut Self) -> Result<usize, SizeError>,
+    {
+        if self.depth>=self.max_depth {
+            return Err(SizeError::RecursionLimitExceeded);
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Determines the heap size of an object which can not fail to be measured, by using
+    /// its [`GetSize`] implementation.
+    pub fn measure<T>(&mut self, value: &T) -> Result<usize, SizeError> where T: GetSize {
+        self.descend(|_| Ok(GetSize::get_heap_size(value)))
+    }
+}
+
+
+
+/// A fallible variant of [`GetSize`], which never panics.
+///
+/// Use this for untrusted or deeply nested data, and for data guarded by locks which might
+/// currently be held or poisoned. Instead of blocking or panicking, an appropriate [`SizeError`]
+/// gets returned.
+///
+/// It is implemented for the basic types of the standard library, as well as for the common
+/// containers like [`Vec`], [`Box`], [`Option`] and the lock types. It can be derived by adding
+/// the `fallible` attribute when deriving [`GetSize`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Mutex;
+/// use get_size::{TryGetSize, SizeContext, SizeError};
+///
+/// let value = Mutex::new(String::from("Hello"));
+///
+/// assert_eq!(value.try_get_heap_size(&mut SizeContext::new()), Ok(5));
+///
+/// let _guard = value.lock().unwrap();
+///
+/// assert_eq!(
+///     value.try_get_heap_size(&mut SizeContext::new()),
+///     Err(SizeError::LockUnavailable),
+/// );
+/// ```
+pub trait TryGetSize {
+    /// Determines how many bytes this object occupies inside the heap.
+    fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError>;
+
+    /// Determines the total size of the object.
+    fn try_get_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> where Self: Sized {
+        Ok(std::mem::size_of::<Self>() + self.try_get_heap_size(ctx)?)
+    }
+}
+
+// Adapts the `GetSize` implementation of types which can not fail to be measured.
+macro_rules! impl_try_size_from_get_size {
+    ($($name:ty),* $(,)?) => {
+        $(
+            impl TryGetSize for $name {
+                fn try_get_heap_size(&self, _ctx: &mut SizeContext) -> Result<usize, SizeError> {
+                    Ok(GetSize::get_heap_size(self))
+                }
+            }
+        )*
+    }
+}
+
+impl_try_size_from_get_size!(
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    char,
+    String,
+    std::ffi::CString,
+    std::ffi::OsString,
+    std::path::PathBuf,
+    Instant,
+    Duration,
+    SystemTime,
+);
+
+// Markers, pointers and references never own the data they refer to.
+impl<T: ?Sized> TryGetSize for PhantomData<T> {
+    fn try_get_heap_size(&self, _ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        Ok(0)
+    }
+}
+
+impl<T: ?Sized> TryGetSize for *const T {
+    fn try_get_heap_size(&self, _ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        Ok(0)
+    }
+}
+
+impl<T: ?Sized> TryGetSize for *mut T {
+    fn try_get_heap_size(&self, _ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        Ok(0)
+    }
+}
+
+impl<T> TryGetSize for &T {
+    fn try_get_heap_size(&self, _ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        Ok(0)
+    }
+}
+
+impl<T> TryGetSize for Box<T> where T: TryGetSize {
+    fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        ctx.descend(|ctx| (**self).try_get_size(ctx))
+    }
+}
+
+impl<T> TryGetSize for Option<T> where T: TryGetSize {
+    fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        match self {
+            // The options stack size already accounts for the values stack size.
+            Some(t) => t.try_get_heap_size(ctx),
+            None => Ok(0),
+        }
+    }
+}
+
+macro_rules! impl_try_size_sequence {
+    ($name:ident) => {
+        impl<T> TryGetSize for $name<T> where T: TryGetSize {
+            fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> {
+                ctx.descend(|ctx| {
+                    let mut total = self.capacity() * std::mem::size_of::<T>();
+
+                    for v in self.iter() {
+                        // The values stack size is already accounted for by the capacity.
+                        total += v.try_get_heap_size(ctx)?;
+                    }
+
+                    Ok(total)
+                })
+            }
+        }
+    }
+}
+
+impl_try_size_sequence!(Vec);
+impl_try_size_sequence!(VecDeque);
+
+impl<T> TryGetSize for Mutex<T> where T: TryGetSize {
+    fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        // We assume that a Mutex does hold its data at the stack.
+        let guard = self.try_lock()?;
+
+        guard.try_get_heap_size(ctx)
+    }
+}
+
+impl<T> TryGetSize for RwLock<T> where T: TryGetSize {
+    fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        // We assume that a RwLock does hold its data at the stack.
+        let guard = self.try_read()?;
+
+        guard.try_get_heap_size(ctx)
+    }
+}
+
+impl<T> TryGetSize for RefCell<T> where T: TryGetSize {
+    fn try_get_heap_size(&self, ctx: &mut SizeContext) -> Result<usize, SizeError> {
+        // A RefCell does hold its data at the stack.
+        let value = self.try_borrow().map_err(|_| SizeError::LockUnavailable)?;
+
+        value.try_get_heap_size(ctx)
+    }
+}
@@ -0,0 +1,13 @@
+use ::fixedbitset::FixedBitSet;
+
+use crate::GetSize;
+
+
+
+/// The blocks holding the bits are allocated in full, so the set occupies its length in bits
+/// rounded up to the next block.
+impl GetSize for FixedBitSet {
+    fn get_heap_size(&self) -> usize {
+        std::mem::size_of_val(self.as_slice())
+    }
+}
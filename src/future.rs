@@ -0,0 +1,71 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::GetSize;
+
+
+
+/// A wrapper around a pinned and boxed future, which accounts for the memory occupied by
+/// the future's state machine.
+///
+/// Futures do not expose their contents, so the heap size is estimated by the size of the
+/// state machine itself, as determined by [`std::mem::size_of_val`]. Heap memory owned by
+/// values captured inside the state machine is __not__ accounted for.
+///
+/// This can be used to estimate the memory occupied by the pending tasks of an async runtime.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, SizedFuture};
+///
+/// let value = [0u8; 64];
+///
+/// let future = SizedFuture::new(async move {
+///     value.len()
+/// });
+///
+/// assert!(future.get_heap_size() >= 64);
+/// ```
+pub struct SizedFuture<F: ?Sized> {
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> SizedFuture<F> {
+    /// Pins the given `future` inside the heap and wraps it.
+    pub fn new(future: F) -> Self {
+        Self {
+            inner: Box::pin(future),
+        }
+    }
+}
+
+impl<F: ?Sized> SizedFuture<F> {
+    /// Returns the wrapped future.
+    pub fn into_inner(self) -> Pin<Box<F>> {
+        self.inner
+    }
+}
+
+impl<F: ?Sized> From<Pin<Box<F>>> for SizedFuture<F> {
+    fn from(inner: Pin<Box<F>>) -> Self {
+        Self {
+            inner,
+        }
+    }
+}
+
+impl<F: Future + ?Sized> Future for SizedFuture<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl<F: ?Sized> GetSize for SizedFuture<F> {
+    fn get_heap_size(&self) -> usize {
+        std::mem::size_of_val(&*self.inner)
+    }
+}
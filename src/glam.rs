@@ -0,0 +1,82 @@
+use ::glam::{
+    Affine2,
+    Affine3A,
+    BVec2,
+    BVec3,
+    BVec3A,
+    BVec4,
+    BVec4A,
+    DAffine2,
+    DAffine3,
+    DMat2,
+    DMat3,
+    DMat4,
+    DQuat,
+    DVec2,
+    DVec3,
+    DVec4,
+    I64Vec2,
+    I64Vec3,
+    I64Vec4,
+    IVec2,
+    IVec3,
+    IVec4,
+    Mat2,
+    Mat3,
+    Mat3A,
+    Mat4,
+    Quat,
+    U64Vec2,
+    U64Vec3,
+    U64Vec4,
+    UVec2,
+    UVec3,
+    UVec4,
+    Vec2,
+    Vec3,
+    Vec3A,
+    Vec4,
+};
+
+use crate::GetSize;
+
+
+
+// Vectors, matrices and quaternions store their components inline, so they never occupy any heap memory.
+impl GetSize for Vec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Vec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Vec3A { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Vec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DVec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DVec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DVec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for IVec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for IVec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for IVec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for UVec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for UVec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for UVec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for I64Vec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for I64Vec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for I64Vec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for U64Vec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for U64Vec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for U64Vec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for BVec2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for BVec3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for BVec3A { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for BVec4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for BVec4A { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Mat2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Mat3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Mat3A { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Mat4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DMat2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DMat3 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DMat4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Quat { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DQuat { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Affine2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Affine3A { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DAffine2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for DAffine3 { const CAN_ALLOCATE_HEAP: bool = false; }
@@ -0,0 +1,144 @@
+use std::hash::{BuildHasher, Hash};
+
+use ::hashbrown::{DefaultHashBuilder, HashMap, HashSet};
+
+use crate::{GetSize, GetSizeTracker, SizeReport, SizeSource};
+
+
+
+// The default hasher state is stored inline by the maps and sets using it.
+impl GetSize for DefaultHashBuilder { const CAN_ALLOCATE_HEAP: bool = false; }
+
+/// The backing allocation, including the spare buckets and the control bytes, is measured exactly
+/// via [`HashMap::allocation_size`], instead of modeling it from the capacity.
+impl<K, V, S> GetSize for HashMap<K, V, S> where K: GetSize + Eq + Hash, V: GetSize, S: GetSize + BuildHasher {
+    fn get_heap_size(&self) -> usize {
+        // The hasher state is stored inline, so only its heap part gets added.
+        let mut total = self.allocation_size() + GetSize::get_heap_size(self.hasher());
+
+        if K::CAN_ALLOCATE_HEAP || V::CAN_ALLOCATE_HEAP {
+            for (k, v) in self.iter() {
+                total += GetSize::get_heap_size(k);
+                total += GetSize::get_heap_size(v);
+            }
+        }
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        if self.allocation_size() > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+            return (GetSize::get_heap_size(self), tracker);
+        }
+
+        let total = self.allocation_size() + GetSize::get_heap_size(self.hasher());
+        let (heap_size, tracker) = crate::entries_heap_size_with_tracker(self.iter(), tracker);
+
+        (total + heap_size, tracker)
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        let additional = self.capacity() - self.len();
+        let mut total = additional * (K::get_stack_size() + V::get_stack_size());
+
+        if K::CAN_ALLOCATE_HEAP || V::CAN_ALLOCATE_HEAP {
+            for (k, v) in self.iter() {
+                total += GetSize::wasted_capacity(k);
+                total += GetSize::wasted_capacity(v);
+            }
+        }
+
+        total
+    }
+
+    fn borrowed_size(&self) -> usize {
+        let mut total = 0;
+
+        if K::CAN_ALLOCATE_HEAP || V::CAN_ALLOCATE_HEAP {
+            for (k, v) in self.iter() {
+                total += GetSize::borrowed_size(k);
+                total += GetSize::borrowed_size(v);
+            }
+        }
+
+        total
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        let heap_size = report.heap_size_of(self);
+        report.add(path, heap_size);
+
+        if heap_size>0 {
+            report.set_source(path, SizeSource::Exact);
+        }
+    }
+}
+
+/// Like the [`HashMap`], the backing allocation is measured exactly via [`HashSet::allocation_size`].
+impl<T, S> GetSize for HashSet<T, S> where T: GetSize + Eq + Hash, S: GetSize + BuildHasher {
+    fn get_heap_size(&self) -> usize {
+        // The hasher state is stored inline, so only its heap part gets added.
+        let mut total = self.allocation_size() + GetSize::get_heap_size(self.hasher());
+
+        if T::CAN_ALLOCATE_HEAP {
+            for v in self.iter() {
+                total += GetSize::get_heap_size(v);
+            }
+        }
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        if self.allocation_size() > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        if !T::CAN_ALLOCATE_HEAP {
+            return (GetSize::get_heap_size(self), tracker);
+        }
+
+        let total = self.allocation_size() + GetSize::get_heap_size(self.hasher());
+        let (heap_size, tracker) = crate::elements_heap_size_with_tracker(self.iter(), tracker);
+
+        (total + heap_size, tracker)
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        let additional = self.capacity() - self.len();
+        let mut total = additional * T::get_stack_size();
+
+        if T::CAN_ALLOCATE_HEAP {
+            for v in self.iter() {
+                total += GetSize::wasted_capacity(v);
+            }
+        }
+
+        total
+    }
+
+    fn borrowed_size(&self) -> usize {
+        let mut total = 0;
+
+        if T::CAN_ALLOCATE_HEAP {
+            for v in self.iter() {
+                total += GetSize::borrowed_size(v);
+            }
+        }
+
+        total
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        let heap_size = report.heap_size_of(self);
+        report.add(path, heap_size);
+
+        if heap_size>0 {
+            report.set_source(path, SizeSource::Exact);
+        }
+    }
+}
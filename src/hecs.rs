@@ -0,0 +1,195 @@
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+
+use ::hecs::{Archetype, Component, World};
+
+use crate::{GetSize, SizeReport};
+
+
+
+// The functions measuring the components of a single registered type.
+#[derive(Clone, Copy)]
+struct ComponentSizer {
+    name: &'static str,
+    stack_size: usize,
+    // Returns the heap size of all components of the registered type stored in the archetype.
+    heap_size: fn(&Archetype) -> usize,
+}
+
+/// Measures the components stored inside a [`hecs::World`](World), grouped by their type.
+///
+/// Worlds store their components type erased inside archetypes, so only components of types
+/// [registered](Self::register) beforehand can be measured by their [`GetSize`] implementation.
+/// Components of all other types are only counted.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, WorldSizer};
+///
+/// #[derive(GetSize)]
+/// struct Name(String);
+///
+/// let mut world = hecs::World::new();
+/// world.spawn((Name(String::from("player")), 42u32));
+///
+/// let mut sizer = WorldSizer::new();
+/// sizer.register::<Name>();
+///
+/// let report = sizer.report(&world);
+///
+/// assert_eq!(report.components()[0].heap_size, 6);
+/// assert_eq!(report.unregistered_components(), 1);
+/// ```
+#[derive(Clone, Default)]
+pub struct WorldSizer {
+    sizers: HashMap<TypeId, ComponentSizer>,
+}
+
+impl WorldSizer {
+    /// Creates a new sizer without any registered component types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the component type `T`, so its components get measured by their [`GetSize`]
+    /// implementation.
+    pub fn register<T>(&mut self) where T: Component + GetSize {
+        self.sizers.insert(TypeId::of::<T>(), ComponentSizer {
+            name: type_name::<T>(),
+            stack_size: T::get_stack_size(),
+            heap_size: |archetype| match archetype.get::<&T>() {
+                Some(column) => column.iter().map(GetSize::get_heap_size).sum(),
+                None => 0,
+            },
+        });
+    }
+
+    /// Whether the component type `T` was registered.
+    pub fn is_registered<T: Component>(&self) -> bool {
+        self.sizers.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Walks all archetypes of the `world`, measuring the components of every registered type.
+    ///
+    /// The stack size of the components is determined by their number, as the spare capacity
+    /// reserved by the archetypes is not exposed by `hecs`.
+    pub fn report(&self, world: &World) -> WorldSizeReport {
+        let mut components: HashMap<TypeId, ComponentSize> = HashMap::new();
+        let mut unregistered = 0;
+
+        for archetype in world.archetypes() {
+            let len = archetype.len() as usize;
+
+            if len==0 {
+                continue;
+            }
+
+            for id in archetype.component_types() {
+                let Some(sizer) = self.sizers.get(&id) else {
+                    unregistered += len;
+                    continue;
+                };
+
+                let size = components.entry(id).or_insert(ComponentSize {
+                    name: sizer.name,
+                    count: 0,
+                    stack_size: 0,
+                    heap_size: 0,
+                });
+
+                size.count += len;
+                size.stack_size += len * sizer.stack_size;
+                size.heap_size += (sizer.heap_size)(archetype);
+            }
+        }
+
+        let mut components: Vec<_> = components.into_values().collect();
+        components.sort_by_key(|size| std::cmp::Reverse(size.total()));
+
+        WorldSizeReport {
+            entities: world.len() as usize,
+            components,
+            unregistered,
+        }
+    }
+}
+
+impl std::fmt::Debug for WorldSizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorldSizer")
+            .field("registered", &self.sizers.len())
+            .finish()
+    }
+}
+
+/// The memory occupied by all components of a single type inside a world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentSize {
+    /// The name of the component type.
+    pub name: &'static str,
+    /// The number of components of this type.
+    pub count: usize,
+    /// The number of bytes the components occupy inside the archetype storages.
+    pub stack_size: usize,
+    /// The number of bytes the components own inside the heap.
+    pub heap_size: usize,
+}
+
+impl ComponentSize {
+    /// Returns the total number of bytes occupied by the components.
+    pub fn total(&self) -> usize {
+        self.stack_size + self.heap_size
+    }
+}
+
+/// Lists the memory occupied by the components of a world per component type, as determined by
+/// [`WorldSizer::report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldSizeReport {
+    entities: usize,
+    components: Vec<ComponentSize>,
+    unregistered: usize,
+}
+
+impl WorldSizeReport {
+    /// Returns the number of entities inside the world.
+    pub fn entities(&self) -> usize {
+        self.entities
+    }
+
+    /// Returns the memory occupied by the components of every registered type found inside the
+    /// world, largest first.
+    pub fn components(&self) -> &[ComponentSize] {
+        &self.components
+    }
+
+    /// Returns the number of components of types which were not registered, and therefore not
+    /// measured.
+    pub fn unregistered_components(&self) -> usize {
+        self.unregistered
+    }
+
+    /// Returns the total number of bytes occupied by the measured components.
+    pub fn total(&self) -> usize {
+        self.components.iter().map(ComponentSize::total).sum()
+    }
+
+    /// Converts this report into a [`SizeReport`], with one entry per component type holding
+    /// the total size of its components.
+    pub fn to_size_report(&self) -> SizeReport {
+        let mut report = SizeReport::new();
+
+        for size in &self.components {
+            report.add(size.name, size.total());
+        }
+
+        report
+    }
+}
+
+impl GetSize for WorldSizeReport {
+    fn get_heap_size(&self) -> usize {
+        self.components.capacity() * std::mem::size_of::<ComponentSize>()
+    }
+}
@@ -0,0 +1,279 @@
+use ::http::{Extensions, HeaderMap, Method, Request, Response, StatusCode, Uri, Version};
+use ::http::header::{HeaderName, HeaderValue};
+
+use crate::{ExtensionsSizer, GetSize, GetSizeTracker};
+
+
+
+// The names of the standard headers, which are stored without any heap allocation.
+// Sorted, so they can be searched for.
+const STANDARD_HEADERS: &[&str] = &[
+    "accept",
+    "accept-charset",
+    "accept-encoding",
+    "accept-language",
+    "accept-ranges",
+    "access-control-allow-credentials",
+    "access-control-allow-headers",
+    "access-control-allow-methods",
+    "access-control-allow-origin",
+    "access-control-expose-headers",
+    "access-control-max-age",
+    "access-control-request-headers",
+    "access-control-request-method",
+    "age",
+    "allow",
+    "alt-svc",
+    "authorization",
+    "cache-control",
+    "cache-status",
+    "cdn-cache-control",
+    "connection",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "content-length",
+    "content-location",
+    "content-range",
+    "content-security-policy",
+    "content-security-policy-report-only",
+    "content-type",
+    "cookie",
+    "date",
+    "dnt",
+    "etag",
+    "expect",
+    "expires",
+    "forwarded",
+    "from",
+    "host",
+    "if-match",
+    "if-modified-since",
+    "if-none-match",
+    "if-range",
+    "if-unmodified-since",
+    "last-modified",
+    "link",
+    "location",
+    "max-forwards",
+    "origin",
+    "pragma",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "public-key-pins",
+    "public-key-pins-report-only",
+    "range",
+    "referer",
+    "referrer-policy",
+    "refresh",
+    "retry-after",
+    "sec-websocket-accept",
+    "sec-websocket-extensions",
+    "sec-websocket-key",
+    "sec-websocket-protocol",
+    "sec-websocket-version",
+    "server",
+    "set-cookie",
+    "strict-transport-security",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "upgrade-insecure-requests",
+    "user-agent",
+    "vary",
+    "via",
+    "warning",
+    "www-authenticate",
+    "x-content-type-options",
+    "x-dns-prefetch-control",
+    "x-frame-options",
+    "x-xss-protection",
+];
+
+/// Standard headers are stored without any heap allocation, while custom ones store their name.
+impl GetSize for HeaderName {
+    fn get_heap_size(&self) -> usize {
+        let name = self.as_str();
+
+        match STANDARD_HEADERS.binary_search(&name) {
+            Ok(_) => 0,
+            Err(_) => name.len(),
+        }
+    }
+}
+
+impl GetSize for HeaderValue {
+    fn get_heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// The table layout is approximated from the number of headers the map can hold, while the
+/// names and values are accounted for individually.
+impl<T> GetSize for HeaderMap<T> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        // Each entry stores a hash, the links to its extra values, the name and its first value.
+        let entry_size = std::mem::size_of::<(u16, Option<(usize, usize)>, HeaderName, T)>();
+        // Additional values for the same name are stored together with the links to their neighbours.
+        let extra_size = std::mem::size_of::<(T, (usize, usize), (usize, usize))>();
+        // The hash table itself stores a 16 bit index and hash per slot.
+        let index_size = 2 * std::mem::size_of::<u16>();
+
+        let mut total = self.capacity() * (entry_size + index_size);
+        total += (self.len() - self.keys_len()) * extra_size;
+
+        for name in self.keys() {
+            total += GetSize::get_heap_size(name);
+        }
+
+        if T::CAN_ALLOCATE_HEAP {
+            for value in self.values() {
+                total += GetSize::get_heap_size(value);
+            }
+        }
+
+        total
+    }
+}
+
+impl GetSize for Uri {
+    fn get_heap_size(&self) -> usize {
+        let mut total = 0;
+
+        // The standard schemes are stored without any heap allocation.
+        if let Some(scheme) = self.scheme_str() {
+            if scheme!="http" && scheme!="https" {
+                total += scheme.len();
+            }
+        }
+
+        if let Some(authority) = self.authority() {
+            total += authority.as_str().len();
+        }
+
+        if let Some(path_and_query) = self.path_and_query() {
+            total += path_and_query.as_str().len();
+        }
+
+        total
+    }
+}
+
+/// Standard methods and short extension methods are stored inline.
+impl GetSize for Method {
+    fn get_heap_size(&self) -> usize {
+        let len = self.as_str().len();
+
+        if len>15 {
+            len
+        } else {
+            0
+        }
+    }
+}
+
+impl GetSize for StatusCode {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl GetSize for Version {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// The extensions are type erased, so only the map holding them is accounted for,
+/// not their contents. Use [`ExtensionsSizer::heap_size_of_extensions`] to include them.
+impl GetSize for Extensions {
+    fn get_heap_size(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let entry_size = std::mem::size_of::<std::any::TypeId>() + std::mem::size_of::<Box<dyn std::any::Any>>();
+
+        std::mem::size_of::<std::collections::HashMap<std::any::TypeId, Box<dyn std::any::Any>>>()
+            + self.len() * entry_size
+    }
+}
+
+// Determines the boxed size of the value of type `T` stored inside the extensions, if any.
+pub(crate) fn extension_size<T>(extensions: &Extensions) -> Option<usize>
+where
+    T: GetSize + Send + Sync + 'static,
+{
+    extensions.get::<T>().map(GetSize::get_size)
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+impl ExtensionsSizer {
+    /// Determines how many bytes the given extensions occupy inside the heap, including the
+    /// values stored inside them.
+    ///
+    /// The extensions can not be iterated, so only values of registered types are found.
+    /// All other values are accounted for by the fallback.
+    pub fn heap_size_of_extensions(&self, extensions: &Extensions) -> usize {
+        let mut total = GetSize::get_heap_size(extensions);
+        let mut found = 0;
+
+        for sizer in self.sizers.values() {
+            if let Some(size) = (sizer.http_extension)(extensions) {
+                total += size;
+                found += 1;
+            }
+        }
+
+        total + (extensions.len() - found) * self.fallback()
+    }
+}
+
+impl<T> GetSize for Request<T> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        let mut total = GetSize::get_heap_size(self.method());
+        total += GetSize::get_heap_size(self.uri());
+        total += GetSize::get_heap_size(self.headers());
+        total += GetSize::get_heap_size(self.extensions());
+        total += GetSize::get_heap_size(self.body());
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        let mut total = GetSize::get_heap_size(self.method());
+        total += GetSize::get_heap_size(self.uri());
+        total += GetSize::get_heap_size(self.headers());
+        total += GetSize::get_heap_size(self.extensions());
+
+        // The parts are measured without a tracker, so they are reported as a single allocation.
+        if total > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        let (body, tracker) = GetSize::get_heap_size_with_tracker(self.body(), tracker);
+
+        (total + body, tracker)
+    }
+}
+
+impl<T> GetSize for Response<T> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        let mut total = GetSize::get_heap_size(self.headers());
+        total += GetSize::get_heap_size(self.extensions());
+        total += GetSize::get_heap_size(self.body());
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        let mut total = GetSize::get_heap_size(self.headers());
+        total += GetSize::get_heap_size(self.extensions());
+
+        // The parts are measured without a tracker, so they are reported as a single allocation.
+        if total > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        let (body, tracker) = GetSize::get_heap_size_with_tracker(self.body(), tracker);
+
+        (total + body, tracker)
+    }
+}
@@ -0,0 +1,134 @@
+use std::any::Any;
+use std::cell::RefCell;
+
+use crate::{GetSize, GetSizeTracker, SizingContext, TraversalContext};
+
+
+
+thread_local! {
+    // The context installed on the current thread by `with_context`, if any.
+    static CURRENT: RefCell<Option<SizingContext>> = const { RefCell::new(None) };
+}
+
+// Moves the installed context back to its owner once `with_context` returns or unwinds, and
+// reinstalls the context which was installed before.
+struct Restore<'a> {
+    context: &'a mut SizingContext,
+    previous: Option<SizingContext>,
+}
+
+impl Drop for Restore<'_> {
+    fn drop(&mut self) {
+        let installed = CURRENT.with(|current| current.replace(self.previous.take()));
+
+        if let Some(installed) = installed {
+            *self.context = installed;
+        }
+    }
+}
+
+/// Installs the `context` on the current thread while calling `f`, returning its result.
+///
+/// All measurements done by [`get_heap_size_implicit`](GetSize::get_heap_size_implicit) and
+/// [`get_size_implicit`](GetSize::get_size_implicit) within `f` use the installed context as
+/// their tracker, no matter how deep down the call chain they happen. This allows code which
+/// can not pass a tracker along, e.g. implementations behind trait objects, to cooperate in
+/// accounting for shared ownership objects only once. The budget of the context does not apply
+/// to these measurements and they are not added to its [used](SizingContext::used) bytes.
+///
+/// The context is moved back into `context` once `f` returns, even if it panics. Nested calls
+/// install their context for the duration of their `f`, after which the outer one is active again.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use get_size::{with_context, GetSize, SizingContext};
+///
+/// trait Shape {
+///     fn heap_size(&self) -> usize;
+/// }
+///
+/// struct Polygon(Arc<Vec<(f64, f64)>>);
+///
+/// impl Shape for Polygon {
+///     fn heap_size(&self) -> usize {
+///         self.0.get_heap_size_implicit()
+///     }
+/// }
+///
+/// let points = Arc::new(vec![(0.0, 0.0); 16]);
+/// let shapes: Vec<Box<dyn Shape>> = vec![
+///     Box::new(Polygon(Arc::clone(&points))),
+///     Box::new(Polygon(Arc::clone(&points))),
+/// ];
+///
+/// let mut ctx = SizingContext::new();
+/// let total: usize = with_context(&mut ctx, || shapes.iter().map(|shape| shape.heap_size()).sum());
+///
+/// // The points are only accounted for once.
+/// assert_eq!(total, points.get_heap_size());
+/// ```
+pub fn with_context<R>(context: &mut SizingContext, f: impl FnOnce() -> R) -> R {
+    let installed = std::mem::take(context);
+    let previous = CURRENT.with(|current| current.replace(Some(installed)));
+
+    let _restore = Restore {
+        context,
+        previous,
+    };
+
+    f()
+}
+
+/// Returns `true` if a context is installed on the current thread by [`with_context`].
+pub fn has_implicit_context() -> bool {
+    CURRENT.with(|current| current.borrow().is_some())
+}
+
+// Measures the heap size of `value` using the installed context, if any.
+pub(crate) fn implicit_heap_size<V: GetSize>(value: &V) -> usize {
+    if has_implicit_context() {
+        GetSize::get_heap_size_with_tracker(value, ImplicitTracker).0
+    } else {
+        GetSize::get_heap_size(value)
+    }
+}
+
+// Forwards to the installed context. The context only gets borrowed for the duration of each
+// single call, so measurements nested inside of a measurement can use it as well.
+struct ImplicitTracker;
+
+impl ImplicitTracker {
+    fn with<R>(&self, f: impl FnOnce(&mut SizingContext) -> R, default: R) -> R {
+        CURRENT.with(|current| match current.borrow_mut().as_mut() {
+            Some(context) => f(context),
+            None => default,
+        })
+    }
+}
+
+impl GetSizeTracker for ImplicitTracker {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        self.with(|context| GetSizeTracker::track(context, addr, strong_ref), true)
+    }
+
+    fn visited_count(&self) -> usize {
+        self.with(|context| GetSizeTracker::visited_count(context), 0)
+    }
+
+    fn hit_count(&self) -> usize {
+        self.with(|context| GetSizeTracker::hit_count(context), 0)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        CURRENT.with(|current| match current.borrow_mut().as_mut() {
+            Some(context) => GetSizeTracker::context(context, f),
+            None => f(&mut TraversalContext::default()),
+        })
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::ops::Deref;
+
+use crate::{AllocationFinder, CapacityReport, DedupEstimator, GetSize, GetSizeTracker, SizeReport};
+
+
+
+/// A wrapper around an append-only collection, which maintains a running total of the
+/// collection's heap size.
+///
+/// Only newly added elements get measured, which is considerably cheaper than measuring
+/// the whole collection again each time its size is of interest. The growth of the backing
+/// buffer is accounted for as reported by the collection, while items which did not add an
+/// element, like duplicate keys, cause a full recomputation. Since elements might still change
+/// their size afterwards, e.g. through interior mutability, the running total is only an
+/// estimate. It gets corrected by a full recomputation after every `interval` added elements.
+///
+/// The running total is returned by [`estimated_heap_size`](Self::estimated_heap_size). The
+/// [`GetSize`] implementation of the wrapper measures the wrapped collection instead, like for
+/// any other collection, so shared ownership objects inside of it get tracked as usual.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, IncrementalSize};
+///
+/// let mut log = IncrementalSize::new(Vec::<String>::new());
+///
+/// log.push(String::from("Hello"));
+/// log.push(String::from("world!"));
+///
+/// log.recompute();
+///
+/// assert_eq!(log.estimated_heap_size(), log.get_heap_size());
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalSize<C> {
+    inner: C,
+    heap_size: usize,
+    added: usize,
+    interval: usize,
+}
+
+impl<C: GetSize> IncrementalSize<C> {
+    /// The default number of added elements after which the heap size gets fully recomputed.
+    pub const DEFAULT_INTERVAL: usize = 1024;
+
+    /// Wraps the given collection, using the [default interval](Self::DEFAULT_INTERVAL).
+    pub fn new(inner: C) -> Self {
+        Self::with_interval(inner, Self::DEFAULT_INTERVAL)
+    }
+
+    /// Wraps the given collection, fully recomputing its heap size after every `interval`
+    /// added elements. An `interval` of `0` disables the periodic recomputation.
+    pub fn with_interval(inner: C, interval: usize) -> Self {
+        let heap_size = GetSize::get_heap_size(&inner);
+
+        Self {
+            inner,
+            heap_size,
+            added: 0,
+            interval,
+        }
+    }
+
+    /// Appends a single `item` to the collection.
+    pub fn push<T>(&mut self, item: T)
+    where
+        T: GetSize,
+        C: Extend<T> + IncrementalCollection,
+    {
+        self.extend(std::iter::once(item));
+    }
+
+    /// Appends all items of the given iterator to the collection.
+    ///
+    /// If some of the items did not add an element, e.g. as they replaced the value of an
+    /// existing key or were equal to an element of a set, the heap size gets fully recomputed.
+    pub fn extend<T, I>(&mut self, iter: I)
+    where
+        T: GetSize,
+        C: Extend<T> + IncrementalCollection,
+        I: IntoIterator<Item = T>,
+    {
+        let mut heap_size = 0;
+        let mut added = 0;
+        let old_buffer = self.inner.buffer_size();
+        let old_count = self.inner.element_count();
+
+        self.inner.extend(iter.into_iter().inspect(|item| {
+            // The stack part of the items is held by the buffer, which is accounted for below.
+            heap_size += GetSize::get_heap_size(item);
+            added += 1;
+        }));
+
+        self.added += added;
+
+        // Replaced and dropped items were measured, but the elements they replaced are unknown.
+        if self.inner.element_count() - old_count != added {
+            self.recompute();
+            return;
+        }
+
+        // The buffer might have grown, which allocated the stack part of the additional slots.
+        self.heap_size = self.heap_size - old_buffer + self.inner.buffer_size() + heap_size;
+
+        if self.interval>0 && self.added>=self.interval {
+            self.recompute();
+        }
+    }
+
+    /// Modifies the collection by the given closure and recomputes its heap size afterwards.
+    pub fn modify<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut C) -> R,
+    {
+        let result = f(&mut self.inner);
+
+        self.recompute();
+
+        result
+    }
+
+    /// Fully recomputes the heap size of the collection.
+    pub fn recompute(&mut self) {
+        self.heap_size = GetSize::get_heap_size(&self.inner);
+        self.added = 0;
+    }
+
+    /// Returns the running total of the collection's heap size.
+    pub fn estimated_heap_size(&self) -> usize {
+        self.heap_size
+    }
+
+    /// Returns a reference to the wrapped collection.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the wrapped collection.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// A collection storing the stack part of its elements inside a single buffer.
+///
+/// Required by [`IncrementalSize`] to account for the growth of that buffer, and to notice
+/// items which did not add an element.
+pub trait IncrementalCollection {
+    /// Returns the size of the buffer in bytes, as accounted for by the [`GetSize`]
+    /// implementation of the collection, without the heap part of the elements.
+    fn buffer_size(&self) -> usize;
+
+    /// Returns the number of elements held by the collection.
+    fn element_count(&self) -> usize;
+}
+
+macro_rules! impl_incremental_sequence {
+    ($name:ident) => {
+        impl<T: GetSize> IncrementalCollection for $name<T> {
+            fn buffer_size(&self) -> usize {
+                self.capacity() * T::get_stack_size()
+            }
+
+            fn element_count(&self) -> usize {
+                self.len()
+            }
+        }
+    }
+}
+
+impl_incremental_sequence!(Vec);
+impl_incremental_sequence!(VecDeque);
+impl_incremental_sequence!(BinaryHeap);
+
+impl<T: GetSize, S> IncrementalCollection for HashSet<T, S> {
+    fn buffer_size(&self) -> usize {
+        self.capacity() * T::get_stack_size()
+    }
+
+    fn element_count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K: GetSize, V: GetSize, S> IncrementalCollection for HashMap<K, V, S> {
+    fn buffer_size(&self) -> usize {
+        self.capacity() * (K::get_stack_size() + V::get_stack_size())
+    }
+
+    fn element_count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<C> Deref for IncrementalSize<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+// Measures the wrapped collection, ignoring the running total.
+impl<C: GetSize> GetSize for IncrementalSize<C> {
+    const CAN_ALLOCATE_HEAP: bool = C::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.inner)
+    }
+
+    fn get_heap_size_with_tracker<T: GetSizeTracker>(&self, tracker: T) -> (usize, T) {
+        GetSize::get_heap_size_with_tracker(&self.inner, tracker)
+    }
+
+    fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+        GetSize::get_heap_size_within(&self.inner, limit)
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        GetSize::wasted_capacity(&self.inner)
+    }
+
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        GetSize::report_wasted_capacity(&self.inner, path, report)
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        GetSize::report_size(&self.inner, path, report)
+    }
+
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        GetSize::record_allocations(&self.inner, path, finder)
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        GetSize::record_payloads(&self.inner, estimator)
+    }
+
+    fn borrowed_size(&self) -> usize {
+        GetSize::borrowed_size(&self.inner)
+    }
+}
@@ -0,0 +1,45 @@
+use ::indexmap::{IndexMap, IndexSet};
+
+use crate::{GetSize, MapBreakdown, MapHeapBreakdown};
+use crate::map::entries_breakdown;
+
+
+
+// The entries are stored inside a vector together with their hash, while the hash table
+// only holds their indices.
+fn table_overhead<K, V>(capacity: usize, len: usize) -> usize {
+    let entry_size = std::mem::size_of::<(usize, K, V)>();
+    let spare = capacity - len;
+
+    spare * entry_size
+        + len * (entry_size - std::mem::size_of::<K>() - std::mem::size_of::<V>())
+        + capacity * std::mem::size_of::<usize>()
+}
+
+impl<K, V, S> MapHeapBreakdown for IndexMap<K, V, S> where K: GetSize, V: GetSize {
+    fn heap_breakdown(&self) -> MapBreakdown {
+        let mut breakdown = entries_breakdown(self.iter());
+
+        breakdown.table_overhead = table_overhead::<K, V>(self.capacity(), self.len());
+
+        breakdown
+    }
+}
+
+impl<K, V, S> GetSize for IndexMap<K, V, S> where K: GetSize, V: GetSize {
+    fn get_heap_size(&self) -> usize {
+        self.heap_breakdown().total()
+    }
+}
+
+impl<T, S> GetSize for IndexSet<T, S> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        let mut total = table_overhead::<T, ()>(self.capacity(), self.len());
+
+        for v in self.iter() {
+            total += GetSize::get_size(v);
+        }
+
+        total
+    }
+}
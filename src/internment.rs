@@ -0,0 +1,49 @@
+use std::hash::Hash;
+
+use ::internment::{Intern, ArcIntern};
+
+use crate::{GetSize, GetSizeTracker};
+
+
+
+/// Interned values are stored only once inside the interner, no matter how many handles point to them.
+///
+/// The handle itself is treated as only occupying the stack. The interned value gets accounted for
+/// when a tracker is used, but only for the first handle seen by the tracker.
+impl<T> GetSize for Intern<T> where T: GetSize + Eq + Hash + Send + Sync + 'static {
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        let addr = &**self as *const T;
+
+        if tracker.track(addr, *self) {
+            tracker.context(|context| context.allocation_found());
+
+            GetSize::get_size_with_tracker(&**self, tracker)
+        } else {
+            (0, tracker)
+        }
+    }
+}
+
+/// Interned values are stored only once inside the interner, no matter how many handles point to them.
+///
+/// The handle itself is treated as only occupying the stack. The interned value gets accounted for
+/// when a tracker is used, but only for the first handle seen by the tracker.
+impl<T> GetSize for ArcIntern<T> where T: GetSize + Eq + Hash + Send + Sync + 'static {
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        let addr = &**self as *const T;
+
+        if tracker.track(addr, self.clone()) {
+            tracker.context(|context| context.allocation_found());
+
+            GetSize::get_size_with_tracker(&**self, tracker)
+        } else {
+            (0, tracker)
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{GetSize, GetSizeTracker, StandardTracker};
+
+/// Metadata about the fields of a type, together with the heap size owned by each of them.
+///
+/// This trait gets implemented by the derive macro for types annotated with
+/// `#[get_size(introspect)]`, which allows generic tooling, like admin interfaces, to
+/// display the memory usage of a value broken down by its fields.
+///
+/// Only measured fields get listed, under the same labels used by [`SizeReport`](crate::SizeReport).
+/// The fields of enum variants are labeled by the variant and field name, e.g. `Some.0`,
+/// while variants measured as a whole are listed by their name alone.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, FieldSizes};
+///
+/// #[derive(GetSize)]
+/// #[get_size(introspect)]
+/// struct User {
+///     name: String,
+///     #[get_size(rename = "tags")]
+///     labels: Vec<u8>,
+///     #[get_size(ignore)]
+///     cache: Vec<u8>,
+/// }
+///
+/// assert_eq!(User::GET_SIZE_FIELDS, &["name", "tags"]);
+/// assert_eq!(User::GET_SIZE_FIELD_TYPES, &["String", "Vec<u8>"]);
+///
+/// let user = User {
+///     name: String::from("Alice"),
+///     labels: vec![1, 2, 3],
+///     cache: Vec::new(),
+/// };
+///
+/// assert_eq!(user.field_heap_sizes(), vec![("name", 5), ("tags", 3)]);
+/// ```
+pub trait FieldSizes: GetSize {
+    /// The labels of all measured fields, in the order they are declared.
+    const GET_SIZE_FIELDS: &'static [&'static str];
+
+    /// The types of all measured fields, in the same order as [`GET_SIZE_FIELDS`](Self::GET_SIZE_FIELDS).
+    ///
+    /// The types are given as written in the type definition, so they are neither resolved
+    /// nor does their formatting match [`type_name`](std::any::type_name).
+    const GET_SIZE_FIELD_TYPES: &'static [&'static str];
+
+    /// Returns a hash of the structure of this type, which allows external systems to tell
+    /// whether reports recorded by different builds are comparable, e.g. to key cached reports.
+    ///
+    /// The derive macro hashes the name of the type, the names and types of all its variants and
+    /// fields, including ignored ones, and the labels of all measured fields by [`shape_hash`].
+    /// The types are hashed as written in the type definition, so the hash is the same for all
+    /// instantiations of a generic type, and it only changes once the definition does, not
+    /// between builds, platforms or versions of Rust.
+    ///
+    /// The default implementation hashes [`GET_SIZE_FIELDS`](Self::GET_SIZE_FIELDS) and
+    /// [`GET_SIZE_FIELD_TYPES`](Self::GET_SIZE_FIELD_TYPES).
+    fn size_shape_hash() -> u64 {
+        let parts: Vec<&str> = Self::GET_SIZE_FIELDS.iter()
+            .zip(Self::GET_SIZE_FIELD_TYPES)
+            .flat_map(|(label, ty)| [*label, *ty])
+            .collect();
+
+        shape_hash(&parts)
+    }
+
+    /// Returns the label of every measured field of this value together with the heap
+    /// size owned by it. Enums only list the fields of their current variant.
+    ///
+    /// Objects shared between the fields are only accounted for once, by the first field
+    /// referring to them.
+    fn field_heap_sizes(&self) -> Vec<(&'static str, usize)> {
+        let tracker = StandardTracker::default();
+
+        Self::field_heap_sizes_with_tracker(self, tracker).0
+    }
+
+    /// Like [`field_heap_sizes`](Self::field_heap_sizes), but uses the given tracker.
+    fn field_heap_sizes_with_tracker<T: GetSizeTracker>(
+        &self,
+        tracker: T,
+    ) -> (Vec<(&'static str, usize)>, T);
+}
+
+/// Hashes the given `parts` by 64 bit FNV-1a, as used by [`FieldSizes::size_shape_hash`].
+///
+/// Unlike the hashers of the standard library, the result is guaranteed to stay the same
+/// across builds, platforms and versions of Rust. The parts are separated from each other,
+/// so e.g. `["ab", "c"]` and `["a", "bc"]` hash differently.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::shape_hash;
+///
+/// assert_eq!(shape_hash(&[]), 0xcbf29ce484222325);
+/// assert_ne!(shape_hash(&["ab", "c"]), shape_hash(&["a", "bc"]));
+/// ```
+pub const fn shape_hash(parts: &[&str]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    // Never occurs within UTF-8 encoded strings.
+    const SEPARATOR: u8 = 0xff;
+
+    let mut hash = OFFSET_BASIS;
+    let mut part = 0;
+
+    while part<parts.len() {
+        let bytes = parts[part].as_bytes();
+        let mut index = 0;
+
+        while index<bytes.len() {
+            hash ^= bytes[index] as u64;
+            hash = hash.wrapping_mul(PRIME);
+            index += 1;
+        }
+
+        hash ^= SEPARATOR as u64;
+        hash = hash.wrapping_mul(PRIME);
+        part += 1;
+    }
+
+    hash
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::iter::{Fuse, FusedIterator, Peekable};
+
+use crate::{GetSize, GetSizeUnsized};
+
+
+
+/// An iterator adapter which allows peeking at any number of upcoming elements,
+/// while exposing its buffered elements for measuring their size.
+///
+/// The adapters provided by the standard library, like [`Peekable`], do not expose their
+/// buffered elements, so they are treated as only occupying the stack. Use this adapter
+/// instead if the buffered elements might occupy considerable amounts of heap memory,
+/// e.g. inside the state of a parser.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, PeekBuffer};
+///
+/// let tokens = vec![String::from("let"), String::from("x"), String::from("=")];
+/// let mut tokens = PeekBuffer::new(tokens.into_iter());
+///
+/// assert_eq!(tokens.peek_nth(1).map(String::as_str), Some("x"));
+/// assert_eq!(tokens.buffered().count(), 2);
+///
+/// assert_eq!(tokens.next().as_deref(), Some("let"));
+/// assert_eq!(tokens.buffered().count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PeekBuffer<I: Iterator> {
+    iter: I,
+    buffer: VecDeque<I::Item>,
+}
+
+impl<I: Iterator> PeekBuffer<I> {
+    /// Creates a new adapter around the given iterator.
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Returns a reference to the next element, without advancing the iterator.
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        self.peek_nth(0)
+    }
+
+    /// Returns a reference to the `n`th upcoming element, without advancing the iterator.
+    ///
+    /// All elements up to it get buffered.
+    pub fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        while self.buffer.len()<=n {
+            self.buffer.push_back(self.iter.next()?);
+        }
+
+        self.buffer.get(n)
+    }
+
+    /// Returns an iterator over the currently buffered elements.
+    pub fn buffered(&self) -> impl Iterator<Item = &I::Item> {
+        self.buffer.iter()
+    }
+
+    /// Returns a reference to the underlying iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.iter
+    }
+
+    /// Consumes the adapter, returning the underlying iterator and the buffered elements.
+    pub fn into_parts(self) -> (I, VecDeque<I::Item>) {
+        (self.iter, self.buffer)
+    }
+}
+
+impl<I: Iterator> Iterator for PeekBuffer<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.buffer.pop_front() {
+            Some(item) => Some(item),
+            None => self.iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let buffered = self.buffer.len();
+
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I: FusedIterator> FusedIterator for PeekBuffer<I> {}
+
+impl<I> GetSize for PeekBuffer<I>
+where
+    I: Iterator + GetSize,
+    I::Item: GetSize,
+{
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.iter) + GetSize::get_heap_size(&self.buffer)
+    }
+}
+
+
+
+/// The remaining elements are accounted for, but not the space formerly occupied by
+/// already consumed elements, since the capacity of the buffer is not exposed.
+impl<T> GetSize for std::vec::IntoIter<T> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        GetSizeUnsized::get_size_of_val(self.as_slice())
+    }
+}
+
+/// The peeked element as well as the inner iterator are not exposed by the standard library,
+/// so only the stack size gets accounted for. Use [`PeekBuffer`] instead if the heap size of
+/// the buffered element is of interest.
+impl<I: Iterator> GetSize for Peekable<I> {}
+
+/// The inner iterator is not exposed by the standard library, so only the stack size
+/// gets accounted for.
+impl<I> GetSize for Fuse<I> {}
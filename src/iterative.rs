@@ -0,0 +1,106 @@
+use crate::AllocationFinder;
+
+
+
+/// Containers of nested objects of type `T`, like the `next: Option<Box<Node>>` field of a
+/// linked list node, which can be measured without recursion.
+///
+/// Types deriving [`GetSize`](crate::GetSize) use it for fields marked with
+/// `#[get_size(iterative)]`, measuring the nested objects one after another instead of
+/// recursively, so long chains of them can not overflow the stack. It is implemented for [`Box<T>`], [`Vec<T>`] and [`Vec<Box<T>>`], as well
+/// as for [`Option`]s of them.
+pub trait IterativeField<T> {
+    /// Returns the number of bytes this container occupies inside the heap, not including the
+    /// heap memory owned by the nested objects, which get added to `nodes` instead.
+    fn visit_nodes<'a>(&'a self, nodes: &mut Vec<&'a T>) -> usize;
+
+    /// Returns the number of bytes this container allocated, but does not use for nested objects.
+    /// The default implementation returns 0.
+    fn spare_capacity(&self) -> usize {
+        0
+    }
+
+    /// Records the allocations of this container with the `finder` under the given `path`,
+    /// adding the nested objects to `nodes`. The default implementation records the result of
+    /// [`visit_nodes`](Self::visit_nodes) as a single allocation.
+    fn record_nodes<'a>(&'a self, path: &str, finder: &mut AllocationFinder, nodes: &mut Vec<&'a T>) {
+        let size = self.visit_nodes(nodes);
+
+        finder.record(path, std::any::type_name::<Self>(), size);
+    }
+}
+
+impl<T> IterativeField<T> for Box<T> {
+    fn visit_nodes<'a>(&'a self, nodes: &mut Vec<&'a T>) -> usize {
+        nodes.push(&**self);
+
+        std::mem::size_of::<T>()
+    }
+
+    fn record_nodes<'a>(&'a self, path: &str, finder: &mut AllocationFinder, nodes: &mut Vec<&'a T>) {
+        finder.record(path, std::any::type_name::<Self>(), std::mem::size_of::<T>());
+
+        nodes.push(&**self);
+    }
+}
+
+impl<T> IterativeField<T> for Vec<T> {
+    fn visit_nodes<'a>(&'a self, nodes: &mut Vec<&'a T>) -> usize {
+        nodes.extend(self.iter());
+
+        self.capacity() * std::mem::size_of::<T>()
+    }
+
+    fn spare_capacity(&self) -> usize {
+        (self.capacity() - self.len()) * std::mem::size_of::<T>()
+    }
+
+    fn record_nodes<'a>(&'a self, path: &str, finder: &mut AllocationFinder, nodes: &mut Vec<&'a T>) {
+        finder.record(path, std::any::type_name::<Self>(), self.capacity() * std::mem::size_of::<T>());
+
+        nodes.extend(self.iter());
+    }
+}
+
+impl<T> IterativeField<T> for Vec<Box<T>> {
+    fn visit_nodes<'a>(&'a self, nodes: &mut Vec<&'a T>) -> usize {
+        nodes.extend(self.iter().map(|node| &**node));
+
+        self.capacity() * std::mem::size_of::<Box<T>>() + self.len() * std::mem::size_of::<T>()
+    }
+
+    fn spare_capacity(&self) -> usize {
+        (self.capacity() - self.len()) * std::mem::size_of::<Box<T>>()
+    }
+
+    fn record_nodes<'a>(&'a self, path: &str, finder: &mut AllocationFinder, nodes: &mut Vec<&'a T>) {
+        finder.record(path, std::any::type_name::<Self>(), self.capacity() * std::mem::size_of::<Box<T>>());
+
+        for node in self.iter() {
+            IterativeField::record_nodes(node, path, finder, nodes);
+        }
+    }
+}
+
+impl<T, F> IterativeField<T> for Option<F> where F: IterativeField<T> {
+    fn visit_nodes<'a>(&'a self, nodes: &mut Vec<&'a T>) -> usize {
+        match self {
+            // The options stack size already accounts for the containers stack size.
+            Some(field) => field.visit_nodes(nodes),
+            None => 0,
+        }
+    }
+
+    fn spare_capacity(&self) -> usize {
+        match self {
+            Some(field) => field.spare_capacity(),
+            None => 0,
+        }
+    }
+
+    fn record_nodes<'a>(&'a self, path: &str, finder: &mut AllocationFinder, nodes: &mut Vec<&'a T>) {
+        if let Some(field) = self {
+            field.record_nodes(path, finder, nodes);
+        }
+    }
+}
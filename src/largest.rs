@@ -0,0 +1,153 @@
+use crate::{GetSize, TraversalOrder};
+
+
+
+/// A single heap allocation found by [`find_largest`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Allocation {
+    /// The path of the object owning the allocation, relative to the measured object,
+    /// e.g. `names[3]` or `entries.Variant.buffer`.
+    pub path: String,
+    /// The name of the type owning the allocation, as returned by [`std::any::type_name`].
+    pub type_name: &'static str,
+    /// The size of the allocation in bytes.
+    pub size: usize,
+}
+
+impl GetSize for Allocation {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.path)
+    }
+}
+
+
+
+/// Keeps track of the largest heap allocations recorded by
+/// [`record_allocations`](GetSize::record_allocations).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AllocationFinder {
+    limit: usize,
+    allocations: Vec<Allocation>,
+    order: TraversalOrder,
+}
+
+impl AllocationFinder {
+    /// Creates a new finder, which keeps the `n` largest allocations.
+    pub fn new(n: usize) -> Self {
+        Self::with_order(n, TraversalOrder::default())
+    }
+
+    /// Creates a new finder, which keeps the `n` largest allocations, ordered by the given
+    /// [`TraversalOrder`].
+    pub fn with_order(n: usize, order: TraversalOrder) -> Self {
+        Self {
+            limit: n,
+            allocations: Vec::with_capacity(n),
+            order,
+        }
+    }
+
+    /// Returns the [`TraversalOrder`] applied by this finder.
+    pub fn order(&self) -> TraversalOrder {
+        self.order
+    }
+
+    /// Records an allocation of `size` bytes owned by an object of the given type found at `path`.
+    ///
+    /// Nothing gets recorded if `size` is 0 or if the `n` largest allocations recorded so far are
+    /// all larger than it. Allocations of equal size are ordered according to the
+    /// [order](Self::order) of this finder.
+    pub fn record(&mut self, path: &str, type_name: &'static str, size: usize) {
+        if size==0 {
+            return;
+        }
+
+        // Allocations of equal size are kept in the order they were found, unless they shall be
+        // ordered deterministically by their path.
+        let index = match self.order {
+            TraversalOrder::Native => self.allocations.partition_point(|allocation| allocation.size >= size),
+            TraversalOrder::Deterministic => self.allocations.partition_point(|allocation| {
+                allocation.size>size || (allocation.size==size && allocation.path.as_str()<=path)
+            }),
+        };
+
+        if index >= self.limit {
+            return;
+        }
+
+        self.allocations.truncate(self.limit - 1);
+        self.allocations.insert(index, Allocation {
+            path: path.to_string(),
+            type_name,
+            size,
+        });
+    }
+
+    // The number of allocations this finder keeps.
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the largest allocations recorded so far, largest first.
+    pub fn allocations(&self) -> &[Allocation] {
+        &self.allocations
+    }
+
+    /// Returns the largest allocations recorded, largest first.
+    pub fn into_allocations(self) -> Vec<Allocation> {
+        self.allocations
+    }
+}
+
+impl GetSize for AllocationFinder {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.allocations)
+    }
+}
+
+
+
+/// Returns the `n` largest individual heap allocations owned by `value`, largest first.
+///
+/// This is useful when hunting down a memory spike, where only the biggest offenders are of
+/// interest rather than a full [`SizeReport`](crate::SizeReport). Collections, [`Box`]es and
+/// derived types are looked into, so each element of a `Vec<String>` is listed on its own.
+/// All other types are listed as a single allocation holding all of their heap memory.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, find_largest};
+///
+/// #[derive(GetSize)]
+/// struct Cache {
+///     names: Vec<String>,
+///     buffer: Box<[u8; 64]>,
+/// }
+///
+/// let cache = Cache {
+///     names: vec![String::from("a"), "b".repeat(100)],
+///     buffer: Box::new([0; 64]),
+/// };
+///
+/// let largest = find_largest(&cache, 2);
+///
+/// assert_eq!(largest[0].path, "names[1]");
+/// assert_eq!(largest[0].type_name, "alloc::string::String");
+/// assert_eq!(largest[0].size, 100);
+/// assert_eq!(largest[1].path, "buffer");
+/// assert_eq!(largest[1].size, 64);
+/// ```
+pub fn find_largest<T: GetSize>(value: &T, n: usize) -> Vec<Allocation> {
+    find_largest_with_order(value, n, TraversalOrder::default())
+}
+
+/// Like [`find_largest`], but lists allocations of equal size, as well as the elements of hash
+/// tables, in the given [`TraversalOrder`].
+pub fn find_largest_with_order<T: GetSize>(value: &T, n: usize, order: TraversalOrder) -> Vec<Allocation> {
+    let mut finder = AllocationFinder::with_order(n, order);
+
+    GetSize::record_allocations(value, "", &mut finder);
+
+    finder.into_allocations()
+}
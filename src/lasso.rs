@@ -0,0 +1,29 @@
+use std::hash::BuildHasher;
+
+use ::lasso::{Key, Spur, MiniSpur, MicroSpur, LargeSpur, Rodeo};
+
+use crate::GetSize;
+
+
+
+impl GetSize for Spur {}
+impl GetSize for MiniSpur {}
+impl GetSize for MicroSpur {}
+impl GetSize for LargeSpur {}
+
+/// The interner storage, consisting of the arena holding the interned strings as well as
+/// the lookup tables.
+///
+/// Keys like [`Spur`] are treated as only occupying the stack, so the interned strings are
+/// only accounted for once, by the interner itself.
+impl<K, S> GetSize for Rodeo<K, S> where K: Key, S: BuildHasher + Clone {
+    fn get_heap_size(&self) -> usize {
+        // The arena holding the interned strings.
+        let mut total = self.current_memory_usage();
+
+        // The lookup tables, which hold a string reference and a key for every interned string.
+        total += self.capacity() * (std::mem::size_of::<&str>() + std::mem::size_of::<K>());
+
+        total
+    }
+}
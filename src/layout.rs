@@ -0,0 +1,97 @@
+/// Information about the stack layout of an enum, which is implemented by the derive macro
+/// for enums deriving [`GetSize`](crate::GetSize) with the `#[get_size(layout)]` attribute.
+///
+/// While the stack size of an enum is always that of its largest variant plus the
+/// discriminant, the discriminant can often be stored inside a niche of the variants fields,
+/// e.g. in the never used null value of a reference, so no additional space is required.
+/// This trait allows to determine how much space is taken up by the discriminant and padding,
+/// and how many bytes of an individual value are not used by its current variant.
+///
+/// The size of a variants fields is determined by the size of a tuple of the same types,
+/// so the results are approximate in the rare case that the compiler lays out an enum
+/// differently.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, EnumLayout};
+///
+/// #[derive(GetSize)]
+/// #[get_size(layout)]
+/// enum Message {
+///     Ping,
+///     Data(u64),
+/// }
+///
+/// #[derive(GetSize)]
+/// #[get_size(layout)]
+/// enum Reference<'a> {
+///     Missing,
+///     Present(&'a u64),
+/// }
+///
+/// // The discriminant requires an additional 8 bytes, due to the alignment of u64.
+/// assert_eq!(Message::variant_stack_overhead(), 8);
+/// assert_eq!(Message::Ping.wasted_stack_size(), 16);
+/// assert_eq!(Message::Data(1).wasted_stack_size(), 8);
+///
+/// // The discriminant is stored inside the null niche of the reference.
+/// assert_eq!(Reference::variant_stack_overhead(), 0);
+/// ```
+pub trait EnumLayout: Sized {
+    /// Returns the number of stack bytes which are not occupied by the fields of the largest
+    /// variant, i.e. the space taken up by the discriminant and padding.
+    ///
+    /// This is 0 if the discriminant gets stored inside a niche.
+    fn variant_stack_overhead() -> usize;
+
+    /// Returns the number of stack bytes occupied by the fields of the current variant.
+    fn variant_payload_size(&self) -> usize;
+
+    /// Returns the name of every variant together with the number of stack bytes occupied by its
+    /// fields, in the order the variants are declared. See [`enum_variant_stack_sizes`].
+    ///
+    /// The default implementation returns an empty list. Derived implementations list every variant.
+    fn variant_stack_sizes() -> Vec<(&'static str, usize)> {
+        Vec::new()
+    }
+
+    /// Returns the number of stack bytes which are not used by the fields of the current variant.
+    ///
+    /// The default implementation subtracts [`variant_payload_size`](Self::variant_payload_size)
+    /// from the size of the enum and is not meant to be changed.
+    fn wasted_stack_size(&self) -> usize {
+        std::mem::size_of::<Self>().saturating_sub(self.variant_payload_size())
+    }
+}
+
+/// Returns the name of every variant of the enum `E` together with the number of stack bytes
+/// occupied by its fields, largest first.
+///
+/// The size of an enum is determined by its largest variant, so a few large but rarely used
+/// variants make all values large. Boxing the fields of the variants at the top of the list
+/// shrinks the enum down to the size of the next variant not being boxed.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, enum_variant_stack_sizes};
+///
+/// #[derive(GetSize)]
+/// #[get_size(layout)]
+/// enum Event {
+///     Click(u32, u32),
+///     Upload([u8; 256]),
+///     Close,
+/// }
+///
+/// assert_eq!(enum_variant_stack_sizes::<Event>(), vec![("Upload", 256), ("Click", 8), ("Close", 0)]);
+/// ```
+pub fn enum_variant_stack_sizes<E: EnumLayout>() -> Vec<(&'static str, usize)> {
+    let mut sizes = E::variant_stack_sizes();
+
+    // Variants of the same size stay in the order they are declared.
+    sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+
+    sizes
+}
@@ -1,10 +1,13 @@
 #![doc = include_str!("./lib.md")]
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
 
 
-use std::sync::{Arc, Weak as ArcWeak, Mutex, RwLock};
+use std::sync::{Arc, Weak as ArcWeak, Mutex, RwLock, OnceLock, PoisonError};
+use std::cell::{Cell, OnceCell, RefCell, UnsafeCell};
 use std::sync::atomic::{
     AtomicBool,
     AtomicI8,
@@ -17,6 +20,7 @@ use std::sync::atomic::{
     AtomicU32,
     AtomicU64,
     AtomicUsize,
+    AtomicPtr,
     Ordering,
 };
 use std::collections::{
@@ -59,10 +63,288 @@ pub use get_size_derive::*;
 mod tracker;
 pub use tracker::*;
 
+mod weak;
+pub use weak::*;
+
+mod future;
+pub use future::*;
+
+mod incremental;
+pub use incremental::*;
+
+mod watermark;
+pub use watermark::*;
+
+mod ext;
+pub use ext::*;
+
+mod fallible;
+pub use fallible::*;
+
+mod watchdog;
+pub use watchdog::*;
+
+mod layout;
+pub use layout::*;
+
+mod introspect;
+pub use introspect::*;
+
+mod pool;
+pub use pool::*;
+
+mod thread_locals;
+pub use thread_locals::*;
+
+mod accumulator;
+pub use accumulator::*;
+
+mod context;
+pub use context::*;
+
+mod sizer;
+pub use sizer::*;
+
+mod memo;
+pub use memo::*;
+
+mod implicit;
+pub use implicit::*;
+
+mod iter;
+pub use iter::*;
+
+mod obj;
+pub use obj::*;
+
+mod iterative;
+pub use iterative::*;
+
+mod extensions;
+pub use extensions::*;
+
+mod capacity;
+pub use capacity::*;
+
+mod report;
+pub use report::*;
+
+mod diff;
+pub use diff::*;
+
+mod largest;
+pub use largest::*;
+
+mod units;
+pub use units::*;
+
+mod dedup;
+pub use dedup::*;
+
+mod borrow;
+pub use borrow::*;
+
+mod padding;
+pub use padding::*;
+
+mod buffers;
+pub use buffers::*;
+
+mod map;
+pub use map::*;
+
+mod progress;
+pub use progress::*;
+
+mod shapes;
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+mod cooperative;
+#[cfg(feature = "tokio")]
+pub use cooperative::*;
+
+#[cfg(feature = "deepsize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deepsize")))]
+mod deepsize;
+#[cfg(feature = "deepsize")]
+pub use deepsize::*;
+
+#[cfg(feature = "internment")]
+#[cfg_attr(docsrs, doc(cfg(feature = "internment")))]
+mod internment;
+
+#[cfg(feature = "lasso")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lasso")))]
+mod lasso;
+
+#[cfg(feature = "simd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
+mod simd;
+
+#[cfg(feature = "wide")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wide")))]
+mod wide;
+
+#[cfg(feature = "glam")]
+#[cfg_attr(docsrs, doc(cfg(feature = "glam")))]
+mod glam;
+
+#[cfg(feature = "euclid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "euclid")))]
+mod euclid;
+
+#[cfg(feature = "cgmath")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cgmath")))]
+mod cgmath;
+
+#[cfg(feature = "rkyv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkyv")))]
+mod rkyv;
+
+#[cfg(feature = "sqlx")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlx")))]
+mod sqlx;
+
+#[cfg(feature = "rusqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rusqlite")))]
+mod rusqlite;
+
+#[cfg(feature = "indexmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "indexmap")))]
+mod indexmap;
+
+#[cfg(feature = "ahash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ahash")))]
+mod ahash;
+
+#[cfg(feature = "sysinfo")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sysinfo")))]
+mod sysinfo;
+
+#[cfg(feature = "num-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "num-format")))]
+mod num_format;
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+mod serde;
+
+#[cfg(feature = "serde_json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
+mod serde_json;
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+mod bytes;
+
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+mod axum;
+
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+mod http;
+
+#[cfg(feature = "tantivy")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tantivy")))]
+mod tantivy;
+
+#[cfg(feature = "hashbrown")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hashbrown")))]
+mod hashbrown;
+
+#[cfg(feature = "wgpu")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wgpu")))]
+mod wgpu;
+
+#[cfg(feature = "memmap2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "memmap2")))]
+mod memmap2;
+
+#[cfg(feature = "bitvec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitvec")))]
+mod bitvec;
+
+#[cfg(feature = "fixedbitset")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fixedbitset")))]
+mod fixedbitset;
+
+#[cfg(feature = "roaring")]
+#[cfg_attr(docsrs, doc(cfg(feature = "roaring")))]
+mod roaring;
+
+#[cfg(feature = "sled")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sled")))]
+mod sled;
+
+#[cfg(feature = "redb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
+mod redb;
+
+#[cfg(feature = "mio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mio")))]
+mod mio;
+
+#[cfg(feature = "socket2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "socket2")))]
+mod socket2;
+
+#[cfg(feature = "trace-alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "trace-alloc")))]
+mod trace_alloc;
+#[cfg(feature = "trace-alloc")]
+pub use trace_alloc::*;
+
+#[cfg(feature = "sharded-slab")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sharded-slab")))]
+mod sharded_slab;
+#[cfg(feature = "sharded-slab")]
+pub use sharded_slab::*;
+
+#[cfg(feature = "hecs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hecs")))]
+mod hecs;
+#[cfg(feature = "hecs")]
+pub use hecs::*;
+
+#[cfg(feature = "bytemuck")]
+mod bytemuck;
+
+#[cfg(feature = "zerocopy")]
+mod zerocopy;
+
+pub mod runtime;
+
+mod elements;
+use elements::*;
+
+// Re-exports used by the exported macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::elements::*;
+
+    #[cfg(feature = "bytemuck")]
+    pub use ::bytemuck;
+
+    #[cfg(feature = "zerocopy")]
+    pub use ::zerocopy;
+}
+
 
 
 /// Determine the size in bytes an object occupies inside RAM.
 pub trait GetSize: Sized {
+    /// Whether objects of this type can occupy any bytes inside the heap.
+    ///
+    /// If this is `false`, [`get_heap_size`](Self::get_heap_size) must always return 0.
+    /// Only a tracker requesting borrowed data to be accounted for, as described by
+    /// [`SizingPolicies::count_borrowed`], may cause borrowed slices and strings to report
+    /// heap bytes nevertheless.
+    /// Collections use this to determine their heap size without iterating over their elements.
+    /// The default is `true`, which is always correct.
+    const CAN_ALLOCATE_HEAP: bool = true;
+
     /// Determines how may bytes this object occupies inside the stack.
     ///
     /// The default implementation uses [std::mem::size_of] and should work for almost all types.
@@ -82,8 +364,14 @@ pub trait GetSize: Sized {
     ///
     /// The default implementation ignores the tracker and calls [`get_heap_size`](Self::get_heap_size)
     /// instead, returning the tracker untouched in the second argument.
-    fn get_heap_size_with_tracker<T: GetSizeTracker>(&self, tracker: T) -> (usize, T) {
-        (GetSize::get_heap_size(self), tracker)
+    fn get_heap_size_with_tracker<T: GetSizeTracker>(&self, mut tracker: T) -> (usize, T) {
+        let size = GetSize::get_heap_size(self);
+
+        if size > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        (size, tracker)
     }
 
     /// Determines the total size of the object.
@@ -105,329 +393,2350 @@ pub trait GetSize: Sized {
 
         let total = stack_size + heap_size;
 
-        (total, tracker)
+        (total, tracker)
+    }
+
+    /// Determines how many bytes this object occupies inside the heap, as long as they do
+    /// not exceed the given `limit`. Returns `None` otherwise.
+    ///
+    /// The default implementation calls [`get_heap_size`](Self::get_heap_size) and compares
+    /// the result against the `limit`. Implementations for collections, boxes, tuples and
+    /// derived types abort as soon as the bytes accounted for so far exceed the `limit`, which
+    /// makes this considerably cheaper than determining the exact size of large objects.
+    fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+        let heap_size = GetSize::get_heap_size(self);
+
+        if heap_size <= limit {
+            Some(heap_size)
+        } else {
+            None
+        }
+    }
+
+    /// Determines the total size of the object, as long as it does not exceed the given `limit`.
+    /// Returns `None` otherwise.
+    ///
+    /// The default implementation simply adds up the results of [`get_stack_size`](Self::get_stack_size)
+    /// and [`get_heap_size_within`](Self::get_heap_size_within) and is not meant to be changed.
+    fn get_size_within(&self, limit: usize) -> Option<usize> {
+        let stack_size = Self::get_stack_size();
+        let remaining = limit.checked_sub(stack_size)?;
+
+        let heap_size = GetSize::get_heap_size_within(self, remaining)?;
+
+        Some(stack_size + heap_size)
+    }
+
+    /// Checks if the total size of the object does not exceed the given `limit`.
+    ///
+    /// This is equivalent to `self.get_size() <= limit`, but aborts the measurement early
+    /// once the `limit` has been exceeded. See [`get_heap_size_within`](Self::get_heap_size_within).
+    fn fits_in(&self, limit: usize) -> bool {
+        GetSize::get_size_within(self, limit).is_some()
+    }
+
+    /// Determines how many bytes this object occupies inside the heap, using the [`SizingContext`]
+    /// installed on the current thread by [`with_context`] as the tracker, if any.
+    ///
+    /// This allows code which can not pass a tracker along, e.g. implementations behind trait
+    /// objects, to still take part in an ongoing measurement, so shared ownership objects are only
+    /// accounted for once. Without an installed context, this is equivalent to
+    /// [`get_heap_size`](Self::get_heap_size). Not meant to be changed.
+    fn get_heap_size_implicit(&self) -> usize {
+        implicit_heap_size(self)
+    }
+
+    /// Determines the total size of the object, using the context installed on the current thread
+    /// by [`with_context`], if any. See [`get_heap_size_implicit`](Self::get_heap_size_implicit).
+    fn get_size_implicit(&self) -> usize {
+        Self::get_stack_size() + GetSize::get_heap_size_implicit(self)
+    }
+
+    /// Adds the number of bytes this object occupies inside the heap to the `accumulator`.
+    ///
+    /// The default implementation adds the result of [`get_heap_size`](Self::get_heap_size).
+    /// Implementations for collections and derived implementations add the size of each element
+    /// or field separately instead, so the total never has to fit into a `usize`.
+    fn accumulate_heap_size<A: SizeAccumulator>(&self, accumulator: &mut A) {
+        accumulator.add(GetSize::get_heap_size(self));
+    }
+
+    /// Determines how many bytes this object occupies inside the heap, summed up by an
+    /// accumulator of type `A`, e.g. a [`u64`]. See [`SizeAccumulator`].
+    ///
+    /// The default implementation calls [`accumulate_heap_size`](Self::accumulate_heap_size)
+    /// and is not meant to be changed.
+    fn get_heap_size_as<A: SizeAccumulator>(&self) -> A {
+        let mut accumulator = A::ZERO;
+
+        GetSize::accumulate_heap_size(self, &mut accumulator);
+
+        accumulator
+    }
+
+    /// Determines the total size of the object, summed up by an accumulator of type `A`.
+    ///
+    /// The default implementation simply adds up the results of [`get_stack_size`](Self::get_stack_size)
+    /// and [`accumulate_heap_size`](Self::accumulate_heap_size) and is not meant to be changed.
+    fn get_size_as<A: SizeAccumulator>(&self) -> A {
+        let mut accumulator = A::ZERO;
+
+        accumulator.add(Self::get_stack_size());
+        GetSize::accumulate_heap_size(self, &mut accumulator);
+
+        accumulator
+    }
+
+    /// Determines how many bytes of the heap memory owned by this object are allocated but
+    /// currently unused, e.g. the spare capacity of a [`Vec`] or [`String`], which could be
+    /// reclaimed by calling `shrink_to_fit`.
+    ///
+    /// The default implementation returns 0. Shared ownership objects like [`Rc`] and [`Arc`]
+    /// are not looked into, as their contents can not be shrunk through them.
+    fn wasted_capacity(&self) -> usize {
+        0
+    }
+
+    /// Adds the spare capacity found inside this object to the `report`, using `path` to
+    /// identify this object.
+    ///
+    /// The default implementation adds the result of [`wasted_capacity`](Self::wasted_capacity)
+    /// under the given `path`. Derived implementations list each field separately instead.
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        report.add(path, GetSize::wasted_capacity(self));
+    }
+
+    /// Adds the heap memory owned by this object to the `report`, using `path` to identify
+    /// this object.
+    ///
+    /// The default implementation adds the heap size under the [policies](SizeReport::policies)
+    /// of the `report`, as determined by [`SizeReport::heap_size_of`], under the given `path`.
+    /// Derived implementations list each field separately instead.
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        let heap_size = report.heap_size_of(self);
+        report.add(path, heap_size);
+    }
+
+    /// Records the individual heap allocations owned by this object with the `finder`, using
+    /// `path` to identify this object. See [`find_largest`].
+    ///
+    /// The default implementation records the result of [`get_heap_size`](Self::get_heap_size)
+    /// as a single allocation. Collections, [`Box`]es and derived implementations look into
+    /// their elements and fields instead.
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        finder.record(path, std::any::type_name::<Self>(), GetSize::get_heap_size(self));
+    }
+
+    /// Records the immutable payloads owned by this object, like the contents of a [`String`],
+    /// with the `estimator`, which determines how many bytes could be saved by deduplicating
+    /// equal payloads.
+    ///
+    /// The default implementation records nothing. Shared ownership objects like [`Rc`] and
+    /// [`Arc`] are not looked into, as their contents are already shared.
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        let _ = estimator;
+    }
+
+    /// Determines how many bytes of borrowed data this object refers to, like the data of a
+    /// [`Cow::Borrowed`]. Following the [ownership based accounting](crate#ownership-based-accounting),
+    /// these bytes are not part of [`get_heap_size`](Self::get_heap_size). A [`BorrowReport`]
+    /// compares both.
+    ///
+    /// The default implementation returns 0. Only data borrowed by a [`Cow`] is accounted for.
+    fn borrowed_size(&self) -> usize {
+        0
+    }
+}
+
+
+
+/// Determine the size in bytes a possibly dynamically sized object occupies inside RAM.
+///
+/// In contrast to [`GetSize`], the stack size gets determined from the value itself rather than
+/// from its type. This allows to correctly account for dynamically sized types like slices,
+/// [`str`] and trait objects, e.g. when they are held inside a [`Box`].
+///
+/// This trait is automatically implemented for all types which implement [`GetSize`].
+/// Trait objects can be supported by making [`GetSizeUnsized`] a supertrait of the trait in question.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, GetSizeUnsized};
+///
+/// trait Shape: GetSizeUnsized {}
+///
+/// struct Polygon {
+///     points: Vec<(f64, f64)>,
+/// }
+///
+/// impl GetSize for Polygon {
+///     fn get_heap_size(&self) -> usize {
+///         GetSize::get_heap_size(&self.points)
+///     }
+/// }
+///
+/// impl Shape for Polygon {}
+///
+/// let points = Vec::with_capacity(4);
+/// let shape: Box<dyn Shape> = Box::new(Polygon { points });
+///
+/// assert_eq!(shape.get_heap_size(), std::mem::size_of::<Polygon>() + 4 * 16);
+/// ```
+pub trait GetSizeUnsized {
+    /// Determines how many bytes this object occupies inside the stack.
+    ///
+    /// The default implementation uses [std::mem::size_of_val].
+    fn get_stack_size_of_val(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
+    /// Determines how many bytes this object occupies inside the heap.
+    ///
+    /// The default implementation returns 0, assuming the object is fully allocated on the stack.
+    fn get_heap_size_of_val(&self) -> usize {
+        0
+    }
+
+    /// Determines the total size of the object.
+    ///
+    /// The default implementation simply adds up the results of
+    /// [`get_stack_size_of_val`](Self::get_stack_size_of_val) and
+    /// [`get_heap_size_of_val`](Self::get_heap_size_of_val) and is not meant to be changed.
+    fn get_size_of_val(&self) -> usize {
+        self.get_stack_size_of_val() + self.get_heap_size_of_val()
+    }
+
+    /// Determines how many bytes this object occupies inside the heap, as long as they do
+    /// not exceed the given `limit`. Returns `None` otherwise. See [`GetSize::get_heap_size_within`].
+    ///
+    /// The default implementation calls [`get_heap_size_of_val`](Self::get_heap_size_of_val)
+    /// and compares the result against the `limit`.
+    fn get_heap_size_of_val_within(&self, limit: usize) -> Option<usize> {
+        let heap_size = self.get_heap_size_of_val();
+
+        if heap_size <= limit {
+            Some(heap_size)
+        } else {
+            None
+        }
+    }
+
+    /// Determines how many bytes this object occupies inside the heap while using the tracker
+    /// of its owner. See [`GetSize::get_heap_size_with_tracker`]. The tracker gets passed as a
+    /// [`DynTracker`], so this trait can still be used as a supertrait of trait objects.
+    ///
+    /// The default implementation ignores the tracker and returns the result of
+    /// [`get_heap_size_of_val`](Self::get_heap_size_of_val).
+    fn get_heap_size_of_val_with_tracker(&self, tracker: &mut DynTracker<'_>) -> usize {
+        let _ = tracker;
+
+        self.get_heap_size_of_val()
+    }
+
+    /// Determines the total size of the object while using the tracker of its owner.
+    ///
+    /// The default implementation simply adds up the results of
+    /// [`get_stack_size_of_val`](Self::get_stack_size_of_val) and
+    /// [`get_heap_size_of_val_with_tracker`](Self::get_heap_size_of_val_with_tracker) and is not
+    /// meant to be changed.
+    fn get_size_of_val_with_tracker(&self, tracker: &mut DynTracker<'_>) -> usize {
+        self.get_stack_size_of_val() + self.get_heap_size_of_val_with_tracker(tracker)
+    }
+
+    /// Determines how many bytes of the heap memory owned by this object are allocated but
+    /// currently unused. See [`GetSize::wasted_capacity`].
+    ///
+    /// The default implementation returns 0.
+    fn wasted_capacity_of_val(&self) -> usize {
+        0
+    }
+
+    /// Records the immutable payloads owned by this object with the `estimator`.
+    /// See [`GetSize::record_payloads`].
+    ///
+    /// The default implementation records nothing.
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        let _ = estimator;
+    }
+
+    /// Determines how many bytes of borrowed data this object refers to.
+    /// See [`GetSize::borrowed_size`].
+    ///
+    /// The default implementation returns 0.
+    fn borrowed_size_of_val(&self) -> usize {
+        0
+    }
+
+    /// Records the individual heap allocations owned by this object with the `finder`.
+    /// See [`GetSize::record_allocations`].
+    ///
+    /// The default implementation records the result of [`get_heap_size_of_val`](Self::get_heap_size_of_val)
+    /// as a single allocation.
+    fn record_allocations_of_val(&self, path: &str, finder: &mut AllocationFinder) {
+        finder.record(path, std::any::type_name_of_val(self), self.get_heap_size_of_val());
+    }
+}
+
+impl<T> GetSizeUnsized for T where T: GetSize {
+    fn get_stack_size_of_val(&self) -> usize {
+        T::get_stack_size()
+    }
+
+    fn get_heap_size_of_val(&self) -> usize {
+        GetSize::get_heap_size(self)
+    }
+
+    fn get_heap_size_of_val_within(&self, limit: usize) -> Option<usize> {
+        GetSize::get_heap_size_within(self, limit)
+    }
+
+    fn get_heap_size_of_val_with_tracker(&self, tracker: &mut DynTracker<'_>) -> usize {
+        GetSize::get_heap_size_with_tracker(self, tracker).0
+    }
+
+    fn wasted_capacity_of_val(&self) -> usize {
+        GetSize::wasted_capacity(self)
+    }
+
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        GetSize::record_payloads(self, estimator);
+    }
+
+    fn borrowed_size_of_val(&self) -> usize {
+        GetSize::borrowed_size(self)
+    }
+
+    fn record_allocations_of_val(&self, path: &str, finder: &mut AllocationFinder) {
+        GetSize::record_allocations(self, path, finder);
+    }
+}
+
+impl<T> GetSizeUnsized for [T] where T: GetSize {
+    fn get_stack_size_of_val(&self) -> usize {
+        self.len() * T::get_stack_size()
+    }
+
+    fn get_heap_size_of_val(&self) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        let mut total = 0;
+
+        for element in self.iter() {
+            // The slices stack size already accounts for the stack size of its elements.
+            total += GetSize::get_heap_size(element);
+        }
+
+        total
+    }
+
+    fn get_heap_size_of_val_within(&self, limit: usize) -> Option<usize> {
+        if !T::CAN_ALLOCATE_HEAP {
+            return Some(0);
+        }
+
+        let mut total = 0;
+
+        for element in self.iter() {
+            let remaining = limit - total;
+
+            total += GetSize::get_heap_size_within(element, remaining)?;
+        }
+
+        Some(total)
+    }
+
+    fn get_heap_size_of_val_with_tracker(&self, tracker: &mut DynTracker<'_>) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        elements_heap_size_with_tracker(self.iter(), tracker).0
+    }
+
+    fn wasted_capacity_of_val(&self) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        self.iter().map(GetSize::wasted_capacity).sum()
+    }
+
+    fn borrowed_size_of_val(&self) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        self.iter().map(GetSize::borrowed_size).sum()
+    }
+
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        if !T::CAN_ALLOCATE_HEAP {
+            return;
+        }
+
+        for element in self.iter() {
+            GetSize::record_payloads(element, estimator);
+        }
+    }
+
+    fn record_allocations_of_val(&self, path: &str, finder: &mut AllocationFinder) {
+        record_element_allocations(self.iter(), path, finder);
+    }
+}
+
+impl GetSizeUnsized for str {
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_bytes(), self.len());
+    }
+}
+
+impl GetSizeUnsized for std::path::Path {
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_os_str().as_encoded_bytes(), self.as_os_str().len());
+    }
+}
+
+impl GetSizeUnsized for std::ffi::OsStr {
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_encoded_bytes(), self.len());
+    }
+}
+
+impl GetSizeUnsized for std::ffi::CStr {
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.to_bytes(), self.to_bytes_with_nul().len());
+    }
+}
+
+// Closures held as trait objects, e.g. inside a `Box<dyn Fn(u32) -> bool>`. Only the captured
+// environment stored inline inside the closure gets accounted for, as returned by `size_of_val`,
+// since the heap memory owned by captured values can not be looked into.
+macro_rules! impl_size_fn {
+    ($F:ident $(, $A:ident)*) => {
+        impl<'a, R, $($A,)*> GetSizeUnsized for dyn $F($($A),*) -> R + 'a {}
+        impl<'a, R, $($A,)*> GetSizeUnsized for dyn $F($($A),*) -> R + Send + 'a {}
+        impl<'a, R, $($A,)*> GetSizeUnsized for dyn $F($($A),*) -> R + Sync + 'a {}
+        impl<'a, R, $($A,)*> GetSizeUnsized for dyn $F($($A),*) -> R + Send + Sync + 'a {}
+    }
+}
+
+macro_rules! execute_fn_macro_8 {
+    ($name:ident, $F:ident) => {
+        $name!($F);
+        $name!($F, A1);
+        $name!($F, A1, A2);
+        $name!($F, A1, A2, A3);
+        $name!($F, A1, A2, A3, A4);
+        $name!($F, A1, A2, A3, A4, A5);
+        $name!($F, A1, A2, A3, A4, A5, A6);
+        $name!($F, A1, A2, A3, A4, A5, A6, A7);
+        $name!($F, A1, A2, A3, A4, A5, A6, A7, A8);
+    }
+}
+
+execute_fn_macro_8!(impl_size_fn, Fn);
+execute_fn_macro_8!(impl_size_fn, FnMut);
+execute_fn_macro_8!(impl_size_fn, FnOnce);
+
+
+
+/// References to data which is owned by the process as a whole, like data intentionally leaked
+/// by [`Box::leak`], or `'static` data which is only borrowed by a [`Cow`].
+///
+/// Following the [ownership based accounting](crate#ownership-based-accounting), such data is
+/// ignored by [`GetSize`]. This trait allows to account for it anyway, and is used by the derive
+/// macro for fields with the `leaked` attribute.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, GetSizeLeaked};
+///
+/// let config: &'static str = Box::leak(String::from("Hello").into_boxed_str());
+///
+/// assert_eq!(GetSize::get_heap_size(&config), 0);
+/// assert_eq!(config.get_heap_size_leaked(), 5);
+/// ```
+pub trait GetSizeLeaked {
+    /// Determines how many bytes this object occupies inside the heap, including the
+    /// referenced `'static` data.
+    fn get_heap_size_leaked(&self) -> usize;
+}
+
+impl<T> GetSizeLeaked for &'static T where T: GetSizeUnsized + ?Sized {
+    fn get_heap_size_leaked(&self) -> usize {
+        GetSizeUnsized::get_size_of_val(*self)
+    }
+}
+
+impl<T> GetSizeLeaked for Cow<'static, T>
+where
+    T: ToOwned + GetSizeUnsized + ?Sized,
+    <T as ToOwned>::Owned: GetSize,
+{
+    fn get_heap_size_leaked(&self) -> usize {
+        match self {
+            Self::Borrowed(borrowed) => GetSizeUnsized::get_size_of_val(*borrowed),
+            Self::Owned(owned) => GetSize::get_heap_size(owned),
+        }
+    }
+}
+
+impl<T> GetSizeLeaked for Option<T> where T: GetSizeLeaked {
+    fn get_heap_size_leaked(&self) -> usize {
+        match self {
+            Some(t) => GetSizeLeaked::get_heap_size_leaked(t),
+            None => 0,
+        }
+    }
+}
+
+
+
+/// Collections of boxed elements, like trait objects, which do not implement [`GetSize`]
+/// themselves, but can be measured by a size function applied to every element.
+///
+/// This can be used for heterogeneous collections like `Vec<Box<dyn Any>>`, and by the derive
+/// macro via the `element_size_fn` attribute.
+///
+/// # Example
+///
+/// ```rust
+/// use std::any::Any;
+/// use get_size::{GetSize, DynElements};
+///
+/// let plugins: Vec<Box<dyn Any>> = vec![Box::new(String::from("Hello")), Box::new(123u64)];
+///
+/// let heap_size = plugins.get_heap_size_with(|element| {
+///     match element.downcast_ref::<String>() {
+///         Some(value) => value.get_heap_size(),
+///         None => 0,
+///     }
+/// });
+///
+/// let expected = 2 * std::mem::size_of::<Box<dyn Any>>()
+///     + std::mem::size_of::<String>() + 5
+///     + std::mem::size_of::<u64>();
+///
+/// assert_eq!(heap_size, expected);
+/// ```
+pub trait DynElements {
+    /// The type of the boxed elements.
+    type Element: ?Sized;
+
+    /// Determines how many bytes this collection occupies inside the heap, using `size_fn` to
+    /// determine how many bytes each element occupies inside the heap.
+    ///
+    /// The bytes occupied by the elements themselves, as determined by [std::mem::size_of_val],
+    /// are already accounted for and must not be returned by `size_fn`.
+    fn get_heap_size_with<F>(&self, size_fn: F) -> usize
+    where
+        F: FnMut(&Self::Element) -> usize;
+}
+
+/// Determines how many bytes the elements of the given `slice` occupy inside the heap,
+/// using `size_fn` to determine how many bytes each element occupies inside the heap.
+///
+/// The bytes occupied by the slice itself are not accounted for. See [`DynElements`] for details.
+pub fn heap_size_of_dyn_slice<T, F>(slice: &[Box<T>], mut size_fn: F) -> usize
+where
+    T: ?Sized,
+    F: FnMut(&T) -> usize,
+{
+    let mut total = 0;
+
+    for element in slice.iter() {
+        total += std::mem::size_of_val(&**element);
+        total += size_fn(&**element);
+    }
+
+    total
+}
+
+impl<T> DynElements for Vec<Box<T>> where T: ?Sized {
+    type Element = T;
+
+    fn get_heap_size_with<F>(&self, size_fn: F) -> usize
+    where
+        F: FnMut(&T) -> usize,
+    {
+        let mut total = self.capacity() * std::mem::size_of::<Box<T>>();
+
+        total += heap_size_of_dyn_slice(self, size_fn);
+
+        total
+    }
+}
+
+impl<T> DynElements for Box<[Box<T>]> where T: ?Sized {
+    type Element = T;
+
+    fn get_heap_size_with<F>(&self, size_fn: F) -> usize
+    where
+        F: FnMut(&T) -> usize,
+    {
+        let mut total = self.len() * std::mem::size_of::<Box<T>>();
+
+        total += heap_size_of_dyn_slice(self, size_fn);
+
+        total
+    }
+}
+
+impl<T> DynElements for VecDeque<Box<T>> where T: ?Sized {
+    type Element = T;
+
+    fn get_heap_size_with<F>(&self, mut size_fn: F) -> usize
+    where
+        F: FnMut(&T) -> usize,
+    {
+        let mut total = self.capacity() * std::mem::size_of::<Box<T>>();
+
+        for element in self.iter() {
+            total += std::mem::size_of_val(&**element);
+            total += size_fn(&**element);
+        }
+
+        total
+    }
+}
+
+
+
+macro_rules! impl_size_stack_only {
+    ($($name:ty),* $(,)?) => {
+        $(
+            impl GetSize for $name {
+                const CAN_ALLOCATE_HEAP: bool = false;
+            }
+        )*
+    }
+}
+
+impl_size_stack_only!(
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    f32,
+    f64,
+    char,
+);
+
+impl_size_stack_only!(
+    AtomicBool,
+    AtomicI8,
+    AtomicI16,
+    AtomicI32,
+    AtomicI64,
+    AtomicIsize,
+    AtomicU8,
+    AtomicU16,
+    AtomicU32,
+    AtomicU64,
+    AtomicUsize,
+    Ordering,
+);
+
+/// The pointed to object is not owned by the [`AtomicPtr`], so only the pointer itself
+/// is accounted for. It is never loaded, so concurrent modifications can not interfere.
+impl<T> GetSize for AtomicPtr<T> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl_size_stack_only!(std::cmp::Ordering);
+
+impl_size_stack_only!(Infallible, PhantomPinned);
+
+impl<T: ?Sized> GetSize for PhantomData<T> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl_size_stack_only!(Instant, Duration, SystemTime);
+
+// Hasher states are stored inline by the maps and sets using them. Hashers like `FxBuildHasher`
+// and `FnvBuildHasher` are aliases of `BuildHasherDefault`, so they are covered as well.
+impl_size_stack_only!(std::collections::hash_map::RandomState, std::collections::hash_map::DefaultHasher);
+
+impl<H> GetSize for std::hash::BuildHasherDefault<H> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+// The data behind a waker is owned by the executor and opaque to us, so only the stack part counts.
+impl_size_stack_only!(std::task::Waker, std::task::RawWaker, std::task::Context<'_>);
+
+impl<T> GetSize for std::task::Poll<T> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        match self {
+            // The polls stack size already accounts for the values stack size.
+            Self::Ready(t) => GetSize::get_heap_size(t),
+            Self::Pending => 0,
+        }
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        match self {
+            Self::Ready(t) => GetSize::get_heap_size_with_tracker(t, tracker),
+            Self::Pending => (0, tracker),
+        }
+    }
+}
+
+
+
+/// The borrowed data is only accounted for by [`borrowed_size`](GetSize::borrowed_size), using
+/// [`std::mem::size_of_val`]. Heap memory owned by the borrowed data is not included.
+impl<'a, T> GetSize for Cow<'a, T>
+where
+    T: ToOwned + ?Sized,
+    <T as ToOwned>::Owned: GetSize,
+{
+    fn get_heap_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_borrowed) => 0,
+            Self::Owned(owned) => GetSize::get_heap_size(owned),
+        }
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        match self {
+            Self::Borrowed(_borrowed) => (0, tracker),
+            Self::Owned(owned) => GetSize::get_heap_size_with_tracker(owned, tracker),
+        }
+    }
+
+    fn borrowed_size(&self) -> usize {
+        match self {
+            Self::Borrowed(borrowed) => std::mem::size_of_val(*borrowed),
+            Self::Owned(owned) => GetSize::borrowed_size(owned),
+        }
+    }
+}
+
+
+
+/// Implements [`GetSize`] for a collection storing its elements inside a single buffer, like
+/// [`Vec`] or [`HashSet`].
+///
+/// The collection must provide `capacity`, `len` and `iter` methods, the latter returning
+/// references to the elements, which must be of the type parameter named `T`. The buffer is
+/// assumed to hold the stack part of `capacity` elements, while the heap part of every element
+/// gets added on top.
+///
+/// The first argument is the path of the collection, which must have exactly one type parameter.
+/// Collections which also store a hasher, returned by a `hasher` method, are supported by adding
+/// `hasher` as the second argument, e.g. `impl_size_set!(HashSet, hasher)`. Collections with any
+/// other type parameters, like a custom allocator, are supported by listing the parameters of the
+/// implementation in brackets, followed by the type and optionally `hasher`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use get_size::GetSize;
+///
+/// // A set with an additional, defaulted type parameter.
+/// pub struct TaggedSet<T, S, M = ()> {
+///     set: HashSet<T, S>,
+///     marker: std::marker::PhantomData<M>,
+/// }
+///
+/// impl<T, S, M> TaggedSet<T, S, M> {
+///     pub fn capacity(&self) -> usize { self.set.capacity() }
+///     pub fn len(&self) -> usize { self.set.len() }
+///     pub fn iter(&self) -> impl Iterator<Item = &T> { self.set.iter() }
+///     pub fn hasher(&self) -> &S { self.set.hasher() }
+/// }
+///
+/// get_size::impl_size_set!([T, S: GetSize, M] TaggedSet<T, S, M>, hasher);
+///
+/// let mut set = HashSet::new();
+/// set.insert(String::from("Hello"));
+///
+/// let tagged: TaggedSet<_, _, u8> = TaggedSet { set, marker: std::marker::PhantomData };
+///
+/// assert_eq!(tagged.get_heap_size(), tagged.capacity() * std::mem::size_of::<String>() + 5);
+/// ```
+#[macro_export]
+macro_rules! impl_size_set {
+    // Whether the collection is a hash table, i.e. whether it got a `hasher` argument.
+    (@is_table) => { false };
+    (@is_table $hasher:ident) => { true };
+    ($($name:ident)::+, hasher $(,)?) => {
+        $crate::impl_size_set!([T, S: $crate::GetSize] $($name)::+<T, S>, hasher);
+    };
+    ($($name:ident)::+ $(,)?) => {
+        $crate::impl_size_set!([T] $($name)::+<T>);
+    };
+    ([$($params:tt)*] $type:ty $(, $hasher:ident)? $(,)?) => {
+        impl<$($params)*> $crate::GetSize for $type where T: $crate::GetSize {
+            fn get_heap_size(&self) -> ::core::primitive::usize {
+                // The buffer holds the stack part of all elements, including the spare capacity,
+                // so only the heap part of the elements gets added on top.
+                let mut total = self.capacity() * T::get_stack_size();
+                // The hasher state is stored inline, so only its heap part gets added.
+                $(total += $crate::GetSize::get_heap_size(self.$hasher());)?
+
+                if !T::CAN_ALLOCATE_HEAP {
+                    // No need to look at the individual values.
+                    return total;
+                }
+
+                for v in self.iter() {
+                    total += $crate::GetSize::get_heap_size(v);
+                }
+
+                total
+            }
+
+            fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(&self, mut tracker: TR) -> (::core::primitive::usize, TR) {
+                let buffer = self.capacity() * T::get_stack_size();
+
+                if buffer > 0 {
+                    tracker.context(|context| context.allocation_found());
+                }
+
+                if !T::CAN_ALLOCATE_HEAP {
+                    return ($crate::GetSize::get_heap_size(self), tracker);
+                }
+
+                let mut total = buffer;
+                // The hasher state is stored inline, so only its heap part gets added.
+                $(total += $crate::GetSize::get_heap_size(self.$hasher());)?
+
+                let (heap_size, tracker) = $crate::__private::elements_heap_size_with_tracker(self.iter(), tracker);
+                total += heap_size;
+
+                (total, tracker)
+            }
+
+            fn get_heap_size_within(
+                &self,
+                limit: ::core::primitive::usize,
+            ) -> ::core::option::Option<::core::primitive::usize> {
+                // The stack part of the elements is known upfront, so we account for it first.
+                let mut total = self.capacity() * T::get_stack_size();
+                // The hasher state is stored inline, so only its heap part gets added.
+                $(total += $crate::GetSize::get_heap_size(self.$hasher());)?
+
+                if total > limit {
+                    return ::core::option::Option::None;
+                } else if !T::CAN_ALLOCATE_HEAP {
+                    return ::core::option::Option::Some(total);
+                }
+
+                for v in self.iter() {
+                    let remaining = limit - total;
+
+                    total += $crate::GetSize::get_heap_size_within(v, remaining)?;
+                }
+
+                ::core::option::Option::Some(total)
+            }
+
+            fn accumulate_heap_size<ACC: $crate::SizeAccumulator>(&self, accumulator: &mut ACC) {
+                accumulator.add(self.capacity() * T::get_stack_size());
+                $($crate::GetSize::accumulate_heap_size(self.$hasher(), accumulator);)?
+
+                if T::CAN_ALLOCATE_HEAP {
+                    for v in self.iter() {
+                        $crate::GetSize::accumulate_heap_size(v, accumulator);
+                    }
+                }
+            }
+
+            fn wasted_capacity(&self) -> ::core::primitive::usize {
+                let additional: ::core::primitive::usize = self.capacity() - self.len();
+                let mut total = additional * T::get_stack_size();
+
+                if T::CAN_ALLOCATE_HEAP {
+                    for v in self.iter() {
+                        total += $crate::GetSize::wasted_capacity(v);
+                    }
+                }
+
+                total
+            }
+
+            fn borrowed_size(&self) -> ::core::primitive::usize {
+                if !T::CAN_ALLOCATE_HEAP {
+                    return 0;
+                }
+
+                self.iter().map($crate::GetSize::borrowed_size).sum()
+            }
+
+            fn record_payloads(&self, estimator: &mut $crate::DedupEstimator) {
+                if !T::CAN_ALLOCATE_HEAP {
+                    return;
+                }
+
+                for v in self.iter() {
+                    $crate::GetSize::record_payloads(v, estimator);
+                }
+            }
+
+            fn report_size(&self, path: &::core::primitive::str, report: &mut $crate::SizeReport) {
+                let heap_size = report.heap_size_of(self);
+                report.add(path, heap_size);
+
+                // The spare buckets and control bytes of hash tables are not known, so their size
+                // is only modeled.
+                if $crate::impl_size_set!(@is_table $($hasher)?) && heap_size > 0 {
+                    report.set_source(path, $crate::SizeSource::Modeled);
+                }
+            }
+
+            fn record_allocations(&self, path: &::core::primitive::str, finder: &mut $crate::AllocationFinder) {
+                // The buffer holding the stack part of all elements.
+                finder.record(path, ::core::any::type_name::<Self>(), self.capacity() * T::get_stack_size());
+
+                // The iteration order of hash tables differs between runs.
+                if $crate::impl_size_set!(@is_table $($hasher)?) {
+                    $crate::__private::record_table_element_allocations(self.iter(), path, finder);
+                } else {
+                    $crate::__private::record_element_allocations(self.iter(), path, finder);
+                }
+            }
+        }
+    }
+}
+
+/// Implements [`GetSize`] for a collection storing every element inside a node of its own, like
+/// [`LinkedList`] or [`BTreeSet`].
+///
+/// Works just like [`impl_size_set!`], but the collection only needs to provide `len` and `iter`
+/// methods, as the stack part of exactly `len` elements is assumed to be stored inside the heap.
+/// Storing a hasher is not supported.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::LinkedList;
+/// use get_size::GetSize;
+///
+/// pub struct History<T, const LIMIT: usize> {
+///     entries: LinkedList<T>,
+/// }
+///
+/// impl<T, const LIMIT: usize> History<T, LIMIT> {
+///     pub fn len(&self) -> usize { self.entries.len() }
+///     pub fn iter(&self) -> impl Iterator<Item = &T> { self.entries.iter() }
+/// }
+///
+/// get_size::impl_size_set_no_capacity!([T, const LIMIT: usize] History<T, LIMIT>);
+///
+/// let history: History<String, 8> = History { entries: LinkedList::from([String::from("Hello")]) };
+///
+/// assert_eq!(history.get_heap_size(), std::mem::size_of::<String>() + 5);
+/// ```
+#[macro_export]
+macro_rules! impl_size_set_no_capacity {
+    ($($name:ident)::+ $(,)?) => {
+        $crate::impl_size_set_no_capacity!([T] $($name)::+<T>);
+    };
+    ([$($params:tt)*] $type:ty $(,)?) => {
+        impl<$($params)*> $crate::GetSize for $type where T: $crate::GetSize {
+            fn get_heap_size(&self) -> ::core::primitive::usize {
+                // The stack part of the elements is held inside the heap as well,
+                // so only the heap part of the elements gets added on top.
+                let mut total = self.len() * T::get_stack_size();
+
+                if !T::CAN_ALLOCATE_HEAP {
+                    // No need to look at the individual values.
+                    return total;
+                }
+
+                for v in self.iter() {
+                    total += $crate::GetSize::get_heap_size(v);
+                }
+
+                total
+            }
+
+            fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(&self, mut tracker: TR) -> (::core::primitive::usize, TR) {
+                // The nodes are not visible, so all of them are reported as a single allocation.
+                let nodes = self.len() * T::get_stack_size();
+
+                if nodes > 0 {
+                    tracker.context(|context| context.allocation_found());
+                }
+
+                if !T::CAN_ALLOCATE_HEAP {
+                    return ($crate::GetSize::get_heap_size(self), tracker);
+                }
+
+                let mut total = nodes;
+
+                let (heap_size, tracker) = $crate::__private::elements_heap_size_with_tracker(self.iter(), tracker);
+                total += heap_size;
+
+                (total, tracker)
+            }
+
+            fn get_heap_size_within(
+                &self,
+                limit: ::core::primitive::usize,
+            ) -> ::core::option::Option<::core::primitive::usize> {
+                // The stack part of the elements is known upfront, so we account for it first.
+                let mut total = self.len() * T::get_stack_size();
+
+                if total > limit {
+                    return ::core::option::Option::None;
+                } else if !T::CAN_ALLOCATE_HEAP {
+                    return ::core::option::Option::Some(total);
+                }
+
+                for v in self.iter() {
+                    let remaining = limit - total;
+
+                    total += $crate::GetSize::get_heap_size_within(v, remaining)?;
+                }
+
+                ::core::option::Option::Some(total)
+            }
+
+            fn accumulate_heap_size<ACC: $crate::SizeAccumulator>(&self, accumulator: &mut ACC) {
+                accumulator.add(self.len() * T::get_stack_size());
+
+                if T::CAN_ALLOCATE_HEAP {
+                    for v in self.iter() {
+                        $crate::GetSize::accumulate_heap_size(v, accumulator);
+                    }
+                }
+            }
+
+            fn wasted_capacity(&self) -> ::core::primitive::usize {
+                if !T::CAN_ALLOCATE_HEAP {
+                    return 0;
+                }
+
+                self.iter().map($crate::GetSize::wasted_capacity).sum()
+            }
+
+            fn borrowed_size(&self) -> ::core::primitive::usize {
+                if !T::CAN_ALLOCATE_HEAP {
+                    return 0;
+                }
+
+                self.iter().map($crate::GetSize::borrowed_size).sum()
+            }
+
+            fn record_payloads(&self, estimator: &mut $crate::DedupEstimator) {
+                if !T::CAN_ALLOCATE_HEAP {
+                    return;
+                }
+
+                for v in self.iter() {
+                    $crate::GetSize::record_payloads(v, estimator);
+                }
+            }
+
+            fn record_allocations(&self, path: &::core::primitive::str, finder: &mut $crate::AllocationFinder) {
+                // The nodes holding the stack part of all elements, which are listed as a whole.
+                finder.record(path, ::core::any::type_name::<Self>(), self.len() * T::get_stack_size());
+
+                $crate::__private::record_element_allocations(self.iter(), path, finder);
+            }
+        }
+    }
+}
+
+/// Implements [`GetSize`] for a map storing its entries inside a single buffer, like [`HashMap`].
+///
+/// Works just like [`impl_size_set!`], but `iter` must return pairs of references to the keys and
+/// values, which must be of the type parameters named `K` and `V`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use std::collections::hash_map::RandomState;
+/// use get_size::GetSize;
+///
+/// // A map with a hasher and an additional type parameter.
+/// pub struct Registry<K, V, S = RandomState, A = ()> {
+///     map: HashMap<K, V, S>,
+///     extra: A,
+/// }
+///
+/// impl<K, V, S, A> Registry<K, V, S, A> {
+///     pub fn capacity(&self) -> usize { self.map.capacity() }
+///     pub fn len(&self) -> usize { self.map.len() }
+///     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> { self.map.iter() }
+///     pub fn hasher(&self) -> &S { self.map.hasher() }
+/// }
+///
+/// get_size::impl_size_map!([K, V, S: GetSize, A] Registry<K, V, S, A>, hasher);
+///
+/// let mut map = HashMap::new();
+/// map.insert(1u32, String::from("Hello"));
+///
+/// let registry = Registry { map, extra: () };
+/// let entry_size = std::mem::size_of::<u32>() + std::mem::size_of::<String>();
+///
+/// assert_eq!(registry.get_heap_size(), registry.capacity() * entry_size + 5);
+/// ```
+#[macro_export]
+macro_rules! impl_size_map {
+    // Whether the collection is a hash table, i.e. whether it got a `hasher` argument.
+    (@is_table) => { false };
+    (@is_table $hasher:ident) => { true };
+    ($($name:ident)::+, hasher $(,)?) => {
+        $crate::impl_size_map!([K, V, S: $crate::GetSize] $($name)::+<K, V, S>, hasher);
+    };
+    ($($name:ident)::+ $(,)?) => {
+        $crate::impl_size_map!([K, V] $($name)::+<K, V>);
+    };
+    ([$($params:tt)*] $type:ty $(, $hasher:ident)? $(,)?) => {
+        impl<$($params)*> $crate::GetSize for $type where K: $crate::GetSize, V: $crate::GetSize {
+            fn get_heap_size(&self) -> ::core::primitive::usize {
+                // The buffer holds the stack part of all keys and values, including the spare capacity,
+                // so only the heap part of the keys and values gets added on top.
+                let mut total = self.capacity() * (K::get_stack_size() + V::get_stack_size());
+                // The hasher state is stored inline, so only its heap part gets added.
+                $(total += $crate::GetSize::get_heap_size(self.$hasher());)?
+
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    // No need to look at the individual values.
+                    return total;
+                }
+
+                for (k, v) in self.iter() {
+                    total += $crate::GetSize::get_heap_size(k);
+                    total += $crate::GetSize::get_heap_size(v);
+                }
+
+                total
+            }
+
+            fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(&self, mut tracker: TR) -> (::core::primitive::usize, TR) {
+                let buffer = self.capacity() * (K::get_stack_size() + V::get_stack_size());
+
+                if buffer > 0 {
+                    tracker.context(|context| context.allocation_found());
+                }
+
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return ($crate::GetSize::get_heap_size(self), tracker);
+                }
+
+                let mut total = buffer;
+                // The hasher state is stored inline, so only its heap part gets added.
+                $(total += $crate::GetSize::get_heap_size(self.$hasher());)?
+
+                let (heap_size, tracker) = $crate::__private::entries_heap_size_with_tracker(self.iter(), tracker);
+                total += heap_size;
+
+                (total, tracker)
+            }
+
+            fn get_heap_size_within(
+                &self,
+                limit: ::core::primitive::usize,
+            ) -> ::core::option::Option<::core::primitive::usize> {
+                // The stack part of the elements is known upfront, so we account for it first.
+                let mut total = self.capacity() * (K::get_stack_size() + V::get_stack_size());
+                // The hasher state is stored inline, so only its heap part gets added.
+                $(total += $crate::GetSize::get_heap_size(self.$hasher());)?
+
+                if total > limit {
+                    return ::core::option::Option::None;
+                } else if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return ::core::option::Option::Some(total);
+                }
+
+                for (k, v) in self.iter() {
+                    let remaining = limit - total;
+                    total += $crate::GetSize::get_heap_size_within(k, remaining)?;
+
+                    let remaining = limit - total;
+                    total += $crate::GetSize::get_heap_size_within(v, remaining)?;
+                }
+
+                ::core::option::Option::Some(total)
+            }
+
+            fn accumulate_heap_size<ACC: $crate::SizeAccumulator>(&self, accumulator: &mut ACC) {
+                accumulator.add(self.capacity() * (K::get_stack_size() + V::get_stack_size()));
+                $($crate::GetSize::accumulate_heap_size(self.$hasher(), accumulator);)?
+
+                if K::CAN_ALLOCATE_HEAP || V::CAN_ALLOCATE_HEAP {
+                    for (k, v) in self.iter() {
+                        $crate::GetSize::accumulate_heap_size(k, accumulator);
+                        $crate::GetSize::accumulate_heap_size(v, accumulator);
+                    }
+                }
+            }
+
+            fn wasted_capacity(&self) -> ::core::primitive::usize {
+                let additional: ::core::primitive::usize = self.capacity() - self.len();
+                let mut total = additional * (K::get_stack_size() + V::get_stack_size());
+
+                if K::CAN_ALLOCATE_HEAP || V::CAN_ALLOCATE_HEAP {
+                    for (k, v) in self.iter() {
+                        total += $crate::GetSize::wasted_capacity(k);
+                        total += $crate::GetSize::wasted_capacity(v);
+                    }
+                }
+
+                total
+            }
+
+            fn borrowed_size(&self) -> ::core::primitive::usize {
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return 0;
+                }
+
+                let mut total = 0;
+
+                for (k, v) in self.iter() {
+                    total += $crate::GetSize::borrowed_size(k);
+                    total += $crate::GetSize::borrowed_size(v);
+                }
+
+                total
+            }
+
+            fn record_payloads(&self, estimator: &mut $crate::DedupEstimator) {
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return;
+                }
+
+                for (k, v) in self.iter() {
+                    $crate::GetSize::record_payloads(k, estimator);
+                    $crate::GetSize::record_payloads(v, estimator);
+                }
+            }
+
+            fn report_size(&self, path: &::core::primitive::str, report: &mut $crate::SizeReport) {
+                let heap_size = report.heap_size_of(self);
+                report.add(path, heap_size);
+
+                // The spare buckets and control bytes of hash tables are not known, so their size
+                // is only modeled.
+                if $crate::impl_size_map!(@is_table $($hasher)?) && heap_size > 0 {
+                    report.set_source(path, $crate::SizeSource::Modeled);
+                }
+            }
+
+            fn record_allocations(&self, path: &::core::primitive::str, finder: &mut $crate::AllocationFinder) {
+                // The buffer holding the stack part of all keys and values.
+                let size = self.capacity() * (K::get_stack_size() + V::get_stack_size());
+                finder.record(path, ::core::any::type_name::<Self>(), size);
+
+                // The iteration order of hash tables differs between runs.
+                if $crate::impl_size_map!(@is_table $($hasher)?) {
+                    $crate::__private::record_table_entry_allocations(self.iter(), path, finder);
+                } else {
+                    $crate::__private::record_entry_allocations(self.iter(), path, finder);
+                }
+            }
+        }
+    }
+}
+
+/// Implements [`GetSize`] for a map storing every entry inside a node of its own, like [`BTreeMap`].
+///
+/// Works just like [`impl_size_map!`], but the map only needs to provide `len` and `iter`
+/// methods, as the stack part of exactly `len` entries is assumed to be stored inside the heap.
+/// Storing a hasher is not supported.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use get_size::GetSize;
+///
+/// pub struct SortedIndex<K, V> {
+///     map: BTreeMap<K, V>,
+/// }
+///
+/// impl<K, V> SortedIndex<K, V> {
+///     pub fn len(&self) -> usize { self.map.len() }
+///     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> { self.map.iter() }
+/// }
+///
+/// get_size::impl_size_map_no_capacity!(SortedIndex);
+///
+/// let index = SortedIndex { map: BTreeMap::from([(1u32, String::from("Hello"))]) };
+/// let entry_size = std::mem::size_of::<u32>() + std::mem::size_of::<String>();
+///
+/// assert_eq!(index.get_heap_size(), entry_size + 5);
+/// ```
+#[macro_export]
+macro_rules! impl_size_map_no_capacity {
+    ($($name:ident)::+ $(,)?) => {
+        $crate::impl_size_map_no_capacity!([K, V] $($name)::+<K, V>);
+    };
+    ([$($params:tt)*] $type:ty $(,)?) => {
+        impl<$($params)*> $crate::GetSize for $type where K: $crate::GetSize, V: $crate::GetSize {
+            fn get_heap_size(&self) -> ::core::primitive::usize {
+                // The stack part of the keys and values is held inside the heap as well,
+                // so only the heap part of the keys and values gets added on top.
+                let mut total = self.len() * (K::get_stack_size() + V::get_stack_size());
+
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    // No need to look at the individual values.
+                    return total;
+                }
+
+                for (k, v) in self.iter() {
+                    total += $crate::GetSize::get_heap_size(k);
+                    total += $crate::GetSize::get_heap_size(v);
+                }
+
+                total
+            }
+
+            fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(&self, mut tracker: TR) -> (::core::primitive::usize, TR) {
+                // The nodes are not visible, so all of them are reported as a single allocation.
+                let nodes = self.len() * (K::get_stack_size() + V::get_stack_size());
+
+                if nodes > 0 {
+                    tracker.context(|context| context.allocation_found());
+                }
+
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return ($crate::GetSize::get_heap_size(self), tracker);
+                }
+
+                let mut total = nodes;
+
+                let (heap_size, tracker) = $crate::__private::entries_heap_size_with_tracker(self.iter(), tracker);
+                total += heap_size;
+
+                (total, tracker)
+            }
+
+            fn get_heap_size_within(
+                &self,
+                limit: ::core::primitive::usize,
+            ) -> ::core::option::Option<::core::primitive::usize> {
+                // The stack part of the elements is known upfront, so we account for it first.
+                let mut total = self.len() * (K::get_stack_size() + V::get_stack_size());
+
+                if total > limit {
+                    return ::core::option::Option::None;
+                } else if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return ::core::option::Option::Some(total);
+                }
+
+                for (k, v) in self.iter() {
+                    let remaining = limit - total;
+                    total += $crate::GetSize::get_heap_size_within(k, remaining)?;
+
+                    let remaining = limit - total;
+                    total += $crate::GetSize::get_heap_size_within(v, remaining)?;
+                }
+
+                ::core::option::Option::Some(total)
+            }
+
+            fn accumulate_heap_size<ACC: $crate::SizeAccumulator>(&self, accumulator: &mut ACC) {
+                accumulator.add(self.len() * (K::get_stack_size() + V::get_stack_size()));
+
+                if K::CAN_ALLOCATE_HEAP || V::CAN_ALLOCATE_HEAP {
+                    for (k, v) in self.iter() {
+                        $crate::GetSize::accumulate_heap_size(k, accumulator);
+                        $crate::GetSize::accumulate_heap_size(v, accumulator);
+                    }
+                }
+            }
+
+            fn wasted_capacity(&self) -> ::core::primitive::usize {
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return 0;
+                }
+
+                let mut total = 0;
+
+                for (k, v) in self.iter() {
+                    total += $crate::GetSize::wasted_capacity(k);
+                    total += $crate::GetSize::wasted_capacity(v);
+                }
+
+                total
+            }
+
+            fn borrowed_size(&self) -> ::core::primitive::usize {
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return 0;
+                }
+
+                let mut total = 0;
+
+                for (k, v) in self.iter() {
+                    total += $crate::GetSize::borrowed_size(k);
+                    total += $crate::GetSize::borrowed_size(v);
+                }
+
+                total
+            }
+
+            fn record_payloads(&self, estimator: &mut $crate::DedupEstimator) {
+                if !K::CAN_ALLOCATE_HEAP && !V::CAN_ALLOCATE_HEAP {
+                    return;
+                }
+
+                for (k, v) in self.iter() {
+                    $crate::GetSize::record_payloads(k, estimator);
+                    $crate::GetSize::record_payloads(v, estimator);
+                }
+            }
+
+            fn record_allocations(&self, path: &::core::primitive::str, finder: &mut $crate::AllocationFinder) {
+                // The nodes holding the stack part of all keys and values, which are listed as a whole.
+                let size = self.len() * (K::get_stack_size() + V::get_stack_size());
+                finder.record(path, ::core::any::type_name::<Self>(), size);
+
+                $crate::__private::record_entry_allocations(self.iter(), path, finder);
+            }
+        }
+    }
+}
+
+impl_size_map!(HashMap, hasher);
+impl_size_set!(HashSet, hasher);
+
+// With the `allocator_api` feature enabled, collections using any allocator are supported.
+#[cfg(not(feature = "allocator_api"))]
+impl_size_map_no_capacity!(BTreeMap);
+#[cfg(feature = "allocator_api")]
+impl_size_map_no_capacity!([K, V, A: std::alloc::Allocator + Clone] BTreeMap<K, V, A>);
+#[cfg(not(feature = "allocator_api"))]
+impl_size_set_no_capacity!(BTreeSet);
+#[cfg(feature = "allocator_api")]
+impl_size_set_no_capacity!([T, A: std::alloc::Allocator + Clone] BTreeSet<T, A>);
+#[cfg(not(feature = "allocator_api"))]
+impl_size_set!(BinaryHeap);
+#[cfg(feature = "allocator_api")]
+impl_size_set!([T, A: std::alloc::Allocator] BinaryHeap<T, A>);
+#[cfg(not(feature = "allocator_api"))]
+impl_size_set_no_capacity!(LinkedList);
+#[cfg(feature = "allocator_api")]
+impl_size_set_no_capacity!([T, A: std::alloc::Allocator] LinkedList<T, A>);
+#[cfg(not(feature = "allocator_api"))]
+impl_size_set!(VecDeque);
+#[cfg(feature = "allocator_api")]
+impl_size_set!([T, A: std::alloc::Allocator] VecDeque<T, A>);
+
+#[cfg(not(feature = "allocator_api"))]
+impl_size_set!(Vec);
+#[cfg(feature = "allocator_api")]
+impl_size_set!([T, A: std::alloc::Allocator] Vec<T, A>);
+
+// Borrowed views into collections do not own any data, so they are treated as only occupying the stack.
+impl<K, V> GetSize for std::collections::btree_map::Iter<'_, K, V> {}
+impl<K, V> GetSize for std::collections::btree_map::IterMut<'_, K, V> {}
+impl<K, V> GetSize for std::collections::btree_map::Keys<'_, K, V> {}
+impl<K, V> GetSize for std::collections::btree_map::Values<'_, K, V> {}
+impl<K, V> GetSize for std::collections::btree_map::ValuesMut<'_, K, V> {}
+impl<K, V> GetSize for std::collections::btree_map::Range<'_, K, V> {}
+impl<K, V> GetSize for std::collections::btree_map::RangeMut<'_, K, V> {}
+impl<T> GetSize for std::collections::btree_set::Iter<'_, T> {}
+impl<T> GetSize for std::collections::btree_set::Range<'_, T> {}
+impl<K, V> GetSize for std::collections::hash_map::Iter<'_, K, V> {}
+impl<K, V> GetSize for std::collections::hash_map::IterMut<'_, K, V> {}
+impl<K, V> GetSize for std::collections::hash_map::Keys<'_, K, V> {}
+impl<K, V> GetSize for std::collections::hash_map::Values<'_, K, V> {}
+impl<K, V> GetSize for std::collections::hash_map::ValuesMut<'_, K, V> {}
+impl<T> GetSize for std::collections::hash_set::Iter<'_, T> {}
+impl<T> GetSize for std::collections::vec_deque::Iter<'_, T> {}
+impl<T> GetSize for std::collections::vec_deque::IterMut<'_, T> {}
+impl<T> GetSize for std::collections::linked_list::Iter<'_, T> {}
+impl<T> GetSize for std::collections::linked_list::IterMut<'_, T> {}
+impl<T> GetSize for std::collections::binary_heap::Iter<'_, T> {}
+impl<T> GetSize for std::slice::Iter<'_, T> {}
+impl<T> GetSize for std::slice::IterMut<'_, T> {}
+impl GetSize for std::str::Chars<'_> {}
+impl GetSize for std::str::CharIndices<'_> {}
+impl GetSize for std::str::Bytes<'_> {}
+
+
+
+macro_rules! impl_size_tuple {
+    ($($t:ident, $T:ident),+) => {
+        impl<$($T,)*> GetSize for ($($T,)*)
+        where
+            $(
+                $T: GetSize,
+            )*
+        {
+            const CAN_ALLOCATE_HEAP: bool = $($T::CAN_ALLOCATE_HEAP)||*;
+
+            fn get_heap_size(&self) -> usize {
+                let mut total = 0;
+
+                let ($($t,)*) = self;
+                $(
+                    total += GetSize::get_heap_size($t);
+                )*
+
+                total
+            }
+
+            fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+                &self,
+                mut tracker: TR,
+            ) -> (usize, TR) {
+                let mut total = 0;
+
+                let ($($t,)*) = self;
+                $(
+                    let (size, returned) = GetSize::get_heap_size_with_tracker($t, tracker);
+                    tracker = returned;
+                    total += size;
+                )*
+
+                (total, tracker)
+            }
+
+            fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+                let mut total = 0;
+
+                let ($($t,)*) = self;
+                $(
+                    total += GetSize::get_heap_size_within($t, limit - total)?;
+                )*
+
+                Some(total)
+            }
+
+            fn wasted_capacity(&self) -> usize {
+                let mut total = 0;
+
+                let ($($t,)*) = self;
+                $(
+                    total += GetSize::wasted_capacity($t);
+                )*
+
+                total
+            }
+
+            fn borrowed_size(&self) -> usize {
+                let mut total = 0;
+
+                let ($($t,)*) = self;
+                $(
+                    total += GetSize::borrowed_size($t);
+                )*
+
+                total
+            }
+
+            fn record_payloads(&self, estimator: &mut DedupEstimator) {
+                let ($($t,)*) = self;
+                $(
+                    GetSize::record_payloads($t, estimator);
+                )*
+            }
+
+            fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+                // The tuple itself does not allocate, so its elements share its path.
+                let ($($t,)*) = self;
+                $(
+                    GetSize::record_allocations($t, path, finder);
+                )*
+            }
+        }
+    }
+}
+
+macro_rules! execute_tuple_macro_16 {
+    ($name:ident) => {
+        $name!(v1,V1);
+        $name!(v1,V1,v2,V2);
+        $name!(v1,V1,v2,V2,v3,V3);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13,v14,V14);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13,v14,V14,v15,V15);
+        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13,v14,V14,v15,V15,v16,V16);
+    }
+}
+
+execute_tuple_macro_16!(impl_size_tuple);
+
+
+
+impl<T, const SIZE: usize> GetSize for [T; SIZE] where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        let mut total = 0;
+
+        for element in self.iter() {
+            // The array stack size already accounts for the stack size of the elements of the array.
+            total += GetSize::get_heap_size(element);
+        }
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        if !T::CAN_ALLOCATE_HEAP {
+            return (0, tracker);
+        }
+
+        elements_heap_size_with_tracker(self.iter(), tracker)
+    }
+
+    fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+        let mut total = 0;
+
+        for element in self.iter() {
+            let remaining = limit - total;
+
+            total += GetSize::get_heap_size_within(element, remaining)?;
+        }
+
+        Some(total)
+    }
+
+    fn accumulate_heap_size<A: SizeAccumulator>(&self, accumulator: &mut A) {
+        if !T::CAN_ALLOCATE_HEAP {
+            return;
+        }
+
+        for element in self.iter() {
+            GetSize::accumulate_heap_size(element, accumulator);
+        }
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        self.iter().map(GetSize::wasted_capacity).sum()
+    }
+
+    fn borrowed_size(&self) -> usize {
+        if !T::CAN_ALLOCATE_HEAP {
+            return 0;
+        }
+
+        self.iter().map(GetSize::borrowed_size).sum()
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        if !T::CAN_ALLOCATE_HEAP {
+            return;
+        }
+
+        for element in self.iter() {
+            GetSize::record_payloads(element, estimator);
+        }
+    }
+
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        record_element_allocations(self.iter(), path, finder);
+    }
+}
+
+// Measures the total size of the possibly unsized `value` using the given tracker.
+fn size_of_val_with_tracker<T, TR>(value: &T, mut tracker: TR) -> (usize, TR)
+where
+    T: GetSizeUnsized + ?Sized,
+    TR: GetSizeTracker,
+{
+    let size = GetSizeUnsized::get_size_of_val_with_tracker(value, &mut DynTracker::new(&mut tracker));
+
+    (size, tracker)
+}
+
+// Marks borrowed data tracked by a tracker, see `SizingPolicies::count_borrowed`.
+struct Borrowed;
+
+// Borrowed data is accounted for depending on the policies of the tracker, so it can not rule
+// out heap memory statically.
+impl<T> GetSize for &[T] where T: GetSize {
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        if tracker.context(|context| context.policies()).count_borrowed && tracker.track(self.as_ptr(), Borrowed) {
+            size_of_val_with_tracker(*self, tracker)
+        } else {
+            (0, tracker)
+        }
+    }
+}
+
+impl<T> GetSize for &T {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+impl<T> GetSize for &mut T {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+impl<T: ?Sized> GetSize for *const T {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+impl<T: ?Sized> GetSize for *mut T {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+macro_rules! impl_size_box {
+    ([$($params:tt)*] $type:ty) => {
+        impl<$($params)*> GetSize for $type where T: GetSizeUnsized + ?Sized {
+            fn get_heap_size(&self) -> usize {
+                GetSizeUnsized::get_size_of_val(&**self)
+            }
+
+            fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+                &self,
+                mut tracker: TR,
+            ) -> (usize, TR) {
+                let size = GetSizeUnsized::get_stack_size_of_val(&**self);
+
+                // Boxed zero sized values do not allocate.
+                if size > 0 {
+                    tracker.context(|context| context.allocation_found());
+                }
+
+                size_of_val_with_tracker(&**self, tracker)
+            }
+
+            fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+                // The boxed value itself is known upfront, so we account for it first.
+                let stack_size = GetSizeUnsized::get_stack_size_of_val(&**self);
+                let remaining = limit.checked_sub(stack_size)?;
+
+                let heap_size = GetSizeUnsized::get_heap_size_of_val_within(&**self, remaining)?;
+
+                Some(stack_size + heap_size)
+            }
+
+            fn wasted_capacity(&self) -> usize {
+                GetSizeUnsized::wasted_capacity_of_val(&**self)
+            }
+
+            fn borrowed_size(&self) -> usize {
+                GetSizeUnsized::borrowed_size_of_val(&**self)
+            }
+
+            fn record_payloads(&self, estimator: &mut DedupEstimator) {
+                GetSizeUnsized::record_payloads_of_val(&**self, estimator);
+            }
+
+            fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+                let size = GetSizeUnsized::get_stack_size_of_val(&**self);
+                finder.record(path, std::any::type_name::<Self>(), size);
+
+                GetSizeUnsized::record_allocations_of_val(&**self, path, finder);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl_size_box!([T] Box<T>);
+#[cfg(feature = "allocator_api")]
+impl_size_box!([T, A: std::alloc::Allocator] Box<T, A>);
+
+impl GetSize for std::alloc::Layout {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl GetSize for std::alloc::System {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+#[cfg(feature = "allocator_api")]
+impl GetSize for std::alloc::Global {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl<T> GetSize for Rc<T> where T: GetSize + 'static {
+    fn get_heap_size(&self) -> usize {
+        let tracker = StandardTracker::default();
+
+        let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        let addr = Rc::as_ptr(self) as *const u8;
+        let strong_ref = SharedAllocation::Rc(Rc::clone(self) as Rc<dyn std::any::Any>);
+
+        if tracker.track(addr, strong_ref) {
+            tracker.context(|context| context.allocation_found());
+
+            GetSize::get_size_with_tracker(&**self, tracker)
+        } else {
+            (0, tracker)
+        }
+    }
+}
+
+/// Only the pointer itself is accounted for, regardless of the [`WeakPolicy`] of the tracker.
+///
+/// Upgrading the weak reference under [`WeakPolicy::CountIfUnvisited`] would require the
+/// referenced type to implement [`GetSize`], which would rule out weak references to trait
+/// objects and other types without an implementation. Wrap it into a [`TrackedWeak`] to account
+/// for the referenced object as well.
+impl<T: ?Sized> GetSize for RcWeak<T> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl<T> GetSize for Arc<T> where T: GetSize + 'static {
+    fn get_heap_size(&self) -> usize {
+        let tracker = StandardTracker::default();
+
+        let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+
+        total
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        let addr = Arc::as_ptr(self) as *const u8;
+        let strong_ref = SharedAllocation::Arc(Arc::clone(self) as Arc<dyn std::any::Any>);
+
+        if !tracker.track(addr, strong_ref) {
+            return (0, tracker);
+        }
+
+        let memoized = tracker.context(|context| {
+            context.allocation_found();
+            context.memoized_size(addr)
+        });
+
+        if let Some(size) = memoized {
+            return (size, tracker);
+        }
+
+        let hit_count = tracker.hit_count();
+        let (size, mut tracker) = GetSize::get_size_with_tracker(&**self, tracker);
+
+        // The result of a cancelled measurement is incomplete, and the size of an object sharing
+        // parts already seen by the tracker depends on the rest of the measurement.
+        if tracker.hit_count()==hit_count {
+            tracker.context(|context| if !context.is_cancelled() {
+                context.memoize_size(addr, size, || Arc::downgrade(self) as ArcWeak<dyn std::any::Any>);
+            });
+        }
+
+        (size, tracker)
+    }
+}
+
+/// Only the pointer itself is accounted for, regardless of the [`WeakPolicy`] of the tracker.
+///
+/// Upgrading the weak reference under [`WeakPolicy::CountIfUnvisited`] would require the
+/// referenced type to implement [`GetSize`], which would rule out weak references to trait
+/// objects and other types without an implementation. Wrap it into a [`TrackedWeak`] to account
+/// for the referenced object as well.
+impl<T: ?Sized> GetSize for ArcWeak<T> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+// Shared ownership of dynamically sized objects, like `Rc<str>`. Only the pointed to allocation
+// itself gets tracked, its contents are measured like the elements of any other collection.
+macro_rules! impl_size_shared_unsized {
+    ($ptr:ident, $name:ty $(, $generic:ident)*) => {
+        impl<$($generic),*> GetSize for $ptr<$name> where $($generic: GetSize + 'static),* {
+            fn get_heap_size(&self) -> usize {
+                let tracker = StandardTracker::default();
+
+                let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+
+                total
+            }
+
+            fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+                &self,
+                mut tracker: TR,
+            ) -> (usize, TR) {
+                let addr = $ptr::as_ptr(self) as *const u8;
+                let strong_ref = SharedAllocation::Unsized(Box::new($ptr::clone(self)));
+
+                if tracker.track(addr, strong_ref) {
+                    tracker.context(|context| context.allocation_found());
+
+                    size_of_val_with_tracker(&**self, tracker)
+                } else {
+                    (0, tracker)
+                }
+            }
+        }
+    }
+}
+
+impl_size_shared_unsized!(Rc, str);
+impl_size_shared_unsized!(Rc, [T], T);
+impl_size_shared_unsized!(Rc, std::path::Path);
+impl_size_shared_unsized!(Rc, std::ffi::OsStr);
+impl_size_shared_unsized!(Rc, std::ffi::CStr);
+impl_size_shared_unsized!(Arc, str);
+impl_size_shared_unsized!(Arc, [T], T);
+impl_size_shared_unsized!(Arc, std::path::Path);
+impl_size_shared_unsized!(Arc, std::ffi::OsStr);
+impl_size_shared_unsized!(Arc, std::ffi::CStr);
+
+impl<T> GetSize for Option<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        match self {
+            // The options stack size already accounts for the values stack size.
+            Some(t) => GetSize::get_heap_size(t),
+            None => 0
+        }
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        match self {
+            Some(t) => GetSize::get_heap_size_with_tracker(t, tracker),
+            None => (0, tracker),
+        }
+    }
+
+    fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+        match self {
+            Some(t) => GetSize::get_heap_size_within(t, limit),
+            None => Some(0),
+        }
+    }
+
+    fn accumulate_heap_size<A: SizeAccumulator>(&self, accumulator: &mut A) {
+        if let Some(t) = self {
+            GetSize::accumulate_heap_size(t, accumulator);
+        }
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        match self {
+            Some(t) => GetSize::wasted_capacity(t),
+            None => 0,
+        }
+    }
+
+    fn borrowed_size(&self) -> usize {
+        match self {
+            Some(t) => GetSize::borrowed_size(t),
+            None => 0,
+        }
+    }
+
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        if let Some(t) = self {
+            GetSize::report_wasted_capacity(t, path, report);
+        }
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        if let Some(t) = self {
+            GetSize::report_size(t, path, report);
+        }
+    }
+
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        if let Some(t) = self {
+            GetSize::record_allocations(t, path, finder);
+        }
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        if let Some(t) = self {
+            GetSize::record_payloads(t, estimator);
+        }
+    }
+}
+
+impl<T, E> GetSize for Result<T, E> where T: GetSize, E: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP || E::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        match self {
+            // The results stack size already accounts for the values stack size.
+            Ok(t) => GetSize::get_heap_size(t),
+            Err(e) => GetSize::get_heap_size(e),
+        }
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        match self {
+            Ok(t) => GetSize::get_heap_size_with_tracker(t, tracker),
+            Err(e) => GetSize::get_heap_size_with_tracker(e, tracker),
+        }
+    }
+
+    fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+        match self {
+            Ok(t) => GetSize::get_heap_size_within(t, limit),
+            Err(e) => GetSize::get_heap_size_within(e, limit),
+        }
+    }
+
+    fn accumulate_heap_size<A: SizeAccumulator>(&self, accumulator: &mut A) {
+        match self {
+            Ok(t) => GetSize::accumulate_heap_size(t, accumulator),
+            Err(e) => GetSize::accumulate_heap_size(e, accumulator),
+        }
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        match self {
+            Ok(t) => GetSize::wasted_capacity(t),
+            Err(e) => GetSize::wasted_capacity(e),
+        }
+    }
+
+    fn borrowed_size(&self) -> usize {
+        match self {
+            Ok(t) => GetSize::borrowed_size(t),
+            Err(e) => GetSize::borrowed_size(e),
+        }
+    }
+
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        match self {
+            Ok(t) => GetSize::report_wasted_capacity(t, path, report),
+            Err(e) => GetSize::report_wasted_capacity(e, path, report),
+        }
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        match self {
+            Ok(t) => GetSize::report_size(t, path, report),
+            Err(e) => GetSize::report_size(e, path, report),
+        }
+    }
+
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        match self {
+            Ok(t) => GetSize::record_allocations(t, path, finder),
+            Err(e) => GetSize::record_allocations(e, path, finder),
+        }
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        match self {
+            Ok(t) => GetSize::record_payloads(t, estimator),
+            Err(e) => GetSize::record_payloads(e, estimator),
+        }
     }
 }
 
+impl<T> GetSize for Mutex<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
 
+    fn get_heap_size(&self) -> usize {
+        // We assume that a Mutex does hold its data at the stack.
+        // A poisoned Mutex does still hold valid data, so we measure it nevertheless.
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-impl GetSize for () {}
-impl GetSize for bool {}
-impl GetSize for u8 {}
-impl GetSize for u16 {}
-impl GetSize for u32 {}
-impl GetSize for u64 {}
-impl GetSize for u128 {}
-impl GetSize for usize {}
-impl GetSize for NonZeroU8 {}
-impl GetSize for NonZeroU16 {}
-impl GetSize for NonZeroU32 {}
-impl GetSize for NonZeroU64 {}
-impl GetSize for NonZeroU128 {}
-impl GetSize for NonZeroUsize {}
-impl GetSize for i8 {}
-impl GetSize for i16 {}
-impl GetSize for i32 {}
-impl GetSize for i64 {}
-impl GetSize for i128 {}
-impl GetSize for isize {}
-impl GetSize for NonZeroI8 {}
-impl GetSize for NonZeroI16 {}
-impl GetSize for NonZeroI32 {}
-impl GetSize for NonZeroI64 {}
-impl GetSize for NonZeroI128 {}
-impl GetSize for NonZeroIsize {}
-impl GetSize for f32 {}
-impl GetSize for f64 {}
-impl GetSize for char {}
-
-impl GetSize for AtomicBool {}
-impl GetSize for AtomicI8 {}
-impl GetSize for AtomicI16 {}
-impl GetSize for AtomicI32 {}
-impl GetSize for AtomicI64 {}
-impl GetSize for AtomicIsize {}
-impl GetSize for AtomicU8 {}
-impl GetSize for AtomicU16 {}
-impl GetSize for AtomicU32 {}
-impl GetSize for AtomicU64 {}
-impl GetSize for AtomicUsize {}
-impl GetSize for Ordering {}
-
-impl GetSize for std::cmp::Ordering {}
-
-impl GetSize for Infallible {}
-impl<T> GetSize for PhantomData<T> {}
-impl GetSize for PhantomPinned {}
-
-impl GetSize for Instant {}
-impl GetSize for Duration {}
-impl GetSize for SystemTime {}
-
+        GetSize::get_heap_size(&*guard)
+    }
 
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-impl<'a, T> GetSize for Cow<'a, T>
-where
-    T: ToOwned,
-    <T as ToOwned>::Owned: GetSize,
-{
-    fn get_heap_size(&self) -> usize {
-        match self {
-            Self::Borrowed(_borrowed) => 0,
-            Self::Owned(owned) => GetSize::get_heap_size(owned),
-        }
+        GetSize::get_heap_size_with_tracker(&*guard, tracker)
     }
-}
 
+    fn wasted_capacity(&self) -> usize {
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
+        GetSize::wasted_capacity(&*guard)
+    }
 
-macro_rules! impl_size_set {
-    ($name:ident) => {
-        impl<T> GetSize for $name<T> where T: GetSize {
-            fn get_heap_size(&self) -> usize {
-                let mut total = 0;
+    fn borrowed_size(&self) -> usize {
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-                for v in self.iter() {
-                    // We assume that value are hold inside the heap.
-                    total += GetSize::get_size(v);
-                }
+        GetSize::borrowed_size(&*guard)
+    }
 
-                let additional: usize = self.capacity() - self.len();
-                total += additional * T::get_stack_size();
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-                total
-            }
-        }
+        GetSize::report_wasted_capacity(&*guard, path, report);
     }
-}
 
-macro_rules! impl_size_set_no_capacity {
-    ($name:ident) => {
-        impl<T> GetSize for $name<T> where T: GetSize {
-            fn get_heap_size(&self) -> usize {
-                let mut total = 0;
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        if report.is_lock_scoped() {
+            // Only hold the lock while measuring, the entry gets added after releasing it.
+            let bytes = {
+                let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-                for v in self.iter() {
-                    // We assume that value are hold inside the heap.
-                    total += GetSize::get_size(v);
-                }
+                report.heap_size_of(&*guard)
+            };
 
-                total
-            }
+            report.add(path, bytes);
+            return;
         }
-    }
-}
 
-macro_rules! impl_size_map {
-    ($name:ident) => {
-        impl<K, V> GetSize for $name<K, V> where K: GetSize, V: GetSize {
-            fn get_heap_size(&self) -> usize {
-                let mut total = 0;
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-                for (k, v) in self.iter() {
-                    // We assume that keys and value are hold inside the heap.
-                    total += GetSize::get_size(k);
-                    total += GetSize::get_size(v);
-                }
+        GetSize::report_size(&*guard, path, report);
+    }
 
-                let additional: usize = self.capacity() - self.len();
-                total += additional * K::get_stack_size();
-                total += additional * V::get_stack_size();
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-                total
-            }
-        }
+        GetSize::record_allocations(&*guard, path, finder);
     }
-}
-
-macro_rules! impl_size_map_no_capacity {
-    ($name:ident) => {
-        impl<K, V> GetSize for $name<K, V> where K: GetSize, V: GetSize {
-            fn get_heap_size(&self) -> usize {
-                let mut total = 0;
 
-                for (k, v) in self.iter() {
-                    // We assume that keys and value are hold inside the heap.
-                    total += GetSize::get_size(k);
-                    total += GetSize::get_size(v);
-                }
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        let guard = self.lock().unwrap_or_else(PoisonError::into_inner);
 
-                total
-            }
-        }
+        GetSize::record_payloads(&*guard, estimator);
     }
 }
 
-impl_size_map_no_capacity!(BTreeMap);
-impl_size_set_no_capacity!(BTreeSet);
-impl_size_set!(BinaryHeap);
-impl_size_map!(HashMap);
-impl_size_set!(HashSet);
-impl_size_set_no_capacity!(LinkedList);
-impl_size_set!(VecDeque);
+impl<T> GetSize for RwLock<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
 
-impl_size_set!(Vec);
+    fn get_heap_size(&self) -> usize {
+        // We assume that a RwLock does hold its data at the stack.
+        // A poisoned RwLock does still hold valid data, so we measure it nevertheless.
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
 
+        GetSize::get_heap_size(&*guard)
+    }
 
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
 
-macro_rules! impl_size_tuple {
-    ($($t:ident, $T:ident),+) => {
-        impl<$($T,)*> GetSize for ($($T,)*)
-        where
-            $(
-                $T: GetSize,
-            )*
-        {
-            fn get_heap_size(&self) -> usize {
-                let mut total = 0;
+        GetSize::get_heap_size_with_tracker(&*guard, tracker)
+    }
 
-                let ($($t,)*) = self;
-                $(
-                    total += GetSize::get_heap_size($t);
-                )*
+    fn wasted_capacity(&self) -> usize {
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
 
-                total
-            }
-        }
+        GetSize::wasted_capacity(&*guard)
     }
-}
 
-macro_rules! execute_tuple_macro_16 {
-    ($name:ident) => {
-        $name!(v1,V1);
-        $name!(v1,V1,v2,V2);
-        $name!(v1,V1,v2,V2,v3,V3);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13,v14,V14);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13,v14,V14,v15,V15);
-        $name!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6,v7,V7,v8,V8,v9,V9,v10,V10,v11,V11,v12,V12,v13,V13,v14,V14,v15,V15,v16,V16);
+    fn borrowed_size(&self) -> usize {
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
+
+        GetSize::borrowed_size(&*guard)
     }
-}
 
-execute_tuple_macro_16!(impl_size_tuple);
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
 
+        GetSize::report_wasted_capacity(&*guard, path, report);
+    }
 
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        if report.is_lock_scoped() {
+            // Only hold the lock while measuring, the entry gets added after releasing it.
+            let bytes = {
+                let guard = self.read().unwrap_or_else(PoisonError::into_inner);
 
-impl<T, const SIZE: usize> GetSize for [T; SIZE] where T: GetSize {
-    fn get_heap_size(&self) -> usize {
-        let mut total = 0;
+                report.heap_size_of(&*guard)
+            };
 
-        for element in self.iter() {
-            // The array stack size already accounts for the stack size of the elements of the array.
-            total += GetSize::get_heap_size(element);
+            report.add(path, bytes);
+            return;
         }
 
-        total
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
+
+        GetSize::report_size(&*guard, path, report);
     }
-}
 
-impl<T> GetSize for &[T] where T: GetSize {}
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
 
-impl<T> GetSize for &T {}
-impl<T> GetSize for &mut T {}
-impl<T> GetSize for *const T {}
-impl<T> GetSize for *mut T {}
+        GetSize::record_allocations(&*guard, path, finder);
+    }
 
-impl<T> GetSize for Box<T> where T: GetSize {
-    fn get_heap_size(&self) -> usize {
-        GetSize::get_size(&**self)
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        let guard = self.read().unwrap_or_else(PoisonError::into_inner);
+
+        GetSize::record_payloads(&*guard, estimator);
     }
 }
 
-impl<T> GetSize for Rc<T> where T: GetSize + 'static {
+/// The value is copied out of the [`Cell`] before measuring it, so no reference to its
+/// contents is ever created.
+impl<T> GetSize for Cell<T> where T: GetSize + Copy {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
     fn get_heap_size(&self) -> usize {
-        let tracker = StandardTracker::default();
+        // A Cell does hold its data at the stack.
+        GetSize::get_heap_size(&self.get())
+    }
+}
 
-        let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+/// The contents of an [`UnsafeCell`] can not be accessed safely, as they might get modified
+/// while being measured. It is thus treated as only occupying the stack. Use the `size_fn`
+/// attribute together with your own synchronization to account for its contents.
+impl<T> GetSize for UnsafeCell<T> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
 
-        total
+impl<T> GetSize for OnceCell<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        // A OnceCell does hold its data at the stack. Once set, its value never changes.
+        match self.get() {
+            Some(value) => GetSize::get_heap_size(value),
+            None => 0,
+        }
     }
 
     fn get_heap_size_with_tracker<TR: GetSizeTracker>(
         &self,
-        mut tracker: TR,
+        tracker: TR,
     ) -> (usize, TR) {
-        let strong_ref = Rc::clone(self);
-
-        let addr = Rc::as_ptr(&strong_ref);
-
-        if tracker.track(addr, strong_ref) {
-            GetSize::get_size_with_tracker(&**self, tracker)
-        } else {
-            (0, tracker)
+        match self.get() {
+            Some(value) => GetSize::get_heap_size_with_tracker(value, tracker),
+            None => (0, tracker),
         }
     }
 }
 
-impl<T> GetSize for RcWeak<T> {}
+impl<T> GetSize for OnceLock<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
 
-impl<T> GetSize for Arc<T> where T: GetSize + 'static {
     fn get_heap_size(&self) -> usize {
-        let tracker = StandardTracker::default();
+        // A OnceLock does hold its data at the stack. Once set, its value never changes.
+        match self.get() {
+            Some(value) => GetSize::get_heap_size(value),
+            None => 0,
+        }
+    }
 
-        let (total, _) = GetSize::get_heap_size_with_tracker(self, tracker);
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        tracker: TR,
+    ) -> (usize, TR) {
+        match self.get() {
+            Some(value) => GetSize::get_heap_size_with_tracker(value, tracker),
+            None => (0, tracker),
+        }
+    }
+}
 
-        total
+impl<T> GetSize for RefCell<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        // A RefCell does hold its data at the stack. If it is currently borrowed mutably, its
+        // value is not accessible and gets treated as occupying no heap memory.
+        // Use `TryGetSize` to detect this case instead.
+        match self.try_borrow() {
+            Ok(value) => GetSize::get_heap_size(&*value),
+            Err(_) => 0,
+        }
     }
 
     fn get_heap_size_with_tracker<TR: GetSizeTracker>(
         &self,
-        mut tracker: TR,
+        tracker: TR,
     ) -> (usize, TR) {
-        let strong_ref = Arc::clone(self);
-
-        let addr = Arc::as_ptr(&strong_ref);
+        match self.try_borrow() {
+            Ok(value) => GetSize::get_heap_size_with_tracker(&*value, tracker),
+            Err(_) => (0, tracker),
+        }
+    }
 
-        if tracker.track(addr, strong_ref) {
-            GetSize::get_size_with_tracker(&**self, tracker)
-        } else {
-            (0, tracker)
+    fn wasted_capacity(&self) -> usize {
+        match self.try_borrow() {
+            Ok(value) => GetSize::wasted_capacity(&*value),
+            Err(_) => 0,
         }
     }
-}
 
-impl<T> GetSize for ArcWeak<T> {}
+    fn borrowed_size(&self) -> usize {
+        match self.try_borrow() {
+            Ok(value) => GetSize::borrowed_size(&*value),
+            Err(_) => 0,
+        }
+    }
 
-impl<T> GetSize for Option<T> where T: GetSize {
-    fn get_heap_size(&self) -> usize {
-        match self {
-            // The options stack size already accounts for the values stack size.
-            Some(t) => GetSize::get_heap_size(t),
-            None => 0
+    fn report_wasted_capacity(&self, path: &str, report: &mut CapacityReport) {
+        if let Ok(value) = self.try_borrow() {
+            GetSize::report_wasted_capacity(&*value, path, report);
         }
     }
-}
 
-impl<T, E> GetSize for Result<T, E> where T: GetSize, E: GetSize {
-    fn get_heap_size(&self) -> usize {
-        match self {
-            // The results stack size already accounts for the values stack size.
-            Ok(t) => GetSize::get_heap_size(t),
-            Err(e) => GetSize::get_heap_size(e),
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        if let Ok(value) = self.try_borrow() {
+            GetSize::report_size(&*value, path, report);
         }
     }
-}
 
-impl<T> GetSize for Mutex<T> where T: GetSize {
-    fn get_heap_size(&self) -> usize {
-        // We assume that a Mutex does hold its data at the stack.
-        GetSize::get_heap_size(&*(self.lock().unwrap()))
+    fn record_allocations(&self, path: &str, finder: &mut AllocationFinder) {
+        if let Ok(value) = self.try_borrow() {
+            GetSize::record_allocations(&*value, path, finder);
+        }
     }
-}
 
-impl<T> GetSize for RwLock<T> where T: GetSize {
-    fn get_heap_size(&self) -> usize {
-        // We assume that a RwLock does hold its data at the stack.
-        GetSize::get_heap_size(&*(self.read().unwrap()))
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        if let Ok(value) = self.try_borrow() {
+            GetSize::record_payloads(&*value, estimator);
+        }
     }
 }
 
@@ -436,14 +2745,45 @@ impl GetSize for String {
     fn get_heap_size(&self) -> usize {
         self.capacity()
     }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        if self.capacity() > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        (tracker.context(|context| context.policies()).strings.heap_size_of(self), tracker)
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_bytes(), self.capacity());
+    }
 }
 
-impl GetSize for &str {}
+impl GetSize for &str {
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        if tracker.context(|context| context.policies()).count_borrowed && tracker.track(self.as_ptr(), Borrowed) {
+            (self.len(), tracker)
+        } else {
+            (0, tracker)
+        }
+    }
+}
 
 impl GetSize for std::ffi::CString {
     fn get_heap_size(&self) -> usize {
         self.as_bytes_with_nul().len()
     }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_bytes(), self.as_bytes_with_nul().len());
+    }
 }
 
 impl GetSize for &std::ffi::CStr {
@@ -452,9 +2792,44 @@ impl GetSize for &std::ffi::CStr {
     }
 }
 
+/// How the heap size of [`OsString`](std::ffi::OsString) and [`PathBuf`](std::path::PathBuf)
+/// gets determined on each platform, as pairs of the target family and a description.
+///
+/// This is the single place listing the behaviour per platform, which the documentation of the
+/// [`GetSize`] implementation of [`OsString`](std::ffi::OsString) refers to.
+pub const OS_STRING_SIZE_BEHAVIOR: &[(&str, &str)] = &[
+    ("unix", "capacity of the buffer holding the raw bytes"),
+    ("windows", "capacity of the buffer holding the WTF-8 encoded string, not its UTF-16 length"),
+    ("wasm", "capacity of the buffer holding the raw bytes"),
+];
+
+/// On all platforms the standard library stores the string as a byte buffer, so its heap size
+/// is the capacity of that buffer in bytes, and not its [`len`](std::ffi::OsStr::len). The same
+/// applies to [`PathBuf`](std::path::PathBuf). See [`OS_STRING_SIZE_BEHAVIOR`] for the
+/// behaviour on each platform.
+///
+/// On Windows the buffer does not hold UTF-16, but the [WTF-8](https://simonsapin.github.io/wtf-8/)
+/// encoding of the string, so its size is only loosely related to the number of wide characters
+/// the string converts to when passed to the OS.
 impl GetSize for std::ffi::OsString {
     fn get_heap_size(&self) -> usize {
-        self.len()
+        self.capacity()
+    }
+
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        if self.capacity() > 0 {
+            tracker.context(|context| context.allocation_found());
+        }
+
+        (tracker.context(|context| context.policies()).strings.select(self.capacity(), self.len()), tracker)
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_encoded_bytes(), self.capacity());
     }
 }
 
@@ -473,6 +2848,108 @@ impl GetSize for std::fs::OpenOptions {}
 impl GetSize for std::fs::Permissions {}
 impl GetSize for std::fs::ReadDir {}
 
+// Sockets and other OS handles only store the handle itself, while their buffers are owned by the OS.
+impl_size_stack_only!(
+    std::net::TcpListener,
+    std::net::TcpStream,
+    std::net::UdpSocket,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6,
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+);
+
+#[cfg(unix)]
+impl_size_stack_only!(
+    std::os::fd::OwnedFd,
+    std::os::fd::BorrowedFd<'_>,
+    std::os::unix::net::UnixDatagram,
+    std::os::unix::net::UnixListener,
+    std::os::unix::net::UnixStream,
+);
+
+#[cfg(windows)]
+crate::impl_size_opaque!(
+    std::os::windows::io::OwnedHandle,
+    std::os::windows::io::OwnedSocket,
+    std::os::windows::io::BorrowedHandle<'_>,
+    std::os::windows::io::BorrowedSocket<'_>,
+);
+
+// Opaque handles, of which the memory behind them is owned by the OS or the runtime.
+crate::impl_size_opaque!(
+    std::ffi::c_void,
+    std::any::TypeId,
+    std::thread::ThreadId,
+    std::io::Stdin,
+    std::io::Stdout,
+    std::io::Stderr,
+    std::process::Child,
+    std::process::ChildStdin,
+    std::process::ChildStdout,
+    std::process::ChildStderr,
+    std::process::ExitStatus,
+    std::process::ExitCode,
+    std::process::Stdio,
+);
+
+crate::impl_size_opaque!([T: ?Sized] std::ptr::NonNull<T>);
+
+// Errors which only hold plain data, so they can be stored e.g. in retry queues.
+impl_size_stack_only!(
+    std::fmt::Error,
+    std::collections::TryReserveError,
+    std::alloc::LayoutError,
+    std::num::ParseIntError,
+    std::num::ParseFloatError,
+    std::num::TryFromIntError,
+    std::str::ParseBoolError,
+    std::str::Utf8Error,
+    std::char::ParseCharError,
+    std::char::CharTryFromError,
+    std::char::DecodeUtf16Error,
+    std::array::TryFromSliceError,
+    std::string::FromUtf16Error,
+    std::net::AddrParseError,
+    std::time::SystemTimeError,
+);
+
+/// The bytes which failed to convert are accounted for by their length, as the capacity of
+/// their buffer is not exposed.
+impl GetSize for std::string::FromUtf8Error {
+    fn get_heap_size(&self) -> usize {
+        self.as_bytes().len()
+    }
+}
+
+impl GetSize for std::env::VarError {
+    fn get_heap_size(&self) -> usize {
+        match self {
+            Self::NotPresent => 0,
+            Self::NotUnicode(value) => GetSize::get_heap_size(value),
+        }
+    }
+}
+
+/// OS error codes and the messages of simple errors are stored inline or in static memory. Custom
+/// errors, created e.g. by [`std::io::Error::new`], box their kind together with the boxed error
+/// payload. Both of these allocations are accounted for, the latter by the stack size of the
+/// payload. The payload is type erased though, so any heap memory it owns itself, like the
+/// message of a [`String`] payload, is unknown and not included.
+impl GetSize for std::io::Error {
+    fn get_heap_size(&self) -> usize {
+        match self.get_ref() {
+            Some(payload) => {
+                std::mem::size_of::<(std::io::ErrorKind, Box<dyn std::error::Error + Send + Sync>)>()
+                    + std::mem::size_of_val(payload)
+            }
+            None => 0,
+        }
+    }
+}
+
 impl<T> GetSize for std::io::BufReader<T> where T: GetSize {
     fn get_heap_size(&self) -> usize {
         let mut total = GetSize::get_heap_size(self.get_ref());
@@ -497,17 +2974,24 @@ impl GetSize for std::path::PathBuf {
     fn get_heap_size(&self) -> usize {
         self.capacity()
     }
-}
 
-impl GetSize for &std::path::Path {}
-
-impl<T> GetSize for Box<[T]> {
-    fn get_heap_size(&self) -> usize {
-        let mut total = 0;
-        for item in self.iter() {
-            total += item.get_size()
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(&self, mut tracker: TR) -> (usize, TR) {
+        if self.capacity() > 0 {
+            tracker.context(|context| context.allocation_found());
         }
 
-        total
+        (tracker.context(|context| context.policies()).strings.select(self.capacity(), self.as_os_str().len()), tracker)
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        self.capacity() - self.as_os_str().len()
     }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.as_os_str().as_encoded_bytes(), self.capacity());
+    }
+}
+
+impl GetSize for &std::path::Path {
+    const CAN_ALLOCATE_HEAP: bool = false;
 }
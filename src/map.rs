@@ -0,0 +1,94 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::GetSize;
+
+
+
+/// How the heap memory occupied by a map is split between its keys, its values and the
+/// table structure itself, as determined by [`map_heap_breakdown`].
+///
+/// The keys and values include both their stack size inside the table and their own heap size.
+/// The parts always add up to the [heap size](GetSize::get_heap_size) of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MapBreakdown {
+    /// The number of bytes occupied by the keys.
+    pub keys: usize,
+    /// The number of bytes occupied by the values.
+    pub values: usize,
+    /// The number of bytes occupied by the table structure, like spare capacity and indices.
+    pub table_overhead: usize,
+}
+
+impl MapBreakdown {
+    /// Returns the total number of bytes occupied by the map inside the heap.
+    pub fn total(&self) -> usize {
+        self.keys + self.values + self.table_overhead
+    }
+}
+
+impl GetSize for MapBreakdown {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// Maps whose heap memory can be split up into keys, values and table overhead.
+pub trait MapHeapBreakdown {
+    /// Determines how the heap memory occupied by this map is split up.
+    fn heap_breakdown(&self) -> MapBreakdown;
+}
+
+/// Determines how the heap memory occupied by the given `map` is split between its keys,
+/// its values and the table structure itself.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use get_size::{GetSize, map_heap_breakdown};
+///
+/// let mut map: HashMap<u64, String> = HashMap::with_capacity(4);
+/// map.insert(1, String::from("Hello world"));
+///
+/// let breakdown = map_heap_breakdown(&map);
+///
+/// assert_eq!(breakdown.keys, 8);
+/// assert_eq!(breakdown.values, std::mem::size_of::<String>() + 11);
+/// assert_eq!(breakdown.total(), map.get_heap_size());
+/// ```
+pub fn map_heap_breakdown<M: MapHeapBreakdown>(map: &M) -> MapBreakdown {
+    map.heap_breakdown()
+}
+
+// Accounts for the entries of a map.
+pub(crate) fn entries_breakdown<'a, K, V, I>(entries: I) -> MapBreakdown
+where
+    K: GetSize + 'a,
+    V: GetSize + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    let mut breakdown = MapBreakdown::default();
+
+    for (k, v) in entries {
+        breakdown.keys += GetSize::get_size(k);
+        breakdown.values += GetSize::get_size(v);
+    }
+
+    breakdown
+}
+
+impl<K, V, S> MapHeapBreakdown for HashMap<K, V, S> where K: GetSize, V: GetSize {
+    fn heap_breakdown(&self) -> MapBreakdown {
+        let mut breakdown = entries_breakdown(self.iter());
+
+        let additional = self.capacity() - self.len();
+        breakdown.table_overhead = additional * (K::get_stack_size() + V::get_stack_size());
+
+        breakdown
+    }
+}
+
+impl<K, V> MapHeapBreakdown for BTreeMap<K, V> where K: GetSize, V: GetSize {
+    fn heap_breakdown(&self) -> MapBreakdown {
+        // The nodes of the tree are not accounted for.
+        entries_breakdown(self.iter())
+    }
+}
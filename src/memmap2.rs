@@ -0,0 +1,26 @@
+use ::memmap2::{Mmap, MmapMut, MmapRaw};
+
+use crate::{GetSize, ResourceKind, SizeReport};
+
+
+
+// The mapped pages are backed by a file and only occupy RAM while they are accessed, so they are
+// not accounted for as heap memory. Reports still list them as memory mapped regions, so they show
+// up in the total footprint.
+macro_rules! impl_size_mapped {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl GetSize for $name {
+                fn get_heap_size(&self) -> usize {
+                    0
+                }
+
+                fn report_size(&self, path: &str, report: &mut SizeReport) {
+                    report.add_external(path, ResourceKind::Mmap, self.len());
+                }
+            }
+        )*
+    }
+}
+
+impl_size_mapped!(Mmap, MmapMut, MmapRaw);
@@ -0,0 +1,273 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError, Weak as ArcWeak};
+
+use crate::{GetSize, GetSizeTracker, SizingPolicies, StandardTracker, TraversalContext};
+
+
+
+// Keeps the allocation of a memoized object reserved, so its address can not be reused by
+// another object as long as the entry exists.
+struct Anchor(ArcWeak<dyn Any>);
+
+// SAFETY: The weak reference is only used to read the strong count and gets dropped eventually,
+// neither of which accesses the object itself, so it can be sent to other threads regardless
+// of the type of the object.
+unsafe impl Send for Anchor {}
+
+struct MemoEntry {
+    anchor: Anchor,
+    // The sizes measured under different policies.
+    sizes: Vec<(SizingPolicies, usize)>,
+}
+
+static GLOBAL_MEMO: SizeMemo = SizeMemo::new();
+
+/// A thread-safe cache of the sizes of objects shared by [`Arc`]s, keyed by the identity of their
+/// allocation.
+///
+/// Immutable subtrees, like a configuration shared by many parents, get traversed in full every
+/// time one of their parents is measured. When measuring with a [`MemoTracker`], the size of every
+/// shared object is memoized after it was measured once, so measuring it again, even by a
+/// different tracker or on a different thread, only takes a lookup. Sizes are memoized per
+/// [`SizingPolicies`], and only if none of the shared objects nested inside of the object had been
+/// seen before by the tracker, as the size would otherwise be incomplete.
+///
+/// The memoized size is the one the object had when it was measured first. If an object gets
+/// mutated, e.g. through interior mutability, its entry has to be dropped by
+/// [`invalidate`](Self::invalidate). Every entry keeps the allocation of its object reserved,
+/// even after the object was dropped, so its address can not be mistaken for another object.
+/// [`prune`](Self::prune) drops the entries of objects which are gone. As the entries hold weak
+/// references, [`Arc::get_mut`] fails and [`Arc::make_mut`] clones the object as long as its size
+/// is memoized, so only immutable objects should be memoized.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use get_size::{GetSize, MemoTracker, SizeMemo, SizingPolicies, StandardTracker, StringPolicy};
+///
+/// let config = Arc::new(vec![String::from("Hello"); 100]);
+/// let memo = SizeMemo::new();
+///
+/// let (size, _) = config.get_size_with_tracker(MemoTracker::new(&memo));
+/// assert_eq!(memo.get(&config), Some(size - std::mem::size_of::<Arc<Vec<String>>>()));
+///
+/// // Sizes measured under other policies are memoized separately.
+/// let mut policies = SizingPolicies::new();
+/// policies.set_strings(StringPolicy::Len);
+/// assert_eq!(memo.get_with_policies(&config, policies), None);
+///
+/// // Measuring the config again only looks up its size.
+/// let parents = vec![Arc::clone(&config), Arc::clone(&config)];
+/// let (total, _) = parents.get_heap_size_with_tracker(MemoTracker::new(&memo));
+/// assert_eq!(total, parents.get_heap_size_with_tracker(StandardTracker::new()).0);
+///
+/// memo.invalidate(&config);
+/// assert_eq!(memo.get(&config), None);
+/// ```
+pub struct SizeMemo {
+    entries: Mutex<BTreeMap<usize, MemoEntry>>,
+}
+
+impl SizeMemo {
+    /// Creates a new empty memo.
+    pub const fn new() -> Self {
+        Self {
+            entries: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the memo shared by the whole process, as used by [`MemoTracker::global`].
+    pub fn global() -> &'static SizeMemo {
+        &GLOBAL_MEMO
+    }
+
+    /// Returns the memoized size of the object pointed to by `arc`, as measured under the
+    /// default policies, if any.
+    pub fn get<T: ?Sized>(&self, arc: &Arc<T>) -> Option<usize> {
+        self.get_with_policies(arc, SizingPolicies::default())
+    }
+
+    /// Returns the memoized size of the object pointed to by `arc`, as measured under the given
+    /// `policies`, if any.
+    pub fn get_with_policies<T: ?Sized>(&self, arc: &Arc<T>, policies: SizingPolicies) -> Option<usize> {
+        self.lookup(Arc::as_ptr(arc) as *const u8, policies)
+    }
+
+    /// Drops the memoized sizes of the object pointed to by `arc`, e.g. after it got mutated.
+    ///
+    /// Returns `true` if any of its sizes was memoized.
+    pub fn invalidate<T: ?Sized>(&self, arc: &Arc<T>) -> bool {
+        let addr = Arc::as_ptr(arc) as *const u8 as usize;
+
+        self.lock().remove(&addr).is_some()
+    }
+
+    /// Drops the memoized sizes of all objects which are not alive anymore, returning how many
+    /// entries got dropped.
+    pub fn prune(&self) -> usize {
+        let mut entries = self.lock();
+        let before = entries.len();
+
+        entries.retain(|_, entry| entry.anchor.0.strong_count()>0);
+
+        before - entries.len()
+    }
+
+    /// Drops all memoized sizes.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the number of objects whose sizes are memoized.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no sizes are memoized.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub(crate) fn lookup(&self, addr: *const u8, policies: SizingPolicies) -> Option<usize> {
+        let entries = self.lock();
+        let entry = entries.get(&(addr as usize))?;
+
+        entry.sizes.iter().find(|(measured, _)| *measured==policies).map(|(_, size)| *size)
+    }
+
+    pub(crate) fn store<F: FnOnce() -> ArcWeak<dyn Any>>(&self, addr: *const u8, policies: SizingPolicies, size: usize, anchor: F) {
+        let mut entries = self.lock();
+        let entry = entries.entry(addr as usize).or_insert_with(|| MemoEntry {
+            anchor: Anchor(anchor()),
+            sizes: Vec::new(),
+        });
+
+        if !entry.sizes.iter().any(|(measured, _)| *measured==policies) {
+            entry.sizes.push((policies, size));
+        }
+    }
+
+    // A panic while holding the lock can not leave the entries in an inconsistent state.
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<usize, MemoEntry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for SizeMemo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SizeMemo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SizeMemo")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Only the entries are accounted for, not the allocations of the objects kept reserved by them.
+impl GetSize for SizeMemo {
+    fn get_heap_size(&self) -> usize {
+        self.lock().values().map(|entry| {
+            std::mem::size_of::<usize>()
+                + std::mem::size_of::<MemoEntry>()
+                + entry.sizes.capacity() * std::mem::size_of::<(SizingPolicies, usize)>()
+        }).sum()
+    }
+}
+
+
+
+/// A tracker which memoizes the sizes of the objects shared by [`Arc`]s in a [`SizeMemo`], and
+/// looks them up instead of measuring these objects again.
+///
+/// It wraps another tracker, by default a [`StandardTracker`], which does the actual tracking,
+/// so objects seen before by the same tracker are still only accounted for once. The wrapped
+/// tracker has to count its [hits](GetSizeTracker::hit_count), as otherwise the sizes of objects
+/// sharing parts with previously measured ones would be memoized incompletely. Note that a
+/// memoized size does not let the tracker know about the shared objects nested inside of the
+/// memoized object, so these might get accounted for again if they are referenced elsewhere.
+#[derive(Debug)]
+pub struct MemoTracker<'a, T = StandardTracker> {
+    memo: &'a SizeMemo,
+    tracker: T,
+}
+
+impl<'a> MemoTracker<'a, StandardTracker> {
+    /// Creates a new tracker using a [`StandardTracker`] and the given `memo`.
+    pub fn new(memo: &'a SizeMemo) -> Self {
+        Self::with_tracker(memo, StandardTracker::new())
+    }
+}
+
+impl MemoTracker<'static, StandardTracker> {
+    /// Creates a new tracker using a [`StandardTracker`] and the [global](SizeMemo::global) memo.
+    pub fn global() -> Self {
+        Self::new(SizeMemo::global())
+    }
+}
+
+impl<'a, T: GetSizeTracker> MemoTracker<'a, T> {
+    /// Creates a new tracker wrapping the given `tracker`, using the given `memo`.
+    pub fn with_tracker(memo: &'a SizeMemo, tracker: T) -> Self {
+        Self {
+            memo,
+            tracker,
+        }
+    }
+
+    /// Returns the memo used by this tracker.
+    pub fn memo(&self) -> &'a SizeMemo {
+        self.memo
+    }
+
+    /// Returns a reference to the wrapped tracker.
+    pub fn tracker(&self) -> &T {
+        &self.tracker
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn tracker_mut(&mut self) -> &mut T {
+        &mut self.tracker
+    }
+
+    /// Consumes this tracker, returning the wrapped tracker.
+    pub fn into_tracker(self) -> T {
+        self.tracker
+    }
+}
+
+impl<T: GetSizeTracker> GetSizeTracker for MemoTracker<'_, T> {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        GetSizeTracker::track(&mut self.tracker, addr, strong_ref)
+    }
+
+    fn visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(&self.tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(&self.tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let memo = self.memo;
+
+        GetSizeTracker::context(&mut self.tracker, |context| {
+            let mut memoizing = context.with_memo(Some(memo));
+            let result = f(&mut memoizing);
+
+            context.update(&memoizing);
+
+            result
+        })
+    }
+}
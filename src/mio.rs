@@ -0,0 +1,33 @@
+use ::mio::{Events, Interest, Poll, Registry, Token, Waker};
+use ::mio::event::Event;
+use ::mio::net::{TcpListener, TcpStream, UdpSocket};
+
+use crate::GetSize;
+
+
+
+// Sockets only hold the OS handle, while their buffers are owned by the OS.
+impl GetSize for TcpListener { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for TcpStream { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for UdpSocket { const CAN_ALLOCATE_HEAP: bool = false; }
+
+#[cfg(unix)]
+impl GetSize for ::mio::net::UnixDatagram { const CAN_ALLOCATE_HEAP: bool = false; }
+#[cfg(unix)]
+impl GetSize for ::mio::net::UnixListener { const CAN_ALLOCATE_HEAP: bool = false; }
+#[cfg(unix)]
+impl GetSize for ::mio::net::UnixStream { const CAN_ALLOCATE_HEAP: bool = false; }
+
+// The selector state of a `Poll` and `Registry` is owned by the OS as well.
+impl GetSize for Poll { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Registry { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Waker { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Token { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Interest { const CAN_ALLOCATE_HEAP: bool = false; }
+
+impl GetSize for Events {
+    fn get_heap_size(&self) -> usize {
+        // The events are stored inside a buffer allocated upfront.
+        self.capacity() * std::mem::size_of::<Event>()
+    }
+}
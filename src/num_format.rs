@@ -0,0 +1,23 @@
+use ::num_format::Locale;
+
+use crate::UnitFormatter;
+
+
+
+#[cfg_attr(docsrs, doc(cfg(feature = "num-format")))]
+impl UnitFormatter {
+    /// Creates a new formatter using binary units with a single decimal place, which takes its
+    /// separators from the given `locale`.
+    pub fn with_locale(locale: &Locale) -> Self {
+        let mut formatter = Self::new();
+        formatter.set_locale(locale);
+
+        formatter
+    }
+
+    /// Takes the thousands and decimal separators from the given `locale`.
+    pub fn set_locale(&mut self, locale: &Locale) {
+        self.set_thousands_separator(locale.separator());
+        self.set_decimal_separator(locale.decimal());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{GetSize, StandardTracker};
+
+
+
+/// An object safe variant of [`GetSize`], which allows measuring differently typed
+/// objects behind trait objects like `&dyn GetSizeObj` or `Box<dyn GetSizeObj>`.
+///
+/// It is implemented for every type implementing [`GetSize`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use get_size::{GetSizeObj, StandardTracker, measure_all};
+///
+/// let names: Vec<String> = vec!["Hello".into()];
+/// let ids: HashMap<u64, u64> = HashMap::new();
+///
+/// let shards: [&dyn GetSizeObj; 2] = [&names, &ids];
+///
+/// let sizes = measure_all(&shards, &mut StandardTracker::new());
+///
+/// assert_eq!(sizes[0], names.get_size_obj());
+/// assert_eq!(sizes.iter().sum::<usize>(), names.get_size_obj() + ids.get_size_obj());
+/// ```
+pub trait GetSizeObj {
+    /// Determines how many bytes this object occupies inside the stack.
+    fn get_stack_size_obj(&self) -> usize;
+
+    /// Determines how many bytes this object occupies inside the heap.
+    fn get_heap_size_obj(&self) -> usize;
+
+    /// Determines the total size of the object.
+    fn get_size_obj(&self) -> usize {
+        self.get_stack_size_obj() + self.get_heap_size_obj()
+    }
+
+    /// Determines the total size of the object, using the given tracker to make sure
+    /// that shared ownership objects are only accounted for once.
+    fn get_size_obj_with_tracker(&self, tracker: &mut StandardTracker) -> usize;
+}
+
+impl<T> GetSizeObj for T where T: GetSize {
+    fn get_stack_size_obj(&self) -> usize {
+        T::get_stack_size()
+    }
+
+    fn get_heap_size_obj(&self) -> usize {
+        GetSize::get_heap_size(self)
+    }
+
+    fn get_size_obj_with_tracker(&self, tracker: &mut StandardTracker) -> usize {
+        let (total, _) = GetSize::get_size_with_tracker(self, tracker);
+
+        total
+    }
+}
+
+/// Determines the total size of each of the given objects, sharing the given tracker
+/// between all of them.
+///
+/// Shared ownership objects referenced by multiple objects are only accounted for by
+/// the first one.
+pub fn measure_all(objs: &[&dyn GetSizeObj], tracker: &mut StandardTracker) -> Vec<usize> {
+    objs
+        .iter()
+        // Dereference explicitly, as the reference itself implements `GetSizeObj` too.
+        .map(|obj| GetSizeObj::get_size_obj_with_tracker(*obj, tracker))
+        .collect()
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+
+
+/// Determines how many bytes of an object are lost to alignment-induced padding.
+///
+/// This includes the padding between and after the fields of the object itself, as well
+/// as the padding inside its fields and the heap allocations owned by it. Use this to decide
+/// whether reordering fields or changing the `#[repr]` of a type is worthwhile.
+///
+/// It can be derived by adding the `padding` attribute when deriving [`GetSize`](crate::GetSize).
+/// The padding of an enum variant is determined from a tuple of its field types, while the space
+/// taken up by the discriminant and unused variants is reported by [`EnumLayout`](crate::EnumLayout).
+///
+/// Shared ownership objects like [`Rc`] and [`Arc`] are not looked into.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetPadding;
+///
+/// let pairs: Vec<(u8, u64)> = vec![(1, 2), (3, 4)];
+///
+/// // Each pair wastes 7 bytes after the `u8`.
+/// assert_eq!(pairs.get_padding_size(), 14);
+/// ```
+pub trait GetPadding {
+    /// Determines how many bytes of this object, including the heap allocations owned
+    /// by it, are lost to padding.
+    fn get_padding_size(&self) -> usize;
+}
+
+// Types which never contain any padding.
+macro_rules! impl_padding_none {
+    ($($name:ty),* $(,)?) => {
+        $(
+            impl GetPadding for $name {
+                fn get_padding_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    }
+}
+
+impl_padding_none!(
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    char,
+    &str,
+    String,
+    std::ffi::CString,
+    std::ffi::OsString,
+    std::path::PathBuf,
+    Instant,
+    Duration,
+    SystemTime,
+);
+
+impl<T> GetPadding for &T {
+    fn get_padding_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: ?Sized> GetPadding for PhantomData<T> {
+    fn get_padding_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: ?Sized> GetPadding for *const T {
+    fn get_padding_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: ?Sized> GetPadding for *mut T {
+    fn get_padding_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T> GetPadding for Rc<T> {
+    fn get_padding_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T> GetPadding for Arc<T> {
+    fn get_padding_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T> GetPadding for Box<T> where T: GetPadding {
+    fn get_padding_size(&self) -> usize {
+        GetPadding::get_padding_size(&**self)
+    }
+}
+
+impl<T> GetPadding for Option<T> where T: GetPadding {
+    fn get_padding_size(&self) -> usize {
+        match self {
+            Some(t) => GetPadding::get_padding_size(t),
+            None => 0,
+        }
+    }
+}
+
+impl<T, const SIZE: usize> GetPadding for [T; SIZE] where T: GetPadding {
+    fn get_padding_size(&self) -> usize {
+        // Array elements are laid out without any padding between them.
+        self.iter().map(GetPadding::get_padding_size).sum()
+    }
+}
+
+impl<T> GetPadding for Vec<T> where T: GetPadding {
+    fn get_padding_size(&self) -> usize {
+        self.iter().map(GetPadding::get_padding_size).sum()
+    }
+}
+
+impl<T> GetPadding for VecDeque<T> where T: GetPadding {
+    fn get_padding_size(&self) -> usize {
+        self.iter().map(GetPadding::get_padding_size).sum()
+    }
+}
+
+impl<K, V, S> GetPadding for HashMap<K, V, S> where K: GetPadding, V: GetPadding {
+    fn get_padding_size(&self) -> usize {
+        // The keys and values are stored next to each other, like a tuple.
+        let entry_padding = std::mem::size_of::<(K, V)>()
+            - std::mem::size_of::<K>()
+            - std::mem::size_of::<V>();
+
+        let mut total = self.capacity() * entry_padding;
+
+        for (k, v) in self.iter() {
+            total += GetPadding::get_padding_size(k);
+            total += GetPadding::get_padding_size(v);
+        }
+
+        total
+    }
+}
+
+macro_rules! impl_padding_tuple {
+    ($($t:ident, $T:ident),+) => {
+        impl<$($T,)*> GetPadding for ($($T,)*)
+        where
+            $(
+                $T: GetPadding,
+            )*
+        {
+            fn get_padding_size(&self) -> usize {
+                let mut total = std::mem::size_of::<Self>();
+
+                let ($($t,)*) = self;
+                $(
+                    total -= std::mem::size_of::<$T>();
+                    total += GetPadding::get_padding_size($t);
+                )*
+
+                total
+            }
+        }
+    }
+}
+
+impl_padding_tuple!(v1,V1);
+impl_padding_tuple!(v1,V1,v2,V2);
+impl_padding_tuple!(v1,V1,v2,V2,v3,V3);
+impl_padding_tuple!(v1,V1,v2,V2,v3,V3,v4,V4);
+impl_padding_tuple!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5);
+impl_padding_tuple!(v1,V1,v2,V2,v3,V3,v4,V4,v5,V5,v6,V6);
@@ -0,0 +1,91 @@
+use std::mem::MaybeUninit;
+
+use crate::GetSize;
+
+
+
+// The value may not be initialized, so only its stack size can be accounted for.
+impl<T> GetSize for MaybeUninit<T> {}
+
+/// Determines the heap size of the slots of a pool, whose first `initialized` slots hold
+/// initialized values.
+///
+/// This is the shape used by many pools and arenas, which allocate all of their slots upfront
+/// and hand them out one after another. The result accounts for the capacity of `slots`,
+/// initialized or not, as well as the heap memory owned by the initialized values. Measuring
+/// `slots` by [`get_heap_size`](GetSize::get_heap_size) instead would only account for the former,
+/// as a [`MaybeUninit`] can not be looked into.
+///
+/// # Safety
+///
+/// The first `initialized` slots must hold initialized values, and `initialized` must not
+/// exceed the length of `slots`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::mem::MaybeUninit;
+/// use get_size::pool_heap_size;
+///
+/// let mut slots: Vec<MaybeUninit<String>> = Vec::with_capacity(4);
+/// slots.push(MaybeUninit::new(String::from("Hello")));
+/// slots.push(MaybeUninit::uninit());
+///
+/// let size = unsafe { pool_heap_size(&slots, 1) };
+/// assert_eq!(size, 4 * std::mem::size_of::<String>() + 5);
+/// #
+/// # unsafe { slots[0].assume_init_drop() };
+/// ```
+pub unsafe fn pool_heap_size<T: GetSize>(slots: &Vec<MaybeUninit<T>>, initialized: usize) -> usize {
+    let mut total = GetSize::get_heap_size(slots);
+
+    for slot in &slots[..initialized] {
+        // SAFETY: The caller guarantees that the first `initialized` slots are initialized.
+        total += GetSize::get_heap_size(unsafe { slot.assume_init_ref() });
+    }
+
+    total
+}
+
+/// Like [`pool_heap_size`], but for pools tracking which of their slots are in use separately,
+/// e.g. by a bitmap. `in_use` gets called with the index of every slot and has to return
+/// whether it holds an initialized value.
+///
+/// # Safety
+///
+/// All slots for which `in_use` returns `true` must hold initialized values.
+///
+/// # Example
+///
+/// ```rust
+/// use std::mem::MaybeUninit;
+/// use get_size::pool_heap_size_in_use;
+///
+/// let mut slots: Vec<MaybeUninit<Vec<u8>>> = Vec::with_capacity(3);
+/// slots.push(MaybeUninit::uninit());
+/// slots.push(MaybeUninit::new(vec![0; 16]));
+/// slots.push(MaybeUninit::uninit());
+///
+/// let bitmap: u64 = 0b010;
+///
+/// let size = unsafe { pool_heap_size_in_use(&slots, |index| bitmap & (1 << index) != 0) };
+/// assert_eq!(size, 3 * std::mem::size_of::<Vec<u8>>() + 16);
+/// #
+/// # unsafe { slots[1].assume_init_drop() };
+/// ```
+pub unsafe fn pool_heap_size_in_use<T, F>(slots: &Vec<MaybeUninit<T>>, mut in_use: F) -> usize
+where
+    T: GetSize,
+    F: FnMut(usize) -> bool,
+{
+    let mut total = GetSize::get_heap_size(slots);
+
+    for (index, slot) in slots.iter().enumerate() {
+        if in_use(index) {
+            // SAFETY: The caller guarantees that all slots in use are initialized.
+            total += GetSize::get_heap_size(unsafe { slot.assume_init_ref() });
+        }
+    }
+
+    total
+}
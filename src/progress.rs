@@ -0,0 +1,231 @@
+use std::any::Any;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{GetSize, GetSizeTracker, SizeError, StandardTracker, TraversalContext};
+
+
+
+/// The progress of a measurement done with a [`ProgressTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Progress {
+    /// The number of bytes accounted for so far.
+    ///
+    /// Only the elements of collections which have been measured completely are included.
+    pub bytes: usize,
+    /// The number of collection elements visited so far, including nested ones.
+    pub elements: usize,
+}
+
+impl GetSize for Progress {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// Gets informed periodically about the [`Progress`] of a measurement done with a [`ProgressTracker`].
+///
+/// This is implemented for all closures taking a [`Progress`] and returning a `bool`.
+pub trait ProgressHook {
+    /// Gets called with the progress made so far.
+    ///
+    /// Returning `false` cancels the measurement.
+    fn on_progress(&mut self, progress: Progress) -> bool;
+}
+
+impl<F> ProgressHook for F where F: FnMut(Progress) -> bool {
+    fn on_progress(&mut self, progress: Progress) -> bool {
+        self(progress)
+    }
+}
+
+
+
+/// A tracker which reports the progress of long running measurements and allows to cancel them.
+///
+/// It wraps another tracker, by default a [`StandardTracker`], which does the actual tracking.
+/// Every time a given number of collection elements has been measured, the [`ProgressHook`]
+/// gets invoked with the [`Progress`] made so far. The measurement gets cancelled once the hook
+/// returns `false`, or once the cancellation token set by [`set_cancel_token`](Self::set_cancel_token)
+/// gets set to `true`. Collections and derived types then stop visiting further elements and fields.
+///
+/// The tracker marks the measurement as [observed](TraversalContext::is_observed), so collections
+/// only do this bookkeeping for every element while it is in use.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, Progress, ProgressTracker, SizeError};
+///
+/// let data: Vec<String> = vec![String::from("Hello"); 100];
+///
+/// let mut reports = Vec::new();
+/// let mut tracker = ProgressTracker::new(|progress: Progress| {
+///     reports.push(progress.elements);
+///     true
+/// });
+/// tracker.set_interval(40);
+///
+/// assert_eq!(tracker.measure(&data), Ok(data.get_size()));
+/// drop(tracker);
+///
+/// // The final report happens once the measurement is done.
+/// assert_eq!(reports, vec![40, 80, 100]);
+///
+/// // Cancel the measurement after 10 elements.
+/// let mut tracker = ProgressTracker::new(|progress: Progress| progress.elements<10);
+/// tracker.set_interval(10);
+///
+/// assert_eq!(tracker.measure(&data), Err(SizeError::Cancelled));
+/// assert_eq!(tracker.progress().elements, 10);
+/// ```
+#[derive(Debug)]
+pub struct ProgressTracker<H, T = StandardTracker> {
+    tracker: T,
+    hook: H,
+    interval: usize,
+    progress: Progress,
+    cancel_token: Option<Arc<AtomicBool>>,
+    cancelled: bool,
+}
+
+impl<H: ProgressHook> ProgressTracker<H, StandardTracker> {
+    /// Creates a new tracker using a [`StandardTracker`], which invokes the `hook` every
+    /// 1024 elements.
+    pub fn new(hook: H) -> Self {
+        Self::with_tracker(StandardTracker::new(), hook)
+    }
+}
+
+impl<H: ProgressHook, T: GetSizeTracker> ProgressTracker<H, T> {
+    /// Creates a new tracker wrapping the given `tracker`, which invokes the `hook` every
+    /// 1024 elements.
+    pub fn with_tracker(tracker: T, hook: H) -> Self {
+        Self {
+            tracker,
+            hook,
+            interval: 1024,
+            progress: Progress::default(),
+            cancel_token: None,
+            cancelled: false,
+        }
+    }
+
+    /// Returns the number of elements measured in between two invocations of the hook.
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// Changes the number of elements measured in between two invocations of the hook.
+    ///
+    /// An `interval` of 0 is treated like 1.
+    pub fn set_interval(&mut self, interval: usize) {
+        self.interval = interval.max(1);
+    }
+
+    /// Sets a token which cancels the measurement once it gets set to `true`, e.g. by another thread.
+    pub fn set_cancel_token(&mut self, token: Arc<AtomicBool>) {
+        self.cancel_token = Some(token);
+    }
+
+    /// Returns the progress made by the current or last measurement.
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Returns a reference to the wrapped tracker.
+    pub fn tracker(&self) -> &T {
+        &self.tracker
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn tracker_mut(&mut self) -> &mut T {
+        &mut self.tracker
+    }
+
+    /// Consumes this tracker, returning the wrapped tracker.
+    pub fn into_tracker(self) -> T {
+        self.tracker
+    }
+
+    /// Determines the total size of `value`, reporting the progress along the way.
+    ///
+    /// The hook gets invoked one last time once the measurement is done. Returns
+    /// [`SizeError::Cancelled`] if the measurement got cancelled.
+    pub fn measure<V: GetSize>(&mut self, value: &V) -> Result<usize, SizeError> {
+        self.progress = Progress::default();
+        self.cancelled = false;
+
+        let (total, tracker) = GetSize::get_size_with_tracker(value, &mut *self);
+
+        if tracker.context(|context| context.is_cancelled()) {
+            return Err(SizeError::Cancelled);
+        }
+
+        tracker.progress.bytes = total;
+
+        if !tracker.hook.on_progress(tracker.progress) {
+            tracker.cancelled = true;
+
+            return Err(SizeError::Cancelled);
+        }
+
+        Ok(total)
+    }
+}
+
+impl<H: ProgressHook, T: GetSizeTracker> GetSizeTracker for ProgressTracker<H, T> {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        GetSizeTracker::track(&mut self.tracker, addr, strong_ref)
+    }
+
+    fn visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(&self.tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(&self.tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        if self.cancel_token.as_ref().is_some_and(|token| token.load(Ordering::Relaxed)) {
+            self.cancelled = true;
+        }
+
+        let (progress, cancelled) = (self.progress, self.cancelled);
+
+        let (result, measured) = GetSizeTracker::context(&mut self.tracker, |context| {
+            let outer = (context.progress(), context.is_cancelled(), context.is_observed());
+            context.resume(progress, cancelled);
+            context.set_observed(true);
+
+            let result = f(context);
+            let measured = (context.progress(), context.is_cancelled());
+
+            context.resume(outer.0, outer.1);
+            context.set_observed(outer.2);
+
+            (result, measured)
+        });
+
+        (self.progress, self.cancelled) = measured;
+
+        if !self.cancelled
+            && self.progress.elements!=progress.elements
+            && self.progress.elements.is_multiple_of(self.interval)
+        {
+            self.cancelled = !self.hook.on_progress(self.progress);
+        }
+
+        result
+    }
+}
+
+/// Only the wrapped tracker is accounted for, not the hook.
+impl<H, T: GetSize> GetSize for ProgressTracker<H, T> {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.tracker)
+    }
+}
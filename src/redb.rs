@@ -0,0 +1,16 @@
+use ::redb::{AccessGuard, Value};
+
+use crate::GetSize;
+
+
+
+/// The value is read from a page owned by the database and its cache, so it only gets accounted
+/// for by [`borrowed_size`](GetSize::borrowed_size).
+impl<V> GetSize for AccessGuard<'_, V> where V: Value + 'static {
+    fn borrowed_size(&self) -> usize {
+        let value = self.value();
+        let bytes = V::as_bytes(&value);
+
+        bytes.as_ref().len()
+    }
+}
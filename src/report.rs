@@ -0,0 +1,679 @@
+use std::fmt;
+
+use crate::{
+    CapacityReport, GetSize, GetSizeTracker, SizeFormatter, SizingPolicies, StandardTracker,
+    TraversalOrder, UnitFormatter,
+};
+
+
+
+/// The memory usage of the process and the system at the time a [`SizeReport`] was created.
+///
+/// Attaching it to a report puts the measured size into perspective, e.g. that a cache occupies
+/// 1.2 GiB of a 3.0 GiB resident set size. With the `sysinfo` feature enabled, it can be captured
+/// by [`SystemContext::capture`], otherwise it has to be provided by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemContext {
+    /// The resident set size of the process in bytes.
+    pub process_rss: usize,
+    /// The virtual memory used by the process in bytes.
+    pub process_virtual: usize,
+    /// The total memory of the system in bytes.
+    pub system_total: usize,
+    /// The memory currently used by the system in bytes.
+    pub system_used: usize,
+}
+
+impl SystemContext {
+    /// Returns the share of the resident set size made up by `bytes`, between 0.0 and 1.0.
+    /// Returns 0.0 if the resident set size is unknown.
+    pub fn rss_share(&self, bytes: usize) -> f64 {
+        if self.process_rss==0 {
+            return 0.0;
+        }
+
+        bytes as f64 / self.process_rss as f64
+    }
+}
+
+impl GetSize for SystemContext {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+
+
+/// How the size listed by an entry of a [`SizeReport`] was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SizeSource {
+    /// The size was taken from the allocation itself, e.g. as reported by the collection owning it.
+    Exact,
+    /// The size was computed by a model of the memory layout, e.g. the capacity of a hash table
+    /// times the size of its entries, ignoring its spare buckets and control bytes.
+    Modeled,
+    /// The size is held by an external resource owned by the object, like a GPU buffer, a memory
+    /// mapped region or the kernel buffers of a socket, as declared by the `extra` attribute of the
+    /// derive macro.
+    External(ResourceKind),
+}
+
+impl GetSize for SizeSource {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// Where the bytes listed by an entry of a [`SizeReport`] reside, so that memory outside of the
+/// RAM of the process can be reported separately from it. See [`SizeReport::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ResourceKind {
+    /// Heap memory, which is the kind of all entries not noted otherwise. External resources of
+    /// this kind are allocated outside of Rust, e.g. by a C library called over FFI.
+    Heap,
+    /// Memory of a GPU, like the buffers and textures of a graphics API.
+    Gpu,
+    /// Memory mapped regions, of which only the pages currently accessed occupy RAM.
+    Mmap,
+    /// Memory held by the kernel on behalf of the process, like socket buffers.
+    Kernel,
+}
+
+impl ResourceKind {
+    /// All kinds, in the order they get listed by reports.
+    pub const ALL: [Self; 4] = [Self::Heap, Self::Gpu, Self::Mmap, Self::Kernel];
+
+    /// Returns the lowercase name of this kind, e.g. `"gpu"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Heap => "heap",
+            Self::Gpu => "gpu",
+            Self::Mmap => "mmap",
+            Self::Kernel => "kernel",
+        }
+    }
+}
+
+impl GetSize for ResourceKind {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// A path exceeding its budget, as listed by [`SizeReport::over_budget`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OverBudget {
+    /// The path the budget was noted for.
+    pub path: String,
+    /// The number of bytes the path should not exceed.
+    pub budget: usize,
+    /// The number of bytes listed at the path and all paths nested below it.
+    pub size: usize,
+}
+
+/// How much of the memory listed by a [`SizeReport`] is shared between its entries, as noted
+/// by [`SizeReport::of_tracked`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SharingStats {
+    /// The number of references to shared objects seen before, which did not get accounted for
+    /// again by the tracker, as returned by [`GetSizeTracker::hit_count`].
+    pub hits: usize,
+    /// The number of heap bytes listed more than once by the entries, as they are reachable
+    /// through multiple references.
+    pub shared_bytes: usize,
+}
+
+impl SharingStats {
+    /// Returns the number of heap bytes listed by `report` which are uniquely owned, i.e. not
+    /// listed more than once.
+    pub fn unique_bytes(&self, report: &SizeReport) -> usize {
+        report.heap_size().saturating_sub(self.shared_bytes)
+    }
+}
+
+impl GetSize for SharingStats {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// Lists how the size of an object is split up between its fields, as determined by
+/// [`report_size`](GetSize::report_size).
+///
+/// Fields are identified by their path relative to the measured object, e.g. `names` or
+/// `entries.Variant.buffer`. Derived implementations list each field separately, while all other
+/// types, including collections, are listed as a whole. Fields without any heap memory are not
+/// listed. Just like [`get_heap_size`](GetSize::get_heap_size), every field gets measured on its
+/// own, so shared ownership objects referenced by multiple fields are listed multiple times.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, SizeReport};
+///
+/// #[derive(GetSize)]
+/// struct Cache {
+///     keys: Vec<u64>,
+///     name: String,
+/// }
+///
+/// let cache = Cache {
+///     keys: vec![1, 2, 3, 4],
+///     name: String::from("users"),
+/// };
+///
+/// let report = SizeReport::of(&cache);
+///
+/// assert_eq!(report.entries(), &[(String::from("keys"), 32), (String::from("name"), 5)]);
+/// assert_eq!(report.total(), cache.get_size());
+/// ```
+///
+/// Objects guarded by a [`Mutex`](std::sync::Mutex) or [`RwLock`](std::sync::RwLock) are looked
+/// into while holding their lock, which can cause contention when measuring hot shared state.
+/// A report created by [`snapshot`](Self::snapshot) only holds each lock while determining the heap
+/// size of its contents, which get listed as a whole under the path of the lock, while building up
+/// the entries happens after the lock got released.
+///
+/// Entries may note how their size was determined by a [`SizeSource`], which is done for hash
+/// tables, as their memory layout is only known exactly if the table reports it, and for external
+/// resources declared by the `extra` attribute of the derive macro. See [`source`](Self::source).
+/// External resources are additionally classified by a [`ResourceKind`], so that e.g. GPU memory
+/// can be reported separately from RAM by [`size_of_kind`](Self::size_of_kind).
+///
+/// Fields may be given a budget, e.g. by the `budget` attribute of the derive macro, which does not
+/// change how they are measured, but lets [`over_budget`](Self::over_budget) list the fields
+/// exceeding it, so that the parts of one large object can be held to their own limits.
+///
+/// A report created by [`of_tracked`](Self::of_tracked) additionally notes [`SharingStats`],
+/// telling how many references to shared objects got deduplicated and how many bytes the entries
+/// list more than once, e.g. to decide whether interning more values would pay off.
+///
+/// With the `serde` feature enabled, reports can be serialized, e.g. to aggregate them centrally
+/// by [`merge`](Self::merge). The serialized form is versioned by [`SCHEMA_VERSION`](Self::SCHEMA_VERSION)
+/// and stores all sizes as `u64`, so it stays the same across platforms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    pub(crate) stack: usize,
+    pub(crate) entries: Vec<(String, usize)>,
+    pub(crate) sources: Vec<(String, SizeSource)>,
+    pub(crate) budgets: Vec<(String, usize)>,
+    pub(crate) system: Option<SystemContext>,
+    pub(crate) sharing: Option<SharingStats>,
+    pub(crate) lock_scoped: bool,
+    pub(crate) order: TraversalOrder,
+    pub(crate) policies: SizingPolicies,
+}
+
+impl SizeReport {
+    /// The version of the serialized form of a report. Reports of a newer version are rejected
+    /// on deserialization.
+    pub const SCHEMA_VERSION: u32 = 1;
+
+    /// Creates a new empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a report listing the size of the fields of `value`.
+    pub fn of<T: GetSize>(value: &T) -> Self {
+        Self::of_with_policies(value, SizingPolicies::default())
+    }
+
+    /// Creates a report listing the size of the fields of `value`, measured under the given
+    /// `policies`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use get_size::{GetSize, SizeReport, SizingPolicies, StringPolicy};
+    ///
+    /// #[derive(GetSize)]
+    /// struct Cache {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut name = String::with_capacity(16);
+    /// name.push_str("users");
+    /// let cache = Cache { name };
+    ///
+    /// let mut policies = SizingPolicies::new();
+    /// policies.set_strings(StringPolicy::Len);
+    ///
+    /// let report = SizeReport::of_with_policies(&cache, policies);
+    ///
+    /// assert_eq!(report.entries(), &[(String::from("name"), 5)]);
+    /// assert_eq!(SizeReport::of(&cache).entries(), &[(String::from("name"), 16)]);
+    /// ```
+    pub fn of_with_policies<T: GetSize>(value: &T, policies: SizingPolicies) -> Self {
+        let mut report = Self::new();
+
+        report.policies = policies;
+        report.stack = T::get_stack_size();
+        GetSize::report_size(value, "", &mut report);
+
+        report
+    }
+
+    /// Creates a report listing the size of the fields of `value`, like [`of`](Self::of), and
+    /// notes the [`SharingStats`] of `value`.
+    ///
+    /// To do so, `value` gets measured a second time using a [`StandardTracker`], which only
+    /// accounts for every shared object once.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use get_size::{GetSize, SizeReport};
+    ///
+    /// #[derive(GetSize)]
+    /// struct Catalog {
+    ///     names: Vec<Arc<String>>,
+    /// }
+    ///
+    /// let name = Arc::new(String::from("Hello"));
+    /// let catalog = Catalog {
+    ///     names: vec![name.clone(), name.clone(), name.clone()],
+    /// };
+    ///
+    /// let report = SizeReport::of_tracked(&catalog);
+    /// let sharing = report.sharing().unwrap();
+    ///
+    /// assert_eq!(sharing.hits, 2);
+    /// assert_eq!(sharing.shared_bytes, 2 * name.get_heap_size());
+    /// ```
+    pub fn of_tracked<T: GetSize>(value: &T) -> Self {
+        let mut report = Self::of(value);
+
+        let tracker = StandardTracker::with_policies(report.policies);
+        let (tracked, tracker) = GetSize::get_heap_size_with_tracker(value, tracker);
+
+        report.sharing = Some(SharingStats {
+            hits: tracker.hit_count(),
+            shared_bytes: report.heap_size().saturating_sub(tracked),
+        });
+
+        report
+    }
+
+    /// Creates a report listing the size of the fields of `value`, in two phases to keep the
+    /// time locks are held short.
+    ///
+    /// In the first phase, each [`Mutex`](std::sync::Mutex) and [`RwLock`](std::sync::RwLock) only
+    /// gets locked while the heap size of its contents is determined, so the contents are listed as
+    /// a single entry under the path of the lock. The entries are only added in the second phase,
+    /// after the lock got released. Nested locks are still locked while their outer lock is held.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::Mutex;
+    /// use get_size::{GetSize, SizeReport};
+    ///
+    /// #[derive(GetSize)]
+    /// struct Shared {
+    ///     sessions: Mutex<Vec<String>>,
+    ///     name: String,
+    /// }
+    ///
+    /// let shared = Shared {
+    ///     sessions: Mutex::new(vec![String::from("abc")]),
+    ///     name: String::from("users"),
+    /// };
+    ///
+    /// let report = SizeReport::snapshot(&shared);
+    ///
+    /// assert_eq!(report.entries()[0], (String::from("sessions"), shared.sessions.get_heap_size()));
+    /// assert_eq!(report.total(), shared.get_size());
+    /// ```
+    pub fn snapshot<T: GetSize>(value: &T) -> Self {
+        let mut report = Self::new();
+
+        report.lock_scoped = true;
+        report.stack = T::get_stack_size();
+        GetSize::report_size(value, "", &mut report);
+
+        report
+    }
+
+    /// Whether this report was created by [`snapshot`](Self::snapshot), listing the contents of
+    /// locks as a whole.
+    pub fn is_lock_scoped(&self) -> bool {
+        self.lock_scoped
+    }
+
+    /// Returns the policies the entries of this report are measured under.
+    pub fn policies(&self) -> SizingPolicies {
+        self.policies
+    }
+
+    /// Changes the policies the entries added from now on are measured under.
+    pub fn set_policies(&mut self, policies: SizingPolicies) {
+        self.policies = policies;
+    }
+
+    /// Determines the heap size of `value` under the [policies](Self::policies) of this report,
+    /// as done by [`GetSize::report_size`] for objects listed as a whole.
+    ///
+    /// Under the default policies this is the result of [`get_heap_size`](GetSize::get_heap_size).
+    /// Otherwise `value` gets measured with a [`StandardTracker`] applying the policies, which
+    /// accounts for objects shared within `value` only once.
+    pub fn heap_size_of<T: GetSize>(&self, value: &T) -> usize {
+        // Keeps listing shared objects once per reference, which the sharing stats rely on.
+        if self.policies==SizingPolicies::default() {
+            return GetSize::get_heap_size(value);
+        }
+
+        let tracker = StandardTracker::with_policies(self.policies);
+
+        GetSize::get_heap_size_with_tracker(value, tracker).0
+    }
+
+    /// Joins the path of an object with the name of one of its fields.
+    pub fn join(path: &str, field: &str) -> String {
+        CapacityReport::join(path, field)
+    }
+
+    /// Adds `bytes` of heap memory found at `path`. Nothing gets added if `bytes` is 0.
+    pub fn add(&mut self, path: &str, bytes: usize) {
+        if bytes>0 {
+            self.entries.push((path.to_string(), bytes));
+        }
+    }
+
+    /// Adds an entry for bytes held by an external resource of the given `kind`, noting
+    /// [`SizeSource::External`] as its source. Nothing gets added if `bytes` is zero.
+    pub fn add_external(&mut self, path: &str, kind: ResourceKind, bytes: usize) {
+        if bytes>0 {
+            self.add(path, bytes);
+            self.set_source(path, SizeSource::External(kind));
+        }
+    }
+
+    /// Notes how the size found at `path` was determined, replacing any previous note.
+    pub fn set_source(&mut self, path: &str, source: SizeSource) {
+        match self.sources.iter_mut().find(|(p, _)| p==path) {
+            Some((_, existing)) => *existing = source,
+            None => self.sources.push((path.to_string(), source)),
+        }
+    }
+
+    /// Returns how the size found at `path` was determined, if noted.
+    pub fn source(&self, path: &str) -> Option<SizeSource> {
+        self.sources.iter()
+            .find(|(p, _)| p==path)
+            .map(|(_, source)| *source)
+    }
+
+    /// Returns the notes of how the sizes of the entries were determined, in the order they were
+    /// noted.
+    pub fn sources(&self) -> &[(String, SizeSource)] {
+        &self.sources
+    }
+
+    /// Notes that the size found at `path`, including all entries nested below it, should not
+    /// exceed `bytes`, replacing any previous budget of the path.
+    pub fn set_budget(&mut self, path: &str, bytes: usize) {
+        match self.budgets.iter_mut().find(|(p, _)| p==path) {
+            Some((_, existing)) => *existing = bytes,
+            None => self.budgets.push((path.to_string(), bytes)),
+        }
+    }
+
+    /// Returns the budget of `path`, if any.
+    pub fn budget(&self, path: &str) -> Option<usize> {
+        self.budgets.iter()
+            .find(|(p, _)| p==path)
+            .map(|(_, bytes)| *bytes)
+    }
+
+    /// Returns the budgets of all paths, in the order they were noted.
+    pub fn budgets(&self) -> &[(String, usize)] {
+        &self.budgets
+    }
+
+    /// Returns all paths exceeding their budget, in the order the budgets were noted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use get_size::{OverBudget, SizeReport};
+    ///
+    /// let mut report = SizeReport::new();
+    /// report.add("cache.keys", 600);
+    /// report.add("cache.values", 600);
+    /// report.add("index", 100);
+    /// report.set_budget("cache", 1000);
+    /// report.set_budget("index", 1000);
+    ///
+    /// assert_eq!(report.over_budget(), vec![
+    ///     OverBudget { path: String::from("cache"), budget: 1000, size: 1200 },
+    /// ]);
+    /// ```
+    pub fn over_budget(&self) -> Vec<OverBudget> {
+        self.budgets.iter()
+            .map(|(path, budget)| OverBudget {
+                path: path.clone(),
+                budget: *budget,
+                size: self.size_at(path),
+            })
+            .filter(|over| over.size>over.budget)
+            .collect()
+    }
+
+    /// Returns the number of bytes listed at `path` and all paths nested below it.
+    pub fn size_at(&self, path: &str) -> usize {
+        self.entries.iter()
+            .filter(|(p, _)| is_nested(p, path))
+            .map(|(_, bytes)| bytes)
+            .sum()
+    }
+
+    /// Returns where the bytes found at `path` reside, which is [`ResourceKind::Heap`] unless the
+    /// entry was added by [`add_external`](Self::add_external).
+    pub fn kind(&self, path: &str) -> ResourceKind {
+        match self.source(path) {
+            Some(SizeSource::External(kind)) => kind,
+            _ => ResourceKind::Heap,
+        }
+    }
+
+    /// Returns the total number of bytes listed by the entries of the given `kind`.
+    pub fn size_of_kind(&self, kind: ResourceKind) -> usize {
+        self.entries.iter()
+            .filter(|(path, _)| self.kind(path)==kind)
+            .map(|(_, bytes)| bytes)
+            .sum()
+    }
+
+    /// Returns the number of bytes of the measured object residing in RAM, which are its stack size
+    /// and all entries besides those of GPU memory and memory mapped regions or held by the kernel.
+    pub fn ram_size(&self) -> usize {
+        self.stack + self.size_of_kind(ResourceKind::Heap)
+    }
+
+    /// Returns all entries in the order they were added.
+    pub fn entries(&self) -> &[(String, usize)] {
+        &self.entries
+    }
+
+    /// Returns the number of bytes the measured object occupies inside the stack.
+    pub fn stack_size(&self) -> usize {
+        self.stack
+    }
+
+    /// Returns the total number of bytes listed by the entries.
+    pub fn heap_size(&self) -> usize {
+        self.entries.iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Returns the total size of the measured object.
+    pub fn total(&self) -> usize {
+        self.stack + self.heap_size()
+    }
+
+    /// Returns the [`TraversalOrder`] applied by [`top`](Self::top).
+    pub fn order(&self) -> TraversalOrder {
+        self.order
+    }
+
+    /// Changes the [`TraversalOrder`] applied by [`top`](Self::top).
+    pub fn set_order(&mut self, order: TraversalOrder) {
+        self.order = order;
+    }
+
+    /// Returns up to `n` entries with the most bytes, largest first. Entries of equal size are
+    /// ordered according to the [order](Self::order) of this report.
+    pub fn top(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut entries: Vec<_> = self.entries.iter()
+            .map(|(path, bytes)| (path.as_str(), *bytes))
+            .collect();
+
+        self.order.sort_largest_first(&mut entries);
+        entries.truncate(n);
+
+        entries
+    }
+
+    /// Returns the memory usage of the process and the system attached to this report, if any.
+    pub fn system_context(&self) -> Option<&SystemContext> {
+        self.system.as_ref()
+    }
+
+    /// Attaches the memory usage of the process and the system to this report.
+    pub fn set_system_context(&mut self, system: Option<SystemContext>) {
+        self.system = system;
+    }
+
+    /// Returns how much of the listed memory is shared, if noted.
+    pub fn sharing(&self) -> Option<&SharingStats> {
+        self.sharing.as_ref()
+    }
+
+    /// Notes how much of the listed memory is shared.
+    pub fn set_sharing(&mut self, sharing: Option<SharingStats>) {
+        self.sharing = sharing;
+    }
+
+    /// Adds all sizes listed by `other` to this report, e.g. to aggregate the reports of
+    /// multiple shards or processes.
+    ///
+    /// Entries with the same path get summed up, new paths are appended in the order of `other`.
+    /// The system contexts are summed up as well, assuming that the reports were created by
+    /// different processes.
+    pub fn merge(&mut self, other: &Self) {
+        self.stack += other.stack;
+
+        for (path, bytes) in &other.entries {
+            match self.entries.iter_mut().find(|(p, _)| p==path) {
+                Some((_, total)) => *total += bytes,
+                None => self.entries.push((path.clone(), *bytes)),
+            }
+        }
+
+        // A size is only exact if it was determined exactly by all reports.
+        for (path, source) in &other.sources {
+            match self.source(path) {
+                Some(SizeSource::Exact) if *source==SizeSource::Modeled => self.set_source(path, *source),
+                Some(_) => {}
+                None => self.set_source(path, *source),
+            }
+        }
+
+        // Budgets already noted take precedence.
+        for (path, bytes) in &other.budgets {
+            if self.budget(path).is_none() {
+                self.set_budget(path, *bytes);
+            }
+        }
+
+        self.system = match (self.system, other.system) {
+            (Some(a), Some(b)) => Some(SystemContext {
+                process_rss: a.process_rss + b.process_rss,
+                process_virtual: a.process_virtual + b.process_virtual,
+                system_total: a.system_total + b.system_total,
+                system_used: a.system_used + b.system_used,
+            }),
+            (a, b) => a.or(b),
+        };
+
+        self.sharing = match (self.sharing, other.sharing) {
+            (Some(a), Some(b)) => Some(SharingStats {
+                hits: a.hits + b.hits,
+                shared_bytes: a.shared_bytes + b.shared_bytes,
+            }),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Renders this report just like its [`Display`](fmt::Display) implementation, but formats
+    /// all sizes with the given `formatter`.
+    pub fn render<F: SizeFormatter + ?Sized>(&self, formatter: &F) -> String {
+        let mut rendered = String::new();
+
+        for (path, bytes) in self.top(self.entries.len()) {
+            rendered.push_str(&format!("{}: {}\n", path, formatter.format_size(bytes)));
+        }
+
+        for over in self.over_budget() {
+            rendered.push_str(&format!(
+                "{}: {} over budget of {}\n",
+                over.path,
+                formatter.format_size(over.size),
+                formatter.format_size(over.budget),
+            ));
+        }
+
+        // Memory outside of RAM gets summed up per kind.
+        for kind in &ResourceKind::ALL[1..] {
+            let bytes = self.size_of_kind(*kind);
+
+            if bytes>0 {
+                rendered.push_str(&format!("{} total: {}\n", kind.name(), formatter.format_size(bytes)));
+            }
+        }
+
+        if let Some(sharing) = &self.sharing {
+            rendered.push_str(&format!(
+                "shared: {} in {} deduplicated references\n",
+                formatter.format_size(sharing.shared_bytes),
+                sharing.hits,
+            ));
+        }
+
+        match &self.system {
+            Some(system) => rendered.push_str(&format!(
+                "total: {} of {} RSS ({:.1}%)\n",
+                formatter.format_size(self.total()),
+                formatter.format_size(system.process_rss),
+                system.rss_share(self.ram_size()) * 100.0,
+            )),
+            None => rendered.push_str(&format!("total: {}\n", formatter.format_size(self.total()))),
+        }
+
+        rendered
+    }
+}
+
+/// Lists all entries, largest first, followed by the paths exceeding their budget, the totals of memory outside of RAM per
+/// [`ResourceKind`], the shared bytes, if noted, and the total and the share of the resident set size of the bytes in RAM,
+/// if a [`SystemContext`] is attached.
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&UnitFormatter::new()))
+    }
+}
+
+impl GetSize for SizeReport {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.entries)
+            + GetSize::get_heap_size(&self.sources)
+            + GetSize::get_heap_size(&self.budgets)
+    }
+}
+
+// Whether `path` is `parent` itself or nested below it.
+fn is_nested(path: &str, parent: &str) -> bool {
+    match path.strip_prefix(parent) {
+        Some(rest) => parent.is_empty() || rest.is_empty() || rest.starts_with('.') || rest.starts_with('['),
+        None => false,
+    }
+}
@@ -0,0 +1,144 @@
+use ::rkyv::boxed::ArchivedBox;
+use ::rkyv::option::ArchivedOption;
+use ::rkyv::rend::{
+    char_be,
+    char_le,
+    f32_be,
+    f32_le,
+    f64_be,
+    f64_le,
+    i16_be,
+    i16_le,
+    i32_be,
+    i32_le,
+    i64_be,
+    i64_le,
+    i128_be,
+    i128_le,
+    u16_be,
+    u16_le,
+    u32_be,
+    u32_le,
+    u64_be,
+    u64_le,
+    u128_be,
+    u128_le,
+};
+use ::rkyv::string::ArchivedString;
+use ::rkyv::traits::ArchivePointee;
+use ::rkyv::util::{Align, AlignedVec};
+use ::rkyv::vec::ArchivedVec;
+
+use crate::{GetSize, GetSizeUnsized};
+
+
+
+/// The buffer holding the serialized archive.
+impl<const ALIGNMENT: usize> GetSize for AlignedVec<ALIGNMENT> {
+    fn get_heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T> GetSize for Align<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.0)
+    }
+}
+
+// The primitives used inside archives, which are stored with a fixed endianness.
+macro_rules! impl_size_archived_primitive {
+    ($($name:ty),* $(,)?) => {
+        $(
+            impl GetSize for $name {
+                const CAN_ALLOCATE_HEAP: bool = false;
+            }
+        )*
+    }
+}
+
+impl_size_archived_primitive!(
+    char_be,
+    char_le,
+    f32_be,
+    f32_le,
+    f64_be,
+    f64_le,
+    i16_be,
+    i16_le,
+    i32_be,
+    i32_le,
+    i64_be,
+    i64_le,
+    i128_be,
+    i128_le,
+    u16_be,
+    u16_le,
+    u32_be,
+    u32_le,
+    u64_be,
+    u64_le,
+    u128_be,
+    u128_le,
+);
+
+// Archived containers do not own any heap memory. Instead they point to data stored elsewhere
+// inside the archive. This data is treated as being owned by the container, so the space taken
+// up by the individual parts of an archive can be determined. Note that it is already accounted
+// for by the buffer holding the archive.
+
+/// The archived elements are stored elsewhere inside the archive.
+impl<T> GetSize for ArchivedVec<T> where T: GetSize {
+    fn get_heap_size(&self) -> usize {
+        let mut total = self.len() * T::get_stack_size();
+
+        if T::CAN_ALLOCATE_HEAP {
+            for v in self.iter() {
+                total += GetSize::get_heap_size(v);
+            }
+        }
+
+        total
+    }
+}
+
+/// The archived string is stored elsewhere inside the archive, unless it is short enough
+/// to be stored inline.
+impl GetSize for ArchivedString {
+    fn get_heap_size(&self) -> usize {
+        let len = self.len();
+
+        if len<=std::mem::size_of::<Self>() {
+            0
+        } else {
+            len
+        }
+    }
+}
+
+/// The archived value is stored elsewhere inside the archive. Unlike the elements of an
+/// [`ArchivedVec`], it is not a part the archive could be broken down into, but a single value
+/// the box refers to, like the data borrowed by a [`Cow`](std::borrow::Cow). It is already
+/// accounted for by the buffer holding the archive, so it only counts towards the
+/// [`borrowed_size`](GetSize::borrowed_size).
+impl<T> GetSize for ArchivedBox<T> where T: GetSizeUnsized + ArchivePointee + ?Sized {
+    const CAN_ALLOCATE_HEAP: bool = false;
+
+    fn borrowed_size(&self) -> usize {
+        GetSizeUnsized::get_size_of_val(self.get())
+    }
+}
+
+impl<T> GetSize for ArchivedOption<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        match self.as_ref() {
+            // The options stack size already accounts for the values stack size.
+            Some(t) => GetSize::get_heap_size(t),
+            None => 0,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use ::roaring::RoaringBitmap;
+
+use crate::{GetSize, SizeReport};
+
+
+
+/// The size of the array and bitset containers is taken from the bytes reported by
+/// [`RoaringBitmap::statistics`]. The bookkeeping of the containers themselves is not included.
+impl GetSize for RoaringBitmap {
+    fn get_heap_size(&self) -> usize {
+        let statistics = self.statistics();
+
+        (statistics.n_bytes_array_containers + statistics.n_bytes_bitset_containers) as usize
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        let statistics = self.statistics();
+
+        report.add(&SizeReport::join(path, "arrays"), statistics.n_bytes_array_containers as usize);
+        report.add(&SizeReport::join(path, "bitsets"), statistics.n_bytes_bitset_containers as usize);
+    }
+}
@@ -0,0 +1,95 @@
+//! Routines shared by the implementations generated by the derive macro.
+//!
+//! Types with many fields would otherwise get all of their fields measured by a single,
+//! huge function, which is slow to compile and grows binaries. Instead, the derive macro
+//! generates a small function per field and lets the routines of this module call them.
+
+use crate::{GetSize, GetSizeTracker, ShallowTracker, StandardTracker};
+
+/// A function determining the heap size of a single field of an `S`, using the tracker `T`.
+pub type FieldFn<S, T> = fn(&S, T) -> (usize, T);
+
+/// Adds up the heap sizes determined by the given `fields` functions for `value`, passing
+/// the tracker from one to the next.
+///
+/// The remaining fields are skipped once the tracker reports the measurement as
+/// [cancelled](crate::TraversalContext::is_cancelled).
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, GetSizeTracker, StandardTracker};
+/// use get_size::runtime::{FieldFn, sum_fields};
+///
+/// struct Pair {
+///     first: String,
+///     second: Vec<u8>,
+/// }
+///
+/// fn fields<T: GetSizeTracker>() -> [FieldFn<Pair, T>; 2] {
+///     [
+///         |pair, tracker| GetSize::get_heap_size_with_tracker(&pair.first, tracker),
+///         |pair, tracker| GetSize::get_heap_size_with_tracker(&pair.second, tracker),
+///     ]
+/// }
+///
+/// let pair = Pair { first: String::from("abc"), second: vec![1, 2] };
+/// let (total, _) = sum_fields(&pair, &fields(), StandardTracker::new());
+///
+/// assert_eq!(total, 5);
+/// ```
+pub fn sum_fields<S: ?Sized, T: GetSizeTracker>(
+    value: &S,
+    fields: &[FieldFn<S, T>],
+    tracker: T,
+) -> (usize, T) {
+    let mut total = 0;
+    let mut tracker = tracker;
+
+    for field in fields {
+        if is_cancelled(&mut tracker) {
+            break;
+        }
+
+        let (size, next) = field(value, tracker);
+
+        total += size;
+        tracker = next;
+    }
+
+    (total, tracker)
+}
+
+/// Determines the heap size of `value` without descending into its contents, using a
+/// [`ShallowTracker`] wrapping the given `tracker`.
+///
+/// This is used for fields annotated with `#[get_size(skip_recursion)]`. Cancellation is
+/// forwarded to the wrapped tracker, which gets handed back afterwards.
+pub fn shallow_heap_size_with_tracker<V: GetSize, T: GetSizeTracker>(value: &V, tracker: T) -> (usize, T) {
+    let (size, tracker) = GetSize::get_heap_size_with_tracker(value, ShallowTracker::with_tracker(tracker));
+
+    (size, tracker.into_tracker())
+}
+
+/// Like [`shallow_heap_size_with_tracker`], but uses a fresh [`StandardTracker`].
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::runtime::shallow_heap_size;
+///
+/// let value = vec![vec![0u8; 100]; 2];
+///
+/// assert_eq!(shallow_heap_size(&value), 2 * std::mem::size_of::<Vec<u8>>());
+/// ```
+pub fn shallow_heap_size<V: GetSize>(value: &V) -> usize {
+    shallow_heap_size_with_tracker(value, StandardTracker::new()).0
+}
+
+/// Returns `true` if the measurement done with the given `tracker` got
+/// [cancelled](crate::TraversalContext::is_cancelled).
+///
+/// The derive macro checks this before measuring the fields of a type.
+pub fn is_cancelled<T: GetSizeTracker>(tracker: &mut T) -> bool {
+    tracker.context(|context| context.is_cancelled())
+}
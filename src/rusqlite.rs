@@ -0,0 +1,29 @@
+use ::rusqlite::Connection;
+use ::rusqlite::types::{Null, Value, ValueRef};
+
+use crate::GetSize;
+
+
+
+impl GetSize for Value {
+    fn get_heap_size(&self) -> usize {
+        match self {
+            Self::Text(text) => GetSize::get_heap_size(text),
+            Self::Blob(blob) => GetSize::get_heap_size(blob),
+            Self::Null | Self::Integer(_) | Self::Real(_) => 0,
+        }
+    }
+}
+
+/// The referenced value is only borrowed from a row.
+impl GetSize for ValueRef<'_> {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl GetSize for Null {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// A connection is only a handle to the database, whose memory is managed by SQLite
+/// itself and thus not accounted for.
+impl GetSize for Connection {}
@@ -0,0 +1,96 @@
+use ::serde::de::Error;
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{SharingStats, SizeReport, SizeSource, SizingPolicies, SystemContext, TraversalOrder};
+
+
+
+// The serialized form of a `SizeReport`, which is kept stable across versions of this crate.
+#[derive(Serialize, Deserialize)]
+struct Wire {
+    version: u32,
+    stack: u64,
+    entries: Vec<WireEntry>,
+    system: Option<SystemContext>,
+    // Added later on, so reports serialized before can still be read.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<WireSource>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    budgets: Vec<WireBudget>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sharing: Option<SharingStats>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireEntry {
+    path: String,
+    bytes: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireSource {
+    path: String,
+    source: SizeSource,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireBudget {
+    path: String,
+    bytes: u64,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for SizeReport {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        Wire {
+            version: Self::SCHEMA_VERSION,
+            stack: self.stack as u64,
+            entries: self.entries.iter()
+                .map(|(path, bytes)| WireEntry { path: path.clone(), bytes: *bytes as u64 })
+                .collect(),
+            system: self.system,
+            sources: self.sources.iter()
+                .map(|(path, source)| WireSource { path: path.clone(), source: *source })
+                .collect(),
+            budgets: self.budgets.iter()
+                .map(|(path, bytes)| WireBudget { path: path.clone(), bytes: *bytes as u64 })
+                .collect(),
+            sharing: self.sharing,
+        }.serialize(serializer)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for SizeReport {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let wire = Wire::deserialize(deserializer)?;
+
+        if wire.version > Self::SCHEMA_VERSION {
+            return Err(D::Error::custom(format!(
+                "unsupported size report version {}, expected at most {}",
+                wire.version,
+                Self::SCHEMA_VERSION,
+            )));
+        }
+
+        let size = |bytes: u64| usize::try_from(bytes).map_err(D::Error::custom);
+
+        Ok(Self {
+            stack: size(wire.stack)?,
+            entries: wire.entries.into_iter()
+                .map(|entry| Ok::<_, D::Error>((entry.path, size(entry.bytes)?)))
+                .collect::<Result<_, _>>()?,
+            sources: wire.sources.into_iter()
+                .map(|source| (source.path, source.source))
+                .collect(),
+            budgets: wire.budgets.into_iter()
+                .map(|budget| Ok::<_, D::Error>((budget.path, size(budget.bytes)?)))
+                .collect::<Result<_, _>>()?,
+            system: wire.system,
+            sharing: wire.sharing,
+            lock_scoped: false,
+            order: TraversalOrder::default(),
+            policies: SizingPolicies::default(),
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use ::serde_json::value::RawValue;
+use ::serde_json::{Map, Number, Value};
+
+use crate::{Borrowed, DedupEstimator, GetSize, GetSizeTracker, GetSizeUnsized};
+
+
+
+/// A raw value is just the JSON text, so a `Box<RawValue>` owns exactly that text inside the heap.
+impl GetSizeUnsized for RawValue {
+    fn record_payloads_of_val(&self, estimator: &mut DedupEstimator) {
+        estimator.record(self.get().as_bytes(), self.get().len());
+    }
+}
+
+/// The JSON text is only borrowed, e.g. from the input of a zero-copy deserialization, so it is
+/// treated just like a `&str`.
+impl GetSize for &RawValue {
+    fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+        &self,
+        mut tracker: TR,
+    ) -> (usize, TR) {
+        let text = self.get();
+
+        if tracker.context(|context| context.policies()).count_borrowed && tracker.track(text.as_ptr(), Borrowed) {
+            (text.len(), tracker)
+        } else {
+            (0, tracker)
+        }
+    }
+}
+
+impl GetSize for Number {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// Just like a [`BTreeMap`](std::collections::BTreeMap), only the stack size of the entries
+/// is accounted for, as the size of the tree nodes is not known.
+impl GetSize for Map<String, Value> {
+    fn get_heap_size(&self) -> usize {
+        let mut total = 0;
+
+        for (key, value) in self.iter() {
+            total += GetSize::get_size(key);
+            total += GetSize::get_size(value);
+        }
+
+        total
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        for (key, value) in self.iter() {
+            GetSize::record_payloads(key, estimator);
+            GetSize::record_payloads(value, estimator);
+        }
+    }
+}
+
+impl GetSize for Value {
+    fn get_heap_size(&self) -> usize {
+        match self {
+            // The values stack size already accounts for the contents stack size.
+            Self::String(string) => GetSize::get_heap_size(string),
+            Self::Array(array) => GetSize::get_heap_size(array),
+            Self::Object(map) => GetSize::get_heap_size(map),
+            Self::Null | Self::Bool(_) | Self::Number(_) => 0,
+        }
+    }
+
+    fn wasted_capacity(&self) -> usize {
+        match self {
+            Self::String(string) => GetSize::wasted_capacity(string),
+            Self::Array(array) => GetSize::wasted_capacity(array),
+            Self::Object(map) => GetSize::wasted_capacity(map),
+            Self::Null | Self::Bool(_) | Self::Number(_) => 0,
+        }
+    }
+
+    fn record_payloads(&self, estimator: &mut DedupEstimator) {
+        match self {
+            Self::String(string) => GetSize::record_payloads(string, estimator),
+            Self::Array(array) => GetSize::record_payloads(array, estimator),
+            Self::Object(map) => GetSize::record_payloads(map, estimator),
+            Self::Null | Self::Bool(_) | Self::Number(_) => {}
+        }
+    }
+}
@@ -0,0 +1,264 @@
+/// Implements [`GetSize`](crate::GetSize) for a generic enum shaped like [`Option`], i.e. having
+/// one variant holding a single value and one variant holding nothing.
+///
+/// The first argument is the path of the enum, followed by the name of the variant holding
+/// the value and the name of the empty variant. The enum must have exactly one type parameter.
+/// Just like for [`Option`], the heap size is that of the held value, if any.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSize;
+///
+/// pub enum Maybe<T> {
+///     Just(T),
+///     Nothing,
+/// }
+///
+/// get_size::impl_size_option_like!(Maybe, Just, Nothing);
+///
+/// assert_eq!(Maybe::Just(String::from("Hello")).get_heap_size(), 5);
+/// assert_eq!(Maybe::<String>::Nothing.get_heap_size(), 0);
+/// ```
+#[macro_export]
+macro_rules! impl_size_option_like {
+    ($($name:ident)::+, $some:ident, $none:ident $(,)?) => {
+        impl<T> $crate::GetSize for $($name)::+<T> where T: $crate::GetSize {
+            const CAN_ALLOCATE_HEAP: ::core::primitive::bool = T::CAN_ALLOCATE_HEAP;
+
+            fn get_heap_size(&self) -> ::core::primitive::usize {
+                match self {
+                    // The enums stack size already accounts for the values stack size.
+                    $($name)::+::$some(t) => $crate::GetSize::get_heap_size(t),
+                    $($name)::+::$none => 0,
+                }
+            }
+
+            fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(
+                &self,
+                tracker: TR,
+            ) -> (::core::primitive::usize, TR) {
+                match self {
+                    $($name)::+::$some(t) => $crate::GetSize::get_heap_size_with_tracker(t, tracker),
+                    $($name)::+::$none => (0, tracker),
+                }
+            }
+
+            fn get_heap_size_within(
+                &self,
+                limit: ::core::primitive::usize,
+            ) -> ::core::option::Option<::core::primitive::usize> {
+                match self {
+                    $($name)::+::$some(t) => $crate::GetSize::get_heap_size_within(t, limit),
+                    $($name)::+::$none => ::core::option::Option::Some(0),
+                }
+            }
+        }
+    }
+}
+
+/// Implements [`GetSize`](crate::GetSize) for a generic enum shaped like [`Result`], i.e. having
+/// two variants holding a single value each, like the `Either` type found in many crates.
+///
+/// The first argument is the path of the enum, followed by the names of its two variants.
+/// The enum must have exactly two type parameters, held by the variants in the same order.
+/// Just like for [`Result`], the heap size is that of the held value.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSize;
+///
+/// pub enum Either<L, R> {
+///     Left(L),
+///     Right(R),
+/// }
+///
+/// get_size::impl_size_result_like!(Either, Left, Right);
+///
+/// assert_eq!(Either::<String, u64>::Left(String::from("Hello")).get_heap_size(), 5);
+/// assert_eq!(Either::<String, u64>::Right(1).get_heap_size(), 0);
+/// ```
+#[macro_export]
+macro_rules! impl_size_result_like {
+    ($($name:ident)::+, $first:ident, $second:ident $(,)?) => {
+        impl<A, B> $crate::GetSize for $($name)::+<A, B>
+        where
+            A: $crate::GetSize,
+            B: $crate::GetSize,
+        {
+            const CAN_ALLOCATE_HEAP: ::core::primitive::bool = A::CAN_ALLOCATE_HEAP || B::CAN_ALLOCATE_HEAP;
+
+            fn get_heap_size(&self) -> ::core::primitive::usize {
+                match self {
+                    // The enums stack size already accounts for the values stack size.
+                    $($name)::+::$first(a) => $crate::GetSize::get_heap_size(a),
+                    $($name)::+::$second(b) => $crate::GetSize::get_heap_size(b),
+                }
+            }
+
+            fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(
+                &self,
+                tracker: TR,
+            ) -> (::core::primitive::usize, TR) {
+                match self {
+                    $($name)::+::$first(a) => $crate::GetSize::get_heap_size_with_tracker(a, tracker),
+                    $($name)::+::$second(b) => $crate::GetSize::get_heap_size_with_tracker(b, tracker),
+                }
+            }
+
+            fn get_heap_size_within(
+                &self,
+                limit: ::core::primitive::usize,
+            ) -> ::core::option::Option<::core::primitive::usize> {
+                match self {
+                    $($name)::+::$first(a) => $crate::GetSize::get_heap_size_within(a, limit),
+                    $($name)::+::$second(b) => $crate::GetSize::get_heap_size_within(b, limit),
+                }
+            }
+        }
+    }
+}
+
+/// Implements [`GetSize`](crate::GetSize) for wrapper newtypes, by forwarding all measurements
+/// to the wrapped value they expose through [`AsRef`].
+///
+/// Each argument consists of the path of a wrapper, followed by `=>` and the type of the wrapped
+/// value. Any number of wrappers can be listed at once, separated by commas. The wrappers must
+/// implement `AsRef` for the wrapped type and must not be generic. The wrapped value is expected
+/// to be stored inline, so its stack size is already part of the stack size of the wrapper.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSize;
+///
+/// pub struct UserName(String);
+///
+/// impl AsRef<String> for UserName {
+///     fn as_ref(&self) -> &String {
+///         &self.0
+///     }
+/// }
+///
+/// pub struct Tags(Vec<u32>);
+///
+/// impl AsRef<Vec<u32>> for Tags {
+///     fn as_ref(&self) -> &Vec<u32> {
+///         &self.0
+///     }
+/// }
+///
+/// get_size::impl_size_via_inner!(UserName => String, Tags => Vec<u32>);
+///
+/// assert_eq!(UserName(String::from("Hello")).get_heap_size(), 5);
+/// assert_eq!(Tags(vec![1, 2, 3]).get_heap_size(), 12);
+/// ```
+#[macro_export]
+macro_rules! impl_size_via_inner {
+    ($($($name:ident)::+ => $inner:ty),+ $(,)?) => {
+        $(
+            impl $crate::GetSize for $($name)::+ {
+                const CAN_ALLOCATE_HEAP: ::core::primitive::bool = <$inner as $crate::GetSize>::CAN_ALLOCATE_HEAP;
+
+                fn get_heap_size(&self) -> ::core::primitive::usize {
+                    // The wrappers stack size already accounts for the inner values stack size.
+                    $crate::GetSize::get_heap_size(::core::convert::AsRef::<$inner>::as_ref(self))
+                }
+
+                fn get_heap_size_with_tracker<TR: $crate::GetSizeTracker>(
+                    &self,
+                    tracker: TR,
+                ) -> (::core::primitive::usize, TR) {
+                    $crate::GetSize::get_heap_size_with_tracker(::core::convert::AsRef::<$inner>::as_ref(self), tracker)
+                }
+
+                fn get_heap_size_within(
+                    &self,
+                    limit: ::core::primitive::usize,
+                ) -> ::core::option::Option<::core::primitive::usize> {
+                    $crate::GetSize::get_heap_size_within(::core::convert::AsRef::<$inner>::as_ref(self), limit)
+                }
+
+                fn wasted_capacity(&self) -> ::core::primitive::usize {
+                    $crate::GetSize::wasted_capacity(::core::convert::AsRef::<$inner>::as_ref(self))
+                }
+
+                fn report_wasted_capacity(&self, path: &::core::primitive::str, report: &mut $crate::CapacityReport) {
+                    $crate::GetSize::report_wasted_capacity(::core::convert::AsRef::<$inner>::as_ref(self), path, report)
+                }
+
+                fn report_size(&self, path: &::core::primitive::str, report: &mut $crate::SizeReport) {
+                    $crate::GetSize::report_size(::core::convert::AsRef::<$inner>::as_ref(self), path, report)
+                }
+
+                fn record_allocations(&self, path: &::core::primitive::str, finder: &mut $crate::AllocationFinder) {
+                    $crate::GetSize::record_allocations(::core::convert::AsRef::<$inner>::as_ref(self), path, finder)
+                }
+
+                fn record_payloads(&self, estimator: &mut $crate::DedupEstimator) {
+                    $crate::GetSize::record_payloads(::core::convert::AsRef::<$inner>::as_ref(self), estimator)
+                }
+
+                fn borrowed_size(&self) -> ::core::primitive::usize {
+                    $crate::GetSize::borrowed_size(::core::convert::AsRef::<$inner>::as_ref(self))
+                }
+            }
+        )+
+    }
+}
+
+/// Implements [`GetSize`](crate::GetSize) for opaque types, which never own any heap memory known
+/// to Rust, like wrappers of FFI handles and raw pointers.
+///
+/// Any number of types can be listed at once, separated by commas. Generic types are supported
+/// one at a time, by listing the parameters of the implementation in brackets, followed by the
+/// type, e.g. `impl_size_opaque!([T] Handle<T>)`. Memory behind the handles, which is owned by
+/// a C library or the OS, is not accounted for. If it matters, the `extra` attribute of the
+/// derive macro can account for it on the struct holding the handle instead.
+///
+/// # Example
+///
+/// ```rust
+/// use std::ffi::c_void;
+/// use get_size::GetSize;
+///
+/// pub struct Context(*mut c_void);
+///
+/// pub struct Window(*mut c_void);
+///
+/// pub struct Shader<T>(*mut c_void, std::marker::PhantomData<T>);
+///
+/// get_size::impl_size_opaque!(Context, Window);
+/// get_size::impl_size_opaque!([T] Shader<T>);
+///
+/// #[derive(GetSize)]
+/// pub struct Renderer {
+///     context: Context,
+///     windows: Vec<Window>,
+///     shader: Shader<f32>,
+/// }
+///
+/// let renderer = Renderer {
+///     context: Context(std::ptr::null_mut()),
+///     windows: Vec::with_capacity(2),
+///     shader: Shader(std::ptr::null_mut(), std::marker::PhantomData),
+/// };
+///
+/// assert_eq!(renderer.get_heap_size(), 2 * std::mem::size_of::<Window>());
+/// ```
+#[macro_export]
+macro_rules! impl_size_opaque {
+    ([$($params:tt)*] $type:ty) => {
+        impl<$($params)*> $crate::GetSize for $type {
+            const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+        }
+    };
+    ($($type:ty),+ $(,)?) => {
+        $(
+            impl $crate::GetSize for $type {
+                const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+            }
+        )+
+    };
+}
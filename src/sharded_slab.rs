@@ -0,0 +1,31 @@
+use ::sharded_slab::{Config, Slab};
+
+use crate::GetSize;
+
+
+
+/// Determines the heap size of the values stored inside a [`Slab`], including their own
+/// stack size, as the slab stores them on the heap.
+///
+/// A [`Slab`] does not expose the pages it allocates, nor allows iterating over its values
+/// through a shared reference, so it can not implement [`GetSize`] itself. The result is a lower
+/// bound, which does not include the unused slots and bookkeeping of the slab.
+///
+/// # Example
+///
+/// ```rust
+/// use sharded_slab::Slab;
+/// use get_size::slab_heap_size;
+///
+/// let mut slab = Slab::new();
+/// slab.insert(String::from("Hello")).unwrap();
+///
+/// assert_eq!(slab_heap_size(&mut slab), std::mem::size_of::<String>() + 5);
+/// ```
+pub fn slab_heap_size<T, C>(slab: &mut Slab<T, C>) -> usize
+where
+    T: GetSize,
+    C: Config,
+{
+    slab.unique_iter().map(GetSize::get_size).sum()
+}
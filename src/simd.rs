@@ -0,0 +1,13 @@
+use std::simd::{Simd, SimdElement, Mask, MaskElement};
+
+use crate::GetSize;
+
+
+
+impl<T, const N: usize> GetSize for Simd<T, N> where T: SimdElement {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl<T, const N: usize> GetSize for Mask<T, N> where T: MaskElement {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
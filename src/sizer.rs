@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+
+use crate::{
+    AllocatorModel, GetSize, GetSizeTracker, ProgressHook, ProgressTracker, SizeError,
+    SizingContext, SizingPolicies, StandardTracker, StringPolicy, WeakPolicy,
+};
+
+
+
+/// A single policy which can be applied to the [`SizingPolicies`] of a [`Sizer`] by
+/// [`with_policy`](Sizer::with_policy).
+///
+/// This is implemented for [`WeakPolicy`], [`StringPolicy`] and [`AllocatorModel`].
+pub trait SizingPolicy {
+    /// Replaces the corresponding policy of `policies` with this one.
+    fn apply(self, policies: &mut SizingPolicies);
+}
+
+impl SizingPolicy for WeakPolicy {
+    fn apply(self, policies: &mut SizingPolicies) {
+        policies.set_weak(self);
+    }
+}
+
+impl SizingPolicy for StringPolicy {
+    fn apply(self, policies: &mut SizingPolicies) {
+        policies.set_strings(self);
+    }
+}
+
+impl SizingPolicy for AllocatorModel {
+    fn apply(self, policies: &mut SizingPolicies) {
+        policies.set_allocator(self);
+    }
+}
+
+
+
+/// A builder bundling all options of a measurement into a single entry point.
+///
+/// The tracker, the budget, the [`SizingPolicies`] and a [`ProgressHook`] get chosen by the
+/// `with_*` methods, after which [`measure`](Self::measure) determines the size of any number of
+/// objects, just like a [`SizingContext`], which does the actual work. All objects measured by the
+/// same sizer share the same tracker, so shared ownership objects are only accounted for once.
+///
+/// The methods of [`GetSize`] remain available for implementing the trait and for measuring
+/// without any options.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{SizeError, Sizer, StringPolicy, WeakPolicy};
+///
+/// let mut value = String::with_capacity(16);
+/// value.push_str("Hello");
+///
+/// let mut sizer = Sizer::new()
+///     .with_policy(StringPolicy::Len)
+///     .with_policy(WeakPolicy::IgnoreWeak)
+///     .with_budget(100);
+///
+/// assert_eq!(sizer.measure(&value), Ok(std::mem::size_of::<String>() + 5));
+/// assert_eq!(sizer.measure(&vec![0u8; 100]), Err(SizeError::BudgetExceeded));
+/// ```
+#[derive(Debug, Default)]
+pub struct Sizer<T = StandardTracker> {
+    context: SizingContext<T>,
+}
+
+impl Sizer<StandardTracker> {
+    /// Creates a new sizer using a [`StandardTracker`], the default policies and no budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: GetSizeTracker> Sizer<T> {
+    /// Replaces the tracker, keeping all other options.
+    pub fn with_tracker<U: GetSizeTracker>(self, tracker: U) -> Sizer<U> {
+        self.map_tracker(|_| tracker)
+    }
+
+    /// Invokes the `hook` with the progress made every `interval` collection elements, allowing
+    /// it to cancel the measurement. See [`ProgressTracker`].
+    pub fn with_progress<H: ProgressHook>(self, hook: H, interval: usize) -> Sizer<ProgressTracker<H, T>> {
+        self.map_tracker(|tracker| {
+            let mut tracker = ProgressTracker::with_tracker(tracker, hook);
+            tracker.set_interval(interval);
+            tracker
+        })
+    }
+
+    /// Names the measurement, e.g. to tell apart the contexts of multiple subsystems.
+    pub fn with_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.context.set_name(Some(name.into()));
+        self
+    }
+
+    /// Limits the total number of bytes all measurements may add up to.
+    pub fn with_budget(mut self, bytes: usize) -> Self {
+        self.context.set_budget(Some(bytes));
+        self
+    }
+
+    /// Replaces all policies.
+    pub fn with_policies(mut self, policies: SizingPolicies) -> Self {
+        self.context.set_policies(policies);
+        self
+    }
+
+    /// Replaces a single policy, keeping all others.
+    pub fn with_policy<P: SizingPolicy>(mut self, policy: P) -> Self {
+        let mut policies = self.context.policies();
+        policy.apply(&mut policies);
+
+        self.context.set_policies(policies);
+        self
+    }
+
+    /// Changes whether the data referenced by borrowed slices and strings gets accounted for.
+    pub fn with_count_borrowed(self, count_borrowed: bool) -> Self {
+        let mut policies = self.context.policies();
+        policies.set_count_borrowed(count_borrowed);
+
+        self.with_policies(policies)
+    }
+
+    /// Determines the total size of `value`, see [`SizingContext::measure`].
+    pub fn measure<V: GetSize>(&mut self, value: &V) -> Result<usize, SizeError> {
+        self.context.measure(value)
+    }
+
+    /// Returns the total number of bytes measured so far.
+    pub fn used(&self) -> usize {
+        self.context.used()
+    }
+
+    /// Returns the number of bytes left within the budget, if any.
+    pub fn remaining(&self) -> Option<usize> {
+        self.context.remaining()
+    }
+
+    /// Returns the context doing the measurements.
+    pub fn context(&self) -> &SizingContext<T> {
+        &self.context
+    }
+
+    /// Consumes the sizer, returning the context doing the measurements.
+    pub fn into_context(self) -> SizingContext<T> {
+        self.context
+    }
+
+    // Replaces the tracker by the one returned by `f`, keeping all other options.
+    fn map_tracker<U: GetSizeTracker>(self, f: impl FnOnce(T) -> U) -> Sizer<U> {
+        let policies = self.context.policies();
+        let budget = self.context.budget();
+        let name = self.context.name().map(|name| Cow::Owned(name.to_string()));
+
+        let mut context = SizingContext::with_tracker(f(self.context.into_tracker()));
+        context.set_name(name);
+        context.set_policies(policies);
+        context.set_budget(budget);
+
+        Sizer { context }
+    }
+}
@@ -0,0 +1,20 @@
+use ::sled::IVec;
+
+use crate::GetSize;
+
+
+
+// The longest value an `IVec` stores inline, next to its length and the variant tag.
+const INLINE_LEN: usize = std::mem::size_of::<IVec>() - 2;
+
+/// Short values are stored inline, while longer ones live inside a shared buffer. Only the
+/// bytes viewed by this handle are accounted for, so handles sharing the same buffer, including
+/// subslices of it, are accounted for separately.
+impl GetSize for IVec {
+    fn get_heap_size(&self) -> usize {
+        match self.len() {
+            len if len>INLINE_LEN => len,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use ::socket2::{Domain, Protocol, SockAddr, Socket, Type};
+
+use crate::GetSize;
+
+
+
+// A socket only holds the OS handle, while its buffers are owned by the OS.
+impl GetSize for Socket { const CAN_ALLOCATE_HEAP: bool = false; }
+
+// Socket addresses are stored inline inside a `sockaddr_storage`.
+impl GetSize for SockAddr { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Domain { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Protocol { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for Type { const CAN_ALLOCATE_HEAP: bool = false; }
@@ -0,0 +1,18 @@
+use ::sqlx::{Database, Pool};
+use ::sqlx::types::Json;
+
+use crate::GetSize;
+
+
+
+impl<T> GetSize for Json<T> where T: GetSize {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.0)
+    }
+}
+
+/// A pool is only a handle to the shared connections, which are managed by the database
+/// driver and thus not accounted for.
+impl<DB> GetSize for Pool<DB> where DB: Database {}
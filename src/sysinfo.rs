@@ -0,0 +1,39 @@
+use ::sysinfo::{ProcessesToUpdate, System};
+
+use crate::{SizeReport, SystemContext};
+
+
+
+#[cfg_attr(docsrs, doc(cfg(feature = "sysinfo")))]
+impl SystemContext {
+    /// Captures the current memory usage of this process and the system.
+    ///
+    /// Returns `None` if the memory usage of this process could not be determined.
+    pub fn capture() -> Option<Self> {
+        let pid = ::sysinfo::get_current_pid().ok()?;
+
+        let mut system = System::new();
+        system.refresh_memory();
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+        let process = system.process(pid)?;
+
+        Some(Self {
+            process_rss: process.memory() as usize,
+            process_virtual: process.virtual_memory() as usize,
+            system_total: system.total_memory() as usize,
+            system_used: system.used_memory() as usize,
+        })
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "sysinfo")))]
+impl SizeReport {
+    /// Attaches the current memory usage of this process and the system, as captured by
+    /// [`SystemContext::capture`], to this report.
+    pub fn with_system_context(mut self) -> Self {
+        self.set_system_context(SystemContext::capture());
+
+        self
+    }
+}
@@ -0,0 +1,68 @@
+use ::tantivy::schema::{Facet, FieldValue, OwnedValue};
+use ::tantivy::tokenizer::{PreTokenizedString, Token};
+use ::tantivy::{TantivyDocument, Term};
+
+use crate::GetSize;
+
+
+
+// Terms do not expose the capacity of their buffer, so only the serialized bytes are counted.
+impl GetSize for Term {
+    fn get_heap_size(&self) -> usize {
+        self.serialized_term().len()
+    }
+}
+
+// Facets store their path encoded inside a single string, of which only the length is exposed.
+impl GetSize for Facet {
+    fn get_heap_size(&self) -> usize {
+        self.encoded_str().len()
+    }
+}
+
+impl GetSize for Token {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.text)
+    }
+}
+
+impl GetSize for PreTokenizedString {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.text) + GetSize::get_heap_size(&self.tokens)
+    }
+}
+
+impl GetSize for OwnedValue {
+    fn get_heap_size(&self) -> usize {
+        match self {
+            Self::Str(value) => GetSize::get_heap_size(value),
+            Self::PreTokStr(value) => GetSize::get_heap_size(value),
+            Self::Facet(value) => GetSize::get_heap_size(value),
+            Self::Bytes(value) => GetSize::get_heap_size(value),
+            Self::Array(values) => GetSize::get_heap_size(values),
+            Self::Object(entries) => GetSize::get_heap_size(entries),
+            // All other values are stored inline.
+            _ => 0,
+        }
+    }
+}
+
+impl GetSize for FieldValue {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(self.value())
+    }
+}
+
+// Documents do not expose the capacity of their field list, so only its length is counted.
+impl GetSize for TantivyDocument {
+    fn get_heap_size(&self) -> usize {
+        let field_values = self.field_values();
+        let mut total = std::mem::size_of_val(field_values);
+
+        for field_value in field_values {
+            total += GetSize::get_heap_size(field_value);
+        }
+
+        total
+    }
+}
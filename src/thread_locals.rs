@@ -0,0 +1,262 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+
+
+// The sizes reported by all live threads, which can be read by any thread.
+static PUBLISHED: Mutex<Vec<Arc<Published>>> = Mutex::new(Vec::new());
+// Notified whenever a thread reported its sizes.
+static REPORTED: Condvar = Condvar::new();
+// The latest request made by `collect_thread_local_sizes`.
+static REQUESTED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+struct Published {
+    thread: ThreadId,
+    thread_name: Option<String>,
+    name: &'static str,
+    size: AtomicUsize,
+    generation: AtomicU64,
+}
+
+struct LocalEntry {
+    measure: Box<dyn Fn() -> usize>,
+    published: Arc<Published>,
+}
+
+// The thread-local state registered on a thread, which gets unpublished once the thread exits.
+#[derive(Default)]
+struct LocalRegistry {
+    entries: RefCell<Vec<LocalEntry>>,
+}
+
+impl Drop for LocalRegistry {
+    fn drop(&mut self) {
+        let entries = self.entries.get_mut();
+        let mut published = PUBLISHED.lock().unwrap_or_else(|err| err.into_inner());
+
+        published.retain(|p| !entries.iter().any(|entry| Arc::ptr_eq(p, &entry.published)));
+    }
+}
+
+thread_local! {
+    static LOCAL: LocalRegistry = LocalRegistry::default();
+}
+
+/// The size of some thread-local state of a single thread, as registered by
+/// [`register_thread_local_size!`](crate::register_thread_local_size).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadLocalSize {
+    /// The thread owning the state.
+    pub thread: ThreadId,
+    /// The name of the thread owning the state, if it has one.
+    pub thread_name: Option<String>,
+    /// The name the state was registered under.
+    pub name: &'static str,
+    /// The size last reported by the thread.
+    pub size: usize,
+    /// Whether the thread reported the size in response to the latest request. Sizes which are
+    /// not fresh were reported earlier and may be outdated.
+    pub fresh: bool,
+}
+
+/// Registers some thread-local state of the current thread under `name`, so it gets included
+/// by [`thread_local_sizes`] and [`collect_thread_local_sizes`]. `measure` gets called on the
+/// current thread whenever the thread reports its sizes, and once right away.
+///
+/// The registration ends once the current thread exits. Usually the
+/// [`register_thread_local_size!`](crate::register_thread_local_size) macro is used instead.
+///
+/// # Panics
+///
+/// If called from within `measure`.
+pub fn register_thread_local<F>(name: &'static str, measure: F)
+where
+    F: Fn() -> usize + 'static,
+{
+    let thread = std::thread::current();
+
+    let published = Arc::new(Published {
+        thread: thread.id(),
+        thread_name: thread.name().map(String::from),
+        name,
+        size: AtomicUsize::new(measure()),
+        generation: AtomicU64::new(REQUESTED.load(Ordering::Acquire)),
+    });
+
+    PUBLISHED.lock().unwrap_or_else(|err| err.into_inner()).push(published.clone());
+
+    LOCAL.with(|local| local.entries.borrow_mut().push(LocalEntry {
+        measure: Box::new(measure),
+        published,
+    }));
+}
+
+/// Measures all thread-local state registered on the current thread and publishes the results,
+/// which answers all requests made by [`collect_thread_local_sizes`] so far.
+///
+/// Threads owning registered state should call this regularly, e.g. once per iteration of
+/// their event loop, so requests get answered timely.
+pub fn report_thread_local_sizes() {
+    let generation = REQUESTED.load(Ordering::Acquire);
+
+    let reported = LOCAL.try_with(|local| {
+        let entries = local.entries.borrow();
+
+        for entry in entries.iter() {
+            entry.published.size.store((entry.measure)(), Ordering::Relaxed);
+            entry.published.generation.store(generation, Ordering::Release);
+        }
+
+        !entries.is_empty()
+    });
+
+    if reported == Ok(true) {
+        // Taking the lock makes sure the waiting thread is either not checking the
+        // generations right now, or already waiting for the notification.
+        drop(PUBLISHED.lock().unwrap_or_else(|err| err.into_inner()));
+        REPORTED.notify_all();
+    }
+}
+
+/// Returns the sizes of the thread-local state of all live threads, as last reported by them.
+///
+/// The state registered on the current thread gets measured right away, while other threads
+/// are not asked to report. See [`collect_thread_local_sizes`] for that.
+pub fn thread_local_sizes() -> Vec<ThreadLocalSize> {
+    report_thread_local_sizes();
+
+    snapshot(REQUESTED.load(Ordering::Acquire))
+}
+
+/// Asks all live threads owning registered thread-local state to report its size, and waits up
+/// to `timeout` for all of them to do so by calling [`report_thread_local_sizes`].
+///
+/// The sizes of threads which did not report in time are the ones they reported last, and are
+/// marked as not [fresh](ThreadLocalSize::fresh).
+///
+/// # Example
+///
+/// ```rust
+/// use std::cell::RefCell;
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::time::Duration;
+/// use get_size::{GetSize, collect_thread_local_sizes, register_thread_local_size, report_thread_local_sizes};
+///
+/// thread_local! {
+///     static SCRATCH: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+/// }
+///
+/// let stop = Arc::new(AtomicBool::new(false));
+/// let stopped = stop.clone();
+///
+/// let worker = std::thread::spawn(move || {
+///     register_thread_local_size!(SCRATCH, || SCRATCH.with(|scratch| scratch.borrow().get_heap_size()));
+///
+///     while !stopped.load(Ordering::Relaxed) {
+///         SCRATCH.with(|scratch| scratch.borrow_mut().resize(1024, 0));
+///
+///         report_thread_local_sizes();
+///         std::thread::sleep(Duration::from_millis(1));
+///     }
+/// });
+///
+/// let sizes = loop {
+///     let sizes = collect_thread_local_sizes(Duration::from_secs(10));
+///
+///     if sizes.iter().any(|size| size.fresh && size.size==1024) {
+///         break sizes;
+///     }
+/// };
+///
+/// assert_eq!(sizes[0].name, "SCRATCH");
+///
+/// stop.store(true, Ordering::Relaxed);
+/// worker.join().unwrap();
+/// ```
+pub fn collect_thread_local_sizes(timeout: Duration) -> Vec<ThreadLocalSize> {
+    let generation = REQUESTED.fetch_add(1, Ordering::AcqRel) + 1;
+    let deadline = Instant::now() + timeout;
+
+    report_thread_local_sizes();
+
+    let mut published = PUBLISHED.lock().unwrap_or_else(|err| err.into_inner());
+
+    loop {
+        let answered = published
+            .iter()
+            .all(|p| p.generation.load(Ordering::Acquire) >= generation);
+
+        let now = Instant::now();
+
+        if answered || now >= deadline {
+            break;
+        }
+
+        published = REPORTED
+            .wait_timeout(published, deadline - now)
+            .unwrap_or_else(|err| err.into_inner())
+            .0;
+    }
+
+    drop(published);
+
+    snapshot(generation)
+}
+
+// Lists the sizes last reported by all live threads.
+fn snapshot(generation: u64) -> Vec<ThreadLocalSize> {
+    let published = PUBLISHED.lock().unwrap_or_else(|err| err.into_inner());
+
+    published
+        .iter()
+        .map(|p| ThreadLocalSize {
+            thread: p.thread,
+            thread_name: p.thread_name.clone(),
+            name: p.name,
+            size: p.size.load(Ordering::Relaxed),
+            fresh: p.generation.load(Ordering::Acquire) >= generation,
+        })
+        .collect()
+}
+
+/// Registers the size of some thread-local state of the current thread, like a scratch buffer
+/// declared by [`thread_local!`], so it can be included in the accounting of the whole process.
+///
+/// The first argument is the name the state gets listed under, given either as an identifier,
+/// usually that of the thread-local, or as a string literal. The second argument is a closure
+/// measuring the state, which gets called on the current thread whenever it reports its sizes
+/// by [`report_thread_local_sizes`](crate::report_thread_local_sizes). The sizes of all threads
+/// are collected by [`collect_thread_local_sizes`](crate::collect_thread_local_sizes).
+///
+/// # Example
+///
+/// ```rust
+/// use std::cell::RefCell;
+/// use get_size::{GetSize, register_thread_local_size, thread_local_sizes};
+///
+/// thread_local! {
+///     static BUFFER: RefCell<String> = RefCell::new(String::with_capacity(64));
+/// }
+///
+/// register_thread_local_size!(BUFFER, || BUFFER.with(|buffer| buffer.borrow().get_heap_size()));
+///
+/// let current = std::thread::current().id();
+/// let sizes = thread_local_sizes();
+/// let buffer = sizes.iter().find(|size| size.thread==current && size.name=="BUFFER").unwrap();
+///
+/// assert_eq!(buffer.size, 64);
+/// ```
+#[macro_export]
+macro_rules! register_thread_local_size {
+    ($name:ident, $measure:expr $(,)?) => {
+        $crate::register_thread_local(::core::stringify!($name), $measure)
+    };
+    ($name:literal, $measure:expr $(,)?) => {
+        $crate::register_thread_local($name, $measure)
+    };
+}
@@ -0,0 +1,280 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+
+
+// The number of distinct tags which can be told apart. Allocations made under further tags
+// get accounted for as untagged.
+const SLOTS: usize = 512;
+
+// The statistics recorded for a single tag. A slot gets claimed by setting its name, which
+// never changes afterwards, so the allocator never has to allocate or lock anything itself.
+struct Slot {
+    name: AtomicPtr<u8>,
+    len: AtomicUsize,
+    bytes: AtomicUsize,
+    allocations: AtomicUsize,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            name: AtomicPtr::new(ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+        }
+    }
+
+    fn record(&self, size: usize) {
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+static SLOT_TABLE: [Slot; SLOTS] = [const { Slot::new() }; SLOTS];
+static UNTAGGED: Slot = Slot::new();
+
+thread_local! {
+    static CURRENT_TAG: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+// Returns the slot of the given tag, claiming a free one if the tag was not seen before.
+fn slot_of(tag: &'static str) -> &'static Slot {
+    let name = tag.as_ptr() as *mut u8;
+    let start = (name as usize >> 3) % SLOTS;
+
+    for offset in 0..SLOTS {
+        let slot = &SLOT_TABLE[(start + offset) % SLOTS];
+
+        match slot.name.compare_exchange(ptr::null_mut(), name, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => {
+                slot.len.store(tag.len(), Ordering::Release);
+
+                return slot;
+            }
+            Err(existing) if existing==name => return slot,
+            Err(_) => {}
+        }
+    }
+
+    &UNTAGGED
+}
+
+// Records an allocation of `size` bytes under the tag currently set on this thread.
+fn record(size: usize) {
+    // The tag is not accessible while the thread is being torn down.
+    let tag = CURRENT_TAG.try_with(Cell::get).ok().flatten();
+
+    match tag {
+        Some(tag) => slot_of(tag).record(size),
+        None => UNTAGGED.record(size),
+    }
+}
+
+/// A global allocator which records the size of every allocation, grouped by the tag set on the
+/// allocating thread by a [`TraceScope`].
+///
+/// While the trait based measurements of this crate determine how much memory a value owns,
+/// the recorded statistics show how much memory the construction of values of a type actually
+/// allocated, including memory freed again in between. All allocations are forwarded to the
+/// wrapped allocator, which is the [`System`] allocator by default.
+///
+/// Up to 512 distinct tags are told apart, allocations under further tags are accounted for as
+/// untagged. The statistics are kept without allocating or locking, so the overhead per allocation
+/// is a thread-local lookup and a few atomic operations.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{TracingAllocator, TraceScope, allocation_stats};
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TracingAllocator = TracingAllocator::system();
+///
+/// struct Document {
+///     text: String,
+/// }
+///
+/// let document = {
+///     let _scope = TraceScope::of::<Document>();
+///
+///     Document { text: String::from("Hello") }
+/// };
+///
+/// let stats = allocation_stats();
+/// let stat = stats.iter().find(|stat| stat.tag == std::any::type_name::<Document>()).unwrap();
+///
+/// assert_eq!(stat.bytes, 5);
+/// assert_eq!(stat.allocations, 1);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TracingAllocator<A = System> {
+    inner: A,
+}
+
+impl TracingAllocator<System> {
+    /// Creates a new tracing allocator wrapping the [`System`] allocator.
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> TracingAllocator<A> {
+    /// Creates a new tracing allocator forwarding all allocations to `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+        }
+    }
+
+    /// Returns a reference to the wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TracingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+
+        if !ptr.is_null() {
+            record(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+
+        if !ptr.is_null() {
+            record(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+
+        // Only growing an allocation allocates additional memory.
+        if !new_ptr.is_null() && new_size>layout.size() {
+            record(new_size - layout.size());
+        }
+
+        new_ptr
+    }
+}
+
+/// Tags all allocations made by the current thread while it is alive, so they get accounted for
+/// under that tag by the [`TracingAllocator`].
+///
+/// Scopes can be nested, in which case the innermost one applies. Dropping a scope restores the
+/// tag which was set when it got created.
+#[derive(Debug)]
+#[must_use = "allocations are only tagged while the scope is alive"]
+pub struct TraceScope {
+    previous: Option<&'static str>,
+    // The tag belongs to the current thread, so the scope must not be sent to another one.
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl TraceScope {
+    /// Tags all following allocations by the name of the type `T`, as returned by
+    /// [`type_name`](std::any::type_name).
+    pub fn of<T: ?Sized>() -> Self {
+        Self::named(std::any::type_name::<T>())
+    }
+
+    /// Tags all following allocations by the given `tag`.
+    ///
+    /// Tags are told apart by the address of the string, so the same tag should always be
+    /// given by the same string constant.
+    pub fn named(tag: &'static str) -> Self {
+        let previous = CURRENT_TAG.with(|current| current.replace(Some(tag)));
+
+        Self {
+            previous,
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the tag currently set on this thread, if any.
+    pub fn current() -> Option<&'static str> {
+        CURRENT_TAG.with(Cell::get)
+    }
+}
+
+impl Drop for TraceScope {
+    fn drop(&mut self) {
+        CURRENT_TAG.with(|current| current.set(self.previous));
+    }
+}
+
+/// The allocations recorded by the [`TracingAllocator`] under a single tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AllocationStats {
+    /// The tag the allocations were made under.
+    pub tag: &'static str,
+    /// The total number of bytes allocated, including memory which was freed again.
+    pub bytes: usize,
+    /// The number of allocations, counting every growing reallocation as well.
+    pub allocations: usize,
+}
+
+/// Returns the allocations recorded by the [`TracingAllocator`] for every tag, largest first.
+///
+/// Nothing gets recorded unless the [`TracingAllocator`] is installed as the global allocator.
+pub fn allocation_stats() -> Vec<AllocationStats> {
+    let mut stats = Vec::new();
+
+    for slot in SLOT_TABLE.iter() {
+        let name = slot.name.load(Ordering::Acquire);
+        let len = slot.len.load(Ordering::Acquire);
+
+        // The slot is either free or just being claimed.
+        if name.is_null() || len==0 {
+            continue;
+        }
+
+        // SAFETY: The name and length were taken from a `&'static str`.
+        let tag = unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(name, len)) };
+
+        stats.push(AllocationStats {
+            tag,
+            bytes: slot.bytes.load(Ordering::Relaxed),
+            allocations: slot.allocations.load(Ordering::Relaxed),
+        });
+    }
+
+    stats.sort_by_key(|stat| std::cmp::Reverse(stat.bytes));
+
+    stats
+}
+
+/// Returns the allocations recorded by the [`TracingAllocator`] while no tag was set, or under
+/// a tag which could not be told apart anymore.
+pub fn untagged_allocation_stats() -> AllocationStats {
+    AllocationStats {
+        tag: "",
+        bytes: UNTAGGED.bytes.load(Ordering::Relaxed),
+        allocations: UNTAGGED.allocations.load(Ordering::Relaxed),
+    }
+}
+
+/// Resets all statistics recorded by the [`TracingAllocator`] to zero.
+///
+/// The tags seen so far stay known, so their statistics get listed with zero bytes.
+pub fn reset_allocation_stats() {
+    for slot in SLOT_TABLE.iter().chain(std::iter::once(&UNTAGGED)) {
+        slot.bytes.store(0, Ordering::Relaxed);
+        slot.allocations.store(0, Ordering::Relaxed);
+    }
+}
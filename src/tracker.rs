@@ -1,7 +1,34 @@
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex, RwLock};
 
+use crate::{Borrowed, GetSize, SizingPolicies, TraversalContext};
+
+
+
+/// Determines how weak references, like [`std::rc::Weak`] and [`std::sync::Weak`], are accounted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WeakPolicy {
+    /// Weak references are ignored, neither the referenced object nor the allocation it lives
+    /// in are accounted for.
+    ///
+    /// The pointer itself still counts towards the stack size of the object holding it, since
+    /// stack sizes are determined by the type alone and can not depend on the tracker.
+    IgnoreWeak,
+    /// Weak references wrapped into a [`TrackedWeak`](crate::TrackedWeak) are upgraded and the
+    /// referenced object gets accounted for if it is still alive and has not yet been seen by
+    /// the tracker. For all other weak references only the pointer itself is accounted for, as
+    /// upgrading them would require the referenced type to implement [`GetSize`].
+    ///
+    /// Note that upgrading temporarily modifies the reference counts.
+    CountIfUnvisited,
+    /// Only the pointer itself is accounted for, the referenced object is never visited.
+    #[default]
+    CountPointerOnly,
+}
+
 
 
 /// A tracker which makes sure that shared ownership objects are only accounted for once.
@@ -20,10 +47,98 @@ pub trait GetSizeTracker {
         addr: *const B,
         strong_ref: A,
     ) -> bool;
+
+    /// Returns the number of objects currently remembered by this tracker.
+    ///
+    /// This allows to monitor the overhead of a measurement, since a tracker has to
+    /// remember every shared object it has seen. The default implementation returns 0,
+    /// which is suitable for trackers which do not remember anything.
+    fn visited_count(&self) -> usize {
+        0
+    }
+
+    /// Returns how many times [`track`](Self::track) returned `false` so far, i.e. how many
+    /// references to already seen shared objects did not get accounted for again.
+    ///
+    /// Together with the [`visited_count`](Self::visited_count), this tells how much sharing
+    /// is going on, e.g. to decide whether interning more values would pay off. The default
+    /// implementation returns 0, which is suitable for trackers which do not count hits.
+    fn hit_count(&self) -> usize {
+        0
+    }
+
+    /// Calls `f` with the [`TraversalContext`] of the current measurement, returning its result.
+    ///
+    /// The context holds everything besides the tracked objects which is shared by all objects
+    /// visited by a measurement, like the [`SizingPolicies`] to apply, the memoized sizes, the
+    /// allocations found, the progress made and whether the measurement got cancelled. Trackers
+    /// wrapping another tracker adjust the context of the wrapped tracker for the duration of `f`.
+    ///
+    /// The default implementation hands out a fresh default context on every call, which is
+    /// suitable for trackers which do not care about any of this.
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        f(&mut TraversalContext::default())
+    }
 }
 
 
-impl<T: GetSizeTracker> GetSizeTracker for &mut T {
+
+/// The identity under which a tracker indexes a tracked object.
+///
+/// Besides the address of the pointed to object it also contains the type of the strong
+/// reference, so different objects which happen to be located at the same address (like
+/// a struct and its first field, or zero sized types) are not confused with each other.
+///
+/// Shared ownership objects like [`Rc`] and [`Arc`] are an exception, as clones which went
+/// through an unsizing coercion, like an `Arc<[u8; 4]>` and an `Arc<[u8]>`, point to the same
+/// allocation while being of different types. They are identified by the address of the shared
+/// value together with a type common to all of them instead, as returned by [`of_rc`](Self::of_rc)
+/// and [`of_arc`](Self::of_arc). The coercion keeps the address of the value, so all clones get
+/// the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TrackKey {
+    /// The address of the pointed to object.
+    pub addr: usize,
+    /// The type of the strong reference pointing to the object.
+    pub type_id: TypeId,
+}
+
+impl TrackKey {
+    /// Creates the key for a strong reference of type `A`, pointing to an object located at `addr`.
+    pub fn new<A: Any + 'static, B>(addr: *const B) -> Self {
+        Self {
+            addr: addr as usize,
+            type_id: TypeId::of::<A>(),
+        }
+    }
+
+    /// Creates the key under which the allocation pointed to by the given [`Rc`] is tracked.
+    pub fn of_rc<T: ?Sized>(rc: &Rc<T>) -> Self {
+        Self::new::<SharedAllocation, u8>(Rc::as_ptr(rc) as *const u8)
+    }
+
+    /// Creates the key under which the allocation pointed to by the given [`Arc`] is tracked.
+    pub fn of_arc<T: ?Sized>(arc: &Arc<T>) -> Self {
+        Self::new::<SharedAllocation, u8>(Arc::as_ptr(arc) as *const u8)
+    }
+}
+
+// The strong reference kept by a tracker for every tracked `Rc` and `Arc`, which keeps the
+// allocation alive so its address does not get reused. All of them share this type, so clones
+// of different types pointing to the same allocation get the same `TrackKey`.
+#[allow(dead_code)]
+pub(crate) enum SharedAllocation {
+    Rc(Rc<dyn Any>),
+    Arc(Arc<dyn Any>),
+    // Dynamically sized objects, like an `Rc<str>`, can not be coerced to `dyn Any`.
+    Unsized(Box<dyn Any>),
+}
+
+
+// Trackers can be passed along owned, borrowed or boxed alike, so code generic over the tracker
+// works with all of them. Note that the trait can not be used as a trait object, as `track` is
+// generic, so `T` is only unsized for custom unsized tracker types.
+impl<T: GetSizeTracker + ?Sized> GetSizeTracker for &mut T {
     fn track<A: Any + 'static, B>(
         &mut self,
         addr: *const B,
@@ -31,9 +146,21 @@ impl<T: GetSizeTracker> GetSizeTracker for &mut T {
     ) -> bool {
         GetSizeTracker::track(*self, addr, strong_ref)
     }
+
+    fn visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(&**self)
+    }
+
+    fn hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(&**self)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        GetSizeTracker::context(*self, f)
+    }
 }
 
-impl<T: GetSizeTracker> GetSizeTracker for Box<T> {
+impl<T: GetSizeTracker + ?Sized> GetSizeTracker for Box<T> {
     fn track<A: Any + 'static, B>(
         &mut self,
         addr: *const B,
@@ -41,6 +168,18 @@ impl<T: GetSizeTracker> GetSizeTracker for Box<T> {
     ) -> bool {
         GetSizeTracker::track(&mut **self, addr, strong_ref)
     }
+
+    fn visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(&**self)
+    }
+
+    fn hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(&**self)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        GetSizeTracker::context(&mut **self, f)
+    }
 }
 
 impl<T: GetSizeTracker> GetSizeTracker for Mutex<T> {
@@ -49,9 +188,27 @@ impl<T: GetSizeTracker> GetSizeTracker for Mutex<T> {
         addr: *const B,
         strong_ref: A,
     ) -> bool {
-        let mut tracker = self.lock().unwrap();
+        let tracker = self.get_mut().unwrap();
 
-        GetSizeTracker::track(&mut *tracker, addr, strong_ref)
+        GetSizeTracker::track(tracker, addr, strong_ref)
+    }
+
+    fn visited_count(&self) -> usize {
+        let tracker = self.lock().unwrap();
+
+        GetSizeTracker::visited_count(&*tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        let tracker = self.lock().unwrap();
+
+        GetSizeTracker::hit_count(&*tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let tracker = self.get_mut().unwrap();
+
+        GetSizeTracker::context(tracker, f)
     }
 }
 
@@ -61,9 +218,27 @@ impl<T: GetSizeTracker> GetSizeTracker for RwLock<T> {
         addr: *const B,
         strong_ref: A,
     ) -> bool {
-        let mut tracker = self.write().unwrap();
+        let tracker = self.get_mut().unwrap();
 
-        GetSizeTracker::track(&mut *tracker, addr, strong_ref)
+        GetSizeTracker::track(tracker, addr, strong_ref)
+    }
+
+    fn visited_count(&self) -> usize {
+        let tracker = self.read().unwrap();
+
+        GetSizeTracker::visited_count(&*tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        let tracker = self.read().unwrap();
+
+        GetSizeTracker::hit_count(&*tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let tracker = self.get_mut().unwrap();
+
+        GetSizeTracker::context(tracker, f)
     }
 }
 
@@ -77,6 +252,24 @@ impl<T: GetSizeTracker> GetSizeTracker for Arc<Mutex<T>> {
 
         GetSizeTracker::track(&mut *tracker, addr, strong_ref)
     }
+
+    fn visited_count(&self) -> usize {
+        let tracker = self.lock().unwrap();
+
+        GetSizeTracker::visited_count(&*tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        let tracker = self.lock().unwrap();
+
+        GetSizeTracker::hit_count(&*tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let mut tracker = self.lock().unwrap();
+
+        GetSizeTracker::context(&mut *tracker, f)
+    }
 }
 
 impl<T: GetSizeTracker> GetSizeTracker for Arc<RwLock<T>> {
@@ -89,14 +282,37 @@ impl<T: GetSizeTracker> GetSizeTracker for Arc<RwLock<T>> {
 
         GetSizeTracker::track(&mut *tracker, addr, strong_ref)
     }
+
+    fn visited_count(&self) -> usize {
+        let tracker = self.read().unwrap();
+
+        GetSizeTracker::visited_count(&*tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        let tracker = self.read().unwrap();
+
+        GetSizeTracker::hit_count(&*tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let mut tracker = self.write().unwrap();
+
+        GetSizeTracker::context(&mut *tracker, f)
+    }
 }
 
 
 
 /// A simple standard tracker which can be used to track shared ownership references.
+///
+/// It counts every reference to an object seen before as a [hit](GetSizeTracker::hit_count),
+/// across all generations.
 #[derive(Debug, Default)]
 pub struct StandardTracker {
-    inner: BTreeMap<usize, Box<dyn Any + 'static>>,
+    inner: BTreeMap<TrackKey, Box<dyn Any + 'static>>,
+    context: TraversalContext<'static>,
+    hits: usize,
 }
 
 impl StandardTracker {
@@ -104,8 +320,39 @@ impl StandardTracker {
         Self::default()
     }
 
+    /// Creates a new tracker which applies the given [`WeakPolicy`] to weak references.
+    pub fn with_weak_policy(weak_policy: WeakPolicy) -> Self {
+        let mut tracker = Self::default();
+        tracker.set_weak_policy(weak_policy);
+
+        tracker
+    }
+
+    /// Creates a new tracker which applies the given `policies`.
+    pub fn with_policies(policies: SizingPolicies) -> Self {
+        let mut tracker = Self::default();
+        tracker.context.set_policies(policies);
+
+        tracker
+    }
+
+    /// Changes the [`WeakPolicy`] applied by this tracker.
+    pub fn set_weak_policy(&mut self, weak_policy: WeakPolicy) {
+        let mut policies = self.context.policies();
+        policies.set_weak(weak_policy);
+
+        self.context.set_policies(policies);
+    }
+
+    /// Forgets about all tracked objects and starts a new [generation](TraversalContext::generation).
     pub fn clear(&mut self) {
         self.inner.clear();
+        self.context.next_generation();
+    }
+
+    /// Returns `true` if the given object has already been seen by this tracker.
+    pub fn contains(&self, key: &TrackKey) -> bool {
+        self.inner.contains_key(key)
     }
 }
 
@@ -115,18 +362,260 @@ impl GetSizeTracker for StandardTracker {
         addr: *const B,
         strong_ref: A,
     ) -> bool {
-        let addr = addr as usize;
+        let key = TrackKey::new::<A, B>(addr);
 
-        if self.inner.contains_key(&addr) {
-            return false;
-        } else {
-            let strong_ref: Box<dyn Any + 'static> = Box::new(strong_ref);
+        match self.inner.entry(key) {
+            Entry::Occupied(_) => {
+                self.hits += 1;
+
+                false
+            }
+            Entry::Vacant(entry) => {
+                let strong_ref: Box<dyn Any + 'static> = Box::new(strong_ref);
+
+                entry.insert(strong_ref);
+
+                true
+            }
+        }
+    }
+
+    fn visited_count(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn hit_count(&self) -> usize {
+        self.hits
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        f(&mut self.context)
+    }
+}
+
+/// Only the strong references kept alive by the tracker are accounted for,
+/// not the objects they point to.
+impl GetSize for StandardTracker {
+    fn get_heap_size(&self) -> usize {
+        let mut total = 0;
+
+        for strong_ref in self.inner.values() {
+            // We assume that keys and values are hold inside the heap.
+            total += TrackKey::get_stack_size();
+            total += std::mem::size_of::<Box<dyn Any>>();
+            total += std::mem::size_of_val(&**strong_ref);
+        }
+
+        total
+    }
+}
+
+
+
+/// A tracker which starts out fresh for every measurement, while still allowing objects
+/// to be deliberately shared between measurements.
+///
+/// Reusing a [`StandardTracker`] across measurements keeps every tracked object alive until
+/// it gets cleared, and clearing it in between measurements is easily forgotten. This tracker
+/// instead forgets all objects seen by [`measure`](Self::measure) once the measurement is done,
+/// so the object is accounted for in full every time. Only objects seen by
+/// [`measure_shared`](Self::measure_shared) are remembered, and are thus never accounted for
+/// again by later measurements.
+///
+/// Since remembered objects are kept alive by the tracker, their addresses can not get reused
+/// by new allocations. Each measurement starts a new [generation](TraversalContext::generation).
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use get_size::{GetSize, ScopedTracker};
+///
+/// #[derive(GetSize)]
+/// struct Entry {
+///     name: Arc<String>,
+/// }
+///
+/// let shared = Arc::new(String::from("Hello"));
+/// let first = Entry { name: shared.clone() };
+/// let second = Entry { name: shared.clone() };
+///
+/// let mut tracker = ScopedTracker::new();
+///
+/// // Both measurements account for the shared string.
+/// assert_eq!(tracker.measure(&first), tracker.measure(&second));
+///
+/// // Once remembered as shared, it does no longer get accounted for.
+/// let with_string = tracker.measure_shared(&first);
+/// assert_eq!(tracker.measure(&second), with_string - shared.get_heap_size());
+/// ```
+#[derive(Debug, Default)]
+pub struct ScopedTracker {
+    shared: StandardTracker,
+    scope: StandardTracker,
+    sharing: bool,
+    hits: usize,
+}
+
+impl ScopedTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new tracker which applies the given [`WeakPolicy`] to weak references.
+    pub fn with_weak_policy(weak_policy: WeakPolicy) -> Self {
+        Self {
+            shared: StandardTracker::with_weak_policy(weak_policy),
+            scope: StandardTracker::with_weak_policy(weak_policy),
+            sharing: false,
+            hits: 0,
+        }
+    }
+
+    /// Determines the total size of `value`, ignoring only those objects which have
+    /// been remembered as shared by previous calls to [`measure_shared`](Self::measure_shared).
+    pub fn measure<T: GetSize>(&mut self, value: &T) -> usize {
+        self.measure_with(value, false)
+    }
+
+    /// Like [`measure`](Self::measure), but remembers all objects seen as shared,
+    /// so they get ignored by all following measurements.
+    pub fn measure_shared<T: GetSize>(&mut self, value: &T) -> usize {
+        self.measure_with(value, true)
+    }
 
-            self.inner.insert(addr, strong_ref);
+    /// Forgets about all objects remembered as shared.
+    pub fn clear(&mut self) {
+        self.shared.clear();
+        self.scope.clear();
+    }
+
+    /// Pushes a new [`TrackerScope`], within which objects are deduplicated across all
+    /// measurements, until the scope gets popped again.
+    ///
+    /// Objects remembered as shared are still ignored inside the scope, while objects only
+    /// seen inside the scope are forgotten once it gets popped. This allows attributing memory
+    /// to e.g. individual tenants, without double counting a common set of interned objects.
+    pub fn push_scope(&mut self) -> TrackerScope<'_> {
+        self.scope.clear();
 
-            return true;
+        TrackerScope {
+            tracker: self,
+            total: 0,
         }
     }
+
+    fn measure_with<T: GetSize>(&mut self, value: &T, sharing: bool) -> usize {
+        self.sharing = sharing;
+
+        let (total, tracker) = GetSize::get_size_with_tracker(value, &mut *self);
+
+        tracker.sharing = false;
+        tracker.scope.clear();
+
+        total
+    }
+}
+
+impl GetSizeTracker for ScopedTracker {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        let tracked = if self.shared.contains(&TrackKey::new::<A, B>(addr)) {
+            false
+        } else if self.sharing {
+            self.shared.track(addr, strong_ref)
+        } else {
+            self.scope.track(addr, strong_ref)
+        };
+
+        if !tracked {
+            self.hits += 1;
+        }
+
+        tracked
+    }
+
+    fn visited_count(&self) -> usize {
+        self.shared.visited_count() + self.scope.visited_count()
+    }
+
+    fn hit_count(&self) -> usize {
+        self.hits
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        self.scope.context(f)
+    }
+}
+
+impl GetSize for ScopedTracker {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.shared) + GetSize::get_heap_size(&self.scope)
+    }
+}
+
+/// A scope pushed onto a [`ScopedTracker`] by [`push_scope`](ScopedTracker::push_scope).
+///
+/// All measurements done through the scope share their deduplication, so an object reachable
+/// from several measured values is accounted for only once. Objects remembered as shared by
+/// the tracker are ignored entirely. The scope gets popped when dropped, or explicitly by
+/// [`pop`](Self::pop), after which the objects only seen inside the scope are forgotten.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use get_size::{GetSize, ScopedTracker};
+///
+/// let interned = Arc::new(String::from("interned"));
+/// let first = (Arc::new(vec![1u8; 16]), interned.clone());
+///
+/// let mut tracker = ScopedTracker::new();
+/// tracker.measure_shared(&interned);
+///
+/// let mut tenant = tracker.push_scope();
+/// let once = tenant.measure(&vec![first.0.clone(), first.0.clone()]);
+/// let again = tenant.measure(&first.0);
+///
+/// assert_eq!(again, <Arc<Vec<u8>> as GetSize>::get_stack_size());
+/// assert_eq!(tenant.pop(), once + again);
+/// ```
+#[derive(Debug)]
+pub struct TrackerScope<'a> {
+    tracker: &'a mut ScopedTracker,
+    total: usize,
+}
+
+impl TrackerScope<'_> {
+    /// Determines the size of `value`, ignoring all objects already seen inside this scope
+    /// or remembered as shared by the tracker.
+    pub fn measure<T: GetSize>(&mut self, value: &T) -> usize {
+        self.tracker.sharing = false;
+
+        let (size, _) = GetSize::get_size_with_tracker(value, &mut *self.tracker);
+        self.total += size;
+
+        size
+    }
+
+    /// Returns the sum of all measurements done inside this scope so far.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Pops this scope, returning the sum of all measurements done inside of it.
+    pub fn pop(self) -> usize {
+        self.total
+    }
+}
+
+impl Drop for TrackerScope<'_> {
+    fn drop(&mut self) {
+        self.tracker.scope.clear();
+    }
 }
 
 
@@ -164,3 +653,224 @@ impl GetSizeTracker for NoTracker {
         self.answer
     }
 }
+
+impl GetSize for NoTracker {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+
+
+/// A tracker which only lets the measured object account for its own allocations, without
+/// descending any further.
+///
+/// It sets the [`shallow`](SizingPolicies::shallow) policy, so collections only account for
+/// their buffer, without measuring the heap part of their elements. All other calls are
+/// forwarded to the wrapped tracker, by default a [`StandardTracker`], so shared ownership
+/// objects are still only accounted for once. Memoized sizes are ignored, as they include the
+/// contents of the shared objects.
+///
+/// The derive macro uses it for fields annotated with `#[get_size(skip_recursion)]`.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, ShallowTracker};
+///
+/// let value = vec![String::from("Hello"); 4];
+///
+/// let (shallow, _) = value.get_heap_size_with_tracker(ShallowTracker::new());
+/// assert_eq!(shallow, 4 * std::mem::size_of::<String>());
+/// assert_eq!(value.get_heap_size(), shallow + 4 * 5);
+/// ```
+#[derive(Debug, Default)]
+pub struct ShallowTracker<T = StandardTracker> {
+    tracker: T,
+}
+
+impl ShallowTracker<StandardTracker> {
+    /// Creates a new shallow tracker wrapping a [`StandardTracker`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: GetSizeTracker> ShallowTracker<T> {
+    /// Creates a new shallow tracker wrapping the given `tracker`.
+    pub fn with_tracker(tracker: T) -> Self {
+        Self {
+            tracker,
+        }
+    }
+
+    /// Returns a reference to the wrapped tracker.
+    pub fn tracker(&self) -> &T {
+        &self.tracker
+    }
+
+    /// Consumes this tracker, returning the wrapped tracker.
+    pub fn into_tracker(self) -> T {
+        self.tracker
+    }
+}
+
+impl<T: GetSizeTracker> GetSizeTracker for ShallowTracker<T> {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        GetSizeTracker::track(&mut self.tracker, addr, strong_ref)
+    }
+
+    fn visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(&self.tracker)
+    }
+
+    fn hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(&self.tracker)
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        GetSizeTracker::context(&mut self.tracker, |context| {
+            let mut shallow = context.with_memo(None);
+            shallow.set_policies(SizingPolicies {
+                shallow: true,
+                ..context.policies()
+            });
+
+            let policies = context.policies();
+            let result = f(&mut shallow);
+
+            context.update(&shallow);
+            context.set_policies(policies);
+
+            result
+        })
+    }
+}
+
+
+
+// Object safe counterpart of `GetSizeTracker`, implemented for every tracker. Since `track` can
+// not be generic here, the strong references used by this crate get their own methods, so they
+// are still tracked under the same `TrackKey` as when passed to the tracker directly.
+trait ErasedTracker {
+    fn track_shared(&mut self, addr: *const u8, strong_ref: SharedAllocation) -> bool;
+    fn track_borrowed(&mut self, addr: *const u8) -> bool;
+    fn track_any(&mut self, addr: *const u8, strong_ref: Box<dyn Any>) -> bool;
+    fn erased_visited_count(&self) -> usize;
+    fn erased_hit_count(&self) -> usize;
+    fn erased_context(&mut self, f: &mut dyn FnMut(&mut TraversalContext<'_>));
+}
+
+impl<T: GetSizeTracker> ErasedTracker for T {
+    fn track_shared(&mut self, addr: *const u8, strong_ref: SharedAllocation) -> bool {
+        GetSizeTracker::track(self, addr, strong_ref)
+    }
+
+    fn track_borrowed(&mut self, addr: *const u8) -> bool {
+        GetSizeTracker::track(self, addr, Borrowed)
+    }
+
+    fn track_any(&mut self, addr: *const u8, strong_ref: Box<dyn Any>) -> bool {
+        GetSizeTracker::track(self, addr, strong_ref)
+    }
+
+    fn erased_visited_count(&self) -> usize {
+        GetSizeTracker::visited_count(self)
+    }
+
+    fn erased_hit_count(&self) -> usize {
+        GetSizeTracker::hit_count(self)
+    }
+
+    fn erased_context(&mut self, f: &mut dyn FnMut(&mut TraversalContext<'_>)) {
+        GetSizeTracker::context(self, f)
+    }
+}
+
+/// A borrowed tracker of any type, which gets passed to the methods of [`GetSizeUnsized`].
+///
+/// As [`GetSizeTracker`] can not be used as a trait object, this allows unsized objects, like
+/// the contents of a `Box<dyn Trait>` or a `Box<[T]>`, to be measured with the tracker of their
+/// owner. All calls are forwarded to the borrowed tracker. Strong references of types not
+/// provided by this crate are tracked as a `Box<dyn Any>`, so they are only deduplicated
+/// against other references seen through a `DynTracker`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use get_size::{DynTracker, GetSize, GetSizeUnsized, StandardTracker};
+///
+/// let shared = Arc::new(vec![1u8; 100]);
+/// let boxed: Box<[Arc<Vec<u8>>]> = vec![Arc::clone(&shared), Arc::clone(&shared)].into_boxed_slice();
+///
+/// let mut tracker = StandardTracker::default();
+/// let size = GetSizeUnsized::get_size_of_val_with_tracker(&*boxed, &mut DynTracker::new(&mut tracker));
+///
+/// // The shared vector is only accounted for once.
+/// assert_eq!(size, 2 * std::mem::size_of::<Arc<Vec<u8>>>() + shared.get_heap_size());
+/// ```
+///
+/// [`GetSizeUnsized`]: crate::GetSizeUnsized
+pub struct DynTracker<'a> {
+    tracker: &'a mut dyn ErasedTracker,
+}
+
+impl<'a> DynTracker<'a> {
+    /// Borrows the given `tracker`.
+    pub fn new<T: GetSizeTracker>(tracker: &'a mut T) -> Self {
+        Self {
+            tracker,
+        }
+    }
+}
+
+impl std::fmt::Debug for DynTracker<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynTracker").finish_non_exhaustive()
+    }
+}
+
+impl GetSizeTracker for DynTracker<'_> {
+    fn track<A: Any + 'static, B>(
+        &mut self,
+        addr: *const B,
+        strong_ref: A,
+    ) -> bool {
+        let addr = addr as *const u8;
+        let strong_ref: Box<dyn Any> = Box::new(strong_ref);
+
+        match strong_ref.downcast::<SharedAllocation>() {
+            Ok(shared) => self.tracker.track_shared(addr, *shared),
+            Err(strong_ref) if strong_ref.is::<Borrowed>() => self.tracker.track_borrowed(addr),
+            Err(strong_ref) => self.tracker.track_any(addr, strong_ref),
+        }
+    }
+
+    fn visited_count(&self) -> usize {
+        self.tracker.erased_visited_count()
+    }
+
+    fn hit_count(&self) -> usize {
+        self.tracker.erased_hit_count()
+    }
+
+    fn context<R>(&mut self, f: impl FnOnce(&mut TraversalContext<'_>) -> R) -> R {
+        let mut f = Some(f);
+        let mut result = None;
+
+        self.tracker.erased_context(&mut |context| result = f.take().map(|f| f(context)));
+
+        result.unwrap()
+    }
+}
+
+impl GetSize for TrackKey {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+impl GetSize for WeakPolicy {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
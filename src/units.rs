@@ -0,0 +1,197 @@
+use crate::GetSize;
+
+
+
+/// Formats a number of bytes as a human readable string.
+///
+/// Reports like the [`SizeReport`](crate::SizeReport) use it for rendering their numbers, see
+/// e.g. [`SizeReport::render`](crate::SizeReport::render). It is implemented by the configurable
+/// [`UnitFormatter`], as well as by all closures taking the number of bytes and returning a [`String`].
+pub trait SizeFormatter {
+    /// Formats the given number of bytes.
+    fn format_size(&self, bytes: usize) -> String;
+}
+
+impl<F> SizeFormatter for F where F: Fn(usize) -> String {
+    fn format_size(&self, bytes: usize) -> String {
+        self(bytes)
+    }
+}
+
+/// The units used by a [`UnitFormatter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnitSystem {
+    /// Binary units based on powers of 1024, like KiB and MiB.
+    #[default]
+    Binary,
+    /// Decimal units based on powers of 1000, like kB and MB.
+    Decimal,
+    /// Plain bytes without any scaling.
+    Bytes,
+}
+
+impl UnitSystem {
+    fn scale(&self) -> Option<(f64, [&'static str; 6])> {
+        match self {
+            Self::Binary => Some((1024.0, ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"])),
+            Self::Decimal => Some((1000.0, ["kB", "MB", "GB", "TB", "PB", "EB"])),
+            Self::Bytes => None,
+        }
+    }
+}
+
+impl GetSize for UnitSystem {
+    const CAN_ALLOCATE_HEAP: bool = false;
+}
+
+/// A configurable [`SizeFormatter`], supporting binary and decimal units, a custom precision
+/// and separators.
+///
+/// Values below the first unit are always printed as whole bytes. The default uses binary units
+/// with a single decimal place and no thousands separator, just like [`format_size`](crate::format_size).
+/// With the `num-format` feature enabled, the separators can be taken from a locale by
+/// `set_locale`.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{SizeFormatter, UnitFormatter, UnitSystem};
+///
+/// let mut formatter = UnitFormatter::new();
+/// assert_eq!(formatter.format_size(1536), "1.5 KiB");
+///
+/// formatter.set_units(UnitSystem::Decimal);
+/// formatter.set_precision(2);
+/// assert_eq!(formatter.format_size(1536), "1.54 kB");
+///
+/// formatter.set_units(UnitSystem::Bytes);
+/// formatter.set_thousands_separator(",");
+/// assert_eq!(formatter.format_size(1234567), "1,234,567 B");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnitFormatter {
+    units: UnitSystem,
+    precision: usize,
+    thousands_separator: String,
+    decimal_separator: String,
+}
+
+impl Default for UnitFormatter {
+    fn default() -> Self {
+        Self {
+            units: UnitSystem::default(),
+            precision: 1,
+            thousands_separator: String::new(),
+            decimal_separator: String::from("."),
+        }
+    }
+}
+
+impl UnitFormatter {
+    /// Creates a new formatter using binary units with a single decimal place.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new formatter using the given units with a single decimal place.
+    pub fn with_units(units: UnitSystem) -> Self {
+        Self {
+            units,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the units used by this formatter.
+    pub fn units(&self) -> UnitSystem {
+        self.units
+    }
+
+    /// Changes the units used by this formatter.
+    pub fn set_units(&mut self, units: UnitSystem) {
+        self.units = units;
+    }
+
+    /// Returns the number of decimal places printed for scaled values.
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+
+    /// Changes the number of decimal places printed for scaled values.
+    pub fn set_precision(&mut self, precision: usize) {
+        self.precision = precision;
+    }
+
+    /// Returns the separator inserted between groups of thousands, which is empty by default.
+    pub fn thousands_separator(&self) -> &str {
+        &self.thousands_separator
+    }
+
+    /// Changes the separator inserted between groups of thousands.
+    pub fn set_thousands_separator(&mut self, separator: impl Into<String>) {
+        self.thousands_separator = separator.into();
+    }
+
+    /// Returns the separator put in front of the decimal places, which is `.` by default.
+    pub fn decimal_separator(&self) -> &str {
+        &self.decimal_separator
+    }
+
+    /// Changes the separator put in front of the decimal places.
+    pub fn set_decimal_separator(&mut self, separator: impl Into<String>) {
+        self.decimal_separator = separator.into();
+    }
+
+    // Inserts the thousands separator into the given string of digits.
+    fn group(&self, digits: &str) -> String {
+        if self.thousands_separator.is_empty() {
+            return digits.to_string();
+        }
+
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 * self.thousands_separator.len());
+
+        for (i, digit) in digits.chars().enumerate() {
+            if i>0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push_str(&self.thousands_separator);
+            }
+            grouped.push(digit);
+        }
+
+        grouped
+    }
+}
+
+impl SizeFormatter for UnitFormatter {
+    fn format_size(&self, bytes: usize) -> String {
+        let (base, units) = match self.units.scale() {
+            Some((base, units)) if bytes as f64 >= base => (base, units),
+            _ => return format!("{} B", self.group(&bytes.to_string())),
+        };
+
+        let mut value = bytes as f64 / base;
+        let mut unit = 0;
+
+        while value >= base && unit < units.len() - 1 {
+            value /= base;
+            unit += 1;
+        }
+
+        let value = format!("{:.*}", self.precision, value);
+
+        match value.split_once('.') {
+            Some((integer, fraction)) => format!(
+                "{}{}{} {}",
+                self.group(integer),
+                self.decimal_separator,
+                fraction,
+                units[unit],
+            ),
+            None => format!("{} {}", self.group(&value), units[unit]),
+        }
+    }
+}
+
+impl GetSize for UnitFormatter {
+    fn get_heap_size(&self) -> usize {
+        GetSize::get_heap_size(&self.thousands_separator) + GetSize::get_heap_size(&self.decimal_separator)
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::GetSize;
+
+
+
+/// Periodically measures the memory used by some objects and reacts once it exceeds a limit.
+///
+/// The measurement is done by a user provided closure, which usually calls
+/// [`get_size`](crate::GetSize::get_size) on the objects of interest. It runs on a dedicated
+/// background thread, or as a task when using [`new_async`](Self::new_async) with the `tokio`
+/// feature enabled. Whenever a measurement exceeds the limit, the `on_breach` callback gets
+/// invoked with the measured size. This happens for every such measurement, not only the first one.
+///
+/// The watchdog stops once it gets dropped or [`stop`](Self::stop) gets called.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+/// use get_size::{GetSize, SizeWatchdog};
+///
+/// let cache = Arc::new(Mutex::new(Vec::<u8>::with_capacity(2048)));
+/// let (sender, receiver) = mpsc::channel();
+///
+/// let measured = cache.clone();
+/// let watchdog = SizeWatchdog::with_callback(
+///     Duration::from_millis(10),
+///     1024,
+///     move || measured.lock().unwrap().get_heap_size(),
+///     move |size| { let _ = sender.send(size); },
+/// );
+///
+/// assert_eq!(receiver.recv().unwrap(), 2048);
+///
+/// watchdog.stop();
+/// ```
+#[derive(Debug)]
+pub struct SizeWatchdog {
+    shared: Arc<Shared>,
+    handle: Option<Handle>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    stopped: Mutex<bool>,
+    wakeup: Condvar,
+    last_size: AtomicUsize,
+}
+
+#[derive(Debug)]
+enum Handle {
+    Thread(JoinHandle<()>),
+    #[cfg(feature = "tokio")]
+    Task(tokio::task::JoinHandle<()>),
+}
+
+impl SizeWatchdog {
+    /// Starts a new watchdog, which calls `measure` every `interval` and logs a warning
+    /// to stderr whenever the result exceeds `limit` bytes.
+    pub fn new<M>(interval: Duration, limit: usize, measure: M) -> Self
+    where
+        M: FnMut() -> usize + Send + 'static,
+    {
+        Self::with_callback(interval, limit, measure, move |size| {
+            eprintln!("memory limit exceeded: {} bytes in use, limit is {} bytes", size, limit);
+        })
+    }
+
+    /// Starts a new watchdog, which calls `measure` every `interval` and invokes `on_breach`
+    /// with the measured size whenever it exceeds `limit` bytes.
+    pub fn with_callback<M, C>(
+        interval: Duration,
+        limit: usize,
+        mut measure: M,
+        mut on_breach: C,
+    ) -> Self
+    where
+        M: FnMut() -> usize + Send + 'static,
+        C: FnMut(usize) + Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+
+        let thread_shared = Arc::clone(&shared);
+        let handle = std::thread::spawn(move || {
+            loop {
+                let size = measure();
+                thread_shared.last_size.store(size, Ordering::Relaxed);
+
+                if size>limit {
+                    on_breach(size);
+                }
+
+                // Sleep until the next measurement is due, unless we get stopped earlier.
+                let stopped = thread_shared.stopped.lock().unwrap();
+                let (stopped, _) = thread_shared.wakeup
+                    .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+                    .unwrap();
+
+                if *stopped {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            shared,
+            handle: Some(Handle::Thread(handle)),
+        }
+    }
+
+    /// Starts a new watchdog as a task on the current tokio runtime, which awaits `measure`
+    /// every `interval` and invokes `on_breach` with the measured size whenever it exceeds
+    /// `limit` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub fn new_async<M, F, C>(
+        interval: Duration,
+        limit: usize,
+        mut measure: M,
+        mut on_breach: C,
+    ) -> Self
+    where
+        M: FnMut() -> F + Send + 'static,
+        F: std::future::Future<Output = usize> + Send,
+        C: FnMut(usize) + Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+
+        let task_shared = Arc::clone(&shared);
+        let handle = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+
+            loop {
+                ticks.tick().await;
+
+                let size = measure().await;
+                task_shared.last_size.store(size, Ordering::Relaxed);
+
+                if size>limit {
+                    on_breach(size);
+                }
+            }
+        });
+
+        Self {
+            shared,
+            handle: Some(Handle::Task(handle)),
+        }
+    }
+
+    /// Returns the result of the most recent measurement.
+    pub fn last_size(&self) -> usize {
+        self.shared.last_size.load(Ordering::Relaxed)
+    }
+
+    /// Stops the watchdog.
+    ///
+    /// A watchdog running on a background thread waits for a currently running measurement
+    /// to finish, while a task gets aborted right away.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        match self.handle.take() {
+            Some(Handle::Thread(handle)) => {
+                *self.shared.stopped.lock().unwrap() = true;
+                self.shared.wakeup.notify_all();
+
+                // A panic inside the user provided closures already got reported by the thread.
+                let _ = handle.join();
+            }
+            #[cfg(feature = "tokio")]
+            Some(Handle::Task(handle)) => handle.abort(),
+            None => (),
+        }
+    }
+}
+
+/// Only the state shared with the background thread or task is accounted for,
+/// not the closures executed by it.
+impl GetSize for SizeWatchdog {
+    fn get_heap_size(&self) -> usize {
+        // The reference counts are stored next to the shared state.
+        2 * std::mem::size_of::<usize>() + std::mem::size_of::<Shared>()
+    }
+}
+
+impl Drop for SizeWatchdog {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
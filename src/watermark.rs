@@ -0,0 +1,148 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::GetSize;
+
+
+
+/// A wrapper remembering the largest size its value ever had, which is what capacity planning
+/// is interested in rather than the current size.
+///
+/// The value gets measured again after every mutation done through [`modify`](Self::modify)
+/// or [`get_mut`](Self::get_mut). Mutations not visible to the wrapper, e.g. through interior
+/// mutability, are only picked up by calling [`measure`](Self::measure).
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::{GetSize, HighWatermark};
+///
+/// let mut queue = HighWatermark::new(Vec::<String>::new());
+///
+/// queue.modify(|queue| queue.push("a".repeat(100)));
+/// let peak = queue.size();
+///
+/// queue.get_mut().clear();
+/// queue.get_mut().shrink_to_fit();
+///
+/// assert_eq!(queue.size(), std::mem::size_of::<Vec<String>>());
+/// assert_eq!(queue.peak_size(), peak);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HighWatermark<T> {
+    inner: T,
+    size: usize,
+    peak: usize,
+}
+
+impl<T: GetSize> HighWatermark<T> {
+    /// Wraps the given value, using its current size as the initial peak.
+    pub fn new(inner: T) -> Self {
+        let size = GetSize::get_size(&inner);
+
+        Self {
+            inner,
+            size,
+            peak: size,
+        }
+    }
+
+    /// Measures the value again, updating the peak if it grew beyond it, and returns its size.
+    pub fn measure(&mut self) -> usize {
+        self.size = GetSize::get_size(&self.inner);
+        self.peak = self.peak.max(self.size);
+
+        self.size
+    }
+
+    /// Modifies the value by the given closure and measures it again afterwards.
+    pub fn modify<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let result = f(&mut self.inner);
+
+        self.measure();
+
+        result
+    }
+
+    /// Returns a guard giving mutable access to the value, which measures it again when dropped.
+    pub fn get_mut(&mut self) -> WatermarkGuard<'_, T> {
+        WatermarkGuard {
+            watermark: self,
+        }
+    }
+
+    /// Returns the size of the value, as of the last time it got measured.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the largest size the value had when it got measured.
+    pub fn peak_size(&self) -> usize {
+        self.peak
+    }
+
+    /// Resets the peak to the current size of the value, e.g. at the start of a new
+    /// observation period, and returns the previous peak.
+    pub fn reset_peak(&mut self) -> usize {
+        std::mem::replace(&mut self.peak, self.size)
+    }
+
+    /// Returns a reference to the wrapped value.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> Deref for HighWatermark<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: GetSize> GetSize for HighWatermark<T> {
+    const CAN_ALLOCATE_HEAP: bool = T::CAN_ALLOCATE_HEAP;
+
+    fn get_heap_size(&self) -> usize {
+        // The stack size of the value is already part of the wrappers stack size.
+        self.size - T::get_stack_size()
+    }
+}
+
+
+
+/// Gives mutable access to the value of a [`HighWatermark`], measuring it again when dropped.
+///
+/// Created by [`HighWatermark::get_mut`].
+#[derive(Debug)]
+pub struct WatermarkGuard<'a, T: GetSize> {
+    watermark: &'a mut HighWatermark<T>,
+}
+
+impl<T: GetSize> Deref for WatermarkGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.watermark.inner
+    }
+}
+
+impl<T: GetSize> DerefMut for WatermarkGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.watermark.inner
+    }
+}
+
+impl<T: GetSize> Drop for WatermarkGuard<'_, T> {
+    fn drop(&mut self) {
+        self.watermark.measure();
+    }
+}
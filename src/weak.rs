@@ -0,0 +1,76 @@
+use std::ops::{Deref, DerefMut};
+use std::rc::Weak as RcWeak;
+use std::sync::Weak as ArcWeak;
+
+use crate::{GetSize, GetSizeTracker, WeakPolicy};
+
+
+
+/// A weak reference which gets accounted for according to the [`WeakPolicy`] of the tracker.
+///
+/// The implementations of [`GetSize`] for [`std::rc::Weak`] and [`std::sync::Weak`] only ever
+/// account for the pointer itself, so they do not require the referenced type to implement
+/// [`GetSize`]. Once wrapped, [`WeakPolicy::CountIfUnvisited`] upgrades the weak reference and
+/// accounts for the referenced object, as long as it is still alive and has not yet been seen
+/// by the tracker.
+///
+/// # Example
+///
+/// ```rust
+/// use std::rc::Rc;
+/// use get_size::{GetSize, StandardTracker, TrackedWeak, WeakPolicy};
+///
+/// let strong = Rc::new(String::from("Hello"));
+/// let weak = TrackedWeak(Rc::downgrade(&strong));
+///
+/// assert_eq!(weak.get_heap_size(), 0);
+///
+/// let tracker = StandardTracker::with_weak_policy(WeakPolicy::CountIfUnvisited);
+/// let (total, _) = weak.get_heap_size_with_tracker(tracker);
+/// assert_eq!(total, std::mem::size_of::<String>() + 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrackedWeak<W>(pub W);
+
+impl<W> Deref for TrackedWeak<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.0
+    }
+}
+
+impl<W> DerefMut for TrackedWeak<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.0
+    }
+}
+
+impl<W> From<W> for TrackedWeak<W> {
+    fn from(weak: W) -> Self {
+        Self(weak)
+    }
+}
+
+macro_rules! impl_size_tracked_weak {
+    ($weak:ident) => {
+        impl<T> GetSize for TrackedWeak<$weak<T>> where T: GetSize + 'static {
+            fn get_heap_size_with_tracker<TR: GetSizeTracker>(
+                &self,
+                mut tracker: TR,
+            ) -> (usize, TR) {
+                match tracker.context(|context| context.policies().weak) {
+                    WeakPolicy::CountIfUnvisited => match self.0.upgrade() {
+                        // The upgraded reference gets tracked like any other strong reference.
+                        Some(strong_ref) => GetSize::get_heap_size_with_tracker(&strong_ref, tracker),
+                        None => (0, tracker),
+                    },
+                    WeakPolicy::IgnoreWeak | WeakPolicy::CountPointerOnly => (0, tracker),
+                }
+            }
+        }
+    }
+}
+
+impl_size_tracked_weak!(RcWeak);
+impl_size_tracked_weak!(ArcWeak);
@@ -0,0 +1,22 @@
+use ::wgpu::Buffer;
+
+use crate::{GetSize, ResourceKind, SizeReport};
+
+
+
+// The contents of a buffer live in the memory of the GPU, so they are not part of the heap size.
+fn buffer_size(buffer: &Buffer) -> usize {
+    usize::try_from(buffer.size()).unwrap_or(usize::MAX)
+}
+
+/// The contents of the buffer are not accounted for as heap memory, but reports list them
+/// separately from RAM as [`ResourceKind::Gpu`].
+impl GetSize for Buffer {
+    fn get_heap_size(&self) -> usize {
+        0
+    }
+
+    fn report_size(&self, path: &str, report: &mut SizeReport) {
+        report.add_external(path, ResourceKind::Gpu, buffer_size(self));
+    }
+}
@@ -0,0 +1,46 @@
+use ::wide::{
+    f32x4,
+    f32x8,
+    f64x2,
+    f64x4,
+    i8x16,
+    i8x32,
+    i16x8,
+    i16x16,
+    i32x4,
+    i32x8,
+    i64x2,
+    i64x4,
+    u8x16,
+    u16x8,
+    u16x16,
+    u32x4,
+    u32x8,
+    u64x2,
+    u64x4,
+};
+
+use crate::GetSize;
+
+
+
+// SIMD vectors store their lanes inline, so they never occupy any heap memory.
+impl GetSize for f32x4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for f32x8 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for f64x2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for f64x4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i8x16 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i8x32 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i16x8 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i16x16 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i32x4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i32x8 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i64x2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for i64x4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u8x16 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u16x8 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u16x16 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u32x4 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u32x8 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u64x2 { const CAN_ALLOCATE_HEAP: bool = false; }
+impl GetSize for u64x4 { const CAN_ALLOCATE_HEAP: bool = false; }
@@ -0,0 +1,45 @@
+/// Implements [`GetSize`](crate::GetSize) for the given types implementing [`zerocopy::FromBytes`].
+///
+/// Types which can be created from arbitrary bytes can not own any heap memory, so they are
+/// treated as only occupying the stack. The `FromBytes` bound gets checked at compile time.
+///
+/// A blanket implementation for all `FromBytes` types is not possible, as it would conflict
+/// with the implementations for the primitive types.
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSize;
+/// use zerocopy::{FromBytes, Immutable, KnownLayout};
+///
+/// #[derive(FromBytes, Immutable, KnownLayout)]
+/// #[repr(C)]
+/// struct Header {
+///     magic: [u8; 4],
+///     length: u32,
+/// }
+///
+/// get_size::impl_from_bytes!(Header);
+///
+/// let headers: Vec<Header> = Vec::with_capacity(4);
+///
+/// assert_eq!(headers.get_heap_size(), 4 * std::mem::size_of::<Header>());
+/// ```
+#[macro_export]
+#[cfg_attr(docsrs, doc(cfg(feature = "zerocopy")))]
+macro_rules! impl_from_bytes {
+    ($($name:ty),* $(,)?) => {
+        $(
+            // Fails to compile if the type does not implement the trait.
+            const _: fn() = {
+                fn assert_from_bytes<T: $crate::__private::zerocopy::FromBytes>() {}
+
+                assert_from_bytes::<$name>
+            };
+
+            impl $crate::GetSize for $name {
+                const CAN_ALLOCATE_HEAP: ::core::primitive::bool = false;
+            }
+        )*
+    }
+}
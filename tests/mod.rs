@@ -1,3 +1,6 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
 use get_size::*;
 
 
@@ -246,3 +249,4020 @@ fn derive_newtype() {
     let test = TestNewType(0);
     assert_eq!(u64::get_stack_size(), test.get_size());
 }
+
+
+#[test]
+fn weak_policy() {
+    let strong = std::rc::Rc::new(String::from("Hello"));
+    let weak = TrackedWeak(std::rc::Rc::downgrade(&strong));
+
+    let expected = std::mem::size_of::<String>() + 5;
+
+    // By default only the pointer itself is accounted for.
+    assert_eq!(weak.get_heap_size(), 0);
+    let (total, _) = weak.get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(total, 0);
+
+    // The pointer itself is part of the stack size, which does not depend on the tracker.
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::IgnoreWeak);
+    let (total, _) = weak.get_size_with_tracker(tracker);
+    assert_eq!(total, std::mem::size_of::<std::rc::Weak<String>>());
+
+    let nested = vec![weak.clone(), weak.clone()];
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::IgnoreWeak);
+    let (total, _) = nested.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, 2 * std::mem::size_of::<std::rc::Weak<String>>());
+
+    // Plain weak references never visit the referenced object.
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::CountIfUnvisited);
+    let (total, _) = weak.0.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, 0);
+
+    // Wrapped weak references get upgraded, also when nested inside other objects.
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::CountIfUnvisited);
+    let (total, _) = Some(weak.clone()).get_heap_size_with_tracker(tracker);
+    assert_eq!(total, expected);
+
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::CountIfUnvisited);
+    let (total, tracker) = weak.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, expected);
+
+    // The strong reference has already been seen by the tracker.
+    let (total, _) = strong.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, 0);
+
+    drop(strong);
+
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::CountIfUnvisited);
+    let (total, _) = weak.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, 0);
+
+    // Weak references to types without a `GetSize` implementation are supported as well.
+    struct Opaque;
+    let strong = std::sync::Arc::new(Opaque);
+    let weak = std::sync::Arc::downgrade(&strong);
+    assert_eq!(weak.get_size(), std::mem::size_of::<std::sync::Weak<Opaque>>());
+
+    let weak: std::rc::Weak<dyn std::fmt::Debug> = std::rc::Rc::downgrade(&(std::rc::Rc::new(1u8) as std::rc::Rc<dyn std::fmt::Debug>));
+    assert_eq!(weak.get_heap_size(), 0);
+}
+
+
+#[test]
+fn weak_policy_ignore() {
+    let strong = std::sync::Arc::new(String::from("Hello"));
+    let weak = TrackedWeak(std::sync::Arc::downgrade(&strong));
+    let nested = vec![weak.clone(), weak.clone()];
+
+    // The referenced object counts 0, even though it is still alive.
+    let tracker = StandardTracker::with_weak_policy(WeakPolicy::IgnoreWeak);
+    let (total, tracker) = weak.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, 0);
+
+    let (total, tracker) = nested.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, 2 * std::mem::size_of::<TrackedWeak<std::sync::Weak<String>>>());
+
+    // Ignored weak references do not mark the referenced object as seen.
+    let (total, _) = strong.get_heap_size_with_tracker(tracker);
+    assert_eq!(total, strong.get_heap_size());
+}
+
+
+#[test]
+fn sized_future() {
+    let value = [0u8; 128];
+
+    let future = SizedFuture::new(async move {
+        value.len()
+    });
+    assert!(future.get_heap_size() >= 128);
+
+    let boxed: std::pin::Pin<Box<dyn std::future::Future<Output = usize>>> = future.into_inner();
+    let future = SizedFuture::from(boxed);
+    assert!(future.get_heap_size() >= 128);
+
+    let poll = std::task::Poll::Ready(String::from("Hello"));
+    assert_eq!(poll.get_heap_size(), 5);
+}
+
+
+trait TestShape: GetSizeUnsized {}
+
+impl TestShape for TestStruct {}
+impl TestShape for u64 {}
+
+#[test]
+fn os_handles() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    assert_eq!(listener.get_heap_size(), 0);
+    assert_eq!(addr.get_size(), std::mem::size_of::<std::net::SocketAddr>());
+    assert_eq!(vec![addr.ip()].get_heap_size(), std::mem::size_of::<std::net::IpAddr>());
+}
+
+#[test]
+fn os_strings() {
+    let mut value = std::ffi::OsString::with_capacity(32);
+    value.push("Hello");
+    assert_eq!(value.get_heap_size(), value.capacity());
+    assert_eq!(value.wasted_capacity(), value.capacity() - 5);
+
+    let path = std::path::PathBuf::from(value.clone());
+    assert_eq!(path.get_heap_size(), path.capacity());
+
+    assert!(OS_STRING_SIZE_BEHAVIOR.iter().any(|(family, _)| *family == "unix"));
+    assert!(OS_STRING_SIZE_BEHAVIOR.iter().any(|(family, _)| *family == "windows"));
+}
+
+#[cfg(unix)]
+#[test]
+fn os_strings_unix() {
+    use std::os::unix::ffi::OsStringExt;
+
+    // Arbitrary bytes are stored as they are.
+    let mut bytes = Vec::with_capacity(16);
+    bytes.extend_from_slice(&[0xff, 0xfe, b'a']);
+    let value = std::ffi::OsString::from_vec(bytes);
+    assert_eq!(value.len(), 3);
+    assert_eq!(value.get_heap_size(), 16);
+}
+
+#[cfg(windows)]
+#[test]
+fn os_strings_windows() {
+    use std::os::windows::ffi::OsStringExt;
+
+    // Two wide characters, one of them an unpaired surrogate, take up four WTF-8 bytes.
+    let value = std::ffi::OsString::from_wide(&[0x61, 0xd800]);
+    assert_eq!(value.len(), 4);
+    assert_eq!(value.get_heap_size(), value.capacity());
+    assert!(value.get_heap_size() >= 4);
+}
+
+
+#[test]
+fn boxed_unsized() {
+    let test: Box<[String]> = vec![String::from("Hello"), String::from("world!")].into_boxed_slice();
+    assert_eq!(test.get_heap_size(), 2 * std::mem::size_of::<String>() + 5 + 6);
+
+    let test: Box<str> = "Hello".into();
+    assert_eq!(test.get_heap_size(), 5);
+
+    let test: Box<dyn TestShape> = Box::new(TestStruct {
+        value1: "Hello".into(),
+        value2: 123,
+    });
+    assert_eq!(test.get_heap_size(), std::mem::size_of::<TestStruct>() + 5);
+
+    let test: Box<dyn TestShape> = Box::new(123u64);
+    assert_eq!(test.get_heap_size(), 8);
+}
+
+
+#[test]
+fn unsized_std_types() {
+    let test: Box<std::path::Path> = std::path::Path::new("/etc/hosts").into();
+    assert_eq!(test.get_heap_size(), 10);
+
+    let test: Box<std::ffi::OsStr> = std::ffi::OsStr::new("hosts").into();
+    assert_eq!(test.get_heap_size(), 5);
+
+    let test: Box<std::ffi::CStr> = std::ffi::CString::new("hosts").unwrap().into_boxed_c_str();
+    assert_eq!(test.get_heap_size(), 6);
+
+    let test: std::rc::Rc<str> = "Hello".into();
+    assert_eq!(test.get_heap_size(), 5);
+
+    let test: std::rc::Rc<[u8]> = vec![1u8, 2, 3].into();
+    assert_eq!(test.get_heap_size(), 3);
+
+    let test: std::sync::Arc<[String]> = vec![String::from("Hello")].into();
+    assert_eq!(test.get_heap_size(), std::mem::size_of::<String>() + 5);
+
+    // Shared contents are only accounted for once.
+    let path: std::sync::Arc<std::path::Path> = std::path::Path::new("/etc/hosts").into();
+    let (size, tracker) = path.clone().get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(size, 10);
+    let (size, _) = path.get_heap_size_with_tracker(tracker);
+    assert_eq!(size, 0);
+}
+
+
+#[no_implicit_prelude]
+#[allow(dead_code)]
+mod no_implicit_prelude {
+    use ::get_size::GetSize;
+
+    #[derive(GetSize)]
+    pub struct TestStruct<A> {
+        pub value1: ::std::string::String,
+        pub value2: A,
+    }
+
+    #[derive(GetSize)]
+    pub enum TestEnum<A> {
+        Variant1(A),
+        Variant2{x: ::std::string::String},
+        Variant3,
+    }
+
+    #[derive(GetSize)]
+    pub struct TestEmpty {}
+}
+
+#[test]
+fn derive_no_implicit_prelude() {
+    let test = no_implicit_prelude::TestStruct {
+        value1: "Hello".into(),
+        value2: 123u64,
+    };
+    assert_eq!(test.get_heap_size(), 5);
+
+    let test: no_implicit_prelude::TestEnum<u64> = no_implicit_prelude::TestEnum::Variant2{x: "Hello".into()};
+    assert_eq!(test.get_heap_size(), 5);
+}
+
+
+mod facade {
+    pub use get_size::*;
+}
+
+#[derive(GetSize)]
+#[get_size(crate = "facade")]
+#[get_size(ignore(B))]
+#[allow(dead_code)]
+pub struct TestStructCratePath<A, B> {
+    value1: A,
+    #[get_size(ignore)]
+    value2: B,
+}
+
+#[test]
+fn derive_struct_with_crate_path() {
+    let test: TestStructCratePath<String, u64> = TestStructCratePath {
+        value1: "Hello".into(),
+        value2: 123,
+    };
+
+    assert_eq!(test.get_heap_size(), 5);
+}
+
+
+#[derive(GetSize)]
+#[get_size(ignore(B, C))]
+#[allow(dead_code)]
+pub enum TestEnumHelpers<A, B, C> {
+    Variant1(A, #[get_size(ignore)] B),
+    Variant2{x: A, #[get_size(size = 100)] y: B},
+    Variant3(#[get_size(size_fn = get_size_helper)] C),
+    #[get_size(ignore)]
+    Variant4(B, C),
+    #[get_size(size = 10)]
+    Variant5{x: C},
+    #[get_size(size_fn = get_size_helper)]
+    Variant6(A, B),
+    Variant7{#[get_size(ignore)] x: B, #[get_size(ignore)] y: C},
+}
+
+#[test]
+fn derive_enum_with_helpers() {
+    type TestEnum = TestEnumHelpers<String, u64, TestStructNoGetSize>;
+
+    let no_impl = || TestStructNoGetSize {
+        value: "World!".into(),
+    };
+
+    let test: TestEnum = TestEnumHelpers::Variant1("Hello".into(), 123);
+    assert_eq!(test.get_heap_size(), 5);
+
+    let test: TestEnum = TestEnumHelpers::Variant2{x: "Hello".into(), y: 123};
+    assert_eq!(test.get_heap_size(), 5 + 100);
+
+    let test: TestEnum = TestEnumHelpers::Variant3(no_impl());
+    assert_eq!(test.get_heap_size(), 50);
+
+    let test: TestEnum = TestEnumHelpers::Variant4(123, no_impl());
+    assert_eq!(test.get_heap_size(), 0);
+
+    let test: TestEnum = TestEnumHelpers::Variant5{x: no_impl()};
+    assert_eq!(test.get_heap_size(), 10);
+
+    let test: TestEnum = TestEnumHelpers::Variant6("Hello".into(), 123);
+    assert_eq!(test.get_heap_size(), 50);
+
+    let test: TestEnum = TestEnumHelpers::Variant7{x: 123, y: no_impl()};
+    assert_eq!(test.get_heap_size(), 0);
+}
+
+
+pub trait TestBuffer {
+    type Buffer;
+}
+
+impl TestBuffer for TestStructNoGetSize {
+    type Buffer = Vec<u8>;
+}
+
+#[derive(GetSize)]
+pub struct TestStructPerfectDerive<A: TestBuffer, B> {
+    value1: A::Buffer,
+    value2: std::marker::PhantomData<B>,
+}
+
+#[derive(GetSize)]
+#[get_size(bound_all_params)]
+pub struct TestStructBoundAllParams<A, B> {
+    value1: Vec<A>,
+    value2: std::marker::PhantomData<B>,
+}
+
+#[test]
+fn derive_struct_perfect_derive() {
+    let test: TestStructPerfectDerive<TestStructNoGetSize, TestStructNoGetSize> = TestStructPerfectDerive {
+        value1: Vec::with_capacity(10),
+        value2: std::marker::PhantomData,
+    };
+    assert_eq!(test.get_heap_size(), 10);
+
+    let test: TestStructBoundAllParams<u8, u8> = TestStructBoundAllParams {
+        value1: Vec::with_capacity(10),
+        value2: std::marker::PhantomData,
+    };
+    assert_eq!(test.get_heap_size(), 10);
+}
+
+
+// `T` only appears inside `PhantomData` and behind pointers, so it is never bound, even though
+// the type refers to itself.
+#[derive(GetSize)]
+pub struct TestStructMarkerNode<'a, T, U: ?Sized> {
+    next: Option<Box<TestStructMarkerNode<'a, T, U>>>,
+    name: String,
+    marker: std::marker::PhantomData<fn() -> T>,
+    owner: &'a T,
+    raw: *const U,
+}
+
+#[derive(GetSize)]
+pub struct TestStructBorrowedUnsized<'a, T: ?Sized> {
+    value: &'a T,
+}
+
+#[derive(GetSize)]
+#[get_size(fallible, padding)]
+pub struct TestStructMarkerTraits<T> {
+    values: Vec<u16>,
+    marker: std::marker::PhantomData<T>,
+}
+
+#[test]
+fn derive_struct_opaque_params() {
+    let owner = TestStructNoGetSize { value: String::new() };
+    let leaf: TestStructMarkerNode<TestStructNoGetSize, str> = TestStructMarkerNode {
+        next: None,
+        name: String::from("leaf"),
+        marker: std::marker::PhantomData,
+        owner: &owner,
+        raw: "raw",
+    };
+    let node = TestStructMarkerNode {
+        next: Some(Box::new(leaf)),
+        name: String::from("node"),
+        marker: std::marker::PhantomData,
+        owner: &owner,
+        raw: "raw",
+    };
+    assert_eq!(node.get_heap_size(), std::mem::size_of::<TestStructMarkerNode<TestStructNoGetSize, str>>() + 4 + 4);
+
+    // References to unsized types keep their bound, so borrowed strings still get measured.
+    let test = TestStructBorrowedUnsized::<str> { value: "abc" };
+    assert_eq!(test.get_heap_size(), 0);
+
+    let test: TestStructMarkerTraits<TestStructNoGetSize> = TestStructMarkerTraits {
+        values: Vec::with_capacity(4),
+        marker: std::marker::PhantomData,
+    };
+    assert_eq!(test.get_heap_size(), 8);
+    assert_eq!(test.try_get_heap_size(&mut SizeContext::new()), Ok(8));
+    assert_eq!(test.get_padding_size(), 0);
+}
+
+#[test]
+fn fits_in() {
+    let value: Vec<String> = vec!["Hello".into(), "world!".into()];
+    let size = value.get_size();
+
+    assert!(value.fits_in(size));
+    assert!(!value.fits_in(size - 1));
+    assert_eq!(value.get_size_within(size), Some(size));
+    assert_eq!(value.get_heap_size_within(size), Some(value.get_heap_size()));
+    assert_eq!(value.get_heap_size_within(value.get_heap_size() - 1), None);
+
+    let mut value: std::collections::HashMap<u64, String> = std::collections::HashMap::new();
+    value.insert(1, "Hello".into());
+    value.insert(2, "world!".into());
+    let size = value.get_size();
+
+    assert!(value.fits_in(size));
+    assert!(!value.fits_in(size - 1));
+
+    let value: Option<[String; 2]> = Some(["Hello".into(), "world!".into()]);
+    assert!(value.fits_in(value.get_size()));
+    assert!(!value.fits_in(value.get_size() - 1));
+    assert!(!value.fits_in(0));
+}
+
+// Only supports bounded measurements, so these must be passed through instead of falling back
+// to `get_heap_size`.
+struct BoundedOnly(usize);
+
+impl GetSize for BoundedOnly {
+    fn get_heap_size(&self) -> usize {
+        unreachable!("measured without a limit")
+    }
+
+    fn get_heap_size_within(&self, limit: usize) -> Option<usize> {
+        if self.0 <= limit {
+            Some(self.0)
+        } else {
+            None
+        }
+    }
+}
+
+// Must not be measured at all, as the limit has already been exceeded.
+struct NeverReached;
+
+impl GetSize for NeverReached {
+    fn get_heap_size_within(&self, _limit: usize) -> Option<usize> {
+        unreachable!("measured after the limit was exceeded")
+    }
+}
+
+fn bounded_fixed_size(value: &u64) -> usize {
+    *value as usize
+}
+
+#[derive(GetSize)]
+struct BoundedStruct {
+    first: BoundedOnly,
+    #[get_size(size_fn = bounded_fixed_size)]
+    fixed: u64,
+    nested: (BoundedOnly, Box<BoundedOnly>),
+    last: NeverReached,
+}
+
+#[derive(GetSize)]
+enum BoundedEnum {
+    Boxed(Box<BoundedOnly>, NeverReached),
+    #[get_size(size = 8)]
+    Fixed,
+}
+
+#[test]
+fn derived_fits_in() {
+    let value = BoundedStruct {
+        first: BoundedOnly(10),
+        fixed: 8,
+        nested: (BoundedOnly(20), Box::new(BoundedOnly(30))),
+        last: NeverReached,
+    };
+    let boxed = std::mem::size_of::<BoundedOnly>();
+
+    // The limit is passed on to all fields, which stop measuring once it got exceeded.
+    assert_eq!(value.get_heap_size_within(10 + 8 + 20), None);
+    assert_eq!(value.get_heap_size_within(10 + 8 + 20 + boxed + 29), None);
+    assert_eq!(value.get_heap_size_within(10), None);
+
+    let value = BoundedEnum::Boxed(Box::new(BoundedOnly(30)), NeverReached);
+    assert_eq!(value.get_heap_size_within(boxed + 29), None);
+    assert_eq!(value.get_heap_size_within(boxed - 1), None);
+
+    let value = BoundedEnum::Fixed;
+    assert_eq!(value.get_heap_size_within(8), Some(8));
+    assert_eq!(value.get_heap_size_within(7), None);
+
+    let value = (BoundedOnly(10), Box::new(BoundedOnly(20)));
+    assert_eq!(value.get_heap_size_within(10 + boxed + 20), Some(10 + boxed + 20));
+    assert_eq!(value.get_heap_size_within(10 + boxed + 19), None);
+}
+
+
+// Pretends a memory mapping of the whole address space.
+fn huge_size_helper<T>(_value: &T) -> usize {
+    usize::MAX
+}
+
+#[derive(GetSize)]
+struct TestHugeStruct {
+    #[get_size(size_fn = huge_size_helper)]
+    mapped: Vec<u8>,
+    name: String,
+}
+
+#[derive(GetSize)]
+#[allow(dead_code)]
+enum TestHugeEnum {
+    Mapped(TestHugeStruct),
+    #[get_size(size = 100)]
+    Small(u8),
+    Empty,
+}
+
+#[test]
+fn size_accumulators() {
+    let value = TestHugeStruct {
+        mapped: Vec::new(),
+        name: String::from("Hello"),
+    };
+    let values = vec![TestHugeEnum::Mapped(value), TestHugeEnum::Small(1), TestHugeEnum::Empty];
+
+    let expected = usize::MAX as u128 + 5 + 100 + 3 * std::mem::size_of::<TestHugeEnum>() as u128;
+
+    assert_eq!(values.get_heap_size_as::<u128>(), expected);
+    assert_eq!(values.get_size_as::<u128>(), expected + std::mem::size_of::<Vec<TestHugeEnum>>() as u128);
+    assert_eq!(values.get_heap_size_as::<std::num::Saturating<usize>>(), std::num::Saturating(usize::MAX));
+    assert_eq!(values.get_heap_size_as::<Option<usize>>(), None);
+
+    let values = vec![TestHugeEnum::Small(1), TestHugeEnum::Empty];
+
+    assert_eq!(values.get_heap_size_as::<Option<usize>>(), Some(values.get_heap_size()));
+    assert_eq!(values.get_heap_size_as::<usize>(), values.get_heap_size());
+}
+
+
+#[test]
+fn incremental_size() {
+    let mut log = IncrementalSize::with_interval(Vec::with_capacity(4), 3);
+    assert_eq!(log.estimated_heap_size(), 4 * std::mem::size_of::<String>());
+
+    log.push(String::from("Hello"));
+    log.push(String::from("world!"));
+
+    // The buffer did not grow, so only the heap part of the elements got added.
+    assert_eq!(log.estimated_heap_size(), 4 * std::mem::size_of::<String>() + 5 + 6);
+    assert_eq!(log.estimated_heap_size(), log.get_heap_size());
+
+    // Measuring the wrapper measures the collection itself.
+    assert_eq!(log.get_heap_size(), log.get_ref().get_heap_size());
+    let (size, _) = log.get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(size, log.get_ref().get_heap_size());
+
+    log.extend(vec![String::from("abc")]);
+    assert_eq!(log.estimated_heap_size(), log.get_heap_size());
+
+    log.modify(|log| log.clear());
+    assert_eq!(log.estimated_heap_size(), log.capacity() * std::mem::size_of::<String>());
+    assert!(log.is_empty());
+
+    // Growing the buffer adds the stack part of the additional slots.
+    let mut log = IncrementalSize::with_interval(Vec::new(), 0);
+    log.extend((0..10).map(|_| String::from("ab")));
+    log.push(String::from("abc"));
+    assert_eq!(log.estimated_heap_size(), log.capacity() * std::mem::size_of::<String>() + 10 * 2 + 3);
+
+    // Replaced values and duplicate elements do not drift the running total apart.
+    let mut index = IncrementalSize::with_interval(std::collections::HashMap::new(), 0);
+    index.push((1u32, String::from("a")));
+    index.extend(vec![(2u32, String::from("bc")), (1u32, String::from("defg"))]);
+    index.push((3u32, String::from("h")));
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.estimated_heap_size(), index.get_ref().get_heap_size());
+
+    let mut tags = IncrementalSize::with_interval(std::collections::HashSet::new(), 0);
+    tags.extend(vec![String::from("a"), String::from("bc"), String::from("a")]);
+    tags.push(String::from("bc"));
+    tags.push(String::from("def"));
+    assert_eq!(tags.len(), 3);
+    assert_eq!(tags.estimated_heap_size(), tags.get_ref().get_heap_size());
+}
+
+
+#[cfg(feature = "internment")]
+#[test]
+fn internment() {
+    use internment::{Intern, ArcIntern};
+
+    let first = Intern::new(String::from("Hello"));
+    let second = Intern::new(String::from("Hello"));
+
+    // The handles themselves only occupy the stack.
+    assert_eq!(first.get_heap_size(), 0);
+
+    let mut tracker = StandardTracker::default();
+    let (total, _) = first.get_heap_size_with_tracker(&mut tracker);
+    assert_eq!(total, std::mem::size_of::<String>() + 5);
+    let (total, _) = second.get_heap_size_with_tracker(&mut tracker);
+    assert_eq!(total, 0);
+
+    let first = ArcIntern::new(String::from("world!"));
+    let second = ArcIntern::new(String::from("world!"));
+
+    let mut tracker = StandardTracker::default();
+    let (total, _) = first.get_heap_size_with_tracker(&mut tracker);
+    assert_eq!(total, std::mem::size_of::<String>() + 6);
+    let (total, _) = second.get_heap_size_with_tracker(&mut tracker);
+    assert_eq!(total, 0);
+}
+
+
+#[cfg(feature = "lasso")]
+#[test]
+fn lasso() {
+    let mut rodeo = lasso::Rodeo::default();
+    let empty = rodeo.get_heap_size();
+
+    let key = rodeo.get_or_intern("Hello");
+    assert_eq!(key.get_heap_size(), 0);
+    assert!(rodeo.get_heap_size() >= empty);
+}
+
+
+#[cfg(feature = "tokio")]
+#[test]
+fn get_size_async() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    runtime.block_on(async {
+        let mut map: std::collections::HashMap<u64, String> = std::collections::HashMap::new();
+        for i in 0..100 {
+            map.insert(i, i.to_string());
+        }
+
+        let mut ctx = YieldContext::with_interval(10);
+        assert_eq!(map.get_heap_size_async(&mut ctx).await, Ok(map.get_heap_size()));
+        assert_eq!(ctx.visited(), 100);
+
+        let values = vec![1u64; 1000];
+        let mut ctx = YieldContext::new();
+        assert_eq!(values.get_size_async(&mut ctx).await, Ok(values.get_size()));
+        assert_eq!(ctx.visited(), 0);
+
+        let token = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let strings = vec![String::from("Hello"); 100];
+        let mut ctx = YieldContext::with_interval(10);
+        ctx.set_cancel_token(token);
+        assert_eq!(strings.get_heap_size_async(&mut ctx).await, Err(SizeError::Cancelled));
+        assert_eq!(ctx.visited(), 10);
+
+        // Wrapped collections get cancelled as well.
+        let token = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let wrapped = Some(Box::new(vec![String::from("Hello"); 100]));
+        let mut ctx = YieldContext::with_interval(10);
+        ctx.set_cancel_token(token.clone());
+        assert_eq!(wrapped.get_heap_size_async(&mut ctx).await, Err(SizeError::Cancelled));
+        assert_eq!(ctx.visited(), 10);
+
+        token.store(false, std::sync::atomic::Ordering::Relaxed);
+        let mut ctx = YieldContext::with_interval(10);
+        ctx.set_cancel_token(token);
+        assert_eq!(wrapped.get_heap_size_async(&mut ctx).await, Ok(wrapped.get_heap_size()));
+        assert_eq!(ctx.visited(), 100);
+    });
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn indexmap() {
+    let mut map: indexmap::IndexMap<u64, String> = indexmap::IndexMap::with_capacity(4);
+    map.insert(1, String::from("Hello"));
+
+    let breakdown = map_heap_breakdown(&map);
+    assert_eq!(breakdown.keys, 8);
+    assert_eq!(breakdown.values, std::mem::size_of::<String>() + 5);
+    assert_eq!(breakdown.total(), map.get_heap_size());
+    assert!(breakdown.table_overhead >= 3 * (8 + std::mem::size_of::<String>()));
+
+    let set: indexmap::IndexSet<u32> = (0..10).collect();
+    assert!(set.get_heap_size() >= 10 * 4);
+}
+
+
+#[cfg(feature = "bytes")]
+#[test]
+fn bytes() {
+    let value = bytes::Bytes::from(vec![0u8; 100]);
+    assert_eq!(value.get_heap_size(), 100);
+    assert_eq!(value.slice(10..20).get_heap_size(), 10);
+
+    let mut value = bytes::BytesMut::with_capacity(64);
+    value.extend_from_slice(b"Hello");
+    assert_eq!(value.get_heap_size(), value.capacity());
+    assert_eq!(value.wasted_capacity(), value.capacity() - 5);
+}
+
+
+#[cfg(feature = "axum")]
+#[test]
+fn axum() {
+    let state = axum::extract::State(std::sync::Arc::new(String::from("Hello")));
+    assert_eq!(state.get_heap_size(), std::mem::size_of::<String>() + 5);
+
+    let shared = std::sync::Arc::new(String::from("Hello"));
+    let extension = axum::Extension(TestStructShared {
+        first: shared.clone(),
+        second: shared.clone(),
+    });
+    let (size, _) = extension.get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(size, std::mem::size_of::<String>() + 5);
+
+    assert_eq!(axum::Json(vec![1u8, 2, 3]).get_heap_size(), 3);
+}
+
+
+#[cfg(feature = "http")]
+#[test]
+fn http() {
+    let mut headers = http::HeaderMap::new();
+    let empty = headers.get_heap_size();
+
+    headers.insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("text/plain"));
+    headers.insert("x-request-id", http::HeaderValue::from_static("abc"));
+
+    assert_eq!(http::header::CONTENT_TYPE.get_heap_size(), 0);
+    assert_eq!(http::HeaderName::from_static("x-request-id").get_heap_size(), 12);
+    assert_eq!(http::HeaderValue::from_static("abc").get_heap_size(), 3);
+    assert!(headers.get_heap_size() >= empty + 12 + 10 + 3);
+
+    let uri: http::Uri = "https://example.com/index.html?page=1".parse().unwrap();
+    assert_eq!(uri.get_heap_size(), "example.com".len() + "/index.html?page=1".len());
+
+    assert_eq!(http::Method::GET.get_heap_size(), 0);
+    assert_eq!(http::StatusCode::OK.get_heap_size(), 0);
+
+    let request = http::Request::builder()
+        .uri(uri.clone())
+        .body(String::from("Hello"))
+        .unwrap();
+    assert_eq!(request.get_heap_size(), uri.get_heap_size() + 5);
+
+    let response = http::Response::new(vec![0u8; 10]);
+    assert_eq!(response.get_heap_size(), http::HeaderMap::<http::HeaderValue>::new().get_heap_size() + 10);
+}
+
+
+#[cfg(feature = "mio")]
+#[test]
+fn mio() {
+    let events = mio::Events::with_capacity(16);
+    assert_eq!(events.get_heap_size(), 16 * std::mem::size_of::<mio::event::Event>());
+
+    let poll = mio::Poll::new().unwrap();
+    assert_eq!(poll.get_heap_size(), 0);
+    assert_eq!(mio::Token(1).get_heap_size(), 0);
+}
+
+
+#[cfg(feature = "socket2")]
+#[test]
+fn socket2() {
+    let socket = socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None).unwrap();
+    assert_eq!(socket.get_heap_size(), 0);
+
+    let addr: std::net::SocketAddr = "127.0.0.1:80".parse().unwrap();
+    assert_eq!(socket2::SockAddr::from(addr).get_heap_size(), 0);
+}
+
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv() {
+    let value = vec![String::from("Hello world, this is long"), String::from("short")];
+
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&value).unwrap();
+    assert_eq!(bytes.get_heap_size(), bytes.capacity());
+
+    let archived = rkyv::access::<rkyv::Archived<Vec<String>>, rkyv::rancor::Error>(&bytes).unwrap();
+    assert_eq!(
+        archived.get_heap_size(),
+        2 * std::mem::size_of::<rkyv::string::ArchivedString>() + 25,
+    );
+    assert!(archived.get_heap_size() <= bytes.len());
+
+    let value = Box::new(String::from("Hello world, this is long"));
+
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&value).unwrap();
+    let archived = rkyv::access::<rkyv::Archived<Box<String>>, rkyv::rancor::Error>(&bytes).unwrap();
+    assert_eq!(archived.get_heap_size(), 0);
+    assert_eq!(archived.borrowed_size(), std::mem::size_of::<rkyv::string::ArchivedString>() + 25);
+}
+
+
+#[cfg(feature = "sqlx")]
+#[test]
+fn sqlx() {
+    let value = sqlx::types::Json(vec![String::from("Hello")]);
+
+    assert_eq!(value.get_heap_size(), value.0.get_heap_size());
+}
+
+
+#[cfg(feature = "rusqlite")]
+#[test]
+fn rusqlite() {
+    use rusqlite::types::Value;
+
+    let row = vec![
+        Value::Null,
+        Value::Integer(1),
+        Value::Text(String::from("Hello")),
+        Value::Blob(vec![0u8; 10]),
+    ];
+
+    assert_eq!(row[2].get_heap_size(), 5);
+    assert_eq!(row.get_heap_size(), 4 * std::mem::size_of::<Value>() + 5 + 10);
+
+    let connection = rusqlite::Connection::open_in_memory().unwrap();
+    assert_eq!(connection.get_heap_size(), 0);
+}
+
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn bytemuck() {
+    #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    #[repr(C)]
+    struct Pixel {
+        rgba: [u8; 4],
+    }
+
+    get_size::impl_pod!(Pixel);
+
+    #[derive(GetSize)]
+    struct Image {
+        pixels: Vec<Pixel>,
+    }
+
+    let image = Image { pixels: vec![Pixel { rgba: [0; 4] }; 16] };
+    assert_eq!(image.pixels[0].rgba, [0; 4]);
+    assert_eq!(image.get_heap_size(), 16 * 4);
+}
+
+
+#[cfg(feature = "zerocopy")]
+#[test]
+fn zerocopy() {
+    #[derive(zerocopy::FromBytes, zerocopy::Immutable, zerocopy::KnownLayout)]
+    #[repr(C)]
+    struct Header {
+        length: u32,
+    }
+
+    get_size::impl_from_bytes!(Header);
+
+    let headers: Vec<Header> = Vec::with_capacity(8);
+    assert_eq!(headers.get_heap_size(), 8 * 4);
+}
+
+
+#[cfg(feature = "simd")]
+#[test]
+fn simd() {
+    use std::simd::{Simd, Mask};
+
+    #[derive(GetSize)]
+    struct Particles {
+        positions: Vec<Simd<f32, 4>>,
+        active: Mask<i32, 4>,
+    }
+
+    let particles = Particles {
+        positions: Vec::with_capacity(8),
+        active: Mask::splat(true),
+    };
+
+    const { assert!(!Simd::<f32, 4>::CAN_ALLOCATE_HEAP) };
+    assert_eq!(particles.active.get_heap_size(), 0);
+    assert_eq!(particles.get_heap_size(), 8 * std::mem::size_of::<Simd<f32, 4>>());
+}
+
+
+#[cfg(feature = "wide")]
+#[test]
+fn wide() {
+    let values = vec![wide::f32x4::splat(1.0); 4];
+
+    assert_eq!(wide::f32x4::splat(1.0).get_heap_size(), 0);
+    assert_eq!(values.get_heap_size(), 4 * std::mem::size_of::<wide::f32x4>());
+}
+
+#[cfg(feature = "glam")]
+#[derive(GetSize)]
+struct TestTransformComponent {
+    translation: glam::Vec3,
+    rotation: glam::Quat,
+    matrix: glam::Mat4,
+}
+
+#[cfg(feature = "glam")]
+#[test]
+fn glam() {
+    let component = TestTransformComponent {
+        translation: glam::Vec3::ONE,
+        rotation: glam::Quat::IDENTITY,
+        matrix: glam::Mat4::IDENTITY,
+    };
+
+    assert_eq!(component.get_heap_size(), 0);
+    assert_eq!(component.get_size(), std::mem::size_of::<TestTransformComponent>());
+    assert_eq!(vec![glam::Vec3A::ZERO; 4].get_heap_size(), 4 * std::mem::size_of::<glam::Vec3A>());
+}
+
+#[cfg(feature = "euclid")]
+#[test]
+fn euclid() {
+    let rect = euclid::default::Rect::new(euclid::point2(1.0f32, 2.0), euclid::size2(3.0, 4.0));
+    let points = vec![euclid::default::Point3D::new(1i32, 2, 3); 4];
+
+    assert_eq!(rect.get_heap_size(), 0);
+    assert_eq!(points.get_heap_size(), 4 * std::mem::size_of::<euclid::default::Point3D<i32>>());
+}
+
+#[cfg(feature = "cgmath")]
+#[test]
+fn cgmath() {
+    let matrix = cgmath::Matrix4::<f32>::from_scale(2.0);
+    let vectors = vec![cgmath::Vector3::new(1.0f64, 2.0, 3.0); 4];
+
+    assert_eq!(matrix.get_heap_size(), 0);
+    assert_eq!(vectors.get_heap_size(), 4 * std::mem::size_of::<cgmath::Vector3<f64>>());
+}
+
+#[cfg(feature = "hecs")]
+#[derive(GetSize)]
+struct TestInventory {
+    items: Vec<u32>,
+}
+
+#[cfg(feature = "hecs")]
+#[test]
+fn hecs_world_report() {
+    let mut world = hecs::World::new();
+
+    world.spawn((TestInventory { items: vec![1, 2, 3] }, String::from("first")));
+    world.spawn((TestInventory { items: Vec::with_capacity(10) },));
+    world.spawn((1u8,));
+
+    let mut sizer = WorldSizer::new();
+    sizer.register::<TestInventory>();
+    sizer.register::<String>();
+
+    assert!(sizer.is_registered::<String>());
+    assert!(!sizer.is_registered::<u8>());
+
+    let report = sizer.report(&world);
+    let inventories = report.components().iter()
+        .find(|size| size.name.ends_with("TestInventory"))
+        .unwrap();
+
+    assert_eq!(report.entities(), 3);
+    assert_eq!(report.unregistered_components(), 1);
+    assert_eq!(inventories.count, 2);
+    assert_eq!(inventories.stack_size, 2 * std::mem::size_of::<TestInventory>());
+    assert_eq!(inventories.heap_size, 13 * std::mem::size_of::<u32>());
+    assert_eq!(report.components()[0], *inventories);
+    assert_eq!(report.total(), 2 * std::mem::size_of::<TestInventory>() + 52 + std::mem::size_of::<String>() + 5);
+    assert_eq!(report.to_size_report().heap_size(), report.total());
+}
+
+#[cfg(feature = "tantivy")]
+#[test]
+fn tantivy() {
+    use tantivy::schema::{Facet, Field, FieldValue, OwnedValue};
+    use tantivy::tokenizer::Token;
+
+    let field = Field::from_field_id(0);
+    let token = Token {
+        text: String::from("search"),
+        ..Token::default()
+    };
+    let value = OwnedValue::Array(vec![OwnedValue::Str(String::from("hello")), OwnedValue::U64(42)]);
+
+    let mut doc = tantivy::TantivyDocument::default();
+    doc.add_text(field, "hello");
+
+    assert_eq!(token.get_heap_size(), 6);
+    assert_eq!(value.get_heap_size(), 2 * std::mem::size_of::<OwnedValue>() + 5);
+    assert_eq!(Facet::from("/category/books").get_heap_size(), Facet::from("/category/books").encoded_str().len());
+    assert!(tantivy::Term::from_field_text(field, "hello").get_heap_size() >= 5);
+    assert_eq!(doc.get_heap_size(), std::mem::size_of::<FieldValue>() + 5);
+}
+
+#[cfg(feature = "sled")]
+#[test]
+fn sled() {
+    let short = sled::IVec::from(&b"key"[..]);
+    let long = sled::IVec::from(vec![0u8; 100]);
+
+    assert_eq!(short.get_heap_size(), 0);
+    assert_eq!(long.get_heap_size(), 100);
+    assert_eq!(long.subslice(0, 50).get_heap_size(), 50);
+}
+
+#[cfg(feature = "hashbrown")]
+#[test]
+fn hashbrown() {
+    let mut map = hashbrown::HashMap::new();
+    map.insert(1u64, String::from("abc"));
+
+    assert_eq!(map.get_heap_size(), map.allocation_size() + 3);
+    assert_eq!(map.get_heap_size_with_tracker(StandardTracker::default()).0, map.get_heap_size());
+    assert_eq!(SizeReport::of(&map).source(""), Some(SizeSource::Exact));
+
+    let mut set = hashbrown::HashSet::new();
+    set.insert(String::from("abcd"));
+
+    assert_eq!(set.get_heap_size(), set.allocation_size() + 4);
+    assert_eq!(set.get_heap_size_with_tracker(StandardTracker::default()).0, set.get_heap_size());
+    assert_eq!(set.wasted_capacity(), (set.capacity() - 1) * std::mem::size_of::<String>());
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn memmap2() {
+    let map = memmap2::MmapMut::map_anon(4096).unwrap();
+    assert_eq!(map.get_heap_size(), 0);
+
+    let report = SizeReport::of(&map);
+    assert_eq!(report.entries(), &[(String::new(), 4096)]);
+    assert_eq!(report.kind(""), ResourceKind::Mmap);
+    assert_eq!(report.ram_size(), std::mem::size_of::<memmap2::MmapMut>());
+
+    let map = map.make_read_only().unwrap();
+    assert_eq!(report.size_of_kind(ResourceKind::Mmap), map.len());
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn bitvec() {
+    use bitvec::prelude::*;
+
+    let mut bits: BitVec<u64, Lsb0> = BitVec::with_capacity(1000);
+    bits.resize(100, true);
+    assert_eq!(bits.get_heap_size(), 16 * 8);
+    assert_eq!(bits.wasted_capacity(), 14 * 8);
+
+    let bools = vec![true; 1000];
+    assert!(bits.get_heap_size() < bools.get_heap_size());
+
+    let bits = bits.into_boxed_bitslice();
+    assert_eq!(bits.get_heap_size(), 2 * 8);
+}
+
+#[cfg(feature = "fixedbitset")]
+#[test]
+fn fixedbitset() {
+    let set = fixedbitset::FixedBitSet::with_capacity(1000);
+    assert!(set.get_heap_size() >= 1000 / 8);
+    assert_eq!(set.get_heap_size(), std::mem::size_of_val(set.as_slice()));
+}
+
+#[cfg(feature = "roaring")]
+#[test]
+fn roaring() {
+    let bitmap: roaring::RoaringBitmap = (0..100_000).collect();
+    let statistics = bitmap.statistics();
+    assert_eq!(bitmap.get_heap_size() as u64, statistics.n_bytes_array_containers + statistics.n_bytes_bitset_containers);
+
+    let report = SizeReport::of(&bitmap);
+    assert_eq!(report.heap_size(), bitmap.get_heap_size());
+}
+
+#[cfg(feature = "redb")]
+#[test]
+fn redb() {
+    use redb::{Database, TableDefinition};
+
+    const TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("values");
+
+    let db = Database::builder().create_with_backend(redb::backends::InMemoryBackend::new()).unwrap();
+
+    let write = db.begin_write().unwrap();
+    write.open_table(TABLE).unwrap().insert("key", &[1u8; 100][..]).unwrap();
+    write.commit().unwrap();
+
+    let read = db.begin_read().unwrap();
+    let table = read.open_table(TABLE).unwrap();
+    let value = table.get("key").unwrap().unwrap();
+
+    assert_eq!(value.get_heap_size(), 0);
+    assert_eq!(value.borrowed_size(), 100);
+}
+
+
+pub trait TestPlugin {
+    fn name(&self) -> &str;
+}
+
+impl TestPlugin for String {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+fn plugin_heap_size(plugin: &dyn TestPlugin) -> usize {
+    plugin.name().len()
+}
+
+#[derive(GetSize)]
+pub struct TestStructDynElements {
+    #[get_size(element_size_fn = plugin_heap_size)]
+    plugins: Vec<Box<dyn TestPlugin>>,
+}
+
+#[derive(GetSize)]
+pub enum TestEnumDynElements {
+    Variant1(#[get_size(element_size_fn = plugin_heap_size)] Box<[Box<dyn TestPlugin>]>),
+}
+
+#[test]
+fn derive_struct_with_element_size_fn() {
+    let mut plugins: Vec<Box<dyn TestPlugin>> = Vec::with_capacity(4);
+    plugins.push(Box::new(String::from("Hello")));
+    plugins.push(Box::new(String::from("world!")));
+
+    let expected = 4 * std::mem::size_of::<Box<dyn TestPlugin>>()
+        + 2 * std::mem::size_of::<String>() + 5 + 6;
+
+    let test = TestStructDynElements {
+        plugins,
+    };
+    assert_eq!(test.get_heap_size(), expected);
+
+    let plugins: Vec<Box<dyn TestPlugin>> = vec![Box::new(String::from("Hello"))];
+    let expected = std::mem::size_of::<Box<dyn TestPlugin>>() + std::mem::size_of::<String>() + 5;
+
+    let test = TestEnumDynElements::Variant1(plugins.into_boxed_slice());
+    assert_eq!(test.get_heap_size(), expected);
+}
+
+
+#[derive(GetSize)]
+pub struct TestStructLeaked {
+    #[get_size(leaked)]
+    value1: &'static str,
+    #[get_size(leaked)]
+    value2: std::borrow::Cow<'static, str>,
+    value3: &'static str,
+}
+
+#[test]
+fn derive_struct_with_leaked() {
+    let value: &'static str = Box::leak(String::from("Hello").into_boxed_str());
+
+    let test = TestStructLeaked {
+        value1: value,
+        value2: std::borrow::Cow::Borrowed("world!"),
+        value3: value,
+    };
+    assert_eq!(test.get_heap_size(), 5 + 6);
+
+    let test = TestStructLeaked {
+        value1: value,
+        value2: std::borrow::Cow::Owned(String::with_capacity(10)),
+        value3: value,
+    };
+    assert_eq!(test.get_heap_size(), 5 + 10);
+}
+
+
+#[derive(GetSize)]
+pub struct TestStructViews<'a> {
+    keys: std::collections::btree_map::Keys<'a, u64, String>,
+    range: std::collections::btree_map::Range<'a, u64, String>,
+}
+
+#[test]
+fn derive_struct_with_views() {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(1u64, String::from("Hello"));
+
+    let test = TestStructViews {
+        keys: map.keys(),
+        range: map.range(0..10),
+    };
+    assert_eq!(test.get_heap_size(), 0);
+}
+
+
+#[test]
+fn can_allocate_heap() {
+    let flags = [
+        u8::CAN_ALLOCATE_HEAP,
+        <(u8, Option<f64>, [char; 4])>::CAN_ALLOCATE_HEAP,
+        String::CAN_ALLOCATE_HEAP,
+        <(u8, String)>::CAN_ALLOCATE_HEAP,
+        Vec::<u8>::CAN_ALLOCATE_HEAP,
+    ];
+    assert_eq!(flags, [false, false, true, true, true]);
+
+    let mut value: Vec<u64> = Vec::with_capacity(10);
+    value.push(1);
+    assert_eq!(value.get_heap_size(), 10 * 8);
+    assert_eq!(value.get_heap_size_within(80), Some(80));
+    assert_eq!(value.get_heap_size_within(79), None);
+
+    let value: std::collections::BTreeMap<u64, u32> = (0..10).map(|i| (i, i as u32)).collect();
+    assert_eq!(value.get_heap_size(), 10 * (8 + 4));
+}
+
+
+#[derive(GetSize)]
+#[allow(dead_code)]
+pub struct TestStructNoHeap {
+    value1: u64,
+    value2: (u8, [char; 2]),
+    #[get_size(ignore)]
+    value3: String,
+}
+
+#[derive(GetSize)]
+pub enum TestEnumNoHeap<A> {
+    Variant1(A),
+    Variant2{x: u32},
+    #[get_size(ignore)]
+    Variant3(String),
+}
+
+#[test]
+fn derive_can_allocate_heap() {
+    let flags = [
+        TestStructNoHeap::CAN_ALLOCATE_HEAP,
+        TestEnumNoHeap::<u8>::CAN_ALLOCATE_HEAP,
+        TestEnumNoHeap::<String>::CAN_ALLOCATE_HEAP,
+        TestStruct::CAN_ALLOCATE_HEAP,
+        TestStructHelpers::<u8, u8, u8>::CAN_ALLOCATE_HEAP,
+        TestEnum2::CAN_ALLOCATE_HEAP,
+    ];
+    assert_eq!(flags, [false, false, true, true, true, false]);
+
+    let value: Vec<TestStructNoHeap> = Vec::with_capacity(10);
+    assert_eq!(value.get_heap_size(), 10 * std::mem::size_of::<TestStructNoHeap>());
+}
+
+
+#[test]
+fn get_size_ext() {
+    let value = String::from("Hello world!");
+    assert_eq!(value.heap_size(), value.get_heap_size());
+    assert_eq!(value.size(), value.get_size());
+
+    let value = "Hello";
+    assert_eq!(GetSizeExt::size(value), 5);
+    assert_eq!(GetSizeExt::heap_size(value), 0);
+
+    let value: Vec<u8> = Vec::with_capacity(10 * 1024 * 1024);
+    assert_eq!(value.size_formatted(), "10.0 MiB");
+
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1024), "1.0 KiB");
+    assert_eq!(format_size(usize::MAX), "16.0 EiB");
+}
+
+
+#[derive(GetSize)]
+#[get_size(fallible)]
+pub struct TestStructFallible<T> {
+    name: String,
+    values: std::sync::Mutex<Vec<T>>,
+    #[get_size(size = 10)]
+    fixed: u8,
+}
+
+#[derive(GetSize)]
+#[get_size(fallible)]
+pub enum TestListFallible {
+    Node(u64, Box<TestListFallible>),
+    Cell { value: std::cell::RefCell<String> },
+    #[get_size(ignore)]
+    Ignored(String),
+    End,
+}
+
+#[test]
+fn try_get_size() {
+    let value = TestStructFallible {
+        name: String::from("Hello"),
+        values: std::sync::Mutex::new(Vec::<u16>::with_capacity(4)),
+        fixed: 0,
+    };
+    assert_eq!(value.fixed, 0);
+
+    let mut ctx = SizeContext::new();
+    assert_eq!(value.try_get_heap_size(&mut ctx), Ok(value.get_heap_size()));
+    assert_eq!(ctx.depth(), 0);
+
+    {
+        let _guard = value.values.lock().unwrap();
+        assert_eq!(value.try_get_heap_size(&mut ctx), Err(SizeError::LockUnavailable));
+    }
+
+    let _ = std::panic::catch_unwind(|| {
+        let _guard = value.values.lock().unwrap();
+        panic!("poison the mutex");
+    });
+    assert_eq!(value.try_get_heap_size(&mut ctx), Err(SizeError::Poisoned));
+    assert_eq!(value.get_heap_size(), 5 + 8 + 10);
+
+    let cell = TestListFallible::Cell { value: std::cell::RefCell::new(String::from("abc")) };
+    assert_eq!(cell.try_get_heap_size(&mut ctx), Ok(3));
+    assert_eq!(TestListFallible::Ignored(String::from("abc")).try_get_heap_size(&mut ctx), Ok(0));
+
+    if let TestListFallible::Cell { value } = &cell {
+        let _borrow = value.borrow_mut();
+        assert_eq!(cell.try_get_heap_size(&mut ctx), Err(SizeError::LockUnavailable));
+    }
+
+    let mut list = TestListFallible::End;
+    for i in 0..10 {
+        list = TestListFallible::Node(i, Box::new(list));
+    }
+    assert_eq!(list.try_get_heap_size(&mut ctx), Ok(10 * std::mem::size_of::<TestListFallible>()));
+
+    let mut ctx = SizeContext::with_max_depth(5);
+    assert_eq!(list.try_get_heap_size(&mut ctx), Err(SizeError::RecursionLimitExceeded));
+    assert_eq!(ctx.depth(), 0);
+}
+
+
+#[test]
+fn track_key() {
+    let value = std::sync::Arc::new(());
+    let other = std::rc::Rc::new(());
+
+    let mut tracker = StandardTracker::new();
+    assert_eq!(tracker.context(|context| context.generation()), 0);
+
+    // Both point to the same dangling address, but are of different types.
+    let addr = &*value as *const ();
+    assert!(tracker.track(addr, value.clone()));
+    assert!(!tracker.track(addr, value.clone()));
+    assert!(tracker.track(addr, other.clone()));
+    assert!(tracker.contains(&TrackKey::new::<std::sync::Arc<()>, ()>(addr)));
+
+    tracker.clear();
+    assert_eq!(tracker.context(|context| context.generation()), 1);
+    assert!(!tracker.contains(&TrackKey::new::<std::sync::Arc<()>, ()>(addr)));
+}
+
+#[test]
+fn track_key_shared() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    // Clones coerced to a dynamically sized type still point to the same allocation.
+    let array: Arc<[u64; 4]> = Arc::new([1, 2, 3, 4]);
+    let slice: Arc<[u64]> = array.clone();
+    let chained: Arc<[u64]> = Arc::clone(&Arc::clone(&slice));
+    assert_eq!(TrackKey::of_arc(&array), TrackKey::of_arc(&slice));
+    assert_eq!(TrackKey::of_arc(&slice), TrackKey::of_arc(&chained));
+    assert_eq!(TrackKey::of_arc(&array).addr, Arc::as_ptr(&array) as *const u8 as usize);
+
+    // Separate allocations of zero sized values are not confused with each other.
+    let (first, second) = (Rc::new(()), Rc::new(()));
+    assert_ne!(TrackKey::of_rc(&first), TrackKey::of_rc(&second));
+
+    let mut tracker = StandardTracker::new();
+    let (first, tracker_ref) = array.get_heap_size_with_tracker(&mut tracker);
+    let (second, tracker_ref) = slice.get_heap_size_with_tracker(tracker_ref);
+    let (third, _) = chained.get_heap_size_with_tracker(tracker_ref);
+    assert_eq!((first, second, third), (32, 0, 0));
+    assert_eq!(tracker.visited_count(), 1);
+    assert!(tracker.contains(&TrackKey::of_arc(&slice)));
+
+    let string: Rc<str> = Rc::from("Hello");
+    let bytes: Rc<[u8]> = Rc::from(*b"Hello");
+    assert_ne!(TrackKey::of_rc(&string), TrackKey::of_rc(&bytes));
+
+    let mut tracker = StandardTracker::new();
+    let values = vec![string.clone(), string.clone()];
+    let (size, tracker_ref) = values.get_heap_size_with_tracker(&mut tracker);
+    let (other, _) = bytes.get_heap_size_with_tracker(tracker_ref);
+    assert_eq!(size, 2 * std::mem::size_of::<Rc<str>>() + 5);
+    assert_eq!(other, 5);
+    assert!(tracker.contains(&TrackKey::of_rc(&string)));
+
+    // Weak references upgrade to the same key.
+    let strong = Rc::new(String::from("abc"));
+    let weak = Rc::downgrade(&strong);
+    let mut tracker = StandardTracker::new();
+    let (size, tracker_ref) = strong.get_heap_size_with_tracker(&mut tracker);
+    let (again, _) = weak.get_heap_size_with_tracker(tracker_ref);
+    assert_eq!((size, again), (std::mem::size_of::<String>() + 3, 0));
+}
+
+#[derive(GetSize)]
+pub struct TestStructShared {
+    first: std::sync::Arc<String>,
+    second: std::sync::Arc<String>,
+}
+
+#[test]
+fn scoped_tracker() {
+    let shared = std::sync::Arc::new(String::from("Hello"));
+    let value = TestStructShared {
+        first: shared.clone(),
+        second: shared.clone(),
+    };
+    let expected = value.get_size();
+
+    let mut tracker = ScopedTracker::new();
+    assert_eq!(tracker.measure(&value), expected);
+    assert_eq!(tracker.measure(&value), expected);
+    assert_eq!(tracker.context(|context| context.generation()), 2);
+
+    assert_eq!(tracker.measure_shared(&value), expected);
+    assert_eq!(tracker.measure(&value), expected - shared.get_heap_size());
+
+    tracker.clear();
+    assert_eq!(tracker.measure(&value), expected);
+}
+
+// A tracker which is unsized, like a slice, can still be passed along behind a reference or a box.
+struct TestUnsizedTracker<P: ?Sized> {
+    tracked: usize,
+    answers: P,
+}
+
+impl GetSizeTracker for TestUnsizedTracker<[bool]> {
+    fn track<A: std::any::Any + 'static, B>(&mut self, _addr: *const B, _strong_ref: A) -> bool {
+        self.tracked += 1;
+
+        self.answers[self.tracked % self.answers.len()]
+    }
+}
+
+#[test]
+fn tracker_calling_conventions() {
+    fn measure<T: GetSize, TR: GetSizeTracker>(value: &T, tracker: TR) -> (usize, TR) {
+        value.get_heap_size_with_tracker(tracker)
+    }
+
+    let shared = std::sync::Arc::new(String::from("abc"));
+    let value = vec![shared.clone(), shared.clone()];
+    let expected = value.get_heap_size_with_tracker(StandardTracker::default()).0;
+
+    let (size, owned) = measure(&value, StandardTracker::default());
+    assert_eq!(size, expected);
+    assert_eq!(owned.visited_count(), 1);
+
+    let mut borrowed = StandardTracker::default();
+    assert_eq!(measure(&value, &mut borrowed).0, expected);
+    assert_eq!(measure(&value, &mut &mut borrowed).0, expected - shared.get_heap_size());
+
+    let mut boxed = Box::new(StandardTracker::default());
+    assert_eq!(measure(&value, &mut boxed).0, expected);
+    assert_eq!(measure(&value, boxed).0, expected - shared.get_heap_size());
+
+    let mut unsized_tracker: Box<TestUnsizedTracker<[bool]>> = Box::new(TestUnsizedTracker {
+        tracked: 0,
+        answers: [true],
+    });
+    // The tracker never reports objects as seen before, so the shared string gets counted twice.
+    assert_eq!(measure(&value, &mut *unsized_tracker).0, expected + shared.get_heap_size());
+    let (_, unsized_tracker) = measure(&value, unsized_tracker);
+    assert_eq!(unsized_tracker.tracked, 4);
+}
+
+#[test]
+fn tracker_scope() {
+    let interned = std::sync::Arc::new(String::from("interned"));
+    let tenant_a = std::sync::Arc::new(String::from("tenant a"));
+    let tenant_b = std::sync::Arc::new(String::from("tenant b"));
+
+    let first = vec![interned.clone(), tenant_a.clone()];
+    let second = vec![interned.clone(), tenant_a.clone(), tenant_b.clone()];
+    let stack = std::mem::size_of::<std::sync::Arc<String>>();
+
+    let mut tracker = ScopedTracker::new();
+    let interned_size = tracker.measure_shared(&interned);
+    assert_eq!(interned_size, interned.get_size());
+
+    let mut scope = tracker.push_scope();
+    assert_eq!(scope.measure(&first), first.get_size() - interned.get_heap_size());
+    assert_eq!(
+        scope.measure(&second),
+        second.get_heap_size() + std::mem::size_of::<Vec<u8>>() - interned.get_heap_size() - tenant_a.get_heap_size()
+    );
+    let total = scope.total();
+    assert_eq!(scope.pop(), total);
+
+    // Objects seen inside the popped scope are accounted for again, the interned string not.
+    let mut scope = tracker.push_scope();
+    assert_eq!(scope.measure(&first), first.get_size() - interned.get_heap_size());
+    drop(scope);
+
+    assert_eq!(tracker.measure(&tenant_b), tenant_b.get_size());
+    assert_eq!(tracker.measure(&interned), stack);
+}
+
+
+// These tests only use safe accesses to the measured values and are also run under Miri by the
+// `miri` workflow, e.g. locally by `cargo +nightly miri test --test mod interior_mutability`.
+#[test]
+fn interior_mutability() {
+    use std::cell::{Cell, OnceCell, RefCell, UnsafeCell};
+    use std::sync::OnceLock;
+    use std::sync::atomic::AtomicPtr;
+
+    let cells = vec![Cell::new(1u32), Cell::new(2u32)];
+    cells[0].set(3);
+    assert_eq!(cells.get_heap_size(), 2 * std::mem::size_of::<Cell<u32>>());
+
+    let once = OnceCell::new();
+    assert_eq!(once.get_heap_size(), 0);
+    once.set(String::from("Hello")).unwrap();
+    assert_eq!(once.get_heap_size(), 5);
+
+    let lock = OnceLock::new();
+    assert_eq!(lock.get_heap_size(), 0);
+    lock.set(vec![0u8; 3]).unwrap();
+    assert_eq!(lock.get_heap_size(), 3);
+
+    let cell = RefCell::new(String::from("Hello"));
+    assert_eq!(cell.get_heap_size(), 5);
+    {
+        let mut value = cell.borrow_mut();
+        value.push_str(" world!");
+        assert_eq!(cell.get_heap_size(), 0);
+    }
+    assert!(cell.get_heap_size() >= 12);
+
+    let mut value = String::from("Hello");
+    let ptr = AtomicPtr::new(&mut value as *mut String);
+    assert_eq!(ptr.get_heap_size(), 0);
+
+    let unsafe_cell = UnsafeCell::new(String::from("Hello"));
+    assert_eq!(unsafe_cell.get_heap_size(), 0);
+
+    // Measure while other threads keep modifying the values. Miri is far slower, so it runs fewer rounds.
+    let rounds = if cfg!(miri) { 5 } else { 50 };
+    let shared = std::sync::Arc::new((
+        std::sync::Mutex::new(Vec::<u64>::new()),
+        std::sync::atomic::AtomicUsize::new(0),
+    ));
+
+    let handles: Vec<_> = (0..2).map(|_| {
+        let shared = shared.clone();
+
+        std::thread::spawn(move || {
+            for i in 0..rounds {
+                shared.0.lock().unwrap().push(i);
+                shared.1.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        })
+    }).collect();
+
+    for _ in 0..rounds {
+        let size = shared.0.get_heap_size();
+        assert_eq!(size % std::mem::size_of::<u64>(), 0);
+        assert_eq!(shared.1.get_heap_size(), 0);
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert!(shared.0.get_heap_size() >= 2 * rounds as usize * std::mem::size_of::<u64>());
+}
+
+
+#[test]
+fn size_watchdog() {
+    use std::sync::{Arc, Mutex, mpsc};
+    use std::time::Duration;
+
+    let values = Arc::new(Mutex::new(Vec::<u64>::new()));
+    let (sender, receiver) = mpsc::channel();
+
+    let measured = values.clone();
+    let watchdog = SizeWatchdog::with_callback(
+        Duration::from_millis(1),
+        64,
+        move || measured.lock().unwrap().get_heap_size(),
+        move |size| { let _ = sender.send(size); },
+    );
+
+    // Stay below the limit first.
+    values.lock().unwrap().reserve_exact(8);
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(watchdog.last_size(), 64);
+
+    values.lock().unwrap().reserve_exact(16);
+    assert!(receiver.recv_timeout(Duration::from_secs(10)).unwrap() > 64);
+
+    watchdog.stop();
+
+    // The callback got dropped together with the stopped thread.
+    while receiver.try_recv().is_ok() {}
+    assert!(receiver.recv().is_err());
+}
+
+
+#[derive(GetSize)]
+#[get_size(layout)]
+pub enum TestEnumLayout<'a, T> {
+    Empty,
+    Small(u8),
+    Large { value: u64, other: u32 },
+    #[get_size(ignore)]
+    Generic(T),
+    Reference(&'a u8),
+}
+
+#[derive(GetSize)]
+#[get_size(layout)]
+pub enum TestEnumNiche<'a> {
+    None,
+    Some(&'a u64),
+}
+
+#[derive(GetSize)]
+#[get_size(layout)]
+pub enum TestEnumEmpty {}
+
+#[test]
+fn enum_layout() {
+    type Layout<'a> = TestEnumLayout<'a, u16>;
+
+    // The discriminant fits into the padding of the `Large` variant.
+    assert_eq!(std::mem::size_of::<Layout>(), 16);
+    assert_eq!(Layout::variant_stack_overhead(), 0);
+
+    assert_eq!(Layout::Empty.variant_payload_size(), 0);
+    assert_eq!(Layout::Empty.wasted_stack_size(), 16);
+    assert_eq!(Layout::Small(1).wasted_stack_size(), 15);
+    assert_eq!(Layout::Large { value: 1, other: 2 }.variant_payload_size(), 16);
+    assert_eq!(Layout::Generic(1).variant_payload_size(), 2);
+
+    assert_eq!(TestEnumNiche::variant_stack_overhead(), 0);
+    assert_eq!(TestEnumNiche::None.wasted_stack_size(), 8);
+    assert_eq!(TestEnumNiche::Some(&1).wasted_stack_size(), 0);
+
+    // Empty enums can not be instantiated, but expose their layout nevertheless.
+    assert_eq!(TestEnumEmpty::variant_stack_overhead(), 0);
+    assert!(TestEnumEmpty::variant_stack_sizes().is_empty());
+
+    let pointer_size = std::mem::size_of::<&u8>();
+    assert_eq!(Layout::variant_stack_sizes(), vec![
+        ("Empty", 0),
+        ("Small", 1),
+        ("Large", 16),
+        ("Generic", 2),
+        ("Reference", pointer_size),
+    ]);
+    assert_eq!(enum_variant_stack_sizes::<Layout>(), vec![
+        ("Large", 16),
+        ("Reference", pointer_size),
+        ("Generic", 2),
+        ("Small", 1),
+        ("Empty", 0),
+    ]);
+}
+
+
+#[test]
+fn tracker_introspection() {
+    let shared = std::sync::Arc::new(String::from("Hello"));
+    let value = TestStructShared {
+        first: shared.clone(),
+        second: std::sync::Arc::new(String::from("world")),
+    };
+
+    let mut tracker = StandardTracker::new();
+    assert_eq!(tracker.visited_count(), 0);
+    assert_eq!(tracker.get_heap_size(), 0);
+
+    value.get_heap_size_with_tracker(&mut tracker);
+    assert_eq!(tracker.visited_count(), 2);
+
+    // The kept strong references are stored as type erased pointers together with their kind.
+    let entry_size = std::mem::size_of::<TrackKey>()
+        + std::mem::size_of::<Box<dyn std::any::Any>>()
+        + std::mem::size_of::<std::sync::Arc<dyn std::any::Any>>()
+        + std::mem::size_of::<usize>();
+    assert_eq!(tracker.get_heap_size(), 2 * entry_size);
+
+    let mut scoped = ScopedTracker::new();
+    scoped.measure_shared(&shared);
+    assert_eq!(scoped.visited_count(), 1);
+    assert_eq!(scoped.get_heap_size(), entry_size);
+
+    assert_eq!(NoTracker::new(true).visited_count(), 0);
+    assert_eq!(NoTracker::new(true).get_heap_size(), 0);
+    assert_eq!(SizeContext::new().get_heap_size(), 0);
+}
+
+
+#[derive(GetSize)]
+#[get_size(delegate_to = "inner")]
+pub struct TestStructDelegate<K, V> {
+    inner: std::collections::HashMap<K, V>,
+    hits: u64,
+    name: String,
+}
+
+#[derive(GetSize)]
+#[get_size(delegate_to = "inner", delegate_extras(name))]
+pub struct TestStructDelegateExtra {
+    inner: Vec<String>,
+    name: String,
+    #[get_size(size = 100)]
+    metadata: String,
+}
+
+#[derive(GetSize)]
+#[get_size(delegate_to = "1")]
+pub struct TestStructDelegateTuple(String, Vec<u8>);
+
+#[test]
+fn derive_delegate_to() {
+    let mut inner = std::collections::HashMap::new();
+    inner.insert(1u8, String::from("Hello"));
+
+    let value = TestStructDelegate {
+        inner,
+        hits: 0,
+        name: String::from("cache"),
+    };
+    assert_eq!(value.hits, 0);
+    assert_eq!(value.name, "cache");
+    assert_eq!(value.get_heap_size(), value.inner.get_heap_size());
+
+    let value = TestStructDelegateExtra {
+        inner: vec![String::from("a")],
+        name: String::from("Hello"),
+        metadata: String::from("ignored"),
+    };
+    assert_eq!(value.metadata, "ignored");
+    assert_eq!(value.get_heap_size(), value.inner.get_heap_size() + 5);
+
+    let value = TestStructDelegateTuple(String::from("ignored"), Vec::with_capacity(10));
+    assert_eq!(value.0, "ignored");
+    assert_eq!(value.get_heap_size(), 10);
+}
+
+
+#[derive(GetSize)]
+#[get_size(extra = 4096, extra_kind = "gpu", fallible)]
+pub struct TestStructExtra {
+    name: String,
+}
+
+#[derive(GetSize)]
+#[get_size(extra_fn = "test_mapped_len")]
+pub struct TestStructExtraFn(u64);
+
+fn test_mapped_len(value: &TestStructExtraFn) -> usize {
+    value.0 as usize
+}
+
+#[derive(GetSize)]
+#[get_size(extra = 100)]
+pub enum TestEnumExtra {
+    Empty,
+    Named(String),
+}
+
+#[test]
+fn derive_extra_size() {
+    // GPU memory is only listed by reports, but not part of the heap size.
+    let value = TestStructExtra { name: String::from("abc") };
+    assert_eq!(value.get_heap_size(), 3);
+    assert_eq!(value.get_heap_size_as::<u64>(), 3);
+    assert_eq!(value.try_get_heap_size(&mut SizeContext::new()), Ok(3));
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.entries(), &[(String::from("name"), 3), (String::from("<external>"), 4096)]);
+    assert_eq!(report.source("<external>"), Some(SizeSource::External(ResourceKind::Gpu)));
+    assert_eq!(report.kind("<external>"), ResourceKind::Gpu);
+    assert_eq!(report.kind("name"), ResourceKind::Heap);
+    assert_eq!(report.size_of_kind(ResourceKind::Gpu), 4096);
+    assert_eq!(report.ram_size(), std::mem::size_of::<TestStructExtra>() + 3);
+    assert!(report.to_string().contains("gpu total: 4.0 KiB\n"));
+
+    assert_eq!(TestStructExtraFn(1024).get_heap_size(), 1024);
+    assert_eq!(vec![TestStructExtraFn(10), TestStructExtraFn(20)].get_heap_size(), 2 * 8 + 30);
+
+    assert_eq!(TestEnumExtra::Empty.get_heap_size(), 100);
+    assert_eq!(TestEnumExtra::Named(String::from("abc")).get_heap_size(), 103);
+    assert_eq!(TestEnumExtra::Empty.get_heap_size_as::<u64>(), 100);
+    assert_eq!(SizeReport::of(&TestEnumExtra::Empty).entries(), &[(String::from("<external>"), 100)]);
+    assert_eq!(SizeReport::of(&TestEnumExtra::Empty).kind("<external>"), ResourceKind::Heap);
+}
+
+
+#[derive(GetSize)]
+pub struct TestStructBorrowed<'a> {
+    name: &'a str,
+    values: &'a [u16],
+    shared: std::sync::Arc<String>,
+}
+
+#[test]
+fn sizing_context() {
+    let name = String::from("Hello");
+    let values = vec![1u16, 2, 3];
+    let shared = std::sync::Arc::new(String::from("world"));
+
+    let value = TestStructBorrowed {
+        name: &name,
+        values: &values,
+        shared: shared.clone(),
+    };
+
+    let mut ctx = SizingContext::named("test");
+    assert_eq!(ctx.name(), Some("test"));
+    assert_eq!(ctx.policies(), SizingPolicies::default());
+    assert_eq!(ctx.measure(&value), Ok(value.get_size()));
+
+    // The shared string has already been seen.
+    assert_eq!(ctx.measure(&value), Ok(std::mem::size_of::<TestStructBorrowed>()));
+    assert_eq!(ctx.used(), 2 * std::mem::size_of::<TestStructBorrowed>() + shared.get_heap_size());
+    assert_eq!(ctx.visited_count(), 1);
+
+    let mut policies = SizingPolicies::new();
+    policies.set_count_borrowed(true);
+    policies.set_allocator(AllocatorModel::PerAllocationOverhead(16));
+
+    let mut ctx = SizingContext::with_tracker(StandardTracker::new());
+    ctx.set_policies(policies);
+    ctx.set_budget(Some(1000));
+
+    // Borrowed data counts once, while the shared object and its string buffer are two
+    // allocations, which cause 16 bytes of overhead each.
+    let expected = value.get_size() + 5 + 6 + 2 * 16;
+    assert_eq!(ctx.measure(&value), Ok(expected));
+    assert_eq!(ctx.remaining(), Some(1000 - expected));
+
+    ctx.set_budget(Some(expected));
+    let other = String::from("Too large");
+    assert_eq!(ctx.measure(&other), Err(SizeError::BudgetExceeded));
+    assert_eq!(ctx.used(), expected);
+
+    // Allocations not involving shared ownership are accounted for as well.
+    let nested = vec![Box::new(1u64), Box::new(2u64)];
+    let mut ctx = SizingContext::new();
+    ctx.set_policies(policies);
+    assert_eq!(ctx.measure(&nested), Ok(nested.get_size() + 3 * 16));
+    assert_eq!(ctx.measure(&Vec::<u64>::new()), Ok(std::mem::size_of::<Vec<u64>>()));
+
+    let mut policies = SizingPolicies::new();
+    policies.set_weak(WeakPolicy::IgnoreWeak);
+    let mut ctx = SizingContext::new();
+    ctx.set_policies(policies);
+    assert_eq!(ctx.context(|context| context.policies().weak), WeakPolicy::IgnoreWeak);
+    let weak = std::sync::Arc::downgrade(&shared);
+    assert_eq!(ctx.measure(&weak), Ok(std::mem::size_of_val(&weak)));
+
+    // Borrowed elements of collections are accounted for as well.
+    let names = vec!["abc", "de"];
+    let mut lookup = std::collections::HashMap::new();
+    lookup.insert(&values[..], 1u8);
+
+    let mut policies = SizingPolicies::new();
+    policies.set_count_borrowed(true);
+    let mut ctx = SizingContext::new();
+    ctx.set_policies(policies);
+    assert_eq!(ctx.measure(&names), Ok(names.get_size() + 5));
+    assert_eq!(ctx.measure(&lookup), Ok(lookup.get_size() + std::mem::size_of_val(&values[..])));
+
+    // Strings only account for their length if requested by the policies.
+    let mut text = String::with_capacity(16);
+    text.push_str("Hello");
+    let mut policies = SizingPolicies::new();
+    policies.set_strings(StringPolicy::Len);
+    let mut ctx = SizingContext::new();
+    ctx.set_policies(policies);
+    let texts = vec![text];
+    assert_eq!(ctx.measure(&texts), Ok(std::mem::size_of::<Vec<String>>() + std::mem::size_of::<String>() + 5));
+    assert_eq!(texts[0].get_heap_size(), 16);
+}
+
+
+#[test]
+fn iterator_buffers() {
+    let values = vec![String::from("a"), String::from("bc"), String::from("def")];
+
+    let iter = values.clone().into_iter();
+    assert_eq!(iter.get_heap_size(), 3 * std::mem::size_of::<String>() + 6);
+
+    let mut buffer = PeekBuffer::new(values.into_iter());
+    assert_eq!(buffer.peek().map(String::as_str), Some("a"));
+    assert_eq!(buffer.peek_nth(2).map(String::as_str), Some("def"));
+    assert_eq!(buffer.peek_nth(3), None);
+    assert_eq!(buffer.size_hint(), (3, Some(3)));
+
+    // All elements are buffered now.
+    let heap_size = buffer.get_heap_size();
+    assert!(heap_size >= 3 * std::mem::size_of::<String>() + 6);
+
+    assert_eq!(buffer.next().as_deref(), Some("a"));
+    assert_eq!(buffer.buffered().count(), 2);
+    assert_eq!(buffer.collect::<Vec<_>>(), vec![String::from("bc"), String::from("def")]);
+
+    let peekable = vec![1u8, 2].into_iter().peekable();
+    assert_eq!(peekable.get_heap_size(), 0);
+    assert_eq!(vec![1u8].into_iter().fuse().get_heap_size(), 0);
+}
+
+
+#[test]
+fn get_size_obj() {
+    let shared = std::sync::Arc::new(String::from("Hello"));
+    let first = TestStructShared {
+        first: shared.clone(),
+        second: shared.clone(),
+    };
+    let second = vec![1u32, 2, 3];
+    let third: Option<std::sync::Arc<String>> = Some(shared.clone());
+
+    let objs: Vec<&dyn GetSizeObj> = vec![&first, &second, &third];
+
+    assert_eq!(objs[1].get_stack_size_obj(), std::mem::size_of::<Vec<u32>>());
+    assert_eq!(objs[1].get_heap_size_obj(), 12);
+
+    let mut tracker = StandardTracker::new();
+    let sizes = measure_all(&objs, &mut tracker);
+
+    // The option shares the string already accounted for by the first object.
+    assert_eq!(sizes, vec![first.get_size(), second.get_size(), std::mem::size_of_val(&third)]);
+    assert_eq!(tracker.visited_count(), 1);
+
+    let boxed: Box<dyn GetSizeObj> = Box::new(String::from("abc"));
+    assert_eq!(boxed.get_size_obj(), std::mem::size_of::<String>() + 3);
+}
+
+
+#[derive(GetSize)]
+#[allow(dead_code)]
+pub struct TestStructCapacity {
+    names: Vec<String>,
+    label: String,
+    #[get_size(ignore)]
+    ignored: String,
+    nested: Option<TestEnumCapacity>,
+}
+
+#[derive(GetSize)]
+pub enum TestEnumCapacity {
+    Buffer { data: Vec<u8>, #[get_size(size = 8)] fixed: String },
+    Text(String),
+    Empty,
+}
+
+#[test]
+fn wasted_capacity() {
+    let mut names = Vec::with_capacity(4);
+    names.push(String::with_capacity(10));
+    names[0].push_str("abc");
+
+    let mut data = Vec::with_capacity(64);
+    data.extend_from_slice(&[0u8; 16]);
+
+    let test = TestStructCapacity {
+        names,
+        label: String::from("exact"),
+        ignored: String::with_capacity(100),
+        nested: Some(TestEnumCapacity::Buffer { data, fixed: String::with_capacity(100) }),
+    };
+
+    let names_wasted = 3 * std::mem::size_of::<String>() + 7;
+    assert_eq!(test.names.wasted_capacity(), names_wasted);
+    assert_eq!(test.label.wasted_capacity(), 0);
+    assert_eq!(test.wasted_capacity(), names_wasted + 48);
+
+    let report = CapacityReport::of(&test);
+
+    assert_eq!(report.total(), test.wasted_capacity());
+    assert_eq!(report.entries().len(), 2);
+    assert_eq!(report.top(1), vec![("names", names_wasted)]);
+    assert_eq!(report.top(5)[1], ("nested.Buffer.data", 48));
+
+    let mut report = CapacityReport::new();
+    TestEnumCapacity::Text(String::with_capacity(5)).report_wasted_capacity("root", &mut report);
+    assert_eq!(report.entries(), &[(String::from("root.Text.0"), 5)]);
+
+    assert_eq!(TestEnumCapacity::Empty.wasted_capacity(), 0);
+    assert_eq!(Box::new(String::with_capacity(3)).wasted_capacity(), 3);
+}
+
+
+#[derive(GetSize)]
+pub struct TestStructPayloads {
+    tags: Vec<String>,
+    owner: Option<String>,
+    path: Box<str>,
+    #[get_size(ignore)]
+    #[allow(dead_code)]
+    ignored: String,
+    kind: TestEnumPayloads,
+}
+
+#[derive(GetSize)]
+pub enum TestEnumPayloads {
+    Named { name: String },
+    Unnamed(#[get_size(size = 0)] String),
+}
+
+#[test]
+fn dedup_estimator() {
+    let test = TestStructPayloads {
+        tags: vec![String::from("admin"), String::from("admin"), String::from("user")],
+        owner: Some(String::from("user")),
+        path: Box::from("admin"),
+        ignored: String::from("admin"),
+        kind: TestEnumPayloads::Named { name: String::from("user") },
+    };
+
+    let estimator = DedupEstimator::of(&test);
+
+    assert_eq!(estimator.payloads(), 6);
+    assert_eq!(estimator.unique_payloads(), 2);
+    assert_eq!(estimator.duplicates(), 4);
+    assert_eq!(estimator.payload_bytes(), 5 + 5 + 4 + 4 + 5 + 4);
+    assert_eq!(estimator.savings(), 5 + 4 + 5 + 4);
+
+    let mut estimator = DedupEstimator::new();
+    TestEnumPayloads::Unnamed(String::from("user")).record_payloads(&mut estimator);
+    String::new().record_payloads(&mut estimator);
+    assert_eq!(estimator.payloads(), 0);
+
+    // Shared ownership objects are already deduplicated.
+    let shared = std::sync::Arc::new(String::from("admin"));
+    let estimator = DedupEstimator::of(&vec![shared.clone(), shared]);
+    assert_eq!(estimator.payloads(), 0);
+}
+
+
+#[derive(GetSize)]
+#[get_size(padding)]
+pub struct TestStructPadding {
+    flag: u8,
+    value: u64,
+    inner: Vec<TestStructPaddingInner>,
+    #[get_size(ignore)]
+    #[allow(dead_code)]
+    ignored: (u8, u32),
+}
+
+#[derive(GetSize)]
+#[get_size(padding)]
+pub struct TestStructPaddingInner(u16, u32);
+
+#[derive(GetSize)]
+#[get_size(padding)]
+pub enum TestEnumPadding {
+    Pair(u8, u64),
+    Nested { inner: Box<TestStructPaddingInner> },
+    Empty,
+}
+
+#[derive(GetSize)]
+#[get_size(padding)]
+pub enum TestEnumPaddingEmpty {}
+
+#[test]
+fn get_padding() {
+    let test = TestStructPadding {
+        flag: 1,
+        value: 2,
+        inner: vec![TestStructPaddingInner(1, 2), TestStructPaddingInner(3, 4)],
+        ignored: (1, 2),
+    };
+
+    // The struct itself has 7 bytes of padding after `flag` and each inner value 2 bytes.
+    assert_eq!(std::mem::size_of::<TestStructPadding>(), 48);
+    assert_eq!(test.get_padding_size(), 7 + 2 * 2);
+
+    assert_eq!(TestEnumPadding::Pair(1, 2).get_padding_size(), 7);
+    assert_eq!(TestEnumPadding::Nested { inner: Box::new(TestStructPaddingInner(1, 2)) }.get_padding_size(), 2);
+    assert_eq!(TestEnumPadding::Empty.get_padding_size(), 0);
+
+    assert_eq!((1u8, 2u32, 3u8).get_padding_size(), 2);
+    assert_eq!([(1u8, 2u16); 3].get_padding_size(), 3);
+    assert_eq!(Some(String::new()).get_padding_size(), 0);
+}
+
+
+#[test]
+fn byte_buffers() {
+    let mut blobs: Vec<Vec<u8>> = Vec::with_capacity(4);
+    blobs.push(vec![0; 100]);
+    blobs.push(Vec::with_capacity(50));
+    assert_eq!(buffers_heap_size(&blobs), blobs.get_heap_size());
+    assert_eq!(buffers_heap_size(&blobs), 4 * std::mem::size_of::<Vec<u8>>() + 150);
+
+    let names: Vec<String> = vec!["Hello".into(), String::with_capacity(10)];
+    assert_eq!(buffers_heap_size(&names), names.get_heap_size());
+
+    let boxed: Vec<Box<str>> = vec!["Hello".into()];
+    assert_eq!(buffers_heap_size(&boxed), boxed.get_heap_size());
+
+    let mut map: std::collections::HashMap<String, Vec<u8>> = std::collections::HashMap::new();
+    map.insert("first".into(), vec![1; 32]);
+    map.insert("second".into(), Vec::with_capacity(8));
+    assert_eq!(buffer_map_heap_size(&map), map.get_heap_size());
+}
+
+
+#[test]
+fn map_breakdown() {
+    let mut map: std::collections::HashMap<String, Vec<u8>> = std::collections::HashMap::with_capacity(8);
+    map.insert(String::from("key"), vec![0; 100]);
+
+    let breakdown = map_heap_breakdown(&map);
+    assert_eq!(breakdown.keys, std::mem::size_of::<String>() + 3);
+    assert_eq!(breakdown.values, std::mem::size_of::<Vec<u8>>() + 100);
+    assert_eq!(
+        breakdown.table_overhead,
+        (map.capacity() - 1) * (std::mem::size_of::<String>() + std::mem::size_of::<Vec<u8>>()),
+    );
+    assert_eq!(breakdown.total(), map.get_heap_size());
+
+    let map: std::collections::BTreeMap<u8, String> = [(1, String::from("a")), (2, String::from("bc"))].into();
+    let breakdown = map_heap_breakdown(&map);
+    assert_eq!(breakdown.keys, 2);
+    assert_eq!(breakdown.values, 2 * std::mem::size_of::<String>() + 3);
+    assert_eq!(breakdown.table_overhead, 0);
+    assert_eq!(breakdown.total(), map.get_heap_size());
+}
+
+
+#[derive(GetSize)]
+#[get_size(tracker = "NoTracker::new(true)")]
+pub struct TestStructCustomTracker {
+    first: std::sync::Arc<String>,
+    second: std::sync::Arc<String>,
+}
+
+#[derive(GetSize)]
+#[get_size(tracker = "StandardTracker::with_weak_policy(WeakPolicy::IgnoreWeak)")]
+pub enum TestEnumCustomTracker {
+    Weak(std::rc::Weak<String>),
+}
+
+#[test]
+fn derive_custom_tracker() {
+    let shared = std::sync::Arc::new(String::from("Hello"));
+    let test = TestStructCustomTracker {
+        first: shared.clone(),
+        second: shared.clone(),
+    };
+
+    // A tracker which never remembers anything accounts for the shared value twice.
+    assert_eq!(test.get_heap_size(), 2 * (std::mem::size_of::<String>() + 5));
+
+    let (size, _) = test.get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(size, std::mem::size_of::<String>() + 5);
+
+    let parent = std::rc::Rc::new(String::from("Hello"));
+    let test = TestEnumCustomTracker::Weak(std::rc::Rc::downgrade(&parent));
+    assert_eq!(test.get_heap_size(), 0);
+}
+
+
+mod third_party {
+    pub enum Maybe<T> {
+        Just(T),
+        Nothing,
+    }
+
+    pub enum Either<L, R> {
+        Left(L),
+        Right(R),
+    }
+
+    pub struct Stack<T> {
+        pub items: Vec<T>,
+    }
+
+    impl<T> Stack<T> {
+        pub fn capacity(&self) -> usize {
+            self.items.capacity()
+        }
+
+        pub fn len(&self) -> usize {
+            self.items.len()
+        }
+
+        pub fn iter(&self) -> std::slice::Iter<'_, T> {
+            self.items.iter()
+        }
+    }
+
+    // A map with a hasher and a trailing allocator-like parameter.
+    pub struct Table<K, V, S = std::collections::hash_map::RandomState, A = ()> {
+        pub map: std::collections::HashMap<K, V, S>,
+        pub alloc: std::marker::PhantomData<A>,
+    }
+
+    impl<K, V, S, A> Table<K, V, S, A> {
+        pub fn capacity(&self) -> usize {
+            self.map.capacity()
+        }
+
+        pub fn len(&self) -> usize {
+            self.map.len()
+        }
+
+        pub fn iter(&self) -> std::collections::hash_map::Iter<'_, K, V> {
+            self.map.iter()
+        }
+
+        pub fn hasher(&self) -> &S {
+            self.map.hasher()
+        }
+    }
+}
+
+get_size::impl_size_option_like!(third_party::Maybe, Just, Nothing);
+get_size::impl_size_result_like!(third_party::Either, Left, Right);
+get_size::impl_size_set!(third_party::Stack);
+get_size::impl_size_map!([K, V, S: GetSize, A: Clone] third_party::Table<K, V, S, A>, hasher);
+
+#[test]
+fn shape_macros() {
+    use third_party::{Either, Maybe};
+
+    assert_eq!(Maybe::Just(String::from("Hello")).get_heap_size(), 5);
+    assert_eq!(Maybe::<String>::Nothing.get_heap_size(), 0);
+    assert_eq!(Maybe::Just(vec![0u8; 10]).get_size_within(10), None);
+
+    let values = [Either::Left(String::from("abc")), Either::Right(vec![1u32, 2])];
+    assert_eq!(values.get_heap_size(), 3 + 8);
+
+    let shared = std::sync::Arc::new(String::from("Hello"));
+    let test = TestStructShared {
+        first: shared.clone(),
+        second: shared.clone(),
+    };
+    let (size, _) = Maybe::Just(test).get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(size, std::mem::size_of::<String>() + 5);
+
+    let flags = [<Maybe<u8> as GetSize>::CAN_ALLOCATE_HEAP, <Either<u8, u8> as GetSize>::CAN_ALLOCATE_HEAP];
+    assert_eq!(flags, [false, false]);
+}
+
+pub struct TestFfiContext(*mut std::ffi::c_void);
+
+pub struct TestFfiBuffer<T>(std::ptr::NonNull<T>);
+
+get_size::impl_size_opaque!(TestFfiContext);
+get_size::impl_size_opaque!([T] TestFfiBuffer<T>);
+
+#[derive(GetSize)]
+pub struct TestFfiStruct {
+    context: TestFfiContext,
+    buffer: TestFfiBuffer<u8>,
+    thread: std::thread::ThreadId,
+    name: String,
+}
+
+#[test]
+fn opaque_macro() {
+    let byte = 0u8;
+
+    let value = TestFfiStruct {
+        context: TestFfiContext(std::ptr::null_mut()),
+        buffer: TestFfiBuffer(std::ptr::NonNull::from(&byte)),
+        thread: std::thread::current().id(),
+        name: String::from("ffi"),
+    };
+    assert!(value.context.0.is_null());
+    assert_eq!(value.buffer.0, std::ptr::NonNull::from(&byte));
+    assert_eq!(value.get_heap_size(), 3);
+
+    assert_eq!(std::ptr::NonNull::from("unsized").get_heap_size(), 0);
+    assert_eq!(vec![TestFfiContext(std::ptr::null_mut())].get_heap_size(), std::mem::size_of::<TestFfiContext>());
+}
+
+#[test]
+fn collection_macros() {
+    use third_party::{Stack, Table};
+
+    let stack = Stack { items: vec![String::from("Hello"), String::from("World")] };
+    assert_eq!(stack.get_heap_size(), stack.capacity() * std::mem::size_of::<String>() + 10);
+    assert_eq!(stack.wasted_capacity(), 0);
+
+    let mut map = std::collections::HashMap::new();
+    map.insert(1u64, String::from("Hello"));
+    let table: Table<_, _, _, ()> = Table { map, alloc: std::marker::PhantomData };
+
+    let entry_size = std::mem::size_of::<u64>() + std::mem::size_of::<String>();
+    assert_eq!(table.get_heap_size(), table.capacity() * entry_size + 5);
+    assert_eq!(table.get_heap_size_within(table.get_heap_size()), Some(table.get_heap_size()));
+}
+
+mod wrappers {
+    pub struct UserName(pub String);
+
+    impl AsRef<String> for UserName {
+        fn as_ref(&self) -> &String {
+            &self.0
+        }
+    }
+
+    pub struct UserId(pub u64);
+
+    impl AsRef<u64> for UserId {
+        fn as_ref(&self) -> &u64 {
+            &self.0
+        }
+    }
+}
+
+get_size::impl_size_via_inner!(wrappers::UserName => String, wrappers::UserId => u64);
+
+#[test]
+fn via_inner_macro() {
+    use wrappers::{UserId, UserName};
+
+    let mut name = String::with_capacity(10);
+    name.push_str("Hello");
+    let name = UserName(name);
+
+    assert_eq!(name.get_heap_size(), 10);
+    assert_eq!(name.get_size(), std::mem::size_of::<String>() + 10);
+    assert_eq!(name.wasted_capacity(), 5);
+    assert_eq!(name.get_size_within(5), None);
+    assert_eq!(UserId(1).get_size(), 8);
+
+    let report = SizeReport::of(&name);
+    assert_eq!(report.entries(), &[(String::new(), 10)]);
+
+    let flags = [<UserName as GetSize>::CAN_ALLOCATE_HEAP, <UserId as GetSize>::CAN_ALLOCATE_HEAP];
+    assert_eq!(flags, [true, false]);
+}
+
+
+#[test]
+fn extensions_sizer() {
+    use std::any::{Any, TypeId};
+    use std::collections::HashMap;
+
+    let mut sizer = ExtensionsSizer::new();
+    sizer.register::<String>();
+    sizer.register::<Vec<u64>>();
+    assert!(sizer.is_registered::<String>());
+    assert!(!sizer.is_registered::<u64>());
+
+    assert_eq!(sizer.heap_size_of_val(&String::from("Hello")), 5);
+    assert_eq!(sizer.heap_size_of_val(&vec![1u64, 2]), 16);
+    assert_eq!(sizer.heap_size_of_val(&1u64), 0);
+
+    sizer.set_fallback(64);
+    assert_eq!(sizer.fallback(), 64);
+    assert_eq!(sizer.heap_size_of_val(&1u64), 64);
+
+    let mut map: HashMap<TypeId, Box<dyn Any + Send + Sync>> = HashMap::new();
+    map.insert(TypeId::of::<String>(), Box::new(String::from("abc")));
+    map.insert(TypeId::of::<u32>(), Box::new(1u32));
+
+    let expected = map.capacity() * std::mem::size_of::<(TypeId, Box<dyn Any + Send + Sync>)>()
+        + std::mem::size_of::<String>() + 3
+        + 4 + 64;
+    assert_eq!(sizer.heap_size_of_map(&map), expected);
+}
+
+#[test]
+fn high_watermark() {
+    let vec_size = std::mem::size_of::<Vec<u64>>();
+
+    let mut values = HighWatermark::new(Vec::<u64>::new());
+    assert_eq!(values.size(), vec_size);
+    assert_eq!(values.peak_size(), vec_size);
+
+    values.modify(|values| values.extend([1, 2, 3, 4]));
+    assert_eq!(values.size(), values.get_ref().get_size());
+    let peak = values.size();
+
+    {
+        let mut guard = values.get_mut();
+        guard.clear();
+        guard.shrink_to_fit();
+    }
+    assert_eq!(values.size(), vec_size);
+    assert_eq!(values.peak_size(), peak);
+    assert_eq!(values.get_heap_size(), 0);
+
+    assert_eq!(values.reset_peak(), peak);
+    assert_eq!(values.peak_size(), vec_size);
+
+    // Changes through interior mutability are only seen on demand.
+    let mut shared = HighWatermark::new(std::cell::RefCell::new(String::new()));
+    shared.borrow_mut().reserve_exact(10);
+    assert_eq!(shared.get_heap_size(), 0);
+    let capacity = shared.borrow().capacity();
+    assert_eq!(shared.measure(), std::mem::size_of::<std::cell::RefCell<String>>() + capacity);
+    assert_eq!(shared.get_heap_size(), capacity);
+}
+
+#[test]
+fn progress_tracker() {
+    let data: Vec<Vec<String>> = vec![vec![String::from("Hello"); 10]; 10];
+
+    let mut reports = Vec::new();
+    let mut tracker = ProgressTracker::new(|progress: Progress| {
+        reports.push(progress);
+        true
+    });
+    tracker.set_interval(11);
+
+    assert_eq!(tracker.measure(&data), Ok(data.get_size()));
+    drop(tracker);
+
+    // The inner strings and the outer vectors are visited.
+    assert_eq!(reports.len(), 11);
+    assert_eq!(reports.last().unwrap().elements, 110);
+    assert_eq!(reports.last().unwrap().bytes, data.get_size());
+
+    // The bytes only ever grow, without counting nested elements twice.
+    for window in reports.windows(2) {
+        assert!(window[0].bytes <= window[1].bytes);
+    }
+    assert!(reports[reports.len() - 2].bytes <= data.get_heap_size());
+
+    // Cancel through the hook.
+    let mut tracker = ProgressTracker::new(|progress: Progress| progress.elements<20);
+    tracker.set_interval(5);
+    assert_eq!(tracker.measure(&data), Err(SizeError::Cancelled));
+    assert_eq!(tracker.progress().elements, 20);
+
+    // Cancel through a token, which also stops derived types.
+    let token = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let mut tracker = ProgressTracker::new(|_: Progress| true);
+    tracker.set_cancel_token(token.clone());
+
+    let test = TestStruct {
+        value1: "Hello".into(),
+        value2: 123,
+    };
+    assert_eq!(tracker.measure(&test), Err(SizeError::Cancelled));
+
+    token.store(false, std::sync::atomic::Ordering::Relaxed);
+    assert_eq!(tracker.measure(&test), Ok(test.get_size()));
+}
+
+#[derive(GetSize)]
+struct TestOptionalRows {
+    rows: Option<Vec<Vec<String>>>,
+    boxed: Box<[String]>,
+}
+
+#[test]
+fn progress_tracker_wrappers() {
+    let data = TestOptionalRows {
+        rows: Some(vec![vec![String::from("Hello"); 10]; 10]),
+        boxed: vec![String::from("World"); 10].into_boxed_slice(),
+    };
+
+    // Elements behind an option and a box are reported as well.
+    let mut elements = 0;
+    let mut tracker = ProgressTracker::new(|progress: Progress| {
+        elements = progress.elements;
+        true
+    });
+    tracker.set_interval(1);
+    assert_eq!(tracker.measure(&data), Ok(data.get_size()));
+    drop(tracker);
+    assert_eq!(elements, 120);
+
+    // Cancel through an option field.
+    let mut tracker = ProgressTracker::new(|progress: Progress| progress.elements<20);
+    tracker.set_interval(5);
+    assert_eq!(tracker.measure(&data), Err(SizeError::Cancelled));
+    assert_eq!(tracker.progress().elements, 20);
+
+    // Unobserved measurements skip the bookkeeping for every element.
+    let mut tracker = StandardTracker::default();
+    let (size, tracker) = data.get_heap_size_with_tracker(&mut tracker);
+    assert_eq!(size, data.get_heap_size());
+    assert_eq!(tracker.context(|context| context.progress().elements), 0);
+}
+
+
+#[derive(GetSize)]
+struct TestConfig {
+    name: String,
+    blob: std::borrow::Cow<'static, [u8]>,
+    fallback: Option<std::borrow::Cow<'static, str>>,
+}
+
+#[test]
+fn borrow_report() {
+    static DEFAULT_BLOB: &[u8] = &[0; 64];
+
+    let borrowed = TestConfig {
+        name: String::from("default"),
+        blob: std::borrow::Cow::Borrowed(DEFAULT_BLOB),
+        fallback: Some(std::borrow::Cow::Borrowed("none")),
+    };
+    assert_eq!(borrowed.get_heap_size(), 7);
+    assert_eq!(borrowed.borrowed_size(), 64 + 4);
+
+    let owned = TestConfig {
+        name: String::from("custom"),
+        blob: std::borrow::Cow::Owned(vec![0; 32]),
+        fallback: None,
+    };
+    assert_eq!(owned.borrowed_size(), 0);
+
+    let mut report = BorrowReport::of(&borrowed);
+    report.record(&owned);
+    assert_eq!(report.owned(), 7 + 6 + 32);
+    assert_eq!(report.borrowed(), 68);
+    assert_eq!(report.total(), 113);
+
+    let mut merged = BorrowReport::new();
+    merged.merge(&report);
+    assert_eq!(merged, report);
+    assert_eq!(BorrowReport::new().borrowed_ratio(), 0.0);
+    assert_eq!(report.to_string(), "45 B owned, 68 B borrowed");
+
+    let configs = vec![borrowed, owned];
+    assert_eq!(configs.borrowed_size(), 68);
+}
+
+
+#[derive(GetSize)]
+#[allow(dead_code)]
+enum TestReportEnum {
+    Named { name: String, #[get_size(size_fn = double_len)] tags: Vec<u8> },
+    #[get_size(size = 50)]
+    External(u64),
+    Empty,
+}
+
+fn double_len(tags: &[u8]) -> usize {
+    2 * tags.len()
+}
+
+#[derive(GetSize)]
+#[allow(dead_code)]
+struct TestReportStruct {
+    kind: TestReportEnum,
+    cached: Option<Vec<u64>>,
+    #[get_size(ignore)]
+    skipped: Vec<u8>,
+}
+
+#[test]
+fn size_report() {
+    let value = TestReportStruct {
+        kind: TestReportEnum::Named { name: String::from("abc"), tags: vec![1, 2] },
+        cached: Some(vec![1, 2, 3]),
+        skipped: vec![0; 10],
+    };
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.entries(), &[
+        (String::from("kind.Named.name"), 3),
+        (String::from("kind.Named.tags"), 4),
+        (String::from("cached"), 24),
+    ]);
+    assert_eq!(report.heap_size(), value.get_heap_size());
+    assert_eq!(report.stack_size(), TestReportStruct::get_stack_size());
+    assert_eq!(report.total(), value.get_size());
+    assert_eq!(report.top(1), vec![("cached", 24)]);
+    assert!(report.system_context().is_none());
+    assert!(report.to_string().starts_with("cached: 24 B\n"));
+
+    let report = SizeReport::of(&TestReportEnum::External(1));
+    assert_eq!(report.entries(), &[(String::new(), 50)]);
+    assert_eq!(SizeReport::of(&TestReportEnum::Empty).heap_size(), 0);
+
+    let mut report = SizeReport::new();
+    report.set_system_context(Some(SystemContext {
+        process_rss: 1000,
+        ..SystemContext::default()
+    }));
+    report.add("cache", 250);
+    assert_eq!(report.system_context().unwrap().rss_share(report.total()), 0.25);
+    assert_eq!(report.to_string(), "cache: 250 B\ntotal: 250 B of 1000 B RSS (25.0%)\n");
+}
+
+#[derive(GetSize)]
+struct TestSourcesStruct {
+    index: std::collections::HashMap<u64, String>,
+    values: Vec<u8>,
+}
+
+#[test]
+fn size_report_sources() {
+    let mut index = std::collections::HashMap::new();
+    index.insert(1u64, String::from("abc"));
+
+    let report = SizeReport::of(&TestSourcesStruct { index, values: vec![1, 2] });
+    assert_eq!(report.source("index"), Some(SizeSource::Modeled));
+    assert_eq!(report.source("values"), None);
+    assert_eq!(report.sources(), &[(String::from("index"), SizeSource::Modeled)]);
+
+    let empty: std::collections::HashSet<u64> = std::collections::HashSet::new();
+    assert_eq!(SizeReport::of(&empty).source(""), None);
+}
+
+#[derive(GetSize)]
+struct TestBudgetStruct {
+    #[get_size(budget = 1_000)]
+    cache: Vec<u64>,
+    #[get_size(budget = 64)]
+    count: u64,
+    kind: TestBudgetEnum,
+}
+
+#[derive(GetSize)]
+enum TestBudgetEnum {
+    Named {
+        #[get_size(budget = 10)]
+        name: String,
+    },
+}
+
+#[test]
+fn size_report_budgets() {
+    let value = TestBudgetStruct {
+        cache: vec![0; 200],
+        count: 0,
+        kind: TestBudgetEnum::Named { name: String::from("abcdefghijkl") },
+    };
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.total(), value.get_size());
+    assert_eq!(report.budget("cache"), Some(1_000));
+    assert_eq!(report.budget("count"), Some(64));
+    assert_eq!(report.budget("kind"), None);
+    assert_eq!(report.size_at("kind"), 12);
+    assert_eq!(report.size_at("kind.Named"), 12);
+    assert_eq!(report.size_at("kin"), 0);
+    assert_eq!(report.over_budget(), vec![
+        OverBudget { path: String::from("cache"), budget: 1_000, size: 1_600 },
+        OverBudget { path: String::from("kind.Named.name"), budget: 10, size: 12 },
+    ]);
+
+    let rendered = report.render(&|bytes: usize| format!("{} B", bytes));
+    assert!(rendered.contains("cache: 1600 B over budget of 1000 B\n"));
+    assert!(rendered.contains("kind.Named.name: 12 B over budget of 10 B\n"));
+
+    let mut merged = SizeReport::new();
+    merged.set_budget("cache", 2_000);
+    merged.merge(&report);
+    assert_eq!(merged.budget("cache"), Some(2_000));
+    assert_eq!(merged.budget("count"), Some(64));
+    assert_eq!(merged.over_budget().len(), 1);
+}
+
+#[test]
+fn size_diff() {
+    let mut value = TestReportStruct {
+        kind: TestReportEnum::Named { name: String::from("abc"), tags: vec![1, 2] },
+        cached: None,
+        skipped: Vec::new(),
+    };
+    let before = SizeReport::of(&value);
+
+    value.cached = Some(vec![0; 256]);
+    value.kind = TestReportEnum::Named { name: String::from("abcd"), tags: vec![1, 2] };
+    let after = SizeReport::of(&value);
+
+    let mut diff = SizeDiff::between(&before, &after);
+    assert_eq!(diff.changes().len(), 3);
+    assert_eq!(diff.total().delta(), 2049);
+    assert_eq!(diff.significant()[0], &SizeChange { path: String::from("cached"), before: 0, after: 2048 });
+
+    diff.set_min_delta_percent(50.0);
+    let rendered = diff.to_string();
+    assert!(rendered.starts_with("field "));
+    assert!(rendered.contains("cached"));
+    assert!(!rendered.contains("kind.Named.name"));
+    assert!(!rendered.contains("kind.Named.tags"));
+    assert!(rendered.ends_with("(1 smaller change hidden)\n"));
+
+    let diff = SizeDiff::between(&after, &before);
+    assert_eq!(diff.significant()[0].delta(), -2048);
+    assert!(diff.render(&|bytes: usize| format!("{} B", bytes)).contains("-2048 B  -100.0%\n"));
+}
+
+#[test]
+fn unit_formatter() {
+    let mut formatter = UnitFormatter::new();
+    assert_eq!(formatter.format_size(1023), "1023 B");
+    assert_eq!(formatter.format_size(1536), format_size(1536));
+    assert_eq!(formatter.format_size(3 * 1024 * 1024), "3.0 MiB");
+
+    formatter.set_units(UnitSystem::Decimal);
+    assert_eq!(formatter.format_size(999), "999 B");
+    assert_eq!(formatter.format_size(1500), "1.5 kB");
+    assert_eq!(formatter.format_size(2_000_000_000), "2.0 GB");
+
+    formatter.set_precision(0);
+    assert_eq!(formatter.format_size(1500), "2 kB");
+
+    formatter.set_units(UnitSystem::Bytes);
+    formatter.set_thousands_separator(".");
+    formatter.set_decimal_separator(",");
+    assert_eq!(formatter.format_size(1_234_567), "1.234.567 B");
+    assert_eq!(formatter.format_size(123), "123 B");
+
+    let mut formatter = UnitFormatter::with_units(UnitSystem::Decimal);
+    formatter.set_precision(3);
+    formatter.set_thousands_separator(".");
+    formatter.set_decimal_separator(",");
+    assert_eq!(formatter.format_size(1_234_567_000_000_000_000), "1,235 EB");
+
+    let mut report = SizeReport::new();
+    report.add("cache", 1500);
+    assert_eq!(report.render(&UnitFormatter::with_units(UnitSystem::Decimal)), "cache: 1.5 kB\ntotal: 1.5 kB\n");
+    assert_eq!(report.render(&|bytes: usize| format!("{bytes} bytes")), "cache: 1500 bytes\ntotal: 1500 bytes\n");
+    assert_eq!(report.render(&UnitFormatter::new()), report.to_string());
+
+    let report = BorrowReport::new();
+    assert_eq!(report.render(&UnitFormatter::new()), report.to_string());
+}
+
+#[cfg(feature = "num-format")]
+#[test]
+fn unit_formatter_locale() {
+    let formatter = UnitFormatter::with_locale(&num_format::Locale::de);
+    assert_eq!(formatter.thousands_separator(), ".");
+    assert_eq!(formatter.decimal_separator(), ",");
+    assert_eq!(formatter.format_size(1536), "1,5 KiB");
+}
+
+#[test]
+fn collection_buffers() {
+    fn check<C: GetSize>(collection: &C, expected: usize) {
+        assert_eq!(collection.get_heap_size(), expected);
+        assert_eq!(GetSize::get_heap_size_with_tracker(collection, StandardTracker::default()).0, expected);
+        assert_eq!(collection.get_heap_size_within(expected), Some(expected));
+        assert_eq!(collection.get_heap_size_within(expected - 1), None);
+    }
+
+    let string_size = std::mem::size_of::<String>();
+
+    let mut vec = Vec::with_capacity(10);
+    vec.extend([String::from("abc"), String::from("defgh")]);
+    check(&vec, 10 * string_size + 8);
+
+    let mut deque = std::collections::VecDeque::with_capacity(10);
+    deque.extend([String::from("abc"), String::from("defgh")]);
+    check(&deque, deque.capacity() * string_size + 8);
+
+    let mut heap = std::collections::BinaryHeap::with_capacity(10);
+    heap.extend([String::from("abc"), String::from("defgh")]);
+    check(&heap, heap.capacity() * string_size + 8);
+
+    let mut map = std::collections::HashMap::with_capacity(10);
+    map.insert(String::from("abc"), vec![0u8; 10]);
+    map.insert(String::from("defgh"), Vec::new());
+    let entry_size = string_size + std::mem::size_of::<Vec<u8>>();
+    check(&map, map.capacity() * entry_size + 8 + 10);
+
+    let list = std::collections::LinkedList::from([String::from("abc"), String::from("defgh")]);
+    check(&list, 2 * string_size + 8);
+}
+
+#[test]
+fn custom_hashers() {
+    use std::collections::{HashMap, HashSet};
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{BuildHasher, BuildHasherDefault};
+
+    // Hashers like `FxBuildHasher` and `FnvBuildHasher` are aliases of `BuildHasherDefault`.
+    let mut map: HashMap<u64, String, BuildHasherDefault<DefaultHasher>> = HashMap::default();
+    map.insert(1, String::from("Hello"));
+    assert_eq!(map.get_heap_size(), map.capacity() * (8 + std::mem::size_of::<String>()) + 5);
+
+    let mut set: HashSet<u32, BuildHasherDefault<DefaultHasher>> = HashSet::default();
+    set.insert(1);
+    assert_eq!(set.get_heap_size(), set.capacity() * 4);
+
+    // A hasher state owning heap memory gets accounted for too.
+    #[derive(Clone, Default)]
+    struct SeededState {
+        seed: Vec<u8>,
+    }
+
+    impl BuildHasher for SeededState {
+        type Hasher = DefaultHasher;
+
+        fn build_hasher(&self) -> DefaultHasher {
+            DefaultHasher::new()
+        }
+    }
+
+    impl GetSize for SeededState {
+        fn get_heap_size(&self) -> usize {
+            GetSize::get_heap_size(&self.seed)
+        }
+    }
+
+    let mut map = HashMap::with_hasher(SeededState { seed: vec![0; 16] });
+    map.insert(1u64, 2u64);
+    let expected = map.capacity() * 16 + 16;
+    assert_eq!(map.get_heap_size(), expected);
+    assert_eq!(GetSize::get_heap_size_with_tracker(&map, StandardTracker::default()).0, expected);
+    assert_eq!(map.get_heap_size_within(expected - 1), None);
+}
+
+#[cfg(feature = "deepsize")]
+#[test]
+fn deepsize_compat() {
+    use deepsize::DeepSizeOf;
+
+    let value = ViaDeepSize(vec![String::from("abc"); 2]);
+    assert_eq!(value.get_heap_size(), 2 * std::mem::size_of::<String>() + 6);
+    assert_eq!(value.get_heap_size(), vec![String::from("abc"); 2].get_heap_size());
+
+    let value = ViaGetSize(vec![String::from("abc"); 2]);
+    assert_eq!(value.deep_size_of(), value.get_size());
+}
+
+#[test]
+fn allocator_types() {
+    let layout = std::alloc::Layout::new::<u64>();
+    assert_eq!(layout.get_heap_size(), 0);
+    assert_eq!(std::alloc::System.get_heap_size(), 0);
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn allocator_api() {
+    use std::alloc::{Global, System};
+
+    let mut vec = Vec::with_capacity_in(10, System);
+    vec.push(String::from("abc"));
+    assert_eq!(vec.get_heap_size(), 10 * std::mem::size_of::<String>() + 3);
+
+    let boxed = Box::new_in(String::from("abc"), System);
+    assert_eq!(boxed.get_heap_size(), std::mem::size_of::<String>() + 3);
+
+    let mut deque = std::collections::VecDeque::with_capacity_in(4, Global);
+    deque.push_back(String::from("abc"));
+    assert_eq!(deque.get_heap_size(), deque.capacity() * std::mem::size_of::<String>() + 3);
+    assert_eq!(Global.get_heap_size(), 0);
+}
+
+#[derive(GetSize)]
+struct TestCfgStruct {
+    always: String,
+    #[get_size(cfg(test))]
+    active: Vec<u8>,
+    #[get_size(cfg(not(test)))]
+    inactive: Vec<u8>,
+    #[get_size(size = 100, cfg(any()))]
+    #[allow(dead_code)]
+    never: u64,
+}
+
+#[derive(GetSize)]
+enum TestCfgEnum {
+    Value {
+        #[get_size(cfg(all(test, not(test))))]
+        buffer: Vec<u8>,
+        name: String,
+    },
+}
+
+#[derive(GetSize)]
+struct TestCfgStack(#[get_size(cfg(any()))] String);
+
+#[test]
+fn derive_cfg() {
+    let value = TestCfgStruct {
+        always: String::from("abc"),
+        active: vec![0; 10],
+        inactive: vec![0; 20],
+        never: 0,
+    };
+    assert_eq!(value.get_heap_size(), 13);
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.entries(), &[(String::from("always"), 3), (String::from("active"), 10)]);
+
+    let value = TestCfgEnum::Value { buffer: vec![0; 10], name: String::from("abc") };
+    assert_eq!(value.get_heap_size(), 3);
+    assert_eq!(GetSize::get_heap_size_with_tracker(&value, StandardTracker::default()).0, 3);
+
+    let flags = [TestCfgStruct::CAN_ALLOCATE_HEAP, TestCfgStack::CAN_ALLOCATE_HEAP];
+    assert_eq!(flags, [true, false]);
+    assert_eq!(TestCfgStack(String::from("abc")).get_heap_size(), 0);
+}
+
+#[derive(GetSize)]
+struct TestIterativeList {
+    value: String,
+    #[get_size(iterative)]
+    next: Option<Box<TestIterativeList>>,
+}
+
+#[derive(GetSize)]
+struct TestIterativeTree {
+    #[get_size(iterative)]
+    children: Vec<TestIterativeTree>,
+    #[get_size(iterative)]
+    #[allow(clippy::vec_box)]
+    boxed: Vec<Box<TestIterativeTree>>,
+}
+
+#[test]
+fn derive_iterative() {
+    let node_size = std::mem::size_of::<TestIterativeList>();
+
+    let mut list = TestIterativeList { value: String::from("a"), next: None };
+    for _ in 0..1_000_000 {
+        list = TestIterativeList { value: String::from("ab"), next: Some(Box::new(list)) };
+    }
+
+    assert_eq!(list.get_heap_size(), 1 + 1_000_000 * (node_size + 2));
+    let (size, _) = list.get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(size, list.get_heap_size());
+
+    // The analysis methods visit the nested objects one after another as well.
+    let report = SizeReport::of(&list);
+    assert_eq!(report.entries(), &[
+        (String::from("value"), 2),
+        (String::from("next"), list.get_heap_size() - 2),
+    ]);
+    assert_eq!(CapacityReport::of(&list).total(), 0);
+    assert_eq!(list.wasted_capacity(), 0);
+    assert_eq!(list.borrowed_size(), 0);
+
+    let estimator = DedupEstimator::of(&list);
+    assert_eq!(estimator.payloads(), 1_000_001);
+    assert_eq!(estimator.unique_payloads(), 2);
+
+    let largest = find_largest(&list, 2);
+    assert_eq!(largest.len(), 2);
+    assert_eq!(largest[0].path, "next");
+    assert_eq!(largest[0].type_name, std::any::type_name::<Box<TestIterativeList>>());
+    assert_eq!(largest[0].size, node_size);
+
+    // A short chain gets measured just like a recursive implementation would.
+    let short = TestIterativeList {
+        value: String::from("abc"),
+        next: Some(Box::new(TestIterativeList { value: String::from("de"), next: None })),
+    };
+    assert_eq!(short.get_heap_size(), 3 + node_size + 2);
+    assert_eq!(short.get_heap_size(), 3 + GetSize::get_heap_size(&short.next));
+
+    let tree_size = std::mem::size_of::<TestIterativeTree>();
+    let leaf = || TestIterativeTree { children: Vec::new(), boxed: Vec::new() };
+    let mut children = Vec::with_capacity(3);
+    children.push(leaf());
+    let tree = TestIterativeTree {
+        children,
+        boxed: vec![Box::new(leaf()), Box::new(leaf())],
+    };
+    let expected = 3 * tree_size + 2 * std::mem::size_of::<Box<TestIterativeTree>>() + 2 * tree_size;
+    assert_eq!(tree.get_heap_size(), expected);
+    assert_eq!(tree.wasted_capacity(), 2 * tree_size);
+
+    let largest = find_largest(&tree, 10);
+    assert_eq!(largest.len(), 4);
+    assert_eq!(largest[0].path, "children");
+    assert_eq!(largest[0].size, 3 * tree_size);
+
+    // Don't overflow the stack when dropping the list.
+    let mut next = list.next.take();
+    while let Some(mut node) = next {
+        next = node.next.take();
+    }
+}
+
+#[derive(GetSize)]
+struct TestRenameStruct {
+    #[get_size(rename = "userName")]
+    user_name: String,
+    kind: TestRenameEnum,
+}
+
+#[derive(GetSize)]
+enum TestRenameEnum {
+    #[get_size(rename = "named")]
+    Named {
+        #[get_size(rename = "allTags")]
+        tags: Vec<u8>,
+    },
+    Unnamed(#[get_size(rename = "first")] String),
+}
+
+#[derive(GetSize)]
+struct TestRenameTuple(#[get_size(rename = "buffer")] Vec<u8>);
+
+#[test]
+fn derive_rename() {
+    let mut tags = Vec::with_capacity(4);
+    tags.push(1);
+    let value = TestRenameStruct {
+        user_name: String::from("abc"),
+        kind: TestRenameEnum::Named { tags },
+    };
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.entries(), &[(String::from("userName"), 3), (String::from("kind.named.allTags"), 4)]);
+
+    let report = CapacityReport::of(&value);
+    assert_eq!(report.entries(), &[(String::from("kind.named.allTags"), 3)]);
+
+    let largest = find_largest(&value, 1);
+    assert_eq!(largest[0].path, "kind.named.allTags");
+
+    let value = TestRenameEnum::Unnamed(String::from("abc"));
+    assert_eq!(SizeReport::of(&value).entries(), &[(String::from("Unnamed.first"), 3)]);
+
+    let value = TestRenameTuple(vec![0; 5]);
+    assert_eq!(SizeReport::of(&value).entries(), &[(String::from("buffer"), 5)]);
+}
+
+#[derive(GetSize)]
+#[get_size(introspect)]
+#[allow(dead_code)]
+struct TestIntrospectStruct<'a> {
+    name: String,
+    #[get_size(rename = "items")]
+    values: Vec<u16>,
+    #[get_size(size = 10)]
+    fixed: u8,
+    #[get_size(ignore)]
+    skipped: Vec<u8>,
+    borrowed: &'a str,
+    shared: std::rc::Rc<[u8; 4]>,
+    again: std::rc::Rc<[u8; 4]>,
+}
+
+#[derive(GetSize)]
+#[get_size(introspect)]
+#[allow(dead_code)]
+enum TestIntrospectEnum {
+    Named { label: String, #[get_size(ignore)] unused: String },
+    Unnamed(Vec<u8>, u8),
+    #[get_size(size = 7)]
+    Fixed(String),
+    Unit,
+}
+
+#[test]
+fn derive_introspect() {
+    assert_eq!(
+        TestIntrospectStruct::GET_SIZE_FIELDS,
+        &["name", "items", "fixed", "borrowed", "shared", "again"],
+    );
+    assert_eq!(
+        TestIntrospectStruct::GET_SIZE_FIELD_TYPES,
+        &["String", "Vec<u16>", "u8", "&'a str", "std::rc::Rc<[u8; 4]>", "std::rc::Rc<[u8; 4]>"],
+    );
+
+    let shared = std::rc::Rc::new([0u8; 4]);
+    let value = TestIntrospectStruct {
+        name: String::from("abc"),
+        values: Vec::with_capacity(2),
+        fixed: 0,
+        skipped: vec![0; 100],
+        borrowed: "borrowed",
+        shared: shared.clone(),
+        again: shared.clone(),
+    };
+
+    let sizes = value.field_heap_sizes();
+    assert_eq!(sizes, vec![
+        ("name", 3),
+        ("items", 4),
+        ("fixed", 10),
+        ("borrowed", 0),
+        ("shared", std::rc::Rc::new([0u8; 4]).get_heap_size()),
+        ("again", 0),
+    ]);
+    assert_eq!(sizes.iter().map(|(_, size)| size).sum::<usize>(), value.get_heap_size());
+
+    assert_eq!(
+        TestIntrospectEnum::GET_SIZE_FIELDS,
+        &["Named.label", "Unnamed.0", "Unnamed.1", "Fixed"],
+    );
+    assert_eq!(TestIntrospectEnum::GET_SIZE_FIELD_TYPES, &["String", "Vec<u8>", "u8", "Self"]);
+
+    let value = TestIntrospectEnum::Named { label: String::from("abcd"), unused: String::from("x") };
+    assert_eq!(value.field_heap_sizes(), vec![("Named.label", 4)]);
+
+    let value = TestIntrospectEnum::Unnamed(vec![0; 3], 1);
+    assert_eq!(value.field_heap_sizes(), vec![("Unnamed.0", 3), ("Unnamed.1", 0)]);
+
+    assert_eq!(TestIntrospectEnum::Fixed(String::from("abc")).field_heap_sizes(), vec![("Fixed", 7)]);
+    assert!(TestIntrospectEnum::Unit.field_heap_sizes().is_empty());
+}
+
+#[test]
+fn find_largest_allocations() {
+    let value = TestReportStruct {
+        kind: TestReportEnum::Named { name: String::from("abc"), tags: vec![1, 2] },
+        cached: Some(vec![1, 2, 3]),
+        skipped: vec![0; 10],
+    };
+
+    let largest = find_largest(&value, 10);
+    assert_eq!(largest, vec![
+        Allocation { path: String::from("cached"), type_name: std::any::type_name::<Vec<u64>>(), size: 24 },
+        Allocation { path: String::from("kind.Named.tags"), type_name: std::any::type_name::<Vec<u8>>(), size: 4 },
+        Allocation { path: String::from("kind.Named.name"), type_name: std::any::type_name::<String>(), size: 3 },
+    ]);
+    assert_eq!(find_largest(&value, 1).len(), 1);
+    assert!(find_largest(&value, 0).is_empty());
+
+    let nested: Vec<Box<Vec<String>>> = vec![
+        Box::new(vec![String::from("a")]),
+        Box::new(vec![String::new(), "b".repeat(200)]),
+    ];
+
+    let largest = find_largest(&nested, 3);
+    assert_eq!(largest[0].path, "[1][1]");
+    assert_eq!(largest[0].type_name, std::any::type_name::<String>());
+    assert_eq!(largest[0].size, 200);
+    assert_eq!(largest[1].path, "[1]");
+    assert_eq!(largest[1].type_name, std::any::type_name::<Vec<String>>());
+    assert_eq!(largest[1].size, 2 * std::mem::size_of::<String>());
+    assert_eq!(largest[2].path, "[0]");
+    assert_eq!(largest[2].type_name, std::any::type_name::<Box<Vec<String>>>());
+    assert_eq!(largest[2].size, std::mem::size_of::<Vec<String>>());
+
+    let map: std::collections::HashMap<String, Vec<u8>> = std::collections::HashMap::from([(String::from("key"), vec![0; 100])]);
+    let largest = find_largest(&map, 3);
+    assert_eq!(largest[0].path, "");
+    assert_eq!(largest[0].size, map.capacity() * (std::mem::size_of::<String>() + std::mem::size_of::<Vec<u8>>()));
+    assert_eq!(largest[1].path, "[0]");
+    assert_eq!(largest[1].size, 100);
+    assert_eq!(largest[2].size, 3);
+
+    let mut finder = AllocationFinder::new(2);
+    finder.record("a", "A", 10);
+    finder.record("b", "B", 30);
+    finder.record("c", "C", 10);
+    finder.record("d", "D", 20);
+    finder.record("e", "E", 0);
+    let paths: Vec<_> = finder.allocations().iter().map(|allocation| allocation.path.as_str()).collect();
+    assert_eq!(paths, vec!["b", "d"]);
+}
+
+#[derive(GetSize)]
+struct TestSnapshotStruct {
+    sessions: std::sync::Mutex<TestReportStruct>,
+    names: std::sync::RwLock<Vec<String>>,
+    label: String,
+}
+
+#[test]
+fn size_report_snapshot() {
+    let value = TestSnapshotStruct {
+        sessions: std::sync::Mutex::new(TestReportStruct {
+            kind: TestReportEnum::Named { name: String::from("abc"), tags: vec![1, 2] },
+            cached: None,
+            skipped: Vec::new(),
+        }),
+        names: std::sync::RwLock::new(vec![String::from("Hello")]),
+        label: String::from("ab"),
+    };
+
+    // A regular report looks into the locks.
+    let report = SizeReport::of(&value);
+    assert!(!report.is_lock_scoped());
+    assert_eq!(report.entries()[0], (String::from("sessions.kind.Named.name"), 3));
+
+    // A snapshot lists the contents of each lock as a whole.
+    let snapshot = SizeReport::snapshot(&value);
+    assert!(snapshot.is_lock_scoped());
+    assert_eq!(snapshot.entries(), &[
+        (String::from("sessions"), 3 + 2 * 2),
+        (String::from("names"), std::mem::size_of::<String>() + 5),
+        (String::from("label"), 2),
+    ]);
+    assert_eq!(snapshot.total(), report.total());
+    assert_eq!(snapshot.total(), value.get_size());
+
+    // The locks are released again.
+    assert!(value.sessions.try_lock().is_ok());
+    assert!(value.names.try_write().is_ok());
+}
+
+#[test]
+fn size_report_merge() {
+    let mut a = SizeReport::new();
+    a.add("cache", 100);
+    a.add("index", 10);
+    a.set_system_context(Some(SystemContext { process_rss: 1000, ..SystemContext::default() }));
+
+    let mut b = SizeReport::new();
+    b.add("queue", 5);
+    b.add("cache", 50);
+    b.set_system_context(Some(SystemContext { process_rss: 500, ..SystemContext::default() }));
+
+    let mut merged = SizeReport::new();
+    merged.merge(&a);
+    assert_eq!(merged, a);
+
+    merged.merge(&b);
+    assert_eq!(merged.entries(), &[
+        (String::from("cache"), 150),
+        (String::from("index"), 10),
+        (String::from("queue"), 5),
+    ]);
+    assert_eq!(merged.total(), a.total() + b.total());
+    assert_eq!(merged.system_context().unwrap().process_rss, 1500);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn serde_json_values() {
+    use std::borrow::Cow;
+    use serde_json::value::RawValue;
+
+    let raw = RawValue::from_string(String::from("[1, 2, 3]")).unwrap();
+    assert_eq!(raw.get_heap_size(), 9);
+
+    // Borrowed raw values are only accounted for if requested.
+    let borrowed: &RawValue = &raw;
+    assert_eq!(borrowed.get_heap_size(), 0);
+    let mut policies = SizingPolicies::new();
+    policies.set_count_borrowed(true);
+    let mut ctx = SizingContext::with_tracker(StandardTracker::new());
+    ctx.set_policies(policies);
+    assert_eq!(ctx.measure(&borrowed), Ok(std::mem::size_of::<&RawValue>() + 9));
+
+    let cow: Cow<RawValue> = Cow::Borrowed(borrowed);
+    assert_eq!(cow.get_heap_size(), 0);
+    assert_eq!(cow.borrowed_size(), 9);
+
+    let mut map = serde_json::Map::new();
+    map.insert(String::from("key"), serde_json::Value::String(String::from("Hello")));
+    let value = serde_json::Value::Object(map);
+    assert_eq!(
+        value.get_heap_size(),
+        std::mem::size_of::<String>() + 3 + std::mem::size_of::<serde_json::Value>() + 5,
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn size_report_serde() {
+    let mut report = SizeReport::of(&vec![String::from("abc")]);
+    report.set_system_context(Some(SystemContext { process_rss: 1000, ..SystemContext::default() }));
+
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.starts_with(r#"{"version":1,"#));
+    assert_eq!(serde_json::from_str::<SizeReport>(&json).unwrap(), report);
+
+    let mut map = std::collections::HashMap::new();
+    map.insert(1u8, 2u8);
+
+    let report = SizeReport::of(&map);
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains(r#""sources":[{"path":"","source":"modeled"}]"#));
+    assert_eq!(serde_json::from_str::<SizeReport>(&json).unwrap(), report);
+
+    let mut report = SizeReport::of(&vec![0u8; 100]);
+    report.set_budget("", 10);
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains(r#""budgets":[{"path":"","bytes":10}]"#));
+    assert_eq!(serde_json::from_str::<SizeReport>(&json).unwrap(), report);
+
+    let json = json.replacen(r#""version":1"#, r#""version":2"#, 1);
+    assert!(serde_json::from_str::<SizeReport>(&json).is_err());
+}
+
+#[cfg(feature = "sysinfo")]
+#[test]
+fn size_report_sysinfo() {
+    let report = SizeReport::of(&vec![0u8; 1024]).with_system_context();
+
+    let system = report.system_context().unwrap();
+    assert!(system.process_rss > 0);
+    assert!(system.system_total >= system.system_used);
+}
+
+#[derive(GetSize)]
+#[allow(dead_code)]
+enum TestHeapFreeEnum {
+    Unit,
+    Code(u32),
+    Point { x: f32, y: f32 },
+    Pair((u8, [u16; 2])),
+    #[get_size(ignore)]
+    Skipped(String),
+    Named(String, u64),
+}
+
+#[test]
+fn derive_heap_free_variants() {
+    assert_eq!(TestHeapFreeEnum::Unit.get_heap_size(), 0);
+    assert_eq!(TestHeapFreeEnum::Code(1).get_heap_size(), 0);
+    assert_eq!(TestHeapFreeEnum::Point { x: 1.0, y: 2.0 }.get_heap_size(), 0);
+    assert_eq!(TestHeapFreeEnum::Pair((1, [2, 3])).get_heap_size(), 0);
+    assert_eq!(TestHeapFreeEnum::Skipped(String::from("abc")).get_heap_size(), 0);
+
+    let mut name = String::with_capacity(8);
+    name.push_str("abc");
+    let value = TestHeapFreeEnum::Named(name, 1);
+    assert_eq!(value.get_heap_size(), 8);
+    assert_eq!(value.wasted_capacity(), 5);
+    assert_eq!(SizeReport::of(&value).entries(), &[(String::from("Named.0"), 8)]);
+    assert!(SizeReport::of(&TestHeapFreeEnum::Code(1)).entries().is_empty());
+}
+
+macro_rules! test_wide_struct {
+    ($($field:ident),*) => {
+        #[derive(GetSize, Default)]
+        #[allow(dead_code)]
+        struct TestWideStruct {
+            $($field: String,)*
+            #[get_size(size = 100)]
+            fixed: u8,
+            shared: std::rc::Rc<Vec<u8>>,
+            again: std::rc::Rc<Vec<u8>>,
+        }
+    }
+}
+
+test_wide_struct!(
+    f00, f01, f02, f03, f04, f05, f06, f07, f08, f09,
+    f10, f11, f12, f13, f14, f15, f16, f17, f18, f19,
+    f20, f21, f22, f23, f24, f25, f26, f27, f28, f29,
+    f30, f31, f32, f33, f34, f35, f36, f37, f38, f39
+);
+
+#[test]
+fn derive_out_of_line_fields() {
+    let shared = std::rc::Rc::new(vec![0u8; 16]);
+    let value = TestWideStruct {
+        f00: String::from("abc"),
+        f39: String::from("defg"),
+        shared: shared.clone(),
+        again: shared.clone(),
+        ..TestWideStruct::default()
+    };
+
+    let shared_size = std::rc::Rc::new(vec![0u8; 16]).get_heap_size();
+    assert_eq!(value.get_heap_size(), 3 + 4 + 100 + shared_size);
+
+    let (total, tracker) = value.get_heap_size_with_tracker(StandardTracker::default());
+    assert_eq!(total, value.get_heap_size());
+    assert_eq!(tracker.visited_count(), 1);
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.entries()[0], (String::from("f00"), 3));
+    assert_eq!(report.entries()[1], (String::from("f39"), 4));
+}
+
+#[test]
+fn pool_slots() {
+    use std::mem::MaybeUninit;
+
+    let mut slots: Vec<MaybeUninit<String>> = Vec::with_capacity(8);
+    slots.push(MaybeUninit::new(String::from("abc")));
+    slots.push(MaybeUninit::new(String::from("defgh")));
+    slots.push(MaybeUninit::uninit());
+
+    // The values themselves can not be looked into.
+    let capacity = 8 * std::mem::size_of::<String>();
+    assert_eq!(slots.get_heap_size(), capacity);
+
+    assert_eq!(unsafe { pool_heap_size(&slots, 0) }, capacity);
+    assert_eq!(unsafe { pool_heap_size(&slots, 2) }, capacity + 3 + 5);
+    assert_eq!(unsafe { pool_heap_size_in_use(&slots, |index| index == 1) }, capacity + 5);
+
+    for slot in &mut slots[..2] {
+        unsafe { slot.assume_init_drop() };
+    }
+}
+
+#[cfg(feature = "sharded-slab")]
+#[test]
+fn sharded_slab_values() {
+    let mut slab = sharded_slab::Slab::new();
+    slab.insert(vec![0u8; 10]).unwrap();
+    slab.insert(vec![0u8; 20]).unwrap();
+
+    assert_eq!(slab_heap_size(&mut slab), 2 * std::mem::size_of::<Vec<u8>>() + 30);
+}
+
+#[cfg(feature = "trace-alloc")]
+#[global_allocator]
+static TRACING_ALLOCATOR: TracingAllocator = TracingAllocator::system();
+
+#[cfg(feature = "trace-alloc")]
+#[test]
+fn trace_allocations() {
+    struct TracedOuter;
+    struct TracedInner;
+
+    let find = |tag: &str| allocation_stats().into_iter().find(|stat| stat.tag == tag);
+
+    let values = {
+        let _outer = TraceScope::of::<TracedOuter>();
+        let mut values: Vec<u64> = Vec::with_capacity(4);
+
+        {
+            let _inner = TraceScope::named("traced inner");
+            assert_eq!(TraceScope::current(), Some("traced inner"));
+
+            values.push(1);
+            let _ = String::from("abc");
+        }
+
+        assert_eq!(TraceScope::current(), Some(std::any::type_name::<TracedOuter>()));
+
+        // Growing the vector accounts for the additional bytes only.
+        values.reserve_exact(11);
+
+        values
+    };
+    assert_eq!(TraceScope::current(), None);
+    assert_eq!(values.capacity(), 12);
+
+    let outer = find(std::any::type_name::<TracedOuter>()).unwrap();
+    assert_eq!(outer.bytes, 12 * 8);
+    assert_eq!(outer.allocations, 2);
+
+    let inner = find("traced inner").unwrap();
+    assert_eq!(inner.bytes, 3);
+    assert_eq!(inner.allocations, 1);
+
+    assert!(find(std::any::type_name::<TracedInner>()).is_none());
+}
+
+#[test]
+fn thread_local_registration() {
+    use std::cell::RefCell;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    thread_local! {
+        static TEST_SCRATCH: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    }
+
+    let (ready_sender, ready) = mpsc::channel();
+    let (stop, stop_receiver) = mpsc::channel::<()>();
+
+    let worker = std::thread::Builder::new().name(String::from("scratch worker")).spawn(move || {
+        register_thread_local_size!("scratch", || TEST_SCRATCH.with(|scratch| scratch.borrow().get_heap_size()));
+        ready_sender.send(std::thread::current().id()).unwrap();
+
+        TEST_SCRATCH.with(|scratch| scratch.borrow_mut().reserve_exact(16));
+
+        // Answer requests until told to stop.
+        while stop_receiver.recv_timeout(Duration::from_millis(1)).is_err() {
+            report_thread_local_sizes();
+        }
+    }).unwrap();
+
+    let id = ready.recv().unwrap();
+    let find = |sizes: Vec<ThreadLocalSize>| sizes.into_iter().find(|size| size.thread == id);
+
+    // Right after registering, the buffer was still empty.
+    let size = find(thread_local_sizes()).unwrap();
+    assert_eq!(size.name, "scratch");
+    assert_eq!(size.thread_name.as_deref(), Some("scratch worker"));
+
+    let size = find(collect_thread_local_sizes(Duration::from_secs(30))).unwrap();
+    assert!(size.fresh);
+    assert_eq!(size.size, 16 * 8);
+
+    stop.send(()).unwrap();
+    worker.join().unwrap();
+
+    assert!(find(thread_local_sizes()).is_none());
+}
+
+#[test]
+fn string_policy() {
+    #[derive(GetSize)]
+    struct TestStrings {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    let mut name = String::with_capacity(16);
+    name.push_str("abc");
+    let mut tags = Vec::with_capacity(2);
+    tags.push(String::from("de"));
+
+    let value = TestStrings { name, tags };
+    let stack = std::mem::size_of::<TestStrings>();
+
+    assert_eq!(StringPolicy::default(), StringPolicy::Capacity);
+    assert_eq!(StringPolicy::Len.heap_size_of(&value.name), 3);
+    assert_eq!(StringPolicy::Capacity.heap_size_of(&value.name), 16);
+
+    let mut context = SizingContext::new();
+    assert_eq!(context.measure(&value), Ok(stack + 16 + 2 * std::mem::size_of::<String>() + 2));
+
+    let mut policies = context.policies();
+    policies.set_strings(StringPolicy::Len);
+
+    let mut context = SizingContext::new();
+    context.set_policies(policies);
+    assert_eq!(context.measure(&value), Ok(stack + 3 + 2 * std::mem::size_of::<String>() + 2));
+
+    // Reports apply the policies they were created with, down to the strings nested in collections.
+    let report = SizeReport::of_with_policies(&value, policies);
+    assert_eq!(report.policies(), policies);
+    assert_eq!(report.size_at("name"), 3);
+    assert_eq!(report.size_at("tags"), 2 * std::mem::size_of::<String>() + 2);
+    assert_eq!(SizeReport::of(&value).size_at("name"), 16);
+
+    let mut os_string = std::ffi::OsString::with_capacity(16);
+    os_string.push("abc");
+    let mut path = std::path::PathBuf::with_capacity(16);
+    path.push("abc");
+    let owned: std::borrow::Cow<str> = std::borrow::Cow::Owned(value.name.clone() + "d");
+    let boxed: Box<str> = Box::from("abcd");
+
+    let (size, _) = os_string.get_heap_size_with_tracker(StandardTracker::with_policies(policies));
+    assert_eq!(size, 3);
+    let (size, _) = path.get_heap_size_with_tracker(StandardTracker::with_policies(policies));
+    assert_eq!(size, 3);
+    let (size, _) = owned.get_heap_size_with_tracker(StandardTracker::with_policies(policies));
+    assert_eq!(size, 4);
+    assert_eq!(SizeReport::of_with_policies(&owned, policies).heap_size(), 4);
+    let (size, _) = boxed.get_heap_size_with_tracker(StandardTracker::with_policies(policies));
+    assert_eq!(size, boxed.get_heap_size());
+}
+
+#[test]
+fn traversal_order() {
+    assert_eq!(TraversalOrder::default(), TraversalOrder::Native);
+
+    let mut report = SizeReport::new();
+    report.add("b", 8);
+    report.add("c", 16);
+    report.add("a", 8);
+
+    let mut names = std::collections::HashSet::new();
+    names.insert(String::from("aa"));
+    names.insert(String::from("bb"));
+    names.insert(String::from("c"));
+
+    assert_eq!(report.order(), TraversalOrder::Native);
+    assert_eq!(report.top(3), vec![("c", 16), ("b", 8), ("a", 8)]);
+
+    report.set_order(TraversalOrder::Deterministic);
+    assert_eq!(report.top(3), vec![("c", 16), ("a", 8), ("b", 8)]);
+
+    let mut capacity = CapacityReport::new();
+    capacity.add("y", 4);
+    capacity.add("x", 4);
+    capacity.set_order(TraversalOrder::Deterministic);
+    assert_eq!(capacity.top(2), vec![("x", 4), ("y", 4)]);
+
+    let largest = find_largest_with_order(&names, 4, TraversalOrder::Deterministic);
+
+    // Elements of equal size get ordered by their path, even though the set iterates randomly.
+    assert_eq!(largest.len(), 4);
+    assert_eq!(largest[1].path, "[0]");
+    assert_eq!(largest[1].size, 2);
+    assert_eq!(largest[2].path, "[1]");
+    assert_eq!(largest[2].size, 2);
+    assert_eq!(largest[3].path, "[2]");
+    assert_eq!(largest[3].size, 1);
+}
+
+#[test]
+fn sizer() {
+    let shared = std::rc::Rc::new(String::from("abc"));
+    let values = vec![shared.clone(), shared.clone()];
+
+    let mut sizer = Sizer::new()
+        .with_name("values")
+        .with_policy(StringPolicy::Len)
+        .with_budget(1000);
+
+    let size = sizer.measure(&values).unwrap();
+    assert_eq!(sizer.used(), size);
+    assert_eq!(sizer.remaining(), Some(1000 - size));
+    assert_eq!(sizer.context().name(), Some("values"));
+
+    // The shared string was already seen by the tracker.
+    assert_eq!(sizer.measure(&shared), Ok(std::mem::size_of::<std::rc::Rc<String>>()));
+
+    let mut elements = 0;
+    let mut sizer = Sizer::new()
+        .with_budget(10)
+        .with_tracker(NoTracker::new(true))
+        .with_progress(|progress: Progress| { elements = progress.elements; true }, 1);
+    assert_eq!(sizer.measure(&values), Err(SizeError::BudgetExceeded));
+    assert_eq!(sizer.context().budget(), Some(10));
+    drop(sizer);
+    assert_eq!(elements, 2);
+
+    let sizer = Sizer::new()
+        .with_count_borrowed(true)
+        .with_policy(WeakPolicy::IgnoreWeak);
+    let policies = sizer.context().policies();
+    assert!(policies.count_borrowed);
+    assert_eq!(policies.weak, WeakPolicy::IgnoreWeak);
+}
+
+#[test]
+fn error_types() {
+    let parse_error = "x".parse::<u8>().unwrap_err();
+    assert_eq!(parse_error.get_heap_size(), 0);
+    assert_eq!(std::fmt::Error.get_heap_size(), 0);
+    assert_eq!(Vec::<u8>::new().try_reserve(usize::MAX).unwrap_err().get_heap_size(), 0);
+
+    let utf8_error = String::from_utf8(vec![0xff, 0xfe]).unwrap_err();
+    assert_eq!(utf8_error.get_heap_size(), 2);
+
+    let os_error = std::io::Error::from_raw_os_error(2);
+    assert_eq!(os_error.get_heap_size(), 0);
+    assert_eq!(std::io::Error::from(std::io::ErrorKind::NotFound).get_heap_size(), 0);
+
+    // Only the boxes of custom errors are accounted for, the heap memory of their payload is unknown.
+    let custom_error = std::io::Error::other("disk on fire");
+    let size = custom_error.get_heap_size();
+    let custom = std::mem::size_of::<(std::io::ErrorKind, Box<dyn std::error::Error + Send + Sync>)>();
+    assert_eq!(size, custom + std::mem::size_of_val(custom_error.get_ref().unwrap()));
+    assert_eq!(std::io::Error::other(String::from("a much longer message")).get_heap_size(), size);
+
+    #[derive(Debug)]
+    struct Overheated([u64; 4]);
+
+    impl std::fmt::Display for Overheated {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "overheated: {:?}", self.0)
+        }
+    }
+
+    impl std::error::Error for Overheated {}
+
+    assert_eq!(std::io::Error::other(Overheated([0; 4])).get_heap_size(), custom + 32);
+
+    let queue: Vec<Result<u64, std::io::Error>> = vec![Ok(1), Err(custom_error)];
+    assert_eq!(queue.get_heap_size(), 2 * std::mem::size_of::<Result<u64, std::io::Error>>() + size);
+}
+
+#[test]
+fn size_memo() {
+    let config = std::sync::Arc::new(vec![String::from("abc"); 10]);
+    let expected = config.get_size_with_tracker(StandardTracker::new()).0;
+
+    let memo = SizeMemo::new();
+    assert!(memo.is_empty());
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let config = std::sync::Arc::clone(&config);
+            let memo = &memo;
+
+            scope.spawn(move || {
+                let parent = vec![config];
+                let (size, _) = parent.get_heap_size_with_tracker(MemoTracker::new(memo));
+                assert_eq!(size, expected);
+            });
+        }
+    });
+
+    assert_eq!(memo.len(), 1);
+    assert_eq!(memo.get(&config), Some(expected - std::mem::size_of::<std::sync::Arc<Vec<String>>>()));
+
+    // The memoized size is used until it gets invalidated.
+    let buffer = std::sync::Arc::new(std::sync::Mutex::new(vec![0u8; 10]));
+    let (before, _) = buffer.get_size_with_tracker(MemoTracker::new(&memo));
+    buffer.lock().unwrap().reserve(100);
+
+    let (stale, _) = buffer.get_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(stale, before);
+
+    assert!(memo.invalidate(&buffer));
+    let (fresh, _) = buffer.get_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(fresh, buffer.get_size());
+    assert!(fresh > before);
+
+    drop(config);
+    assert_eq!(memo.prune(), 1);
+    assert_eq!(memo.len(), 1);
+
+    // Trackers wrapping a memo tracker pass the memo along.
+    let shared = std::sync::Arc::new(String::from("shared"));
+    let mut context = SizingContext::with_tracker(MemoTracker::global());
+    context.measure(&shared).unwrap();
+    assert!(SizeMemo::global().get(&shared).is_some());
+    SizeMemo::global().clear();
+}
+
+#[derive(GetSize)]
+#[allow(clippy::redundant_allocation)]
+struct TestMemoWrappers {
+    optional: Option<std::sync::Arc<String>>,
+    boxed: Box<std::sync::Arc<String>>,
+}
+
+#[test]
+fn size_memo_wrappers() {
+    let shared = std::sync::Arc::new(String::from("Hello, world!"));
+    let value = TestMemoWrappers {
+        optional: Some(std::sync::Arc::clone(&shared)),
+        boxed: Box::new(std::sync::Arc::clone(&shared)),
+    };
+
+    // The string is only accounted for once, even behind an option and a box.
+    let expected = std::mem::size_of::<std::sync::Arc<String>>() + shared.get_heap_size();
+
+    let memo = SizeMemo::new();
+    let (size, _) = value.get_heap_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(size, expected);
+    assert_eq!(memo.len(), 1);
+    assert_eq!(memo.get(&shared), Some(shared.get_heap_size()));
+
+    // Measuring again uses the memoized size, once.
+    let (size, _) = value.get_heap_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(size, expected);
+
+    let clones = vec![Some(std::sync::Arc::clone(&shared)), Some(std::sync::Arc::clone(&shared))];
+    let (size, _) = clones.get_heap_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(size, 2 * std::mem::size_of::<Option<std::sync::Arc<String>>>() + shared.get_heap_size());
+}
+
+#[test]
+fn size_memo_nested_shared() {
+    use std::sync::Arc;
+
+    let inner = Arc::new(vec![0u8; 1000]);
+    let config = Arc::new(vec![Arc::clone(&inner)]);
+    let (fresh, _) = config.get_heap_size_with_tracker(StandardTracker::new());
+
+    // The config shares the inner vector already seen, so its size is incomplete and not memoized.
+    let memo = SizeMemo::new();
+    let parent = (Arc::clone(&inner), Arc::clone(&config));
+    let _ = parent.get_heap_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(memo.get(&config), None);
+    assert_eq!(memo.get(&inner), Some(inner.get_heap_size()));
+
+    let (memoized, _) = config.get_heap_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(memoized, fresh);
+
+    // Sizes measured under different policies are kept apart.
+    let names = Arc::new(String::with_capacity(16));
+    let mut policies = SizingPolicies::new();
+    policies.set_strings(StringPolicy::Len);
+
+    let mut context = SizingContext::with_tracker(MemoTracker::new(&memo));
+    context.set_policies(policies);
+    assert_eq!(context.measure(&names), Ok(std::mem::size_of::<Arc<String>>() + std::mem::size_of::<String>()));
+    assert_eq!(memo.get(&names), None);
+
+    let (size, _) = names.get_heap_size_with_tracker(MemoTracker::new(&memo));
+    assert_eq!(size, std::mem::size_of::<String>() + 16);
+    assert_eq!(memo.get(&names), Some(size));
+    assert_eq!(memo.get_with_policies(&names, policies), Some(std::mem::size_of::<String>()));
+}
+
+#[test]
+fn implicit_context() {
+    use std::sync::Arc;
+
+    trait Payload {
+        fn heap_size(&self) -> usize;
+    }
+
+    impl Payload for Arc<String> {
+        fn heap_size(&self) -> usize {
+            self.get_heap_size_implicit()
+        }
+    }
+
+    struct Holder(Box<dyn Payload>);
+
+    impl GetSize for Holder {
+        fn get_heap_size(&self) -> usize {
+            self.0.heap_size()
+        }
+    }
+
+    let shared = Arc::new(String::from("Hello"));
+    let holders = vec![Holder(Box::new(shared.clone())), Holder(Box::new(shared.clone()))];
+    let once = shared.get_heap_size();
+
+    // Without a context, every holder accounts for the string.
+    assert!(!has_implicit_context());
+    assert_eq!(holders.get_heap_size_implicit(), holders.get_heap_size());
+    assert_eq!(holders.get_heap_size(), 2 * std::mem::size_of::<Holder>() + 2 * once);
+
+    let mut policies = SizingPolicies::new();
+    policies.set_strings(StringPolicy::Len);
+
+    let mut ctx = SizingContext::new();
+    ctx.set_policies(policies);
+
+    let size = with_context(&mut ctx, || {
+        assert!(has_implicit_context());
+        holders.get_heap_size_implicit()
+    });
+    assert_eq!(size, 2 * std::mem::size_of::<Holder>() + once);
+    assert!(!has_implicit_context());
+
+    // The context keeps tracking the string after being moved back.
+    assert_eq!(ctx.measure(&shared), Ok(std::mem::size_of::<Arc<String>>()));
+
+    // Nested contexts are independent of each other.
+    let mut inner = SizingContext::new();
+    with_context(&mut ctx, || {
+        assert_eq!(with_context(&mut inner, || shared.get_size_implicit()), shared.get_size());
+        assert_eq!(shared.get_size_implicit(), std::mem::size_of::<Arc<String>>());
+    });
+
+    // The context gets moved back even if measuring panics.
+    let mut ctx = SizingContext::named("panicking");
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        with_context(&mut ctx, || panic!("measurement failed"))
+    }));
+    assert!(result.is_err());
+    assert_eq!(ctx.name(), Some("panicking"));
+    assert!(!has_implicit_context());
+}
+
+mod shape_v1 {
+    use get_size::GetSize;
+
+    #[allow(dead_code)]
+    #[derive(GetSize)]
+    #[get_size(introspect)]
+    pub struct Record {
+        pub name: String,
+        #[get_size(ignore)]
+        pub cache: Vec<u8>,
+    }
+}
+
+mod shape_v2 {
+    use get_size::GetSize;
+
+    #[allow(dead_code)]
+    #[derive(GetSize)]
+    #[get_size(introspect)]
+    pub struct Record {
+        pub name: String,
+        #[get_size(ignore)]
+        pub cache: Vec<u16>,
+    }
+}
+
+mod shape_v3 {
+    use get_size::GetSize;
+
+    #[allow(dead_code)]
+    #[derive(GetSize)]
+    #[get_size(introspect)]
+    pub struct Record {
+        #[get_size(rename = "title")]
+        pub name: String,
+        #[get_size(ignore)]
+        pub cache: Vec<u8>,
+    }
+}
+
+#[test]
+fn size_shape_hash() {
+    assert_eq!(
+        shape_v1::Record::size_shape_hash(),
+        shape_hash(&["struct", "Record", "{}", "name", "String", "cache", "Vec<u8>", "=", "name"]),
+    );
+
+    // Changing the type of an ignored field or the label of a field changes the shape.
+    assert_ne!(shape_v1::Record::size_shape_hash(), shape_v2::Record::size_shape_hash());
+    assert_ne!(shape_v1::Record::size_shape_hash(), shape_v3::Record::size_shape_hash());
+
+    assert_ne!(TestIntrospectEnum::size_shape_hash(), shape_v1::Record::size_shape_hash());
+    assert_eq!(
+        TestIntrospectEnum::size_shape_hash(),
+        shape_hash(&[
+            "enum", "TestIntrospectEnum",
+            "|", "Named", "{}", "label", "String", "unused", "String",
+            "|", "Unnamed", "()", "0", "Vec<u8>", "1", "u8",
+            "|", "Fixed", "()", "0", "String",
+            "|", "Unit", ";",
+            "=", "Named.label", "Unnamed.0", "Unnamed.1", "Fixed",
+        ]),
+    );
+}
+
+#[test]
+fn tracker_hits() {
+    let shared = std::rc::Rc::new(String::from("Hello"));
+    let values = vec![shared.clone(), shared.clone(), shared.clone()];
+
+    let (_, tracker) = values.get_heap_size_with_tracker(StandardTracker::new());
+    assert_eq!(tracker.visited_count(), 1);
+    assert_eq!(tracker.hit_count(), 2);
+
+    let mut ctx = SizingContext::new();
+    ctx.measure(&values).unwrap();
+    ctx.measure(&shared).unwrap();
+    assert_eq!(ctx.hit_count(), 3);
+
+    // Objects remembered as shared count as hits as well.
+    let mut tracker = ScopedTracker::new();
+    tracker.measure_shared(&shared);
+    tracker.measure(&values);
+    assert_eq!(tracker.hit_count(), 3);
+
+    assert_eq!(NoTracker::new(false).hit_count(), 0);
+
+    let report = SizeReport::of_tracked(&values);
+    let sharing = *report.sharing().unwrap();
+    assert_eq!(sharing.hits, 2);
+    assert_eq!(sharing.shared_bytes, 2 * shared.get_heap_size());
+    assert_eq!(sharing.unique_bytes(&report), values.get_heap_size_with_tracker(StandardTracker::new()).0);
+    assert!(report.to_string().contains("deduplicated references"));
+    assert_eq!(SizeReport::of(&values).sharing(), None);
+
+    let mut merged = report.clone();
+    merged.merge(&report);
+    assert_eq!(merged.sharing().unwrap().hits, 4);
+}
+
+#[derive(GetSize)]
+struct TestSubsystem {
+    names: Vec<String>,
+}
+
+#[derive(GetSize)]
+#[allow(clippy::box_collection)]
+struct TestSkipRecursion {
+    #[get_size(skip_recursion)]
+    index: Vec<Vec<u8>>,
+    #[get_size(skip_recursion)]
+    subsystem: TestSubsystem,
+    #[get_size(skip_recursion)]
+    shared: std::rc::Rc<Vec<String>>,
+    #[get_size(skip_recursion)]
+    boxed: Box<Vec<String>>,
+    #[get_size(skip_recursion)]
+    optional: Option<Vec<String>>,
+    name: String,
+}
+
+#[test]
+fn derive_skip_recursion() {
+    let shared = std::rc::Rc::new(vec![String::from("abc"); 2]);
+    let value = TestSkipRecursion {
+        index: vec![vec![0u8; 100]; 2],
+        subsystem: TestSubsystem { names: vec![String::from("Hello"); 3] },
+        shared: shared.clone(),
+        boxed: Box::new(vec![String::from("Hello"); 4]),
+        optional: Some(vec![String::from("World"); 4]),
+        name: String::from("value"),
+    };
+
+    // Only the buffers of the index, the subsystem and the wrapped vectors and the allocation of
+    // the shared vector get accounted for.
+    let index = 2 * std::mem::size_of::<Vec<u8>>();
+    let subsystem = 3 * std::mem::size_of::<String>();
+    let shared_size = std::rc::Rc::new(Vec::<String>::with_capacity(2)).get_heap_size();
+    let boxed = std::mem::size_of::<Vec<String>>() + 4 * std::mem::size_of::<String>();
+    let optional = 4 * std::mem::size_of::<String>();
+    let expected = index + subsystem + shared_size + boxed + optional + 5;
+    assert_eq!(value.get_heap_size(), expected);
+    assert_eq!(runtime::shallow_heap_size(&value.index), index);
+
+    let report = SizeReport::of(&value);
+    assert_eq!(report.size_at("index"), index);
+    assert_eq!(report.size_at("subsystem"), subsystem);
+    assert_eq!(report.size_at("boxed"), boxed);
+    assert_eq!(report.size_at("optional"), optional);
+    assert_eq!(report.heap_size(), expected);
+
+    let mut accumulator = 0u128;
+    value.accumulate_heap_size(&mut accumulator);
+    assert_eq!(accumulator, expected as u128);
+
+    // The shallow measurement still shares the tracker.
+    let (_, tracker) = shared.get_heap_size_with_tracker(StandardTracker::new());
+    let (size, tracker) = value.get_heap_size_with_tracker(tracker);
+    assert_eq!(size, index + subsystem + boxed + optional + 5);
+    assert_eq!(tracker.hit_count(), 1);
+}
+
+#[derive(GetSize)]
+struct TestCallbacks {
+    on_click: Box<dyn Fn(u32, u32) -> bool>,
+    on_close: Box<dyn FnMut() + Send>,
+    on_done: Option<Box<dyn FnOnce(String) + Send + Sync>>,
+}
+
+#[test]
+fn boxed_closures() {
+    let offset = [0u64; 4];
+    let name = String::from("Hello");
+
+    let callbacks = TestCallbacks {
+        on_click: Box::new(move |x, y| offset[0] + x as u64 > y as u64),
+        on_close: Box::new(|| {}),
+        // Only the captured string itself is accounted for, not its contents.
+        on_done: Some(Box::new(move |other| drop((name, other)))),
+    };
+
+    assert_eq!(callbacks.on_click.get_heap_size(), std::mem::size_of::<[u64; 4]>());
+    assert_eq!(callbacks.on_close.get_heap_size(), 0);
+    assert_eq!(callbacks.get_heap_size(), std::mem::size_of::<[u64; 4]>() + std::mem::size_of::<String>());
+    assert!((callbacks.on_click)(2, 1));
+}
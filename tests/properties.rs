@@ -0,0 +1,162 @@
+// Property based tests, which check invariants every implementation has to uphold against
+// randomly generated values, like collections nested into each other.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::mem::size_of;
+use std::sync::Arc;
+
+use get_size::*;
+use proptest::prelude::*;
+
+
+
+// A randomly nested structure, covering the most common compositions of standard types.
+#[derive(Debug, Clone, GetSize)]
+#[allow(dead_code)]
+enum Node {
+    Leaf(String),
+    Number(u64),
+    Boxed(Box<Node>),
+    Optional(Option<Box<Node>>),
+    List(Vec<Node>),
+    Queue(VecDeque<Node>),
+    Map(HashMap<u16, Node>),
+    Sorted(BTreeMap<String, Node>),
+    Shared(Arc<Node>),
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    let leaf = prop_oneof![
+        any::<String>().prop_map(Node::Leaf),
+        any::<u64>().prop_map(Node::Number),
+    ];
+
+    leaf.prop_recursive(4, 64, 8, |inner| prop_oneof![
+        inner.clone().prop_map(|node| Node::Boxed(Box::new(node))),
+        prop::option::of(inner.clone()).prop_map(|node| Node::Optional(node.map(Box::new))),
+        prop::collection::vec(inner.clone(), 0..8).prop_map(Node::List),
+        prop::collection::vec_deque(inner.clone(), 0..8).prop_map(Node::Queue),
+        prop::collection::hash_map(any::<u16>(), inner.clone(), 0..8).prop_map(Node::Map),
+        prop::collection::btree_map(any::<String>(), inner.clone(), 0..8).prop_map(Node::Sorted),
+        inner.prop_map(|node| Node::Shared(Arc::new(node))),
+    ])
+}
+
+// Adds up the bytes of all strings inside the node, which is a lower bound of its heap size.
+fn string_bytes(node: &Node) -> usize {
+    match node {
+        Node::Leaf(value) => value.len(),
+        Node::Number(_) => 0,
+        Node::Boxed(node) => string_bytes(node),
+        Node::Optional(node) => node.as_deref().map_or(0, string_bytes),
+        Node::List(nodes) => nodes.iter().map(string_bytes).sum(),
+        Node::Queue(nodes) => nodes.iter().map(string_bytes).sum(),
+        Node::Map(nodes) => nodes.values().map(string_bytes).sum(),
+        Node::Sorted(nodes) => nodes.iter().map(|(key, node)| key.len() + string_bytes(node)).sum(),
+        Node::Shared(node) => string_bytes(node),
+    }
+}
+
+proptest! {
+    #[test]
+    fn total_is_stack_plus_heap(node in node()) {
+        prop_assert_eq!(node.get_size(), Node::get_stack_size() + node.get_heap_size());
+    }
+
+    #[test]
+    fn heap_covers_contents(node in node()) {
+        prop_assert!(node.get_heap_size() >= string_bytes(&node));
+    }
+
+    #[test]
+    fn boxing_adds_the_stack_size(node in node()) {
+        let heap_size = node.get_heap_size();
+
+        prop_assert_eq!(Box::new(node).get_heap_size(), Node::get_stack_size() + heap_size);
+    }
+
+    #[test]
+    fn limit_matches_exact_size(node in node(), limit in 0usize..4096) {
+        let heap_size = node.get_heap_size();
+        let expected = (heap_size <= limit).then_some(heap_size);
+
+        prop_assert_eq!(node.get_heap_size_within(limit), expected);
+    }
+
+    #[test]
+    fn strings_are_measured_by_capacity(value in any::<String>(), extra in 0usize..64) {
+        let mut value = value;
+        value.reserve_exact(extra);
+
+        prop_assert_eq!(value.get_heap_size(), value.capacity());
+        prop_assert_eq!(value.wasted_capacity(), value.capacity() - value.len());
+    }
+
+    #[test]
+    fn vectors_are_measured_by_capacity(values in prop::collection::vec(any::<String>(), 0..32), extra in 0usize..16) {
+        let mut values = values;
+        values.reserve_exact(extra);
+
+        let elements: usize = values.iter().map(String::capacity).sum();
+
+        prop_assert_eq!(values.get_heap_size(), values.capacity() * size_of::<String>() + elements);
+    }
+
+    #[test]
+    fn vectors_grow_monotonically(values in prop::collection::vec(any::<String>(), 0..32), pushed in any::<String>()) {
+        let mut values = values;
+        let before = values.get_heap_size();
+
+        values.push(pushed);
+
+        prop_assert!(values.get_heap_size() >= before);
+    }
+
+    #[test]
+    fn maps_grow_monotonically(map in prop::collection::hash_map(any::<u32>(), any::<String>(), 0..32), key in any::<u32>(), value in any::<String>()) {
+        let mut map = map;
+        let before = map.get_heap_size();
+
+        // Replacing a value may free the heap memory of the previous one.
+        if let Entry::Vacant(entry) = map.entry(key) {
+            entry.insert(value);
+
+            prop_assert!(map.get_heap_size() >= before);
+        }
+    }
+
+    #[test]
+    fn cleared_collections_keep_their_capacity(values in prop::collection::vec(any::<String>(), 0..32)) {
+        let mut values = values;
+        values.clear();
+
+        prop_assert_eq!(values.get_heap_size(), values.capacity() * size_of::<String>());
+
+        values.shrink_to_fit();
+
+        prop_assert_eq!(values.get_heap_size(), 0);
+    }
+
+    #[test]
+    fn shared_values_are_counted_once(node in node(), clones in 1usize..16) {
+        let shared = Arc::new(node);
+        let values = vec![shared.clone(); clones];
+
+        let expected = clones * size_of::<Arc<Node>>() + shared.get_heap_size();
+
+        let (heap_size, _) = values.get_heap_size_with_tracker(StandardTracker::default());
+
+        prop_assert_eq!(heap_size, expected);
+    }
+}
+
+#[test]
+fn empty_collections_do_not_allocate() {
+    assert_eq!(String::new().get_heap_size(), 0);
+    assert_eq!(Vec::<Node>::new().get_heap_size(), 0);
+    assert_eq!(VecDeque::<Node>::new().get_heap_size(), 0);
+    assert_eq!(HashMap::<u16, Node>::new().get_heap_size(), 0);
+    assert_eq!(BTreeMap::<String, Node>::new().get_heap_size(), 0);
+    assert_eq!(Option::<Box<Node>>::None.get_heap_size(), 0);
+}
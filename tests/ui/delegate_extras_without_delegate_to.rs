@@ -0,0 +1,10 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+#[get_size(delegate_extras(name))]
+struct TestStruct {
+    inner: Vec<u8>,
+    name: String,
+}
+
+fn main() {}
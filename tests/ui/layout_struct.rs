@@ -0,0 +1,9 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+#[get_size(layout)]
+struct TestStruct {
+    value: String,
+}
+
+fn main() {}
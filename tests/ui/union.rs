@@ -0,0 +1,9 @@
+use get_size::GetSize;
+
+#[derive(GetSize)]
+union TestUnion {
+    value1: u32,
+    value2: f32,
+}
+
+fn main() {}